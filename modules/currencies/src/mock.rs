@@ -64,6 +64,9 @@ parameter_type_with_key! {
 
 parameter_types! {
 	pub DustAccount: AccountId = ModuleId(*b"orml/dst").into_account();
+	pub const SponsorModuleId: ModuleId = ModuleId(*b"snp/evsp");
+	pub const MaxBatchCalls: u32 = 4;
+	pub const RecurringCallRetention: u64 = 10;
 }
 
 impl tokens::Config for Runtime {
@@ -118,7 +121,10 @@ ord_parameter_types! {
 	pub const CouncilAccount: AccountId32 = AccountId32::from([1u8; 32]);
 	pub const NetworkContractAccount: AccountId32 = AccountId32::from([0u8; 32]);
 	pub const StorageDepositPerByte: u128 = 10;
+	pub const StorageCleanupWeight: u64 = 10;
 	pub const MaxCodeSize: u32 = 60 * 1024;
+	pub const EthereumTransactionStorageLimit: u32 = 60 * 1024;
+	pub const AllowUnprotectedTransactions: bool = false;
 	pub const DeveloperDeposit: u64 = 1000;
 	pub const DeploymentFee: u64 = 200;
 }
@@ -129,12 +135,17 @@ impl module_evm::Config for Runtime {
 	type MergeAccount = ();
 	type NewContractExtraBytes = NewContractExtraBytes;
 	type StorageDepositPerByte = StorageDepositPerByte;
+	type StorageCleanupWeight = StorageCleanupWeight;
 	type MaxCodeSize = MaxCodeSize;
 
 	type Event = Event;
 	type Precompiles = ();
 	type ChainId = ();
 	type GasToWeight = ();
+	type WeightToGas = ();
+	type FindAuthor = ();
+	type Randomness = ();
+	type NetGasMetering = ();
 	type ChargeTransactionPayment = ();
 	type NetworkContractOrigin = EnsureSignedBy<NetworkContractAccount, AccountId>;
 	type NetworkContractSource = NetworkContractSource;
@@ -142,6 +153,14 @@ impl module_evm::Config for Runtime {
 	type DeveloperDeposit = DeveloperDeposit;
 	type DeploymentFee = DeploymentFee;
 	type FreeDeploymentOrigin = EnsureSignedBy<CouncilAccount, AccountId32>;
+	type PauseOrigin = EnsureSignedBy<CouncilAccount, AccountId32>;
+	type CodeUpgradeOrigin = EnsureSignedBy<CouncilAccount, AccountId32>;
+	type DispatchCallOrigin = EnsureSignedBy<CouncilAccount, AccountId32>;
+	type EthereumTransactionStorageLimit = EthereumTransactionStorageLimit;
+	type AllowUnprotectedTransactions = AllowUnprotectedTransactions;
+	type SponsorModuleId = SponsorModuleId;
+	type MaxBatchCalls = MaxBatchCalls;
+	type RecurringCallRetention = RecurringCallRetention;
 
 	type WeightInfo = ();
 }
@@ -278,6 +297,7 @@ impl ExtBuilder {
 		);
 		module_evm::GenesisConfig::<Runtime> {
 			accounts,
+			dispatchable_calls: Default::default(),
 		}
 		.assimilate_storage(&mut t)
 		.unwrap();
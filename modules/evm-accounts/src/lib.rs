@@ -36,9 +36,11 @@ use sp_std::{marker::PhantomData, vec::Vec};
 
 mod default_weight;
 mod mock;
+pub mod precompile;
 mod tests;
 
 pub use module::*;
+pub use precompile::EvmAccountsPrecompile;
 
 pub trait WeightInfo {
 	fn claim_account() -> Weight;
@@ -197,10 +199,7 @@ impl<T: Config> Pallet<T> {
 	// using the Ethereum RPC's `personal_sign` and `eth_sign`.
 	pub fn eth_recover(s: &EcdsaSignature, what: &[u8], extra: &[u8]) -> Option<EvmAddress> {
 		let msg = keccak_256(&Self::ethereum_signable_message(what, extra));
-		let mut res = EvmAddress::default();
-		res.0
-			.copy_from_slice(&keccak_256(&secp256k1_ecdsa_recover(&s.0, &msg).ok()?[..])[12..]);
-		Some(res)
+		eth_recover_prehashed(s, &msg)
 	}
 
 	pub fn eth_public(secret: &secp256k1::SecretKey) -> secp256k1::PublicKey {
@@ -221,6 +220,15 @@ impl<T: Config> Pallet<T> {
 	}
 }
 
+/// Attempts to recover the Ethereum address that signed the given 32 byte
+/// message hash, e.g. the signing hash of an Ethereum transaction.
+pub fn eth_recover_prehashed(s: &EcdsaSignature, msg: &[u8; 32]) -> Option<EvmAddress> {
+	let mut res = EvmAddress::default();
+	res.0
+		.copy_from_slice(&keccak_256(&secp256k1_ecdsa_recover(&s.0, msg).ok()?[..])[12..]);
+	Some(res)
+}
+
 fn account_to_default_evm_address(account_id: &impl Encode) -> EvmAddress {
 	let payload = (b"evm:", account_id);
 	EvmAddress::from_slice(&payload.using_encoded(blake2_256)[0..20])
@@ -0,0 +1,113 @@
+//! The address mapping queries of the EVM accounts precompile.
+//!
+//! The EVM itself depends on this module, so the `Precompile` impl charging
+//! gas lives in the runtime and calls `EvmAccountsPrecompile::execute`.
+
+use super::*;
+use sp_std::convert::TryFrom;
+
+pub const SELECTOR_BYTES: usize = 4;
+pub const PER_PARAM_BYTES: usize = 32;
+
+/// The Solidity functions of the precompile.
+#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum Action {
+	/// `getAccountId(address)`, returns `bytes32`.
+	GetAccountId,
+	/// `getEvmAddress(bytes32)`, returns `address`, zero if not linked.
+	GetEvmAddress,
+	/// `isLinked(bytes32,address)`, returns `bool`.
+	IsLinked,
+	/// `getDefaultEvmAddress(bytes32)`, returns `address`.
+	GetDefaultEvmAddress,
+}
+
+impl TryFrom<[u8; SELECTOR_BYTES]> for Action {
+	type Error = ();
+
+	fn try_from(selector: [u8; SELECTOR_BYTES]) -> Result<Self, Self::Error> {
+		match selector {
+			// getAccountId(address)
+			[0xe0, 0xb4, 0x90, 0xf7] => Ok(Action::GetAccountId),
+			// getEvmAddress(bytes32)
+			[0x02, 0x32, 0x02, 0x7e] => Ok(Action::GetEvmAddress),
+			// isLinked(bytes32,address)
+			[0x39, 0xc0, 0x5b, 0x3f] => Ok(Action::IsLinked),
+			// getDefaultEvmAddress(bytes32)
+			[0x65, 0x1d, 0xc5, 0xf4] => Ok(Action::GetDefaultEvmAddress),
+			_ => Err(()),
+		}
+	}
+}
+
+/// Answers ABI encoded `EvmAddressMapping` queries.
+pub struct EvmAccountsPrecompile<T>(PhantomData<T>);
+
+impl<T: Config> EvmAccountsPrecompile<T>
+where
+	T::AccountId: IsType<AccountId32>,
+{
+	/// Execute the ABI encoded call `input`, returning the ABI encoded result.
+	pub fn execute(input: &[u8]) -> Result<Vec<u8>, &'static str> {
+		ensure!(input.len() >= SELECTOR_BYTES, "invalid input");
+		let mut selector = [0u8; SELECTOR_BYTES];
+		selector.copy_from_slice(&input[..SELECTOR_BYTES]);
+		let action = Action::try_from(selector).map_err(|_| "invalid action")?;
+		let params = &input[SELECTOR_BYTES..];
+
+		let mut output = [0u8; PER_PARAM_BYTES];
+		match action {
+			Action::GetAccountId => {
+				let address = evm_address_at(params, 0)?;
+				let account_id: AccountId32 = EvmAddressMapping::<T>::get_account_id(&address).into();
+				output.copy_from_slice(account_id.as_ref());
+			}
+			Action::GetEvmAddress => {
+				let account_id = account_id_at::<T>(params, 0)?;
+				if let Some(address) = EvmAddressMapping::<T>::get_evm_address(&account_id) {
+					output[12..].copy_from_slice(address.as_bytes());
+				}
+			}
+			Action::IsLinked => {
+				let account_id = account_id_at::<T>(params, 0)?;
+				let address = evm_address_at(params, 1)?;
+				if EvmAddressMapping::<T>::is_linked(&account_id, &address) {
+					output[PER_PARAM_BYTES - 1] = 1;
+				}
+			}
+			Action::GetDefaultEvmAddress => {
+				let account_id = account_id_at::<T>(params, 0)?;
+				let address = EvmAddressMapping::<T>::get_default_evm_address(&account_id);
+				output[12..].copy_from_slice(address.as_bytes());
+			}
+		}
+
+		Ok(output.to_vec())
+	}
+}
+
+fn param_at(params: &[u8], index: usize) -> Result<&[u8], &'static str> {
+	let start = PER_PARAM_BYTES * index;
+	let end = start + PER_PARAM_BYTES;
+	ensure!(end <= params.len(), "invalid input");
+
+	Ok(&params[start..end])
+}
+
+fn evm_address_at(params: &[u8], index: usize) -> Result<EvmAddress, &'static str> {
+	let param = param_at(params, index)?;
+
+	Ok(EvmAddress::from_slice(&param[12..]))
+}
+
+fn account_id_at<T: Config>(params: &[u8], index: usize) -> Result<T::AccountId, &'static str>
+where
+	T::AccountId: IsType<AccountId32>,
+{
+	let param = param_at(params, index)?;
+
+	let mut account_id = [0u8; 32];
+	account_id.copy_from_slice(param);
+
+	Ok(AccountId32::from(account_id).into())
+}
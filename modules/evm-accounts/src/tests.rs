@@ -5,6 +5,7 @@
 use super::*;
 use frame_support::{assert_noop, assert_ok};
 use mock::{trillian, ford, Event, EvmAccountsModule, ExtBuilder, Origin, Runtime, System, TRILLIAN, FORD};
+use primitives::mocks::InputBuilder;
 use std::str::FromStr;
 
 #[test]
@@ -174,3 +175,74 @@ fn account_to_evm_with_create_default() {
 		);
 	});
 }
+
+#[test]
+fn precompile_works() {
+	ExtBuilder::default().build().execute_with(|| {
+		let evm_account = EvmAccountsModule::eth_address(&trillian());
+		let default_evm_account = EvmAddress::from_str("f0bd9ffde7f9f4394d8cc1d86bf24d87e5d5a9a9").unwrap();
+		let trillian_param: [u8; 32] = TRILLIAN.into();
+		let call = |input: InputBuilder| EvmAccountsPrecompile::<Runtime>::execute(&input.build());
+		// getAccountId(address)
+		let get_account_id = || InputBuilder::selector([0xe0, 0xb4, 0x90, 0xf7]).address(evm_account);
+		// getEvmAddress(bytes32)
+		let get_evm_address = || InputBuilder::selector([0x02, 0x32, 0x02, 0x7e]).bytes32(trillian_param);
+		// getDefaultEvmAddress(bytes32)
+		let get_default_evm_address = || InputBuilder::selector([0x65, 0x1d, 0xc5, 0xf4]).bytes32(trillian_param);
+		// isLinked(bytes32,address)
+		let is_linked = |address: EvmAddress| {
+			InputBuilder::selector([0x39, 0xc0, 0x5b, 0x3f])
+				.bytes32(trillian_param)
+				.address(address)
+		};
+		let not_linked = InputBuilder::default().uint(0).build();
+		let linked = InputBuilder::default().uint(1).build();
+
+		let mut evm_account_to_default = [0u8; 32];
+		evm_account_to_default[0..4].copy_from_slice(b"evm:");
+		evm_account_to_default[4..24].copy_from_slice(evm_account.as_bytes());
+		assert_eq!(call(get_account_id()), Ok(evm_account_to_default.to_vec()));
+		assert_eq!(
+			call(get_evm_address()),
+			Ok(InputBuilder::default().address(EvmAddress::zero()).build())
+		);
+		assert_eq!(
+			call(get_default_evm_address()),
+			Ok(InputBuilder::default().address(default_evm_account).build())
+		);
+		assert_eq!(call(is_linked(evm_account)), Ok(not_linked));
+
+		assert_ok!(EvmAccountsModule::claim_account(
+			Origin::signed(TRILLIAN),
+			evm_account,
+			EvmAccountsModule::eth_sign(&trillian(), &TRILLIAN.encode(), &[][..])
+		));
+
+		assert_eq!(call(get_account_id()), Ok(trillian_param.to_vec()));
+		assert_eq!(
+			call(get_evm_address()),
+			Ok(InputBuilder::default().address(evm_account).build())
+		);
+		assert_eq!(call(is_linked(evm_account)), Ok(linked.clone()));
+		assert_eq!(call(is_linked(default_evm_account)), Ok(linked));
+	});
+}
+
+#[test]
+fn precompile_handles_invalid_input() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_eq!(
+			EvmAccountsPrecompile::<Runtime>::execute(&[0xe0, 0xb4]),
+			Err("invalid input")
+		);
+		assert_eq!(
+			EvmAccountsPrecompile::<Runtime>::execute(&[0u8; 4 + 32]),
+			Err("invalid action")
+		);
+		// isLinked(bytes32,address) without the address
+		let input = InputBuilder::selector([0x39, 0xc0, 0x5b, 0x3f])
+			.bytes32([0u8; 32])
+			.build();
+		assert_eq!(EvmAccountsPrecompile::<Runtime>::execute(&input), Err("invalid input"));
+	});
+}
@@ -8,7 +8,7 @@ use frame_system::EnsureSignedBy;
 use module_evm::GenesisAccount;
 use primitives::{evm::EvmAddress, mocks::MockAddressMapping};
 use sp_core::{bytes::from_hex, crypto::AccountId32, H256};
-use sp_runtime::{testing::Header, traits::IdentityLookup};
+use sp_runtime::{testing::Header, traits::IdentityLookup, ModuleId};
 use sp_std::{collections::btree_map::BTreeMap, str::FromStr};
 
 pub type AccountId = AccountId32;
@@ -76,13 +76,19 @@ impl pallet_timestamp::Config for Runtime {
 parameter_types! {
 	pub const NewContractExtraBytes: u32 = 1;
 	pub NetworkContractSource: EvmAddress = trillian();
+	pub const SponsorModuleId: ModuleId = ModuleId(*b"snp/evsp");
+	pub const MaxBatchCalls: u32 = 4;
+	pub const RecurringCallRetention: BlockNumber = 10;
 }
 
 ord_parameter_types! {
 	pub const CouncilAccount: AccountId32 = AccountId32::from([1u8; 32]);
 	pub const NetworkContractAccount: AccountId32 = AccountId32::from([0u8; 32]);
 	pub const StorageDepositPerByte: u128 = 10;
+	pub const StorageCleanupWeight: u64 = 10;
 	pub const MaxCodeSize: u32 = 60 * 1024;
+	pub const EthereumTransactionStorageLimit: u32 = 60 * 1024;
+	pub const AllowUnprotectedTransactions: bool = false;
 	pub const DeveloperDeposit: u64 = 1000;
 	pub const DeploymentFee: u64 = 200;
 }
@@ -93,12 +99,17 @@ impl module_evm::Config for Runtime {
 	type MergeAccount = ();
 	type NewContractExtraBytes = NewContractExtraBytes;
 	type StorageDepositPerByte = StorageDepositPerByte;
+	type StorageCleanupWeight = StorageCleanupWeight;
 	type MaxCodeSize = MaxCodeSize;
 
 	type Event = Event;
 	type Precompiles = ();
 	type ChainId = ();
 	type GasToWeight = ();
+	type WeightToGas = ();
+	type FindAuthor = ();
+	type Randomness = ();
+	type NetGasMetering = ();
 	type ChargeTransactionPayment = ();
 	type NetworkContractOrigin = EnsureSignedBy<NetworkContractAccount, AccountId32>;
 	type NetworkContractSource = NetworkContractSource;
@@ -106,6 +117,14 @@ impl module_evm::Config for Runtime {
 	type DeveloperDeposit = DeveloperDeposit;
 	type DeploymentFee = DeploymentFee;
 	type FreeDeploymentOrigin = EnsureSignedBy<CouncilAccount, AccountId32>;
+	type PauseOrigin = EnsureSignedBy<CouncilAccount, AccountId32>;
+	type CodeUpgradeOrigin = EnsureSignedBy<CouncilAccount, AccountId32>;
+	type DispatchCallOrigin = EnsureSignedBy<CouncilAccount, AccountId32>;
+	type EthereumTransactionStorageLimit = EthereumTransactionStorageLimit;
+	type AllowUnprotectedTransactions = AllowUnprotectedTransactions;
+	type SponsorModuleId = SponsorModuleId;
+	type MaxBatchCalls = MaxBatchCalls;
+	type RecurringCallRetention = RecurringCallRetention;
 
 	type WeightInfo = ();
 }
@@ -193,6 +212,7 @@ impl ExtBuilder {
 		);
 		module_evm::GenesisConfig::<Runtime> {
 			accounts,
+			dispatchable_calls: Default::default(),
 		}
 		.assimilate_storage(&mut t)
 		.unwrap();
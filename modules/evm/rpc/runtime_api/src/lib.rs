@@ -1,15 +1,24 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 #![allow(clippy::all)]
 
-use ethereum_types::H160;
-use primitives::evm::{CallInfo, CreateInfo, EstimateResourcesRequest};
+use ethereum_types::{H160, H256, U256};
+use primitives::{
+	evm::{
+		AccessListItem, Account, BatchCall, BatchCallInfo, CallInfo, CodeInfo, CodeUpgrade, CreateInfo,
+		EstimateResourcesRequest, Receipt, Sponsorship, StorageUsage, Trace, TracerConfig, VerificationMetadata,
+	},
+	BlockNumber,
+};
 use sp_runtime::{
 	codec::Codec,
-	traits::{MaybeDisplay, MaybeFromStr},
+	traits::{Block as BlockT, MaybeDisplay, MaybeFromStr},
 };
 use sp_std::vec::Vec;
 
 sp_api::decl_runtime_apis! {
+	/// Version 2 changed `call`, `create` and `EstimateResourcesRequest`,
+	/// and added the other calls. Version 3 added `ethereum_transaction`.
+	#[api_version(3)]
 	pub trait EVMRuntimeRPCApi<Balance> where
 		Balance: Codec + MaybeDisplay + MaybeFromStr,
 	{
@@ -20,6 +29,7 @@ sp_api::decl_runtime_apis! {
 			value: Balance,
 			gas_limit: u64,
 			storage_limit: u32,
+			access_list: Vec<AccessListItem>,
 			estimate: bool,
 		) -> Result<CallInfo, sp_runtime::DispatchError>;
 
@@ -29,11 +39,95 @@ sp_api::decl_runtime_apis! {
 			value: Balance,
 			gas_limit: u64,
 			storage_limit: u32,
+			access_list: Vec<AccessListItem>,
 			estimate: bool,
 		) -> Result<CreateInfo, sp_runtime::DispatchError>;
 
+		/// Execute a batch of calls, all applied or all reverted.
+		fn batch_call(
+			from: H160,
+			calls: Vec<BatchCall<Balance>>,
+			estimate: bool,
+		) -> Result<BatchCallInfo, sp_runtime::DispatchError>;
+
  		fn get_estimate_resources_request(
 			data: Vec<u8>
 		) -> Result<EstimateResourcesRequest, sp_runtime::DispatchError>;
+
+		/// Chain ID of EVM.
+		fn chain_id() -> u64;
+
+		/// Balance and nonce of the given address.
+		fn account_basic(address: H160) -> Account;
+
+		/// Contract code deployed at the given address.
+		fn get_code(address: H160) -> Vec<u8>;
+
+		/// Contract storage value at the given address and index.
+		fn get_storage_at(address: H160, index: H256) -> H256;
+
+		/// Receipts of the EVM extrinsics in the current block.
+		fn block_receipts() -> Vec<Receipt>;
+
+		/// Base fee per gas of Ethereum transactions in the next block.
+		fn base_fee_per_gas() -> U256;
+
+		/// Gas used and gas limit of the current block.
+		fn block_gas() -> (U256, U256);
+
+		/// Sponsorship of the calls to the given contract, if any.
+		fn sponsorship(contract: H160) -> Option<Sponsorship<Balance>>;
+
+		/// Storage held by the given contract, if it exists.
+		fn storage_usage(contract: H160) -> Option<StorageUsage<Balance>>;
+
+		/// Metadata for verifying the source of the given contract, if set.
+		fn verification_metadata(contract: H160) -> Option<VerificationMetadata>;
+
+		/// Code upgrade proposed for the given contract, if any.
+		fn code_upgrade(contract: H160) -> Option<CodeUpgrade<BlockNumber>>;
+
+		/// Size and ref count of the code stored under the given hash, if any.
+		fn code_info(code_hash: H256) -> Option<CodeInfo>;
+
+		/// Contracts using the code with the given hash.
+		fn contracts_by_code_hash(code_hash: H256) -> Vec<H160>;
+
+		/// Whether all EVM executions are refused.
+		fn evm_paused() -> bool;
+
+		/// Whether the calls to the given contract are refused.
+		fn contract_paused(contract: H160) -> bool;
+
+		/// Wrap a raw RLP encoded Ethereum transaction into an unsigned
+		/// extrinsic.
+		fn convert_transaction(transaction: Vec<u8>) -> Result<<Block as BlockT>::Extrinsic, sp_runtime::DispatchError>;
+
+		/// RLP encoding of the raw Ethereum transaction wrapped by
+		/// `extrinsic`, if any.
+		fn ethereum_transaction(extrinsic: <Block as BlockT>::Extrinsic) -> Option<Vec<u8>>;
+
+		/// Execute the `extrinsics` of the block with `header` before
+		/// `transaction_index` on top of the parent state, then trace the
+		/// extrinsic at `transaction_index`.
+		fn trace_extrinsic(
+			header: <Block as BlockT>::Header,
+			extrinsics: Vec<<Block as BlockT>::Extrinsic>,
+			transaction_index: u32,
+			tracer: TracerConfig,
+		) -> Result<Trace, sp_runtime::DispatchError>;
+
+		/// Trace an EVM call, or a contract creation if `to` is `None`, with
+		/// the accounts and storage slots of `access_list` warm.
+		fn trace_call(
+			from: H160,
+			to: Option<H160>,
+			data: Vec<u8>,
+			value: Balance,
+			gas_limit: u64,
+			storage_limit: u32,
+			access_list: Vec<AccessListItem>,
+			tracer: TracerConfig,
+		) -> Result<Trace, sp_runtime::DispatchError>;
 	}
 }
@@ -1,4 +1,5 @@
 use ethereum_types::{H160, U256};
+use module_evm::AccessListItem;
  use serde::{Deserialize, Serialize};
 use sp_core::Bytes;
 use sp_rpc::number::NumberOrHex;
@@ -13,13 +14,18 @@ pub struct CallRequest {
 	/// To
 	pub to: Option<H160>,
 	/// Gas Limit
+	#[serde(alias = "gas")]
 	pub gas_limit: Option<u64>,
+	/// Gas Price, accepted for compatibility with Ethereum tooling
+	pub gas_price: Option<U256>,
 	/// Storage Limit
 	pub storage_limit: Option<u32>,
 	/// Value
 	pub value: Option<NumberOrHex>,
 	/// Data
 	pub data: Option<Bytes>,
+	/// Access list, EIP-2930
+	pub access_list: Option<Vec<AccessListItem>>,
 }
 
  /// EstimateResources response
@@ -33,3 +39,26 @@ pub struct EstimateResourcesResponse {
 	/// Adjusted weight fee
 	pub weight_fee: U256,
 }
+
+#[test]
+fn call_request_should_accept_access_list() {
+	let request: CallRequest = serde_json::from_str(
+		r#"{
+			"from": "0x1000000000000000000000000000000000000001",
+			"to": "0x2000000000000000000000000000000000000002",
+			"accessList": [{
+				"address": "0x0000000000000000000000000000000000000003",
+				"storageKeys": ["0x0000000000000000000000000000000000000000000000000000000000000001"]
+			}]
+		}"#,
+	)
+	.unwrap();
+
+	assert_eq!(
+		request.access_list,
+		Some(vec![AccessListItem {
+			address: H160::from_low_u64_be(3),
+			storage_keys: vec![ethereum_types::H256::from_low_u64_be(1)],
+		}])
+	);
+}
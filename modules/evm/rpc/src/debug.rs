@@ -0,0 +1,377 @@
+use ethereum_types::{H256, U256};
+use jsonrpc_core::{Error, ErrorCode, Result};
+use sc_client_api::{AuxStore, BlockBackend};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::Bytes;
+use sp_runtime::{
+	codec::Codec,
+	generic::BlockId,
+	traits::{Block as BlockT, Header as HeaderT, MaybeDisplay, MaybeFromStr},
+};
+use std::convert::{TryFrom, TryInto};
+use std::{marker::PhantomData, sync::Arc};
+
+use crate::call_request::CallRequest;
+use crate::debug_api::DebugApi as DebugApiT;
+use crate::eth::block_hash;
+use crate::index::transaction_location;
+use crate::types::{BlockNumber, CallFrame, StructLog, StructLogs, TraceOptions, TraceResult};
+use crate::{internal_err, to_u128, EVMRuntimeRPCApi, GAS_LIMIT, STORAGE_LIMIT};
+use module_evm::{CallTrace, CallType, ExitError, ExitReason, Trace, TracerConfig};
+
+pub struct DebugApi<B, C, Balance> {
+	client: Arc<C>,
+	_marker: PhantomData<(B, Balance)>,
+}
+
+impl<B, C, Balance> DebugApi<B, C, Balance> {
+	pub fn new(client: Arc<C>) -> Self {
+		Self {
+			client,
+			_marker: Default::default(),
+		}
+	}
+}
+
+impl<B, C, Balance> DebugApi<B, C, Balance>
+where
+	B: BlockT<Hash = H256>,
+	C: ProvideRuntimeApi<B> + HeaderBackend<B> + BlockBackend<B> + AuxStore,
+	C::Api: EVMRuntimeRPCApi<B, Balance>,
+	Balance: Codec + MaybeDisplay + MaybeFromStr,
+{
+	/// Find the block and index of the EVM extrinsic with `hash` in the
+	/// transaction index. `hash` is the hash of its receipt: the hash of the
+	/// Ethereum transaction, or of the extrinsic otherwise.
+	fn find_extrinsic(&self, hash: H256) -> Result<Option<(H256, u32)>> {
+		let (block_hash, index) = match transaction_location(&*self.client, hash).map_err(internal_err)? {
+			Some(location) => location,
+			None => return Ok(None),
+		};
+		let number = match self
+			.client
+			.number(block_hash)
+			.map_err(|err| internal_err(format!("fetch block number failed: {:?}", err)))?
+		{
+			Some(number) => number,
+			None => return Ok(None),
+		};
+
+		// the block left the best chain after a reorg
+		let canonical_hash = self
+			.client
+			.hash(number)
+			.map_err(|err| internal_err(format!("fetch block hash failed: {:?}", err)))?;
+		if canonical_hash != Some(block_hash) {
+			return Ok(None);
+		}
+
+		Ok(Some((block_hash, index)))
+	}
+}
+
+fn tracer_config(options: &Option<TraceOptions>) -> Result<TracerConfig> {
+	match options.as_ref().and_then(|options| options.tracer.as_deref()) {
+		None => Ok(TracerConfig::OpcodeLogger),
+		Some("callTracer") => Ok(TracerConfig::CallTracer),
+		Some(tracer) => Err(Error {
+			code: ErrorCode::InvalidParams,
+			message: format!("Unsupported tracer: {}", tracer),
+			data: None,
+		}),
+	}
+}
+
+fn call_type_name(call_type: CallType) -> &'static str {
+	match call_type {
+		CallType::Call => "CALL",
+		CallType::CallCode => "CALLCODE",
+		CallType::DelegateCall => "DELEGATECALL",
+		CallType::StaticCall => "STATICCALL",
+		CallType::Create => "CREATE",
+		CallType::Create2 => "CREATE2",
+	}
+}
+
+pub(crate) fn exit_error(reason: &ExitReason) -> Option<String> {
+	match reason {
+		ExitReason::Succeed(_) => None,
+		ExitReason::Revert(_) => Some("execution reverted".into()),
+		ExitReason::Error(ExitError::OutOfGas) => Some("out of gas".into()),
+		ExitReason::Error(e) => Some(format!("{:?}", e)),
+		ExitReason::Fatal(e) => Some(format!("{:?}", e)),
+	}
+}
+
+/// Geth mnemonic of the opcode `op`.
+pub fn opcode_name(op: u8) -> String {
+	let name = match op {
+		0x00 => "STOP",
+		0x01 => "ADD",
+		0x02 => "MUL",
+		0x03 => "SUB",
+		0x04 => "DIV",
+		0x05 => "SDIV",
+		0x06 => "MOD",
+		0x07 => "SMOD",
+		0x08 => "ADDMOD",
+		0x09 => "MULMOD",
+		0x0a => "EXP",
+		0x0b => "SIGNEXTEND",
+		0x10 => "LT",
+		0x11 => "GT",
+		0x12 => "SLT",
+		0x13 => "SGT",
+		0x14 => "EQ",
+		0x15 => "ISZERO",
+		0x16 => "AND",
+		0x17 => "OR",
+		0x18 => "XOR",
+		0x19 => "NOT",
+		0x1a => "BYTE",
+		0x1b => "SHL",
+		0x1c => "SHR",
+		0x1d => "SAR",
+		0x20 => "SHA3",
+		0x30 => "ADDRESS",
+		0x31 => "BALANCE",
+		0x32 => "ORIGIN",
+		0x33 => "CALLER",
+		0x34 => "CALLVALUE",
+		0x35 => "CALLDATALOAD",
+		0x36 => "CALLDATASIZE",
+		0x37 => "CALLDATACOPY",
+		0x38 => "CODESIZE",
+		0x39 => "CODECOPY",
+		0x3a => "GASPRICE",
+		0x3b => "EXTCODESIZE",
+		0x3c => "EXTCODECOPY",
+		0x3d => "RETURNDATASIZE",
+		0x3e => "RETURNDATACOPY",
+		0x3f => "EXTCODEHASH",
+		0x40 => "BLOCKHASH",
+		0x41 => "COINBASE",
+		0x42 => "TIMESTAMP",
+		0x43 => "NUMBER",
+		0x44 => "DIFFICULTY",
+		0x45 => "GASLIMIT",
+		0x46 => "CHAINID",
+		0x47 => "SELFBALANCE",
+		0x48 => "BASEFEE",
+		0x50 => "POP",
+		0x51 => "MLOAD",
+		0x52 => "MSTORE",
+		0x53 => "MSTORE8",
+		0x54 => "SLOAD",
+		0x55 => "SSTORE",
+		0x56 => "JUMP",
+		0x57 => "JUMPI",
+		0x58 => "PC",
+		0x59 => "MSIZE",
+		0x5a => "GAS",
+		0x5b => "JUMPDEST",
+		0x60..=0x7f => return format!("PUSH{}", op - 0x5f),
+		0x80..=0x8f => return format!("DUP{}", op - 0x7f),
+		0x90..=0x9f => return format!("SWAP{}", op - 0x8f),
+		0xa0..=0xa4 => return format!("LOG{}", op - 0xa0),
+		0xf0 => "CREATE",
+		0xf1 => "CALL",
+		0xf2 => "CALLCODE",
+		0xf3 => "RETURN",
+		0xf4 => "DELEGATECALL",
+		0xf5 => "CREATE2",
+		0xfa => "STATICCALL",
+		0xfd => "REVERT",
+		0xfe => "INVALID",
+		0xff => "SELFDESTRUCT",
+		_ => return format!("opcode 0x{:x} not defined", op),
+	};
+	name.into()
+}
+
+fn call_frame(trace: CallTrace) -> CallFrame {
+	CallFrame {
+		call_type: call_type_name(trace.call_type).into(),
+		from: trace.from,
+		to: trace.to,
+		value: trace.value,
+		gas: trace.gas.into(),
+		gas_used: trace.gas_used.into(),
+		input: Bytes(trace.input),
+		output: Bytes(trace.output),
+		error: exit_error(&trace.exit_reason),
+		calls: trace.calls.into_iter().map(call_frame).collect(),
+	}
+}
+
+/// Convert the runtime trace to the geth output of the requested tracer.
+fn trace_result(trace: Trace, options: &Option<TraceOptions>) -> Result<TraceResult> {
+	let root = trace
+		.calls
+		.into_iter()
+		.next()
+		.ok_or_else(|| internal_err("no EVM execution to trace"))?;
+
+	match tracer_config(options)? {
+		TracerConfig::CallTracer => Ok(TraceResult::CallTrace(call_frame(root))),
+		TracerConfig::OpcodeLogger => {
+			let disable_stack = options.as_ref().and_then(|o| o.disable_stack).unwrap_or(false);
+			let disable_storage = options.as_ref().and_then(|o| o.disable_storage).unwrap_or(false);
+
+			let struct_logs = trace
+				.steps
+				.into_iter()
+				.map(|step| StructLog {
+					pc: step.pc,
+					op: opcode_name(step.op),
+					gas: step.gas,
+					gas_cost: step.gas_cost,
+					depth: step.depth,
+					stack: if disable_stack {
+						None
+					} else {
+						Some(step.stack.iter().map(|word| U256::from(word.as_bytes())).collect())
+					},
+					storage: if disable_storage {
+						None
+					} else {
+						Some(step.storage.into_iter().collect())
+					},
+				})
+				.collect();
+
+			Ok(TraceResult::StructLogs(StructLogs {
+				gas: root.gas_used.into(),
+				failed: !root.exit_reason.is_succeed(),
+				return_value: Bytes(root.output),
+				struct_logs,
+			}))
+		}
+	}
+}
+
+impl<B, C, Balance> DebugApiT for DebugApi<B, C, Balance>
+where
+	B: BlockT<Hash = H256>,
+	C: ProvideRuntimeApi<B> + HeaderBackend<B> + BlockBackend<B> + AuxStore + Send + Sync + 'static,
+	C::Api: EVMRuntimeRPCApi<B, Balance>,
+	Balance: Codec + MaybeDisplay + MaybeFromStr + Default + Send + Sync + 'static + TryFrom<u128> + Into<U256>,
+{
+	fn trace_transaction(&self, hash: H256, options: Option<TraceOptions>) -> Result<TraceResult> {
+		let config = tracer_config(&options)?;
+		let (block_hash, index) = self.find_extrinsic(hash)?.ok_or_else(|| Error {
+			code: ErrorCode::InvalidParams,
+			message: format!("Unknown transaction: {:?}", hash),
+			data: None,
+		})?;
+
+		let id = BlockId::Hash(block_hash);
+		let header = self
+			.client
+			.header(id)
+			.map_err(|err| internal_err(format!("fetch header failed: {:?}", err)))?
+			.ok_or_else(|| internal_err(format!("unknown block: {:?}", block_hash)))?;
+		let body = self
+			.client
+			.block_body(&id)
+			.map_err(|err| internal_err(format!("fetch block body failed: {:?}", err)))?
+			.unwrap_or_default();
+
+		// replay on top of the parent state
+		let parent = BlockId::Hash(*header.parent_hash());
+		let trace = self
+			.client
+			.runtime_api()
+			.trace_extrinsic(&parent, header, body, index, config)
+			.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
+			.map_err(|err| internal_err(format!("execution fatal: {:?}", err)))?;
+
+		trace_result(trace, &options)
+	}
+
+	fn trace_call(
+		&self,
+		request: CallRequest,
+		number: Option<BlockNumber>,
+		options: Option<TraceOptions>,
+	) -> Result<TraceResult> {
+		let config = tracer_config(&options)?;
+		let hash = block_hash(&*self.client, number)?;
+
+		let CallRequest {
+			from,
+			to,
+			gas_limit,
+			storage_limit,
+			value,
+			data,
+			access_list,
+			..
+		} = request;
+
+		let value = match value {
+			Some(value) => to_u128(value)
+				.and_then(|v| TryInto::<Balance>::try_into(v).map_err(|_| ()))
+				.map_err(|_| Error {
+					code: ErrorCode::InvalidParams,
+					message: format!("Invalid parameter value: {:?}", value),
+					data: None,
+				})?,
+			None => Default::default(),
+		};
+
+		let trace = self
+			.client
+			.runtime_api()
+			.trace_call(
+				&BlockId::Hash(hash),
+				from.unwrap_or_default(),
+				to,
+				data.map(|d| d.0).unwrap_or_default(),
+				value,
+				gas_limit.unwrap_or(GAS_LIMIT).min(GAS_LIMIT),
+				storage_limit.unwrap_or(STORAGE_LIMIT).min(STORAGE_LIMIT),
+				access_list.unwrap_or_default(),
+				config,
+			)
+			.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
+			.map_err(|err| internal_err(format!("execution fatal: {:?}", err)))?;
+
+		trace_result(trace, &options)
+	}
+}
+
+#[test]
+fn opcode_name_should_work() {
+	assert_eq!(opcode_name(0x00), "STOP");
+	assert_eq!(opcode_name(0x48), "BASEFEE");
+	assert_eq!(opcode_name(0x60), "PUSH1");
+	assert_eq!(opcode_name(0x7f), "PUSH32");
+	assert_eq!(opcode_name(0x8f), "DUP16");
+	assert_eq!(opcode_name(0x90), "SWAP1");
+	assert_eq!(opcode_name(0xa4), "LOG4");
+	assert_eq!(opcode_name(0xff), "SELFDESTRUCT");
+	assert_eq!(opcode_name(0x0c), "opcode 0xc not defined");
+}
+
+#[test]
+fn tracer_config_should_work() {
+	let options = |tracer: Option<&str>| {
+		Some(TraceOptions {
+			tracer: tracer.map(Into::into),
+			..Default::default()
+		})
+	};
+
+	assert_eq!(tracer_config(&None), Ok(TracerConfig::OpcodeLogger));
+	assert_eq!(tracer_config(&options(None)), Ok(TracerConfig::OpcodeLogger));
+	assert_eq!(
+		tracer_config(&options(Some("callTracer"))),
+		Ok(TracerConfig::CallTracer)
+	);
+	assert_eq!(
+		tracer_config(&options(Some("prestateTracer"))).map_err(|e| e.code),
+		Err(ErrorCode::InvalidParams)
+	);
+}
@@ -0,0 +1,27 @@
+//! Geth compatible tracing rpc interface.
+
+use ethereum_types::H256;
+use jsonrpc_core::Result;
+use jsonrpc_derive::rpc;
+
+pub use rpc_impl_DebugApi::gen_server::DebugApi as DebugApiServer;
+
+use crate::call_request::CallRequest;
+use crate::types::{BlockNumber, TraceOptions, TraceResult};
+
+/// Geth compatible tracing rpc interface.
+#[rpc(server)]
+pub trait DebugApi {
+	/// Replays the extrinsic with the given hash and returns its trace.
+	#[rpc(name = "debug_traceTransaction")]
+	fn trace_transaction(&self, hash: H256, options: Option<TraceOptions>) -> Result<TraceResult>;
+
+	/// Traces a call on top of the state of the given block.
+	#[rpc(name = "debug_traceCall")]
+	fn trace_call(
+		&self,
+		request: CallRequest,
+		number: Option<BlockNumber>,
+		options: Option<TraceOptions>,
+	) -> Result<TraceResult>;
+}
@@ -0,0 +1,639 @@
+use ethereum_types::{H160, H256, U256, U64};
+use jsonrpc_core::{Error, ErrorCode, Result};
+use sc_client_api::{AuxStore, BlockBackend};
+use sp_api::{ApiExt, ProvideRuntimeApi};
+use sp_blockchain::HeaderBackend;
+use sp_core::Bytes;
+use sp_runtime::{
+	codec::Codec,
+	generic::BlockId,
+	traits::{Block as BlockT, Header as HeaderT, MaybeDisplay, MaybeFromStr},
+	transaction_validity::TransactionSource,
+	SaturatedConversion,
+};
+use sp_transaction_pool::TransactionPool;
+use std::convert::TryFrom;
+use std::sync::Arc;
+
+use pallet_transaction_payment_rpc_runtime_api::TransactionPaymentApi;
+
+use crate::call_request::CallRequest;
+use crate::debug::exit_error;
+use crate::eth_api::EthApi as EthApiT;
+use crate::index::transaction_location;
+use crate::types::{
+	AccessListResult, BlockNumber, BlockTransactions, FeeHistory, Filter, Log, Receipt, RichBlock, Transaction,
+};
+use crate::{internal_err, to_u128, EVMApi, EVMRuntimeRPCApi, GAS_LIMIT, STORAGE_LIMIT};
+use module_evm::{Bloom, EthereumTransaction, TracerConfig};
+
+// maximum number of blocks a single `eth_getLogs` request may scan
+pub const MAX_LOGS_BLOCK_RANGE: u64 = 1_000;
+// maximum number of blocks a single `eth_feeHistory` request may cover
+pub const MAX_FEE_HISTORY: u64 = 1_024;
+// maximum number of traces `eth_createAccessList` runs to settle the list
+pub const MAX_ACCESS_LIST_ROUNDS: u32 = 10;
+
+/// Resolve `number` to the hash of a block known to `client`.
+pub(crate) fn block_hash<B, C>(client: &C, number: Option<BlockNumber>) -> Result<H256>
+where
+	B: BlockT<Hash = H256>,
+	C: HeaderBackend<B>,
+{
+	match number.unwrap_or_default() {
+		BlockNumber::Latest | BlockNumber::Pending => Ok(client.info().best_hash),
+		BlockNumber::Earliest => Ok(client.info().genesis_hash),
+		BlockNumber::Num(n) => client
+			.hash(n.saturated_into())
+			.map_err(|err| internal_err(format!("fetch block hash failed: {:?}", err)))?
+			.ok_or_else(|| Error {
+				code: ErrorCode::InvalidParams,
+				message: format!("Unknown block number: {}", n),
+				data: None,
+			}),
+	}
+}
+
+pub struct EthApi<B, C, P, Balance> {
+	client: Arc<C>,
+	pool: Arc<P>,
+	evm: EVMApi<B, C, Balance>,
+}
+
+impl<B, C, P, Balance> EthApi<B, C, P, Balance> {
+	pub fn new(client: Arc<C>, pool: Arc<P>) -> Self {
+		Self {
+			evm: EVMApi::new(client.clone()),
+			client,
+			pool,
+		}
+	}
+}
+
+impl<B, C, P, Balance> EthApi<B, C, P, Balance>
+where
+	B: BlockT<Hash = H256>,
+	C: HeaderBackend<B>,
+{
+	fn block_hash(&self, number: Option<BlockNumber>) -> Result<H256> {
+		block_hash(&*self.client, number)
+	}
+
+	fn block_height(&self, number: Option<BlockNumber>) -> u64 {
+		match number.unwrap_or_default() {
+			BlockNumber::Latest | BlockNumber::Pending => self.client.info().best_number.saturated_into(),
+			BlockNumber::Earliest => 0,
+			BlockNumber::Num(n) => n,
+		}
+	}
+}
+
+impl<B, C, P, Balance> EthApi<B, C, P, Balance>
+where
+	B: BlockT<Hash = H256>,
+	C: ProvideRuntimeApi<B> + HeaderBackend<B>,
+	C::Api: EVMRuntimeRPCApi<B, Balance>,
+	Balance: Codec + MaybeDisplay + MaybeFromStr,
+{
+	/// Receipts of block `number` with its hash, `None` if the block is unknown.
+	fn block_receipts(&self, number: u64) -> Result<Option<(H256, Vec<module_evm::Receipt>)>> {
+		let block_hash = match self
+			.client
+			.hash(number.saturated_into())
+			.map_err(|err| internal_err(format!("fetch block hash failed: {:?}", err)))?
+		{
+			Some(hash) => hash,
+			None => return Ok(None),
+		};
+
+		let receipts = self
+			.client
+			.runtime_api()
+			.block_receipts(&BlockId::Hash(block_hash))
+			.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?;
+
+		Ok(Some((block_hash, receipts)))
+	}
+
+	/// Base fee per gas at the state of `block_hash`, which applies to the
+	/// block after it.
+	fn base_fee_per_gas_at(&self, block_hash: H256) -> Result<U256> {
+		self.client
+			.runtime_api()
+			.base_fee_per_gas(&BlockId::Hash(block_hash))
+			.map_err(|err| internal_err(format!("runtime error: {:?}", err)))
+	}
+}
+
+/// Whether `bloom` may contain logs matching `filter`.
+fn bloom_matches(bloom: &Bloom, filter: &Filter) -> bool {
+	if let Some(address) = &filter.address {
+		if !address.values().iter().any(|a| bloom.contains_input(a.as_bytes())) {
+			return false;
+		}
+	}
+
+	filter.topics.iter().flatten().flatten().all(|topics| {
+		topics
+			.values()
+			.iter()
+			.any(|topic| bloom.contains_input(topic.as_bytes()))
+	})
+}
+
+/// Tips over `base_fee` at `percentiles` of the gas used in a block.
+fn rewards(receipts: &[module_evm::Receipt], base_fee: U256, percentiles: &[f64]) -> Vec<U256> {
+	let mut tips = receipts
+		.iter()
+		.map(|receipt| (receipt.effective_gas_price.saturating_sub(base_fee), receipt.used_gas))
+		.collect::<Vec<_>>();
+	tips.sort_by(|a, b| a.0.cmp(&b.0));
+	let total_gas = tips.iter().map(|(_, gas)| gas.low_u128() as f64).sum::<f64>();
+
+	percentiles
+		.iter()
+		.map(|percentile| {
+			let threshold = total_gas * percentile / 100.0;
+			let mut gas = 0.0;
+			for (tip, used_gas) in tips.iter() {
+				gas += used_gas.low_u128() as f64;
+				if gas >= threshold {
+					return *tip;
+				}
+			}
+			tips.last().map(|(tip, _)| *tip).unwrap_or_default()
+		})
+		.collect()
+}
+
+/// Convert the logs of `receipt`, numbering them from `log_index`.
+fn receipt_logs(receipt: &module_evm::Receipt, block_hash: H256, block_number: u64, log_index: usize) -> Vec<Log> {
+	receipt
+		.logs
+		.iter()
+		.enumerate()
+		.map(|(i, log)| Log {
+			address: log.address,
+			topics: log.topics.clone(),
+			data: Bytes(log.data.clone()),
+			block_hash,
+			block_number: block_number.into(),
+			transaction_hash: receipt.transaction_hash,
+			transaction_index: receipt.transaction_index.into(),
+			log_index: (log_index + i).into(),
+		})
+		.collect()
+}
+
+/// Ethereum-like view of the extrinsic of `receipt`, with the fields of the
+/// raw Ethereum transaction it wraps, if any.
+fn receipt_transaction(
+	receipt: &module_evm::Receipt,
+	block_hash: H256,
+	block_number: u64,
+	ethereum: Option<EthereumTransaction>,
+) -> Transaction {
+	let mut transaction = Transaction {
+		hash: receipt.transaction_hash,
+		block_hash,
+		block_number: block_number.into(),
+		transaction_index: receipt.transaction_index.into(),
+		from: receipt.from,
+		to: receipt.to,
+		gas_price: receipt.effective_gas_price,
+		gas: receipt.used_gas,
+		..Default::default()
+	};
+	if let Some(ethereum) = ethereum {
+		transaction.nonce = ethereum.nonce;
+		transaction.value = ethereum.value;
+		transaction.gas = ethereum.gas_limit;
+		transaction.input = Bytes(ethereum.input);
+		transaction.v = ethereum.v.into();
+		transaction.r = U256::from_big_endian(ethereum.r.as_bytes());
+		transaction.s = U256::from_big_endian(ethereum.s.as_bytes());
+	}
+	transaction
+}
+
+impl<B, C, P, Balance> EthApiT for EthApi<B, C, P, Balance>
+where
+	B: BlockT<Hash = H256>,
+	C: ProvideRuntimeApi<B> + HeaderBackend<B> + BlockBackend<B> + AuxStore + Send + Sync + 'static,
+	P: TransactionPool<Block = B> + 'static,
+	C::Api: EVMRuntimeRPCApi<B, Balance>,
+	C::Api: TransactionPaymentApi<B, Balance>,
+	Balance: Codec + MaybeDisplay + MaybeFromStr + Default + Send + Sync + 'static + TryFrom<u128> + Into<U256>,
+{
+	fn chain_id(&self) -> Result<U64> {
+		let hash = self.client.info().best_hash;
+		let chain_id = self
+			.client
+			.runtime_api()
+			.chain_id(&BlockId::Hash(hash))
+			.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?;
+
+		Ok(chain_id.into())
+	}
+
+	fn block_number(&self) -> Result<U256> {
+		Ok(self.client.info().best_number.saturated_into::<u64>().into())
+	}
+
+	fn balance(&self, address: H160, number: Option<BlockNumber>) -> Result<U256> {
+		let hash = self.block_hash(number)?;
+		let account = self
+			.client
+			.runtime_api()
+			.account_basic(&BlockId::Hash(hash), address)
+			.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?;
+
+		Ok(account.balance)
+	}
+
+	fn code_at(&self, address: H160, number: Option<BlockNumber>) -> Result<Bytes> {
+		let hash = self.block_hash(number)?;
+		let code = self
+			.client
+			.runtime_api()
+			.get_code(&BlockId::Hash(hash), address)
+			.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?;
+
+		Ok(Bytes(code))
+	}
+
+	fn storage_at(&self, address: H160, index: U256, number: Option<BlockNumber>) -> Result<H256> {
+		let hash = self.block_hash(number)?;
+		let mut key = [0u8; 32];
+		index.to_big_endian(&mut key);
+
+		self.client
+			.runtime_api()
+			.get_storage_at(&BlockId::Hash(hash), address, H256::from(key))
+			.map_err(|err| internal_err(format!("runtime error: {:?}", err)))
+	}
+
+	fn gas_price(&self) -> Result<U256> {
+		self.base_fee_per_gas_at(self.client.info().best_hash)
+	}
+
+	fn fee_history(
+		&self,
+		block_count: U256,
+		newest_block: BlockNumber,
+		reward_percentiles: Option<Vec<f64>>,
+	) -> Result<FeeHistory> {
+		let block_count = block_count.min(U256::from(MAX_FEE_HISTORY)).as_u64();
+		let best: u64 = self.client.info().best_number.saturated_into();
+		let newest = self.block_height(Some(newest_block)).min(best);
+		if block_count == 0 {
+			return Ok(FeeHistory {
+				oldest_block: newest.into(),
+				..Default::default()
+			});
+		}
+		let oldest = newest.saturating_sub(block_count - 1);
+
+		let mut history = FeeHistory {
+			oldest_block: oldest.into(),
+			reward: reward_percentiles.as_ref().map(|_| Vec::new()),
+			..Default::default()
+		};
+		for number in oldest..=newest {
+			let (block_hash, receipts) = self.block_receipts(number)?.ok_or_else(|| Error {
+				code: ErrorCode::InvalidParams,
+				message: format!("Unknown block number: {}", number),
+				data: None,
+			})?;
+			// the base fee of a block is set by its parent
+			let parent_hash = self.block_hash(Some(BlockNumber::Num(number.saturating_sub(1))))?;
+			let base_fee = self.base_fee_per_gas_at(parent_hash)?;
+			let (gas_used, gas_limit) = self
+				.client
+				.runtime_api()
+				.block_gas(&BlockId::Hash(block_hash))
+				.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?;
+
+			history.base_fee_per_gas.push(base_fee);
+			history.gas_used_ratio.push(if gas_limit.is_zero() {
+				0.0
+			} else {
+				gas_used.low_u128() as f64 / gas_limit.low_u128() as f64
+			});
+			if let (Some(percentiles), Some(reward)) = (&reward_percentiles, &mut history.reward) {
+				reward.push(rewards(&receipts, base_fee, percentiles));
+			}
+		}
+
+		let newest_hash = self.block_hash(Some(BlockNumber::Num(newest)))?;
+		history.base_fee_per_gas.push(self.base_fee_per_gas_at(newest_hash)?);
+
+		Ok(history)
+	}
+
+	fn transaction_count(&self, address: H160, number: Option<BlockNumber>) -> Result<U256> {
+		let hash = self.block_hash(number)?;
+		let account = self
+			.client
+			.runtime_api()
+			.account_basic(&BlockId::Hash(hash), address)
+			.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?;
+
+		Ok(account.nonce)
+	}
+
+	fn call(&self, request: CallRequest, number: Option<BlockNumber>) -> Result<Bytes> {
+		let hash = self.block_hash(number)?;
+		self.evm.call_at(request, hash)
+	}
+
+	fn estimate_gas(&self, request: CallRequest, number: Option<BlockNumber>) -> Result<U256> {
+		let hash = self.block_hash(number)?;
+		self.evm.estimate_gas_at(request, hash)
+	}
+
+	fn create_access_list(&self, request: CallRequest, number: Option<BlockNumber>) -> Result<AccessListResult> {
+		let hash = self.block_hash(number)?;
+
+		let CallRequest {
+			from,
+			to,
+			gas_limit,
+			storage_limit,
+			value,
+			data,
+			access_list,
+			..
+		} = request;
+
+		let value = match value {
+			Some(value) => to_u128(value).map_err(|_| Error {
+				code: ErrorCode::InvalidParams,
+				message: format!("Invalid parameter value: {:?}", value),
+				data: None,
+			})?,
+			None => 0,
+		};
+		let data = data.map(|d| d.0).unwrap_or_default();
+
+		// warming the accessed slots can change the execution, trace again
+		// with the generated list until it stops changing, at most
+		// `MAX_ACCESS_LIST_ROUNDS` times as geth does
+		let mut access_list = access_list.unwrap_or_default();
+		let mut round = 0;
+		loop {
+			round += 1;
+			let balance_value = Balance::try_from(value).map_err(|_| Error {
+				code: ErrorCode::InvalidParams,
+				message: format!("Invalid parameter value: {:?}", value),
+				data: None,
+			})?;
+
+			let trace = self
+				.client
+				.runtime_api()
+				.trace_call(
+					&BlockId::Hash(hash),
+					from.unwrap_or_default(),
+					to,
+					data.clone(),
+					balance_value,
+					gas_limit.unwrap_or(GAS_LIMIT).min(GAS_LIMIT),
+					storage_limit.unwrap_or(STORAGE_LIMIT).min(STORAGE_LIMIT),
+					access_list.clone(),
+					TracerConfig::CallTracer,
+				)
+				.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
+				.map_err(|err| internal_err(format!("execution fatal: {:?}", err)))?;
+
+			let root = trace
+				.calls
+				.into_iter()
+				.next()
+				.ok_or_else(|| internal_err("no EVM execution to trace"))?;
+
+			let settled = trace.access_list == access_list;
+			access_list = trace.access_list;
+			if settled || round == MAX_ACCESS_LIST_ROUNDS {
+				return Ok(AccessListResult {
+					access_list,
+					gas_used: root.gas_used.into(),
+					error: exit_error(&root.exit_reason),
+				});
+			}
+		}
+	}
+
+	// Only the EVM extrinsics of the block are reported.
+	fn block_by_number(&self, number: BlockNumber, full: bool) -> Result<Option<RichBlock>> {
+		let number = self.block_height(Some(number));
+		let (block_hash, receipts) = match self.block_receipts(number)? {
+			Some(block) => block,
+			None => return Ok(None),
+		};
+		let id = BlockId::Hash(block_hash);
+
+		let header = self
+			.client
+			.header(id)
+			.map_err(|err| internal_err(format!("fetch header failed: {:?}", err)))?
+			.ok_or_else(|| internal_err(format!("header not found: {:?}", block_hash)))?;
+		let (gas_used, gas_limit) = self
+			.client
+			.runtime_api()
+			.block_gas(&id)
+			.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?;
+		// the base fee of a block is set by the state of its parent
+		let parent_hash = if number == 0 { block_hash } else { *header.parent_hash() };
+		let base_fee_per_gas = self.base_fee_per_gas_at(parent_hash)?;
+
+		let mut logs_bloom = Bloom::default();
+		for receipt in receipts.iter() {
+			logs_bloom.accrue_bloom(&receipt.logs_bloom);
+		}
+
+		let transactions = if full {
+			let body = self
+				.client
+				.block_body(&id)
+				.map_err(|err| internal_err(format!("fetch block body failed: {:?}", err)))?
+				.unwrap_or_default();
+			let api_version = self
+				.client
+				.runtime_api()
+				.api_version::<dyn EVMRuntimeRPCApi<B, Balance>>(&id)
+				.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?;
+
+			let mut transactions = Vec::with_capacity(receipts.len());
+			for receipt in receipts.iter() {
+				// older runtimes can not unwrap the raw Ethereum transactions
+				let ethereum = match body.get(receipt.transaction_index as usize) {
+					Some(extrinsic) if api_version.map_or(false, |version| version >= 3) => self
+						.client
+						.runtime_api()
+						.ethereum_transaction(&id, extrinsic.clone())
+						.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
+						.and_then(|rlp| EthereumTransaction::decode_rlp(&rlp).ok()),
+					_ => None,
+				};
+				transactions.push(receipt_transaction(receipt, block_hash, number, ethereum));
+			}
+			BlockTransactions::Full(transactions)
+		} else {
+			BlockTransactions::Hashes(receipts.iter().map(|receipt| receipt.transaction_hash).collect())
+		};
+
+		Ok(Some(RichBlock {
+			hash: block_hash,
+			parent_hash: *header.parent_hash(),
+			number: number.into(),
+			state_root: *header.state_root(),
+			transactions_root: *header.extrinsics_root(),
+			gas_limit,
+			gas_used,
+			logs_bloom: Bytes(logs_bloom.0.to_vec()),
+			base_fee_per_gas,
+			transactions,
+		}))
+	}
+
+	fn logs(&self, filter: Filter) -> Result<Vec<Log>> {
+		let (from, to) = match filter.block_hash {
+			Some(hash) => {
+				let number = self
+					.client
+					.number(hash)
+					.map_err(|err| internal_err(format!("fetch block number failed: {:?}", err)))?
+					.ok_or_else(|| Error {
+						code: ErrorCode::InvalidParams,
+						message: format!("Unknown block hash: {:?}", hash),
+						data: None,
+					})?
+					.saturated_into::<u64>();
+				(number, number)
+			}
+			None => (self.block_height(filter.from_block), self.block_height(filter.to_block)),
+		};
+
+		if to.saturating_sub(from) >= MAX_LOGS_BLOCK_RANGE {
+			return Err(Error {
+				code: ErrorCode::InvalidParams,
+				message: format!("Block range exceeds limit of {}", MAX_LOGS_BLOCK_RANGE),
+				data: None,
+			});
+		}
+
+		let mut logs = Vec::new();
+		for number in from..=to {
+			let (block_hash, receipts) = match self.block_receipts(number)? {
+				Some(block) => block,
+				None => break,
+			};
+
+			let mut log_index = 0;
+			for receipt in receipts.iter() {
+				if bloom_matches(&receipt.logs_bloom, &filter) {
+					logs.extend(
+						receipt_logs(receipt, block_hash, number, log_index)
+							.into_iter()
+							.filter(|log| filter.matches(&log.address, &log.topics)),
+					);
+				}
+				log_index += receipt.logs.len();
+			}
+		}
+
+		Ok(logs)
+	}
+
+	fn transaction_receipt(&self, hash: H256) -> Result<Option<Receipt>> {
+		let indexed_hash = match transaction_location(&*self.client, hash).map_err(internal_err)? {
+			Some((block_hash, _)) => block_hash,
+			None => return Ok(None),
+		};
+		let number: u64 = match self
+			.client
+			.number(indexed_hash)
+			.map_err(|err| internal_err(format!("fetch block number failed: {:?}", err)))?
+		{
+			Some(number) => number.saturated_into(),
+			None => return Ok(None),
+		};
+
+		// the block left the best chain after a reorg
+		let (block_hash, receipts) = match self.block_receipts(number)? {
+			Some(block) if block.0 == indexed_hash => block,
+			_ => return Ok(None),
+		};
+
+		let mut log_index = 0;
+		for receipt in receipts.iter() {
+			if receipt.transaction_hash == hash {
+				return Ok(Some(Receipt {
+					transaction_hash: receipt.transaction_hash,
+					transaction_index: receipt.transaction_index.into(),
+					block_hash,
+					block_number: number.into(),
+					from: receipt.from,
+					to: receipt.to,
+					cumulative_gas_used: receipt.cumulative_gas_used,
+					gas_used: receipt.used_gas,
+					effective_gas_price: receipt.effective_gas_price,
+					contract_address: receipt.contract_address,
+					logs: receipt_logs(receipt, block_hash, number, log_index),
+					logs_bloom: Bytes(receipt.logs_bloom.0.to_vec()),
+					status: (receipt.status as u64).into(),
+				}));
+			}
+			log_index += receipt.logs.len();
+		}
+
+		Ok(None)
+	}
+
+	fn send_raw_transaction(&self, bytes: Bytes) -> Result<H256> {
+		let transaction = EthereumTransaction::decode_rlp(&bytes).map_err(|err| Error {
+			code: ErrorCode::InvalidParams,
+			message: format!("Invalid Ethereum transaction: {:?}", err),
+			data: None,
+		})?;
+
+		let hash = self.client.info().best_hash;
+		let extrinsic = self
+			.client
+			.runtime_api()
+			.convert_transaction(&BlockId::Hash(hash), bytes.to_vec())
+			.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
+			.map_err(|err| internal_err(format!("execution fatal: {:?}", err)))?;
+
+		futures::executor::block_on(self.pool.submit_one(&BlockId::Hash(hash), TransactionSource::External, extrinsic))
+			.map_err(|err| internal_err(format!("submit transaction to pool failed: {:?}", err)))?;
+
+		Ok(transaction.hash())
+	}
+}
+
+#[test]
+fn rewards_should_work() {
+	let receipt = |effective_gas_price: u64, used_gas: u64| module_evm::Receipt {
+		transaction_hash: H256::zero(),
+		transaction_index: 0,
+		from: H160::zero(),
+		to: None,
+		status: true,
+		used_gas: used_gas.into(),
+		cumulative_gas_used: used_gas.into(),
+		effective_gas_price: effective_gas_price.into(),
+		logs: Vec::new(),
+		logs_bloom: Bloom::default(),
+		contract_address: None,
+	};
+	let receipts = vec![receipt(30, 10_000), receipt(12, 30_000), receipt(5, 60_000)];
+
+	// tips of 20, 2 and 0 after the base fee
+	assert_eq!(
+		rewards(&receipts, 10.into(), &[0.0, 50.0, 70.0, 95.0, 100.0]),
+		vec![0.into(), 0.into(), 2.into(), 20.into(), 20.into()]
+	);
+	assert_eq!(rewards(&[], 10.into(), &[50.0]), vec![U256::zero()]);
+}
@@ -0,0 +1,84 @@
+//! Ethereum compatible rpc interface.
+
+use ethereum_types::{H160, H256, U256, U64};
+use jsonrpc_core::Result;
+use jsonrpc_derive::rpc;
+use sp_core::Bytes;
+
+pub use rpc_impl_EthApi::gen_server::EthApi as EthApiServer;
+
+use crate::call_request::CallRequest;
+use crate::types::{AccessListResult, BlockNumber, FeeHistory, Filter, Log, Receipt, RichBlock};
+
+/// Ethereum compatible rpc interface.
+#[rpc(server)]
+pub trait EthApi {
+	/// Returns the chain ID used for transaction signing.
+	#[rpc(name = "eth_chainId")]
+	fn chain_id(&self) -> Result<U64>;
+
+	/// Returns the number of the best block.
+	#[rpc(name = "eth_blockNumber")]
+	fn block_number(&self) -> Result<U256>;
+
+	/// Returns the balance of the given address.
+	#[rpc(name = "eth_getBalance")]
+	fn balance(&self, address: H160, number: Option<BlockNumber>) -> Result<U256>;
+
+	/// Returns the code deployed at the given address.
+	#[rpc(name = "eth_getCode")]
+	fn code_at(&self, address: H160, number: Option<BlockNumber>) -> Result<Bytes>;
+
+	/// Returns the contract storage value at the given address and index.
+	#[rpc(name = "eth_getStorageAt")]
+	fn storage_at(&self, address: H160, index: U256, number: Option<BlockNumber>) -> Result<H256>;
+
+	/// Returns the base fee per gas of the next block.
+	#[rpc(name = "eth_gasPrice")]
+	fn gas_price(&self) -> Result<U256>;
+
+	/// Returns base fees, block fullness and tips of up to `block_count`
+	/// blocks ending at `newest_block`.
+	#[rpc(name = "eth_feeHistory")]
+	fn fee_history(
+		&self,
+		block_count: U256,
+		newest_block: BlockNumber,
+		reward_percentiles: Option<Vec<f64>>,
+	) -> Result<FeeHistory>;
+
+	/// Returns the nonce of the given address.
+	#[rpc(name = "eth_getTransactionCount")]
+	fn transaction_count(&self, address: H160, number: Option<BlockNumber>) -> Result<U256>;
+
+	/// Call contract, returning the output data.
+	#[rpc(name = "eth_call")]
+	fn call(&self, request: CallRequest, number: Option<BlockNumber>) -> Result<Bytes>;
+
+	/// Estimate gas needed for execution of given contract.
+	#[rpc(name = "eth_estimateGas")]
+	fn estimate_gas(&self, request: CallRequest, number: Option<BlockNumber>) -> Result<U256>;
+
+	/// Generates an access list of the accounts and storage slots accessed
+	/// by the given call, with the gas it uses with the list.
+	#[rpc(name = "eth_createAccessList")]
+	fn create_access_list(&self, request: CallRequest, number: Option<BlockNumber>) -> Result<AccessListResult>;
+
+	/// Returns the block with the given number, with its EVM transactions as
+	/// hashes or, if `full`, in full.
+	#[rpc(name = "eth_getBlockByNumber")]
+	fn block_by_number(&self, number: BlockNumber, full: bool) -> Result<Option<RichBlock>>;
+
+	/// Returns the logs matching the given filter.
+	#[rpc(name = "eth_getLogs")]
+	fn logs(&self, filter: Filter) -> Result<Vec<Log>>;
+
+	/// Returns the receipt of the transaction with the given hash, searching
+	/// recent blocks only.
+	#[rpc(name = "eth_getTransactionReceipt")]
+	fn transaction_receipt(&self, hash: H256) -> Result<Option<Receipt>>;
+
+	/// Submits a signed, RLP encoded Ethereum transaction and returns its hash.
+	#[rpc(name = "eth_sendRawTransaction")]
+	fn send_raw_transaction(&self, bytes: Bytes) -> Result<H256>;
+}
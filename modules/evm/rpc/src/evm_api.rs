@@ -1,6 +1,6 @@
 //! EVM rpc interface.
 
-use ethereum_types::{U256, H160};
+use ethereum_types::{U256, H160, H256};
 use jsonrpc_core::Result;
 use jsonrpc_derive::rpc;
 use sp_core::Bytes;
@@ -8,6 +8,7 @@ use sp_core::Bytes;
 pub use rpc_impl_EVMApi::gen_server::EVMApi as EVMApiServer;
 
 use crate::call_request::{CallRequest, EstimateResourcesResponse};
+use crate::types::{CodeInfo, CodeUpgrade, PauseStatus, Sponsorship, StorageUsage, VerificationMetadata};
 
 /// EVM rpc interface.
 #[rpc(server)]
@@ -23,4 +24,33 @@ pub trait EVMApi<BlockHash> {
 	/// Estimate resources needed for execution of given contract.
 	#[rpc(name = "evm_estimateResources")]
 	fn estimate_resources(&self, from: H160, unsigned_extrinsic: Bytes, at: Option<BlockHash>) -> Result<EstimateResourcesResponse>;
+
+	/// Returns the sponsorship of the calls to the given contract.
+	#[rpc(name = "evm_getSponsorship")]
+	fn sponsorship(&self, contract: H160, at: Option<BlockHash>) -> Result<Option<Sponsorship>>;
+
+	/// Returns the storage held by the given contract.
+	#[rpc(name = "evm_getStorageUsage")]
+	fn storage_usage(&self, contract: H160, at: Option<BlockHash>) -> Result<Option<StorageUsage>>;
+
+	/// Returns the metadata for verifying the source of the given contract.
+	#[rpc(name = "evm_getVerificationMetadata")]
+	fn verification_metadata(&self, contract: H160, at: Option<BlockHash>) -> Result<Option<VerificationMetadata>>;
+
+	/// Returns the code upgrade proposed for the given contract.
+	#[rpc(name = "evm_getCodeUpgrade")]
+	fn code_upgrade(&self, contract: H160, at: Option<BlockHash>) -> Result<Option<CodeUpgrade>>;
+
+	/// Returns the size and ref count of the code with the given hash.
+	#[rpc(name = "evm_getCodeInfo")]
+	fn code_info(&self, code_hash: H256, at: Option<BlockHash>) -> Result<Option<CodeInfo>>;
+
+	/// Returns the contracts using the code with the given hash.
+	#[rpc(name = "evm_getContractsByCodeHash")]
+	fn contracts_by_code_hash(&self, code_hash: H256, at: Option<BlockHash>) -> Result<Vec<H160>>;
+
+	/// Returns whether the EVM, and the calls to the given contract, are
+	/// paused.
+	#[rpc(name = "evm_getPauseStatus")]
+	fn pause_status(&self, contract: H160, at: Option<BlockHash>) -> Result<PauseStatus>;
 }
@@ -0,0 +1,136 @@
+//! Index of the EVM transactions by hash, kept in the auxiliary database of
+//! the client so that receipts and traces are found without scanning blocks.
+
+use ethereum_types::H256;
+use frame_support::debug;
+use futures::StreamExt;
+use sc_client_api::{AuxStore, BlockchainEvents};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{
+	codec::{Codec, Decode, Encode},
+	generic::BlockId,
+	traits::{Block as BlockT, Header as HeaderT, MaybeDisplay, MaybeFromStr},
+	SaturatedConversion,
+};
+use std::sync::Arc;
+
+use crate::EVMRuntimeRPCApi;
+
+const TRANSACTION_PREFIX: &[u8] = b"evm_rpc:transaction:";
+// number of the last best block whose transactions are indexed
+const INDEXED_BLOCK_KEY: &[u8] = b"evm_rpc:indexed_block";
+
+fn transaction_key(hash: &H256) -> Vec<u8> {
+	[TRANSACTION_PREFIX, hash.as_bytes()].concat()
+}
+
+/// Hash of the block and index of the extrinsic of the EVM transaction with
+/// `hash`, if indexed.
+pub fn transaction_location<C: AuxStore>(client: &C, hash: H256) -> Result<Option<(H256, u32)>, String> {
+	let location = client
+		.get_aux(&transaction_key(&hash))
+		.map_err(|err| format!("fetch transaction index failed: {:?}", err))?;
+	Ok(location.and_then(|location| Decode::decode(&mut &location[..]).ok()))
+}
+
+/// Index the EVM transactions of the block with `block_hash`, by the hashes
+/// of their receipts.
+fn index_block<B, C, Balance>(client: &C, block_hash: H256) -> Result<(), String>
+where
+	B: BlockT<Hash = H256>,
+	C: ProvideRuntimeApi<B> + AuxStore,
+	C::Api: EVMRuntimeRPCApi<B, Balance>,
+	Balance: Codec + MaybeDisplay + MaybeFromStr,
+{
+	let receipts = client
+		.runtime_api()
+		.block_receipts(&BlockId::Hash(block_hash))
+		.map_err(|err| format!("runtime error: {:?}", err))?;
+
+	let entries = receipts
+		.iter()
+		.map(|receipt| {
+			(
+				transaction_key(&receipt.transaction_hash),
+				(block_hash, receipt.transaction_index).encode(),
+			)
+		})
+		.collect::<Vec<_>>();
+	client
+		.insert_aux(
+			entries
+				.iter()
+				.map(|(key, value)| (&key[..], &value[..]))
+				.collect::<Vec<_>>()
+				.iter(),
+			&[],
+		)
+		.map_err(|err| format!("write transaction index failed: {:?}", err))
+}
+
+/// Index the canonical blocks after the last indexed one up to `best`.
+fn index_canonical_blocks<B, C, Balance>(client: &C, best: u64) -> Result<(), String>
+where
+	B: BlockT<Hash = H256>,
+	C: ProvideRuntimeApi<B> + HeaderBackend<B> + AuxStore,
+	C::Api: EVMRuntimeRPCApi<B, Balance>,
+	Balance: Codec + MaybeDisplay + MaybeFromStr,
+{
+	let indexed = client
+		.get_aux(INDEXED_BLOCK_KEY)
+		.map_err(|err| format!("fetch transaction index failed: {:?}", err))?
+		.and_then(|number| u64::decode(&mut &number[..]).ok());
+
+	let from = indexed.map_or(0, |number| number + 1);
+	for number in from..=best {
+		let block_hash = match client
+			.hash(number.saturated_into())
+			.map_err(|err| format!("fetch block hash failed: {:?}", err))?
+		{
+			Some(hash) => hash,
+			None => break,
+		};
+		index_block::<B, C, Balance>(client, block_hash)?;
+		client
+			.insert_aux(&[(INDEXED_BLOCK_KEY, &number.encode()[..])], &[])
+			.map_err(|err| format!("write transaction index failed: {:?}", err))?;
+	}
+
+	Ok(())
+}
+
+/// Keep the transaction index up to date with the best chain. Blocks
+/// imported before the index existed are indexed on the first new best
+/// block, and the blocks enacted by a reorg are indexed again, so the index
+/// points to the best chain.
+pub async fn index_transactions<B, C, Balance>(client: Arc<C>)
+where
+	B: BlockT<Hash = H256>,
+	C: ProvideRuntimeApi<B> + HeaderBackend<B> + BlockchainEvents<B> + AuxStore,
+	C::Api: EVMRuntimeRPCApi<B, Balance>,
+	Balance: Codec + MaybeDisplay + MaybeFromStr,
+{
+	let mut notifications = client.import_notification_stream();
+	while let Some(notification) = notifications.next().await {
+		if !notification.is_new_best {
+			continue;
+		}
+
+		// the route of a reorg ends at the parent of the new best block
+		let mut enacted = notification
+			.tree_route
+			.map(|route| route.enacted().iter().map(|block| block.hash).collect::<Vec<_>>())
+			.unwrap_or_default();
+		enacted.push(notification.hash);
+		let result = enacted
+			.into_iter()
+			.try_for_each(|block_hash| index_block::<B, C, Balance>(&*client, block_hash))
+			.and_then(|_| {
+				index_canonical_blocks::<B, C, Balance>(&*client, (*notification.header.number()).saturated_into())
+			});
+		if let Err(err) = result {
+			debug::warn!(target: "evm-rpc", "indexing EVM transactions failed: {}", err);
+		}
+	}
+}
@@ -1,12 +1,12 @@
 #![allow(clippy::upper_case_acronyms)]
 
 use frame_support::debug;
-use ethereum_types::{U256, H160};
+use ethereum_types::{U256, H160, H256};
 use jsonrpc_core::{Error, ErrorCode, Result, Value};
 use rustc_hex::ToHex;
-use sp_api::ProvideRuntimeApi;
+use sp_api::{ApiExt, ProvideRuntimeApi};
 use sp_blockchain::HeaderBackend;
-use sp_core::{Bytes, Decode};
+use sp_core::{hashing::keccak_256, Bytes, Decode};
 use sp_rpc::number::NumberOrHex;
 use sp_runtime::{
 	codec::Codec,
@@ -19,14 +19,31 @@ use std::{marker::PhantomData, sync::Arc};
 
 use call_request::{CallRequest, EstimateResourcesResponse};
 pub use module_evm::{AddressMapping, ExitError, ExitReason};
+use module_evm::BatchCall;
 pub use module_evm_rpc_runtime_api::EVMRuntimeRPCApi;
 
 use pallet_transaction_payment_rpc_runtime_api::TransactionPaymentApi;
 
+pub use crate::debug::DebugApi;
+pub use crate::debug_api::{DebugApi as DebugApiT, DebugApiServer};
+pub use crate::eth::EthApi;
+pub use crate::eth_api::{EthApi as EthApiT, EthApiServer};
 pub use crate::evm_api::{EVMApi as EVMApiT, EVMApiServer};
+pub use crate::index::index_transactions;
+pub use crate::types::{
+	AccessListResult, BlockNumber, CallFrame, CodeInfo, CodeUpgrade, FeeHistory, Filter, Log, PauseStatus, Receipt,
+	RichBlock, Sponsorship, StorageUsage, StructLog, StructLogs, TraceOptions, TraceResult, VariadicValue,
+	VerificationMetadata,
+};
 
 mod call_request;
+mod debug;
+mod debug_api;
+mod eth;
+mod eth_api;
 mod evm_api;
+mod index;
+mod types;
 
 // default gas and storage limits:
 // limits only apply to call() API
@@ -111,7 +128,7 @@ fn to_u128(val: NumberOrHex) -> std::result::Result<u128, ()> {
 	val.into_u256().try_into().map_err(|_| ())
 }
 
-impl<B, C, Balance> EVMApiT<B> for EVMApi<B, C, Balance>
+impl<B, C, Balance> EVMApi<B, C, Balance>
 where
 	B: BlockT,
 	C: ProvideRuntimeApi<B> + HeaderBackend<B> + Send + Sync + 'static,
@@ -119,12 +136,8 @@ where
 	C::Api: TransactionPaymentApi<B, Balance>,
 	Balance: Codec + MaybeDisplay + MaybeFromStr + Default + Send + Sync + 'static + TryFrom<u128> + Into<U256>,
 {
-	fn call(&self, request: CallRequest, at: Option<B>) -> Result<Bytes> {
-		let hash = match at {
-			Some(hash) => hash.hash(),
-			None => self.client.info().best_hash,
-		};
-
+	/// Execute `request` against the state at block `hash`.
+	pub fn call_at(&self, request: CallRequest, hash: B::Hash) -> Result<Bytes> {
 		let CallRequest {
 			from,
 			to,
@@ -132,6 +145,8 @@ where
 			storage_limit,
 			value,
 			data,
+			access_list,
+			..
 		} = request;
 
 		let gas_limit = gas_limit.unwrap_or(GAS_LIMIT).min(GAS_LIMIT);
@@ -163,6 +178,7 @@ where
 						balance_value,
 						gas_limit,
 						storage_limit,
+						access_list.unwrap_or_default(),
 						false,
 					)
 					.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
@@ -181,6 +197,7 @@ where
 						balance_value,
 						gas_limit,
 						storage_limit,
+						access_list.unwrap_or_default(),
 						false,
 					)
 					.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
@@ -193,12 +210,32 @@ where
 		}
 	}
 
-	fn estimate_gas(&self, request: CallRequest, at: Option<B>) -> Result<U256> {
-		let hash = match at {
-			Some(hash) => hash.hash(),
-			None => self.client.info().best_hash,
-		};
+	/// Adjusted weight fee of `unsigned_extrinsic` at block `hash`.
+	fn weight_fee(&self, hash: B::Hash, unsigned_extrinsic: &Bytes) -> Result<U256> {
+		let uxt: <B as BlockT>::Extrinsic = Decode::decode(&mut &**unsigned_extrinsic).map_err(|e| Error {
+			code: ErrorCode::InternalError,
+			message: "Unable to dry run extrinsic.".into(),
+			data: Some(format!("{:?}", e).into()),
+		})?;
+
+		let fee = self
+			.client
+			.runtime_api()
+			.query_fee_details(&BlockId::Hash(hash), uxt, unsigned_extrinsic.len() as u32)
+			.map_err(|e| Error {
+				code: ErrorCode::InternalError,
+				message: "Unable to query fee details.".into(),
+				data: Some(format!("{:?}", e).into()),
+			})?;
 
+		Ok(fee
+			.inclusion_fee
+			.map_or_else(Default::default, |inclusion| inclusion.adjusted_weight_fee)
+			.into())
+	}
+
+	/// Estimate gas used by `request` against the state at block `hash`.
+	pub fn estimate_gas_at(&self, request: CallRequest, hash: B::Hash) -> Result<U256> {
 		let calculate_gas_used = |request| {
 			let CallRequest {
 				from,
@@ -207,6 +244,8 @@ where
 				storage_limit,
 				value,
 				data,
+				access_list,
+				..
 			} = request;
 
 			let gas_limit = gas_limit.unwrap_or(GAS_LIMIT).min(GAS_LIMIT);
@@ -238,6 +277,7 @@ where
 							balance_value,
 							gas_limit,
 							storage_limit,
+							access_list.unwrap_or_default(),
 							true,
 						)
 						.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
@@ -258,6 +298,7 @@ where
 							balance_value,
 							gas_limit,
 							storage_limit,
+							access_list.unwrap_or_default(),
 							true,
 						)
 						.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
@@ -319,7 +360,33 @@ where
 			calculate_gas_used(request)
 		}
 	}
+}
+
+impl<B, C, Balance> EVMApiT<B> for EVMApi<B, C, Balance>
+where
+	B: BlockT,
+	C: ProvideRuntimeApi<B> + HeaderBackend<B> + Send + Sync + 'static,
+	C::Api: EVMRuntimeRPCApi<B, Balance>,
+	C::Api: TransactionPaymentApi<B, Balance>,
+	Balance: Codec + MaybeDisplay + MaybeFromStr + Default + Send + Sync + 'static + TryFrom<u128> + Into<U256>,
+{
+	fn call(&self, request: CallRequest, at: Option<B>) -> Result<Bytes> {
+		let hash = match at {
+			Some(hash) => hash.hash(),
+			None => self.client.info().best_hash,
+		};
+
+		self.call_at(request, hash)
+	}
+
+	fn estimate_gas(&self, request: CallRequest, at: Option<B>) -> Result<U256> {
+		let hash = match at {
+			Some(hash) => hash.hash(),
+			None => self.client.info().best_hash,
+		};
 
+		self.estimate_gas_at(request, hash)
+	}
 
 	fn estimate_resources(
 		&self,
@@ -331,6 +398,16 @@ where
 			Some(hash) => hash.hash(),
 			None => self.client.info().best_hash,
 		};
+		// older runtimes encode `EstimateResourcesRequest` without the batch calls
+		let api_version = self
+			.client
+			.runtime_api()
+			.api_version::<dyn EVMRuntimeRPCApi<B, Balance>>(&BlockId::Hash(hash))
+			.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?;
+		if api_version.map_or(true, |version| version < 2) {
+			return Err(internal_err("estimate_resources is not supported by the runtime at this block"));
+		}
+
 		let request = self
 			.client
 			.runtime_api()
@@ -338,6 +415,41 @@ where
 			.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
 			.map_err(|err| internal_err(format!("execution fatal: {:?}", err)))?;
 
+		if let Some(calls) = request.batch {
+			let calls = calls
+				.into_iter()
+				.map(|call| {
+					let value = TryInto::<Balance>::try_into(call.value).map_err(|_| Error {
+						code: ErrorCode::InvalidParams,
+						message: format!("Invalid parameter value: {:?}", call.value),
+						data: None,
+					})?;
+					Ok(BatchCall {
+						target: call.target,
+						input: call.input,
+						value,
+						gas_limit: call.gas_limit,
+						storage_limit: call.storage_limit,
+					})
+				})
+				.collect::<Result<Vec<_>>>()?;
+
+			let info = self
+				.client
+				.runtime_api()
+				.batch_call(&BlockId::Hash(hash), from, calls, true)
+				.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
+				.map_err(|err| internal_err(format!("execution fatal: {:?}", err)))?;
+
+			error_on_execution_failure(&info.exit_reason, &info.output)?;
+
+			return Ok(EstimateResourcesResponse {
+				gas: info.used_gas,
+				storage: info.used_storage,
+				weight_fee: self.weight_fee(hash, &unsigned_extrinsic)?,
+			});
+		}
+
 		let request = CallRequest {
 			from: Some(from),
 			to: request.to,
@@ -345,6 +457,8 @@ where
 			storage_limit: request.storage_limit,
 			value: request.value.map(|v| NumberOrHex::Hex(U256::from(v))),
 			data: request.data.map(Bytes),
+			gas_price: None,
+			access_list: request.access_list,
 		};
 
 		let calculate_gas_used = |request| -> Result<(U256, i32)> {
@@ -356,6 +470,8 @@ where
 				storage_limit,
 				value,
 				data,
+				access_list,
+				..
 			} = request;
 
 			let gas_limit = gas_limit.unwrap_or(GAS_LIMIT).min(GAS_LIMIT);
@@ -387,6 +503,7 @@ where
 							balance_value,
 							gas_limit,
 							storage_limit,
+							access_list.unwrap_or_default(),
 							true,
 						)
 						.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
@@ -407,6 +524,7 @@ where
 							balance_value,
 							gas_limit,
 							storage_limit,
+							access_list.unwrap_or_default(),
 							true,
 						)
 						.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
@@ -481,67 +599,158 @@ where
 				}
 			}
 
-			let uxt: <B as BlockT>::Extrinsic =
-				Decode::decode(&mut &*unsigned_extrinsic)
-				.map_err(|e| Error {
-					code: ErrorCode::InternalError,
-					message: "Unable to dry run extrinsic.".into(),
-					data: Some(format!("{:?}", e).into()),
-				})?;
-
-			let fee = self
-				.client
-				.runtime_api()
-				.query_fee_details(&BlockId::Hash(hash), uxt, unsigned_extrinsic.len() as u32)
-				.map_err(|e| Error {
-					code: ErrorCode::InternalError,
-					message: "Unable to query fee details.".into(),
-					data: Some(format!("{:?}", e).into()),
-				})?;
-
-			let adjusted_weight_fee = fee
-				.inclusion_fee
-				.map_or_else(Default::default, |inclusion| inclusion.adjusted_weight_fee);
-
 			Ok(EstimateResourcesResponse {
 				gas: best,
 				storage,
-				weight_fee: adjusted_weight_fee.into(),
+				weight_fee: self.weight_fee(hash, &unsigned_extrinsic)?,
 			})
 		} else {
 			let (used_gas, used_storage) = calculate_gas_used(request)?;
 
-			let uxt: <B as BlockT>::Extrinsic =
-				Decode::decode(&mut &*unsigned_extrinsic)
-				.map_err(|e| Error {
-					code: ErrorCode::InternalError,
-					message: "Unable to dry run extrinsic.".into(),
-					data: Some(format!("{:?}", e).into()),
-				})?;
-
-			let fee = self
-				.client
-				.runtime_api()
-				.query_fee_details(&BlockId::Hash(hash), uxt, unsigned_extrinsic.len() as u32)
-				.map_err(|e| Error {
-					code: ErrorCode::InternalError,
-					message: "Unable to query fee details.".into(),
-					data: Some(format!("{:?}", e).into()),
-				})?;
-
-			let adjusted_weight_fee = fee
-				.inclusion_fee
-				.map_or_else(Default::default, |inclusion| inclusion.adjusted_weight_fee);
-
 			Ok(EstimateResourcesResponse {
 				gas: used_gas,
 				storage: used_storage,
-				weight_fee: adjusted_weight_fee.into(),
+				weight_fee: self.weight_fee(hash, &unsigned_extrinsic)?,
 			})
 		}
 	}
 
+	fn sponsorship(&self, contract: H160, at: Option<B>) -> Result<Option<Sponsorship>> {
+		let hash = match at {
+			Some(hash) => hash.hash(),
+			None => self.client.info().best_hash,
+		};
+
+		let sponsorship = self
+			.client
+			.runtime_api()
+			.sponsorship(&BlockId::Hash(hash), contract)
+			.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?;
+
+		Ok(sponsorship.map(|sponsorship| Sponsorship {
+			balance: sponsorship.balance.into(),
+			per_caller_limit: sponsorship.limits.per_caller.map(Into::into),
+			per_block_limit: sponsorship.limits.per_block.map(Into::into),
+			used_in_block: sponsorship.used_in_block.into(),
+		}))
+	}
+
+	fn storage_usage(&self, contract: H160, at: Option<B>) -> Result<Option<StorageUsage>> {
+		let hash = match at {
+			Some(hash) => hash.hash(),
+			None => self.client.info().best_hash,
+		};
+
+		let usage = self
+			.client
+			.runtime_api()
+			.storage_usage(&BlockId::Hash(hash), contract)
+			.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?;
+
+		Ok(usage.map(|usage| StorageUsage {
+			storage_size: usage.storage_size,
+			reserved_deposit: usage.reserved_deposit.into(),
+			code_size: usage.code_size,
+		}))
+	}
+
+	fn verification_metadata(&self, contract: H160, at: Option<B>) -> Result<Option<VerificationMetadata>> {
+		let hash = match at {
+			Some(hash) => hash.hash(),
+			None => self.client.info().best_hash,
+		};
+
+		let api = self.client.runtime_api();
+		let metadata = match api
+			.verification_metadata(&BlockId::Hash(hash), contract)
+			.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
+		{
+			Some(metadata) => metadata,
+			None => return Ok(None),
+		};
+		let code = api
+			.get_code(&BlockId::Hash(hash), contract)
+			.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?;
+
+		Ok(Some(VerificationMetadata {
+			code_hash: keccak_256(&code).into(),
+			compiler_version: String::from_utf8_lossy(&metadata.compiler_version).into_owned(),
+			optimizer_enabled: metadata.optimizer_enabled,
+			optimizer_runs: metadata.optimizer_runs,
+			source_hash: metadata.source_hash.into(),
+			abi_hash: metadata.abi_hash,
+		}))
+	}
 
+	fn code_upgrade(&self, contract: H160, at: Option<B>) -> Result<Option<CodeUpgrade>> {
+		let hash = match at {
+			Some(hash) => hash.hash(),
+			None => self.client.info().best_hash,
+		};
+
+		let upgrade = self
+			.client
+			.runtime_api()
+			.code_upgrade(&BlockId::Hash(hash), contract)
+			.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?;
+
+		Ok(upgrade.map(|upgrade| CodeUpgrade {
+			code_hash: keccak_256(&upgrade.code).into(),
+			code_size: upgrade.code.len() as u32,
+			delay: upgrade.delay.into(),
+			execute_at: upgrade.execute_at.map(Into::into),
+		}))
+	}
+
+	fn code_info(&self, code_hash: H256, at: Option<B>) -> Result<Option<CodeInfo>> {
+		let hash = match at {
+			Some(hash) => hash.hash(),
+			None => self.client.info().best_hash,
+		};
+
+		let code_info = self
+			.client
+			.runtime_api()
+			.code_info(&BlockId::Hash(hash), code_hash)
+			.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?;
+
+		Ok(code_info.map(|code_info| CodeInfo {
+			code_size: code_info.code_size,
+			ref_count: code_info.ref_count,
+		}))
+	}
+
+	fn contracts_by_code_hash(&self, code_hash: H256, at: Option<B>) -> Result<Vec<H160>> {
+		let hash = match at {
+			Some(hash) => hash.hash(),
+			None => self.client.info().best_hash,
+		};
+
+		self.client
+			.runtime_api()
+			.contracts_by_code_hash(&BlockId::Hash(hash), code_hash)
+			.map_err(|err| internal_err(format!("runtime error: {:?}", err)))
+	}
+
+	fn pause_status(&self, contract: H160, at: Option<B>) -> Result<PauseStatus> {
+		let hash = match at {
+			Some(hash) => hash.hash(),
+			None => self.client.info().best_hash,
+		};
+
+		let api = self.client.runtime_api();
+		let evm_paused = api
+			.evm_paused(&BlockId::Hash(hash))
+			.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?;
+		let contract_paused = api
+			.contract_paused(&BlockId::Hash(hash), contract)
+			.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?;
+
+		Ok(PauseStatus {
+			evm_paused,
+			contract_paused,
+		})
+	}
 }
 
 #[test]
@@ -0,0 +1,530 @@
+use ethereum_types::{H160, H256, U256, U64};
+use module_evm::AccessListItem;
+use serde::{de::Error as DeError, Deserialize, Deserializer, Serialize, Serializer};
+use sp_core::Bytes;
+use std::collections::BTreeMap;
+
+/// Block number tag or height, as accepted by the `eth_` namespace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockNumber {
+	/// Latest block
+	Latest,
+	/// Earliest block (genesis)
+	Earliest,
+	/// Pending block, treated as latest
+	Pending,
+	/// Block with the given number
+	Num(u64),
+}
+
+impl Default for BlockNumber {
+	fn default() -> Self {
+		BlockNumber::Latest
+	}
+}
+
+impl<'a> Deserialize<'a> for BlockNumber {
+	fn deserialize<D>(deserializer: D) -> Result<BlockNumber, D::Error>
+	where
+		D: Deserializer<'a>,
+	{
+		let value = String::deserialize(deserializer)?;
+		match value.as_str() {
+			"latest" => Ok(BlockNumber::Latest),
+			"earliest" => Ok(BlockNumber::Earliest),
+			"pending" => Ok(BlockNumber::Pending),
+			_ if value.starts_with("0x") => u64::from_str_radix(&value[2..], 16)
+				.map(BlockNumber::Num)
+				.map_err(|e| D::Error::custom(format!("Invalid block number: {}", e))),
+			_ => value
+				.parse::<u64>()
+				.map(BlockNumber::Num)
+				.map_err(|_| D::Error::custom("Invalid block number: non-decimal or missing 0x prefix")),
+		}
+	}
+}
+
+impl Serialize for BlockNumber {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+	where
+		S: Serializer,
+	{
+		match *self {
+			BlockNumber::Latest => serializer.serialize_str("latest"),
+			BlockNumber::Earliest => serializer.serialize_str("earliest"),
+			BlockNumber::Pending => serializer.serialize_str("pending"),
+			BlockNumber::Num(ref x) => serializer.serialize_str(&format!("0x{:x}", x)),
+		}
+	}
+}
+
+/// Ethereum-like view of a block.
+#[derive(Debug, Default, PartialEq, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RichBlock {
+	/// Block hash
+	pub hash: H256,
+	/// Hash of the parent
+	pub parent_hash: H256,
+	/// Block number
+	pub number: U256,
+	/// State root hash
+	pub state_root: H256,
+	/// Transactions root hash
+	pub transactions_root: H256,
+	/// Gas limit
+	pub gas_limit: U256,
+	/// Gas used
+	pub gas_used: U256,
+	/// Bloom of the logs of all the transactions
+	pub logs_bloom: Bytes,
+	/// Base fee per gas
+	pub base_fee_per_gas: U256,
+	/// EVM transactions, as hashes or in full
+	pub transactions: BlockTransactions,
+}
+
+/// Transactions of a block
+#[derive(Debug, PartialEq, Serialize, Clone)]
+#[serde(untagged)]
+pub enum BlockTransactions {
+	/// Transaction hashes
+	Hashes(Vec<H256>),
+	/// Full transactions
+	Full(Vec<Transaction>),
+}
+
+impl Default for BlockTransactions {
+	fn default() -> Self {
+		BlockTransactions::Hashes(Vec::new())
+	}
+}
+
+/// Ethereum-like view of a transaction. Only raw Ethereum transactions have
+/// a nonce, value, input and signature, other EVM extrinsics report zeros.
+#[derive(Debug, Default, PartialEq, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Transaction {
+	/// Transaction hash
+	pub hash: H256,
+	/// Nonce
+	pub nonce: U256,
+	/// Block hash
+	pub block_hash: H256,
+	/// Block number
+	pub block_number: U256,
+	/// Extrinsic index in the block
+	pub transaction_index: U64,
+	/// Sender
+	pub from: H160,
+	/// Called contract, `None` for contract creations
+	pub to: Option<H160>,
+	/// Transferred value
+	pub value: U256,
+	/// Price paid per gas
+	pub gas_price: U256,
+	/// Gas limit
+	pub gas: U256,
+	/// Input data
+	pub input: Bytes,
+	/// Signature V
+	pub v: U64,
+	/// Signature R
+	pub r: U256,
+	/// Signature S
+	pub s: U256,
+}
+
+/// Log filter
+#[derive(Debug, Default, PartialEq, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct Filter {
+	/// From block
+	pub from_block: Option<BlockNumber>,
+	/// To block
+	pub to_block: Option<BlockNumber>,
+	/// Block hash, mutually exclusive with the block range
+	pub block_hash: Option<H256>,
+	/// Emitting contract addresses
+	pub address: Option<VariadicValue<H160>>,
+	/// Topics by position, `None` matches any topic
+	pub topics: Option<Vec<Option<VariadicValue<H256>>>>,
+}
+
+impl Filter {
+	/// Whether a log with `address` and `topics` passes the filter.
+	pub fn matches(&self, address: &H160, topics: &[H256]) -> bool {
+		if let Some(filter) = &self.address {
+			if !filter.contains(address) {
+				return false;
+			}
+		}
+
+		self.topics.iter().flatten().enumerate().all(|(i, filter)| match filter {
+			Some(filter) => topics.get(i).map_or(false, |topic| filter.contains(topic)),
+			None => true,
+		})
+	}
+}
+
+/// A single value or a list of alternatives.
+#[derive(Debug, PartialEq, Deserialize, Clone)]
+#[serde(untagged)]
+pub enum VariadicValue<T> {
+	/// Single value
+	Single(T),
+	/// Any of the values
+	Multiple(Vec<T>),
+}
+
+impl<T: PartialEq> VariadicValue<T> {
+	/// Whether `value` is one of the values.
+	pub fn contains(&self, value: &T) -> bool {
+		match self {
+			VariadicValue::Single(v) => v == value,
+			VariadicValue::Multiple(values) => values.contains(value),
+		}
+	}
+
+	/// All the values.
+	pub fn values(&self) -> Vec<&T> {
+		match self {
+			VariadicValue::Single(v) => vec![v],
+			VariadicValue::Multiple(values) => values.iter().collect(),
+		}
+	}
+}
+
+/// Log
+#[derive(Debug, Default, PartialEq, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Log {
+	/// Address
+	pub address: H160,
+	/// Topics
+	pub topics: Vec<H256>,
+	/// Data
+	pub data: Bytes,
+	/// Block hash
+	pub block_hash: H256,
+	/// Block number
+	pub block_number: U256,
+	/// Transaction hash
+	pub transaction_hash: H256,
+	/// Extrinsic index in the block
+	pub transaction_index: U64,
+	/// Log index in the block
+	pub log_index: U256,
+}
+
+/// Transaction receipt
+#[derive(Debug, Default, PartialEq, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Receipt {
+	/// Transaction hash
+	pub transaction_hash: H256,
+	/// Extrinsic index in the block
+	pub transaction_index: U64,
+	/// Block hash
+	pub block_hash: H256,
+	/// Block number
+	pub block_number: U256,
+	/// Sender
+	pub from: H160,
+	/// Called contract, `None` for contract creations
+	pub to: Option<H160>,
+	/// Gas used in the block up to and including this transaction
+	pub cumulative_gas_used: U256,
+	/// Gas used by this transaction
+	pub gas_used: U256,
+	/// Price paid per gas
+	pub effective_gas_price: U256,
+	/// Created contract
+	pub contract_address: Option<H160>,
+	/// Logs
+	pub logs: Vec<Log>,
+	/// Logs bloom
+	pub logs_bloom: Bytes,
+	/// `1` on success, `0` on failure
+	pub status: U64,
+}
+
+/// Fee history of a range of blocks
+#[derive(Debug, Default, PartialEq, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeHistory {
+	/// First block of the range
+	pub oldest_block: U256,
+	/// Base fee per gas of each block, and of the block after the range
+	pub base_fee_per_gas: Vec<U256>,
+	/// Gas used divided by the gas limit of each block
+	pub gas_used_ratio: Vec<f64>,
+	/// Tips at the requested percentiles of gas used in each block
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub reward: Option<Vec<Vec<U256>>>,
+}
+
+/// Sponsorship of the calls to a contract
+#[derive(Debug, Default, PartialEq, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Sponsorship {
+	/// Funds left to pay for calls
+	pub balance: U256,
+	/// Total amount sponsored for a single caller
+	pub per_caller_limit: Option<U256>,
+	/// Amount sponsored in a single block
+	pub per_block_limit: Option<U256>,
+	/// Amount sponsored in the current block
+	pub used_in_block: U256,
+}
+
+/// Storage held by a contract
+#[derive(Debug, Default, PartialEq, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageUsage {
+	/// Bytes of storage paid for by deposits, code included
+	pub storage_size: u32,
+	/// Deposit reserved by the contract account for its storage
+	pub reserved_deposit: U256,
+	/// Size of the contract code
+	pub code_size: u32,
+}
+
+/// Metadata for verifying the source of a contract
+#[derive(Debug, Default, PartialEq, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct VerificationMetadata {
+	/// Hash of the code deployed at the contract, to compare with the
+	/// recompiled code
+	pub code_hash: H256,
+	/// Version of the compiler
+	pub compiler_version: String,
+	/// Whether the optimizer was enabled
+	pub optimizer_enabled: bool,
+	/// Optimizer runs setting
+	pub optimizer_runs: u32,
+	/// Hash of the source bundle
+	pub source_hash: Bytes,
+	/// Hash of the contract ABI
+	pub abi_hash: H256,
+}
+
+/// Code upgrade proposed for a contract
+#[derive(Debug, Default, PartialEq, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CodeUpgrade {
+	/// Hash of the new code
+	pub code_hash: H256,
+	/// Size of the new code
+	pub code_size: u32,
+	/// Blocks between the approval and the upgrade
+	pub delay: U256,
+	/// Block the code is upgraded at, `None` until approved
+	pub execute_at: Option<U256>,
+}
+
+/// Code stored once for all the contracts using it
+#[derive(Debug, Default, PartialEq, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CodeInfo {
+	/// Size of the code
+	pub code_size: u32,
+	/// Number of contracts using the code
+	pub ref_count: u32,
+}
+
+/// Access list generated by `eth_createAccessList`
+#[derive(Debug, Default, PartialEq, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessListResult {
+	/// Accounts and storage slots accessed by the call
+	pub access_list: Vec<AccessListItem>,
+	/// Gas used with the access list
+	pub gas_used: U256,
+	/// Error of the call, if it failed
+	pub error: Option<String>,
+}
+
+/// Whether executions are refused by governance
+#[derive(Debug, Default, PartialEq, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PauseStatus {
+	/// All EVM executions are refused
+	pub evm_paused: bool,
+	/// Calls to the contract are refused
+	pub contract_paused: bool,
+}
+
+/// Options of `debug_traceTransaction` and `debug_traceCall`.
+#[derive(Debug, Default, PartialEq, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceOptions {
+	/// `callTracer` for the call tree, struct logs otherwise
+	pub tracer: Option<String>,
+	/// Omit the stack from struct logs
+	pub disable_stack: Option<bool>,
+	/// Omit storage writes from struct logs
+	pub disable_storage: Option<bool>,
+}
+
+/// Frame of the call tree, as returned by geth's `callTracer`.
+#[derive(Debug, Default, PartialEq, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CallFrame {
+	/// Call type, e.g. `CALL` or `CREATE2`
+	#[serde(rename = "type")]
+	pub call_type: String,
+	/// Caller
+	pub from: H160,
+	/// Called or created contract
+	pub to: H160,
+	/// Transferred value
+	pub value: U256,
+	/// Gas available to the frame
+	pub gas: U256,
+	/// Gas used by the frame
+	pub gas_used: U256,
+	/// Input data
+	pub input: Bytes,
+	/// Output data
+	pub output: Bytes,
+	/// Error, if the frame failed
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub error: Option<String>,
+	/// Sub calls
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	pub calls: Vec<CallFrame>,
+}
+
+/// Executed opcode, as returned by geth's struct logger.
+#[derive(Debug, Default, PartialEq, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StructLog {
+	/// Program counter
+	pub pc: u64,
+	/// Opcode name
+	pub op: String,
+	/// Gas left before the opcode
+	pub gas: u64,
+	/// Gas cost of the opcode
+	pub gas_cost: u64,
+	/// Call depth
+	pub depth: u32,
+	/// Stack, bottom first
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub stack: Option<Vec<U256>>,
+	/// Storage slots written by the opcode
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub storage: Option<BTreeMap<H256, H256>>,
+}
+
+/// Struct logger result
+#[derive(Debug, Default, PartialEq, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StructLogs {
+	/// Gas used
+	pub gas: U256,
+	/// Whether the execution failed
+	pub failed: bool,
+	/// Output data
+	pub return_value: Bytes,
+	/// Executed opcodes
+	pub struct_logs: Vec<StructLog>,
+}
+
+/// Result of `debug_traceTransaction` and `debug_traceCall`.
+#[derive(Debug, PartialEq, Serialize, Clone)]
+#[serde(untagged)]
+pub enum TraceResult {
+	/// Call tree
+	CallTrace(CallFrame),
+	/// Struct logs
+	StructLogs(StructLogs),
+}
+
+#[test]
+fn block_number_deserialize_should_work() {
+	let numbers: Vec<BlockNumber> =
+		serde_json::from_str(r#"["latest", "earliest", "pending", "0x1f", "42"]"#).unwrap();
+	assert_eq!(
+		numbers,
+		vec![
+			BlockNumber::Latest,
+			BlockNumber::Earliest,
+			BlockNumber::Pending,
+			BlockNumber::Num(31),
+			BlockNumber::Num(42),
+		]
+	);
+	assert!(serde_json::from_str::<BlockNumber>(r#""0xzz""#).is_err());
+}
+
+#[test]
+fn filter_deserialize_should_work() {
+	let filter: Filter = serde_json::from_str(
+		r#"{
+			"address": ["0x0000000000000000000000000000000000000001", "0x0000000000000000000000000000000000000002"],
+			"topics": [null, "0x0000000000000000000000000000000000000000000000000000000000000003"]
+		}"#,
+	)
+	.unwrap();
+	let address = H160::from_low_u64_be(2);
+	let topic = H256::from_low_u64_be(3);
+
+	assert!(filter.matches(&address, &[H256::zero(), topic]));
+	assert!(!filter.matches(&address, &[topic]));
+	assert!(!filter.matches(&H160::from_low_u64_be(3), &[H256::zero(), topic]));
+
+	let filter: Filter = serde_json::from_str(r#"{"address": "0x0000000000000000000000000000000000000001"}"#).unwrap();
+	assert_eq!(filter.address, Some(VariadicValue::Single(H160::from_low_u64_be(1))));
+	assert!(filter.matches(&H160::from_low_u64_be(1), &[]));
+}
+
+#[test]
+fn call_frame_serialize_should_work() {
+	let frame = CallFrame {
+		call_type: "CALL".into(),
+		gas: 21_000.into(),
+		error: Some("execution reverted".into()),
+		..Default::default()
+	};
+	let json = serde_json::to_value(TraceResult::CallTrace(frame)).unwrap();
+	assert_eq!(json["type"], "CALL");
+	assert_eq!(json["gas"], "0x5208");
+	assert_eq!(json["error"], "execution reverted");
+	assert!(json.get("calls").is_none());
+}
+
+#[test]
+fn storage_usage_serialize_should_work() {
+	let usage = StorageUsage {
+		storage_size: 100,
+		reserved_deposit: 1_000.into(),
+		code_size: 5,
+	};
+	let json = serde_json::to_value(usage).unwrap();
+	assert_eq!(json["storageSize"], 100);
+	assert_eq!(json["reservedDeposit"], "0x3e8");
+	assert_eq!(json["codeSize"], 5);
+}
+
+#[test]
+fn block_transactions_serialize_should_work() {
+	let hashes = BlockTransactions::Hashes(vec![H256::repeat_byte(1)]);
+	assert_eq!(
+		serde_json::to_string(&hashes).unwrap(),
+		r#"["0x0101010101010101010101010101010101010101010101010101010101010101"]"#
+	);
+
+	let full = BlockTransactions::Full(vec![Transaction {
+		nonce: 9.into(),
+		v: 37.into(),
+		..Default::default()
+	}]);
+	let value = serde_json::to_value(&full).unwrap();
+	assert_eq!(value[0]["nonce"], "0x9");
+	assert_eq!(value[0]["to"], serde_json::Value::Null);
+	assert_eq!(value[0]["v"], "0x25");
+	assert_eq!(value[0]["input"], "0x");
+}
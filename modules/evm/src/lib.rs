@@ -7,44 +7,66 @@
 use codec::{Decode, Encode};
 use evm::Config as EvmConfig;
 use frame_support::{
-	dispatch::{DispatchError, DispatchResult, DispatchResultWithPostInfo},
+	dispatch::{DispatchError, DispatchErrorWithPostInfo, DispatchResult, DispatchResultWithPostInfo},
 	ensure,
 	error::BadOrigin,
 	pallet_prelude::*,
 	traits::{
+		BalanceStatus,
 		Currency,
 		EnsureOrigin,
 		ExistenceRequirement,
+		FindAuthor,
 		Get,
+		Filter,
+		GetCallMetadata,
+		Imbalance,
+		IsSubType,
 		OnKilledAccount,
+		OnUnbalanced,
+		Randomness,
 		ReservableCurrency,
 		WithdrawReasons,
 	},
 	transactional,
-	weights::{Pays, PostDispatchInfo, Weight},
+	weights::{DispatchClass, Pays, PostDispatchInfo, Weight},
 	RuntimeDebug,
 };
-use frame_system::{ensure_root, ensure_signed, pallet_prelude::*, EnsureOneOf, EnsureRoot, EnsureSigned};
+use frame_system::{ensure_none, ensure_root, ensure_signed, pallet_prelude::*, EnsureOneOf, EnsureRoot, EnsureSigned};
 use primitive_types::{H256, U256};
 #[cfg(feature = "std")]
 use serde::{Deserialize, Serialize};
 use sha3::{Digest, Keccak256};
 use sp_runtime::{
-	traits::{Convert, DispatchInfoOf, One, PostDispatchInfoOf, SignedExtension, UniqueSaturatedInto},
+	traits::{
+		AccountIdConversion, Convert, DispatchInfoOf, One, PostDispatchInfoOf, SaturatedConversion, SignedExtension,
+		UniqueSaturatedInto, Zero,
+	},
 	transaction_validity::TransactionValidityError,
-	Either, TransactionOutcome,
+	Either, ModuleId, TransactionOutcome,
 };
-use sp_std::{marker::PhantomData, vec::Vec};
-use support::{EVMStateRentTrait, ExecutionMode, InvokeContext, TransactionPayment, EVM as EVMTrait};
+use sp_std::{convert::TryFrom, marker::PhantomData, vec::Vec};
+use support::{EVMStateRentTrait, ExecutionMode, FeeSponsor, InvokeContext, TransactionPayment, EVM as EVMTrait};
+
+use crate::runner::handler::Handler;
 
 pub use crate::precompiles::{Precompile, Precompiles};
-pub use crate::runner::Runner;
+pub use crate::runner::{
+	tracing::{self, Tracer},
+	Runner,
+};
+pub use crate::transaction::{EthereumTransaction, TransactionAction};
 pub use evm::{Context, ExitError, ExitFatal, ExitReason, ExitRevert, ExitSucceed};
 pub use orml_traits::account::MergeAccount;
-pub use primitives::evm::{Account, AddressMapping, CallInfo, CreateInfo, EvmAddress, Log, Vicinity};
+pub use primitives::evm::{
+	AccessListItem, Account, AddressMapping, BatchCall, BatchCallInfo, Bloom, CallInfo, CallTrace, CallType, CodeInfo,
+	CodeUpgrade, CreateInfo, EvmAddress, Log, Receipt, Sponsorship, SponsorshipLimits, Step, StorageUsage, Trace,
+	TracerConfig, VerificationMetadata, Vicinity,
+};
 
 pub mod precompiles;
 pub mod runner;
+pub mod transaction;
 
 mod default_weight;
 mod mock;
@@ -65,10 +87,23 @@ pub trait WeightInfo {
 	fn disable_contract_development() -> Weight;
 	fn set_code() -> Weight;
 	fn selfdestruct() -> Weight;
+	fn set_hardfork() -> Weight;
+	fn fund_sponsorship() -> Weight;
+	fn withdraw_sponsorship() -> Weight;
+	fn set_sponsorship() -> Weight;
+	fn set_contract_paused() -> Weight;
+	fn set_evm_paused() -> Weight;
+	fn set_dispatch_call_allowed() -> Weight;
+	fn set_verification_metadata() -> Weight;
+	fn propose_code_upgrade() -> Weight;
+	fn approve_code_upgrade() -> Weight;
+	fn cancel_code_upgrade() -> Weight;
+	fn upgrade_code() -> Weight;
+	fn batch_call(c: u32) -> Weight;
 }
 
 // Initially based on Istanbul hard fork configuration.
-static EVM_CONFIG: EvmConfig = EvmConfig {
+const ISTANBUL: EvmConfig = EvmConfig {
 	gas_ext_code: 700,
 	gas_ext_code_hash: 700,
 	gas_balance: 700,
@@ -93,7 +128,7 @@ static EVM_CONFIG: EvmConfig = EvmConfig {
 	stack_limit: 1024,
 	memory_limit: usize::max_value(),
 	call_stack_limit: 1024,
-	create_contract_limit: None, // ignored
+	create_contract_limit: None, // `MaxCodeSize` is checked by `Pallet::validate_code`
 	call_stipend: 2300,
 	has_delegate_call: true,
 	has_create2: true,
@@ -106,6 +141,137 @@ static EVM_CONFIG: EvmConfig = EvmConfig {
 	estimate: false,
 };
 
+static ISTANBUL_CONFIG: EvmConfig = ISTANBUL;
+
+static BERLIN_CONFIG: EvmConfig = berlin_config(ISTANBUL);
+
+static LONDON_CONFIG: EvmConfig = london_config(ISTANBUL);
+
+static ISTANBUL_METERED_CONFIG: EvmConfig = metered_config(ISTANBUL);
+
+static BERLIN_METERED_CONFIG: EvmConfig = berlin_config(metered_config(ISTANBUL));
+
+static LONDON_METERED_CONFIG: EvmConfig = london_config(metered_config(ISTANBUL));
+
+/// Cost of reading a warm storage slot or touching a warm account, EIP-2929.
+pub const WARM_STORAGE_READ_COST: u64 = 100;
+/// Cost of the first read of a storage slot in a transaction, EIP-2929.
+pub const COLD_SLOAD_COST: u64 = 2100;
+/// Cost of the first access of an account in a transaction, EIP-2929.
+pub const COLD_ACCOUNT_ACCESS_COST: u64 = 2600;
+/// Cost of an address of the access list of a transaction, EIP-2930.
+pub const ACCESS_LIST_ADDRESS_COST: u64 = 2400;
+/// Cost of a storage slot of the access list of a transaction, EIP-2930.
+pub const ACCESS_LIST_STORAGE_KEY_COST: u64 = 1900;
+/// Refund for clearing a storage slot, EIP-2200.
+pub const SSTORE_CLEARS_REFUND: i64 = 15000;
+/// Refund for clearing a storage slot, as reduced by EIP-3529.
+pub const LONDON_SSTORE_CLEARS_REFUND: i64 = 4800;
+/// Max length of the byte fields of contract verification metadata.
+pub const MAX_VERIFICATION_FIELD_LENGTH: usize = 128;
+/// Gas whose fee is divided to get the gas price.
+pub const GAS_PRICE_UNIT: u64 = 1_000_000;
+
+/// Enable EIP-2200 net gas metering for SSTORE, with its gas refunds.
+pub const fn metered_config(config: EvmConfig) -> EvmConfig {
+	EvmConfig {
+		refund_sstore_clears: SSTORE_CLEARS_REFUND,
+		sstore_gas_metering: true,
+		sstore_revert_under_stipend: true,
+		..config
+	}
+}
+
+/// Apply the EIP-2929 warm access costs to an Istanbul based config. The cold
+/// access surcharges are charged by the handler on top of these.
+pub const fn berlin_config(config: EvmConfig) -> EvmConfig {
+	EvmConfig {
+		gas_ext_code: WARM_STORAGE_READ_COST,
+		gas_ext_code_hash: WARM_STORAGE_READ_COST,
+		gas_balance: WARM_STORAGE_READ_COST,
+		gas_sload: WARM_STORAGE_READ_COST,
+		gas_sstore_reset: 5000 - COLD_SLOAD_COST,
+		gas_call: WARM_STORAGE_READ_COST,
+		..config
+	}
+}
+
+/// Apply the EIP-3529 refund changes on top of `berlin_config`. Configs with
+/// refunds disabled keep them disabled.
+pub const fn london_config(config: EvmConfig) -> EvmConfig {
+	let refund_sstore_clears = if config.refund_sstore_clears == 0 {
+		0
+	} else {
+		LONDON_SSTORE_CLEARS_REFUND
+	};
+	EvmConfig {
+		refund_sstore_clears,
+		..berlin_config(config)
+	}
+}
+
+/// Hard fork rule sets the EVM can execute with.
+#[derive(Clone, Copy, Eq, PartialEq, PartialOrd, Ord, RuntimeDebug, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum EvmHardfork {
+	Istanbul,
+	/// EIP-2929 cold/warm access costs.
+	Berlin,
+	/// EIP-3529 refund reduction and EIP-3541 0xEF code rejection.
+	London,
+}
+
+impl Default for EvmHardfork {
+	fn default() -> Self {
+		EvmHardfork::Istanbul
+	}
+}
+
+impl EvmHardfork {
+	/// The EVM config of the hard fork, with or without SSTORE net gas
+	/// metering.
+	pub fn config(&self, net_gas_metering: bool) -> &'static EvmConfig {
+		match (self, net_gas_metering) {
+			(EvmHardfork::Istanbul, false) => &ISTANBUL_CONFIG,
+			(EvmHardfork::Berlin, false) => &BERLIN_CONFIG,
+			(EvmHardfork::London, false) => &LONDON_CONFIG,
+			(EvmHardfork::Istanbul, true) => &ISTANBUL_METERED_CONFIG,
+			(EvmHardfork::Berlin, true) => &BERLIN_METERED_CONFIG,
+			(EvmHardfork::London, true) => &LONDON_METERED_CONFIG,
+		}
+	}
+
+	/// Whether self destructs are refunded, removed by EIP-3529.
+	pub fn has_suicide_refund(&self) -> bool {
+		*self < EvmHardfork::London
+	}
+
+	/// Whether the first access of accounts and storage slots is charged
+	/// extra, EIP-2929.
+	pub fn has_access_costs(&self) -> bool {
+		*self >= EvmHardfork::Berlin
+	}
+
+	/// Gas refunds are capped to `used_gas / max_refund_quotient`, EIP-3529.
+	pub fn max_refund_quotient(&self) -> u64 {
+		if *self >= EvmHardfork::London {
+			5
+		} else {
+			2
+		}
+	}
+
+	/// Whether new code starting with the 0xEF byte is rejected, EIP-3541.
+	pub fn disallow_ef_code(&self) -> bool {
+		*self >= EvmHardfork::London
+	}
+
+	/// Whether the BASEFEE opcode is available, EIP-3198.
+	pub fn has_base_fee(&self) -> bool {
+		*self >= EvmHardfork::London
+	}
+}
+
 #[frame_support::pallet]
 pub mod module {
 	use super::*;
@@ -131,6 +297,11 @@ pub mod module {
 		#[pallet::constant]
 		type StorageDepositPerByte: Get<BalanceOf<Self>>;
 
+		/// Weight `on_initialize` may spend clearing the storage of removed
+		/// contracts.
+		#[pallet::constant]
+		type StorageCleanupWeight: Get<Weight>;
+
 		/// Contract max code size.
 		#[pallet::constant]
 		type MaxCodeSize: Get<u32>;
@@ -147,12 +318,26 @@ pub mod module {
 		/// Convert gas to weight.
 		type GasToWeight: Convert<u64, Weight>;
 
+		/// Convert weight to gas, the inverse of `GasToWeight`.
+		type WeightToGas: Convert<Weight, u64>;
+
+		/// Find the block author, reported as the coinbase.
+		type FindAuthor: FindAuthor<Self::AccountId>;
+
+		/// Randomness reported as the block difficulty.
+		type Randomness: Randomness<Self::Hash>;
+
+		/// Enable EIP-2200 net gas metering and refunds for SSTORE. Refunds are
+		/// capped by the hard fork's refund quotient.
+		#[pallet::constant]
+		type NetGasMetering: Get<bool>;
+
 		/// ChargeTransactionPayment convert weight to fee.
 		type ChargeTransactionPayment: TransactionPayment<Self::AccountId, BalanceOf<Self>, NegativeImbalanceOf<Self>>;
 
-		/// EVM config used in the module.
+		/// EVM config used in the module, follows the active hard fork.
 		fn config() -> &'static EvmConfig {
-			&EVM_CONFIG
+			Pallet::<Self>::hardfork().config(Self::NetGasMetering::get())
 		}
 
 		/// Required origin for creating system contract.
@@ -172,6 +357,39 @@ pub mod module {
 
 		type FreeDeploymentOrigin: EnsureOrigin<Self::Origin>;
 
+		/// Required origin for pausing contracts or the whole EVM.
+		type PauseOrigin: EnsureOrigin<Self::Origin>;
+
+		/// Required origin for approving the code upgrades of deployed
+		/// contracts.
+		type CodeUpgradeOrigin: EnsureOrigin<Self::Origin>;
+
+		/// Required origin for changing the runtime calls contracts may
+		/// dispatch.
+		type DispatchCallOrigin: EnsureOrigin<Self::Origin>;
+
+		/// Storage limit applied to raw Ethereum transactions, which carry no
+		/// storage limit of their own.
+		#[pallet::constant]
+		type EthereumTransactionStorageLimit: Get<u32>;
+
+		/// Accept raw Ethereum transactions without a chain id, pre EIP-155.
+		/// They can be replayed from any other chain.
+		#[pallet::constant]
+		type AllowUnprotectedTransactions: Get<bool>;
+
+		/// Derives the accounts holding the sponsorship funds of contracts.
+		#[pallet::constant]
+		type SponsorModuleId: Get<ModuleId>;
+
+		/// Max number of calls of a batch call.
+		#[pallet::constant]
+		type MaxBatchCalls: Get<u32>;
+
+		/// Blocks the status of a recurring call is kept after its last run.
+		#[pallet::constant]
+		type RecurringCallRetention: Get<Self::BlockNumber>;
+
 		/// Weight information for the extrinsics in this module.
 		type WeightInfo: WeightInfo;
 	}
@@ -181,6 +399,8 @@ pub mod module {
 		pub code_hash: H256,
 		pub maintainer: EvmAddress,
 		pub deployed: bool,
+		/// Bytes of storage paid for by deposits, code included.
+		pub storage_size: u32,
 	}
 
 	#[derive(Clone, Eq, PartialEq, RuntimeDebug, Encode, Decode)]
@@ -200,10 +420,47 @@ pub mod module {
 		}
 	}
 
-	#[derive(Clone, Copy, Eq, PartialEq, RuntimeDebug, Encode, Decode)]
-	pub struct CodeInfo {
-		pub code_size: u32,
-		pub ref_count: u32,
+	/// Storage of a removed contract waiting to be cleared.
+	#[derive(Clone, Eq, PartialEq, RuntimeDebug, Encode, Decode)]
+	pub struct PendingCleanup<T: Config> {
+		/// Account refunded the storage deposit once the storage is cleared.
+		pub maintainer: T::AccountId,
+		/// Storage deposit held on the maintainer until then.
+		pub deposit: BalanceOf<T>,
+		/// Storage slots cleared so far.
+		pub cleared_slots: u32,
+	}
+
+	/// Status of a call scheduled to run periodically.
+	#[derive(Clone, Eq, PartialEq, RuntimeDebug, Encode, Decode)]
+	pub struct RecurringCall<T: Config> {
+		/// Blocks between two runs.
+		pub period: T::BlockNumber,
+		/// Runs left, including the next one.
+		pub remaining_runs: u32,
+		/// Block of the next run, or of the last one once there is no run
+		/// left.
+		pub next_execution: T::BlockNumber,
+		/// Fee reserved up front for the remaining runs.
+		pub reserved_fee: BalanceOf<T>,
+		/// Exit reason of the last run.
+		pub last_exit_reason: Option<ExitReason>,
+	}
+
+	/// Layout versions of the storage of the module.
+	#[derive(Clone, Copy, Eq, PartialEq, PartialOrd, Ord, RuntimeDebug, Encode, Decode)]
+	pub enum Releases {
+		V1,
+		/// `ContractInfo::storage_size` added.
+		V2,
+		/// `ContractsByCodeHash` added.
+		V3,
+	}
+
+	impl Default for Releases {
+		fn default() -> Self {
+			Releases::V1
+		}
 	}
 
 	#[cfg(feature = "std")]
@@ -220,6 +477,11 @@ pub mod module {
 		pub code: Vec<u8>,
 	}
 
+	/// Layout version of the storage, `Releases::V1` for the chains started
+	/// before it was recorded.
+	#[pallet::storage]
+	pub(crate) type StorageVersion<T: Config> = StorageValue<_, Releases, ValueQuery>;
+
 	/// Accounts info.
 	#[pallet::storage]
 	#[pallet::getter(fn accounts)]
@@ -238,6 +500,11 @@ pub mod module {
 	#[pallet::getter(fn code_infos)]
 	pub type CodeInfos<T: Config> = StorageMap<_, Identity, H256, CodeInfo>;
 
+	/// Contracts using a code, keyed by its hash.
+	#[pallet::storage]
+	pub type ContractsByCodeHash<T: Config> =
+		StorageDoubleMap<_, Identity, H256, Twox64Concat, EvmAddress, (), OptionQuery>;
+
 	/// Next available system contract address.
 	#[pallet::storage]
 	#[pallet::getter(fn network_contract_index)]
@@ -248,9 +515,106 @@ pub mod module {
 	#[pallet::getter(fn extrinsic_origin)]
 	pub type ExtrinsicOrigin<T: Config> = StorageValue<_, T::AccountId>;
 
+	/// The hard fork rules the EVM executes with.
+	#[pallet::storage]
+	#[pallet::getter(fn hardfork)]
+	pub type Hardfork<T: Config> = StorageValue<_, EvmHardfork, ValueQuery>;
+
+	/// Logs of the current EVM execution, moved to its receipt when the
+	/// execution ends.
+	#[pallet::storage]
+	pub type PendingLogs<T: Config> = StorageValue<_, Vec<Log>, ValueQuery>;
+
+	/// Receipts of the extrinsics executed so far in the current block.
+	#[pallet::storage]
+	pub type PendingReceipts<T: Config> = StorageValue<_, Vec<Receipt>, ValueQuery>;
+
+	/// Receipts of the block, committed in `on_finalize`.
+	#[pallet::storage]
+	#[pallet::getter(fn current_receipts)]
+	pub type CurrentReceipts<T: Config> = StorageValue<_, Vec<Receipt>, ValueQuery>;
+
+	/// Limits of the sponsored contracts. Fees and storage deposits of calls
+	/// to them are paid by their sponsor account.
+	#[pallet::storage]
+	#[pallet::getter(fn sponsorships)]
+	pub type Sponsorships<T: Config> = StorageMap<_, Twox64Concat, EvmAddress, SponsorshipLimits<BalanceOf<T>>>;
+
+	/// Total amount sponsored by a contract for a caller.
+	#[pallet::storage]
+	#[pallet::getter(fn sponsored_amounts)]
+	pub type SponsoredAmounts<T: Config> =
+		StorageDoubleMap<_, Twox64Concat, EvmAddress, Twox64Concat, T::AccountId, BalanceOf<T>, ValueQuery>;
+
+	/// Amount sponsored by a contract in the block it was last charged.
+	#[pallet::storage]
+	pub type BlockSponsoredAmounts<T: Config> =
+		StorageMap<_, Twox64Concat, EvmAddress, (T::BlockNumber, BalanceOf<T>), ValueQuery>;
+
+	/// Removed contracts with storage left to clear. Their addresses cannot
+	/// be used again until it is cleared.
+	#[pallet::storage]
+	#[pallet::getter(fn pending_cleanups)]
+	pub type PendingCleanups<T: Config> = StorageMap<_, Twox64Concat, EvmAddress, PendingCleanup<T>>;
+
+	/// Removed contracts in the order their storage is cleared, keyed by
+	/// their position in the queue.
+	#[pallet::storage]
+	pub type CleanupQueue<T: Config> = StorageMap<_, Twox64Concat, u64, EvmAddress>;
+
+	/// Positions of the first contract of `CleanupQueue` and of the next one
+	/// queued.
+	#[pallet::storage]
+	pub type CleanupQueueRange<T: Config> = StorageValue<_, (u64, u64), ValueQuery>;
+
+	/// Contracts that cannot be called until governance unpauses them.
+	#[pallet::storage]
+	#[pallet::getter(fn paused_contracts)]
+	pub type PausedContracts<T: Config> = StorageMap<_, Twox64Concat, EvmAddress, (), OptionQuery>;
+
+	/// Whether all EVM executions are refused.
+	#[pallet::storage]
+	#[pallet::getter(fn evm_paused)]
+	pub type EvmPaused<T: Config> = StorageValue<_, bool, ValueQuery>;
+
+	/// Runtime calls contracts may dispatch, keyed by pallet and function
+	/// name.
+	#[pallet::storage]
+	pub type DispatchableCalls<T: Config> = StorageMap<_, Twox64Concat, (Vec<u8>, Vec<u8>), (), OptionQuery>;
+
+	/// Metadata for verifying the source of contracts, set by their
+	/// maintainers.
+	#[pallet::storage]
+	#[pallet::getter(fn verification_metadata)]
+	pub type ContractVerifications<T: Config> = StorageMap<_, Twox64Concat, EvmAddress, VerificationMetadata>;
+
+	/// Code upgrades proposed by the maintainers of contracts.
+	#[pallet::storage]
+	#[pallet::getter(fn code_upgrades)]
+	pub type CodeUpgrades<T: Config> = StorageMap<_, Twox64Concat, EvmAddress, CodeUpgrade<T::BlockNumber>>;
+
+	/// Contracts with an approved code upgrade, keyed by the block they are
+	/// upgraded at.
+	#[pallet::storage]
+	pub type ScheduledCodeUpgrades<T: Config> =
+		StorageMap<_, Twox64Concat, T::BlockNumber, Vec<EvmAddress>, ValueQuery>;
+
+	/// Recurring scheduled calls, keyed by their scheduler task id.
+	#[pallet::storage]
+	#[pallet::getter(fn recurring_calls)]
+	pub type RecurringCalls<T: Config> = StorageMap<_, Twox64Concat, Vec<u8>, RecurringCall<T>>;
+
+	/// Recurring calls with no run left, keyed by the block their status is
+	/// removed at.
+	#[pallet::storage]
+	pub type ExpiringRecurringCalls<T: Config> = StorageMap<_, Twox64Concat, T::BlockNumber, Vec<Vec<u8>>, ValueQuery>;
+
 	#[pallet::genesis_config]
 	pub struct GenesisConfig<T: Config> {
 		pub accounts: std::collections::BTreeMap<EvmAddress, GenesisAccount<BalanceOf<T>, T::Index>>,
+		/// Pallet and function names of the runtime calls contracts may
+		/// dispatch.
+		pub dispatchable_calls: Vec<(Vec<u8>, Vec<u8>)>,
 	}
 
 	#[cfg(feature = "std")]
@@ -258,6 +622,7 @@ pub mod module {
 		fn default() -> Self {
 			GenesisConfig {
 				accounts: Default::default(),
+				dispatchable_calls: Default::default(),
 			}
 		}
 	}
@@ -286,7 +651,11 @@ pub mod module {
 					}
 				}
 			});
+			for call in &self.dispatchable_calls {
+				DispatchableCalls::<T>::insert(call, ());
+			}
 			NetworkContractIndex::<T>::put(primitives::NETWORK_CONTRACT_START);
+			StorageVersion::<T>::put(Releases::V3);
 		}
 	}
 
@@ -306,6 +675,12 @@ pub mod module {
 		/// A contract has been executed with errors. States are reverted with
 		/// only gas fees applied. \[contract, exit_reason, output\]
 		ExecutedFailed(EvmAddress, ExitReason, Vec<u8>),
+		/// A batch of calls has been executed successfully with states
+		/// applied. \[calls, used_gas\]
+		BatchExecuted(u32, U256),
+		/// A call of a batch failed. States of all the calls are reverted with
+		/// only gas fees applied. \[index, exit_reason, output\]
+		BatchExecutedFailed(u32, ExitReason, Vec<u8>),
 		/// A deposit has been made at a given address. \[sender, address,
 		/// value\]
 		BalanceDeposit(T::AccountId, EvmAddress, U256),
@@ -334,6 +709,51 @@ pub mod module {
 		ContractSetCode(EvmAddress),
 		/// Selfdestructed contract code. \[contract\]
 		ContractSelfdestructed(EvmAddress),
+		/// Switched the EVM to a hard fork. \[hardfork\]
+		HardforkChanged(EvmHardfork),
+		/// Funds added to the sponsorship of a contract. \[contract, amount\]
+		SponsorshipFunded(EvmAddress, BalanceOf<T>),
+		/// Funds withdrawn from the sponsorship of a contract. \[contract,
+		/// amount\]
+		SponsorshipWithdrawn(EvmAddress, BalanceOf<T>),
+		/// Set the sponsorship limits of a contract. \[contract\]
+		SponsorshipSet(EvmAddress),
+		/// Stopped sponsoring calls to a contract. \[contract\]
+		SponsorshipRemoved(EvmAddress),
+		/// Paused a contract. \[contract\]
+		ContractPaused(EvmAddress),
+		/// Unpaused a contract. \[contract\]
+		ContractUnpaused(EvmAddress),
+		/// Paused all EVM executions.
+		EvmPaused,
+		/// Unpaused EVM executions.
+		EvmUnpaused,
+		/// Allowed contracts to dispatch a runtime call.
+		/// \[pallet_name, function_name\]
+		DispatchCallAllowed(Vec<u8>, Vec<u8>),
+		/// Disallowed contracts to dispatch a runtime call.
+		/// \[pallet_name, function_name\]
+		DispatchCallDisallowed(Vec<u8>, Vec<u8>),
+		/// Cleared storage slots of a removed contract. \[contract, slots\]
+		ContractStorageCleared(EvmAddress, u32),
+		/// Finished clearing the storage of a removed contract, and refunded
+		/// its storage deposit. \[contract, maintainer, deposit\]
+		ContractStorageCleanupFinished(EvmAddress, T::AccountId, BalanceOf<T>),
+		/// Set the verification metadata of a contract. \[contract\]
+		VerificationMetadataSet(EvmAddress),
+		/// Removed the verification metadata of a contract. \[contract\]
+		VerificationMetadataRemoved(EvmAddress),
+		/// Proposed a code upgrade of a contract. \[contract, code_hash\]
+		CodeUpgradeProposed(EvmAddress, H256),
+		/// Approved the code upgrade of a contract. \[contract, execute_at\]
+		CodeUpgradeApproved(EvmAddress, T::BlockNumber),
+		/// Cancelled the code upgrade of a contract. \[contract\]
+		CodeUpgradeCancelled(EvmAddress),
+		/// Upgraded the code of a contract. \[contract, old_hash, new_hash\]
+		ContractCodeUpgraded(EvmAddress, H256, H256),
+		/// Failed to apply the approved code upgrade of a contract.
+		/// \[contract, error\]
+		ContractCodeUpgradeFailed(EvmAddress, DispatchError),
 	}
 
 	#[pallet::error]
@@ -356,19 +776,67 @@ pub mod module {
 		ContractAlreadyDeployed,
 		/// Contract exceeds max code size
 		ContractExceedsMaxCodeSize,
+		/// Contract code starts with the 0xEF byte, EIP-3541
+		InvalidCodePrefix,
 		/// Storage usage exceeds storage limit
 		OutOfStorage,
 		/// Charge fee failed
 		ChargeFeeFailed,
 		/// Contract address conflicts with the system contract
 		ConflictContractAddress,
+		/// Ethereum transaction signature is invalid
+		InvalidSignature,
+		/// Ethereum transaction is signed for another chain
+		InvalidChainId,
+		/// Ethereum transaction nonce does not match the sender nonce
+		InvalidNonce,
+		/// Ethereum transaction gas price is below the base fee
+		GasPriceTooLow,
+		/// Recurring scheduled call not found
+		RecurringCallNotFound,
+		/// Verification metadata field exceeds max length
+		VerificationMetadataTooLarge,
+		/// Code upgrade not found
+		CodeUpgradeNotFound,
+		/// Contract already has a code upgrade proposed
+		CodeUpgradeAlreadyProposed,
+		/// Code upgrade already approved
+		CodeUpgradeAlreadyApproved,
+		/// No code stored with the code hash
+		CodeNotFound,
+		/// Batch call has more calls than allowed
+		TooManyCalls,
 	}
 
 	#[pallet::pallet]
 	pub struct Pallet<T>(PhantomData<T>);
 
 	#[pallet::hooks]
-	impl<T: Config> Hooks<T::BlockNumber> for Pallet<T> {}
+	impl<T: Config> Hooks<T::BlockNumber> for Pallet<T> {
+		fn on_initialize(now: T::BlockNumber) -> Weight {
+			// `on_finalize` moves the pending receipts
+			T::DbWeight::get()
+				.reads_writes(1, 2)
+				.saturating_add(Self::apply_code_upgrades(now))
+				.saturating_add(Self::remove_expired_recurring_calls(now))
+				.saturating_add(Self::clear_removed_contracts(T::StorageCleanupWeight::get()))
+		}
+
+		fn on_finalize(_: T::BlockNumber) {
+			CurrentReceipts::<T>::put(PendingReceipts::<T>::take());
+		}
+
+		fn on_runtime_upgrade() -> Weight {
+			let mut weight = T::DbWeight::get().reads(1);
+			if StorageVersion::<T>::get() < Releases::V2 {
+				weight = weight.saturating_add(Self::migrate_to_v2());
+			}
+			if StorageVersion::<T>::get() < Releases::V3 {
+				weight = weight.saturating_add(Self::migrate_to_v3());
+			}
+			weight
+		}
+	}
 
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
@@ -411,6 +879,39 @@ pub mod module {
 			})
 		}
 
+		/// Issue EVM calls in order as one atomic operation. If a call fails,
+		/// the states of all the calls are reverted.
+		#[pallet::weight(<T as Config>::WeightInfo::batch_call(calls.len() as u32).saturating_add(
+			T::GasToWeight::convert(calls.iter().fold(0u64, |total, call| total.saturating_add(call.gas_limit)))
+		))]
+		pub fn batch_call(origin: OriginFor<T>, calls: Vec<BatchCall<BalanceOf<T>>>) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			let count = calls.len() as u32;
+			ensure!(count <= T::MaxBatchCalls::get(), Error::<T>::TooManyCalls);
+			let source = T::AddressMapping::get_or_create_evm_address(&who);
+
+			let info = Runner::<T>::batch_call(source, calls, T::config())?;
+
+			if info.exit_reason.is_succeed() {
+				Pallet::<T>::deposit_event(Event::<T>::BatchExecuted(count, info.used_gas));
+			} else {
+				Pallet::<T>::deposit_event(Event::<T>::BatchExecutedFailed(
+					info.index,
+					info.exit_reason,
+					info.output,
+				));
+			}
+
+			let used_gas: u64 = info.used_gas.unique_saturated_into();
+
+			Ok(PostDispatchInfo {
+				actual_weight: Some(
+					<T as Config>::WeightInfo::batch_call(count).saturating_add(T::GasToWeight::convert(used_gas)),
+				),
+				pays_fee: Pays::Yes,
+			})
+		}
+
 		#[pallet::weight(T::GasToWeight::convert(*gas_limit))]
 		#[transactional]
 		pub fn scheduled_call(
@@ -466,6 +967,90 @@ pub mod module {
 			})
 		}
 
+		/// Run a recurring scheduled call. The fee of a run is taken from the
+		/// fee reserved when scheduling it, if any, or else from the free
+		/// balance of `from`.
+		#[pallet::weight(T::GasToWeight::convert(*gas_limit))]
+		pub fn scheduled_recurring_call(
+			origin: OriginFor<T>,
+			task_id: Vec<u8>,
+			from: EvmAddress,
+			target: EvmAddress,
+			input: Vec<u8>,
+			value: BalanceOf<T>,
+			gas_limit: u64,
+			storage_limit: u32,
+		) -> DispatchResultWithPostInfo {
+			ensure_root(origin)?;
+
+			let mut task = Self::recurring_calls(&task_id).ok_or(Error::<T>::RecurringCallNotFound)?;
+
+			let _from_account = T::AddressMapping::get_account_id(&from);
+			#[cfg(not(feature = "with-ethereum-compatibility"))]
+			let payed = {
+				// the reserved fee is spread evenly over the remaining runs
+				let share = task.reserved_fee / BalanceOf::<T>::from(task.remaining_runs.max(1));
+				T::ChargeTransactionPayment::unreserve_fee(&_from_account, share);
+				task.reserved_fee = task.reserved_fee.saturating_sub(share);
+
+				let fee = T::ChargeTransactionPayment::weight_to_fee(T::GasToWeight::convert(gas_limit));
+				match T::Currency::withdraw(
+					&_from_account,
+					fee,
+					WithdrawReasons::TRANSACTION_PAYMENT,
+					ExistenceRequirement::KeepAlive,
+				) {
+					Ok(imbalance) => imbalance,
+					Err(_) => {
+						let exit_reason = ExitReason::Error(ExitError::OutOfFund);
+						Self::finish_recurring_run(task_id, task, exit_reason.clone());
+						Pallet::<T>::deposit_event(Event::<T>::ExecutedFailed(target, exit_reason, Vec::new()));
+						return Ok(().into());
+					}
+				}
+			};
+
+			let (exit_reason, output, used_gas) =
+				match Runner::<T>::call(from, from, target, input, value, gas_limit, storage_limit, T::config()) {
+					Ok(info) => (info.exit_reason, info.output, info.used_gas.unique_saturated_into()),
+					Err(e) => {
+						let err_msg: &str = e.into();
+						(
+							ExitReason::Error(ExitError::Other(err_msg.into())),
+							Vec::new(),
+							gas_limit,
+						)
+					}
+				};
+
+			if exit_reason.is_succeed() {
+				Pallet::<T>::deposit_event(Event::<T>::Executed(target));
+			} else {
+				Pallet::<T>::deposit_event(Event::<T>::ExecutedFailed(target, exit_reason.clone(), output));
+			}
+			Self::finish_recurring_run(task_id, task, exit_reason);
+
+			#[cfg(not(feature = "with-ethereum-compatibility"))]
+			{
+				use sp_runtime::traits::Zero;
+				let refund_gas = gas_limit.saturating_sub(used_gas);
+				if !refund_gas.is_zero() {
+					// ignore the result to continue. if it fails, just the user will not
+					// be refunded, there will not increase user balance.
+					let _ = T::ChargeTransactionPayment::refund_fee(
+						&_from_account,
+						T::GasToWeight::convert(refund_gas),
+						payed,
+					);
+				}
+			}
+
+			Ok(PostDispatchInfo {
+				actual_weight: Some(T::GasToWeight::convert(used_gas)),
+				pays_fee: Pays::Yes,
+			})
+		}
+
 		/// Issue an EVM create operation. This is similar to a contract
 		/// creation transaction in Ethereum.
 		#[pallet::weight(T::GasToWeight::convert(*gas_limit))]
@@ -661,67 +1246,1079 @@ pub mod module {
 
 			Ok(().into())
 		}
-	}
-}
 
-impl<T: Config> Pallet<T> {
-	/// Remove an account.
-	pub fn remove_account(address: &EvmAddress) -> Result<u32, ExitError> {
-		let mut size = 0u32;
+		/// Execute a raw, signed Ethereum transaction. The sender is recovered
+		/// from the signature and pays the fee for `gas_limit` and the length
+		/// fee up front through `ChargeTransactionPayment`, the unused gas is
+		/// refunded.
+		///
+		/// If the execution fails, its state changes are reverted but the
+		/// nonce is still bumped and the whole `gas_limit` paid.
+		#[pallet::weight(T::GasToWeight::convert(transaction.gas_limit.unique_saturated_into()))]
+		pub fn transact(origin: OriginFor<T>, transaction: EthereumTransaction) -> DispatchResultWithPostInfo {
+			ensure_none(origin)?;
+
+			let source = Self::recover_transaction_sender(&transaction)?;
+			let transaction_hash = transaction.hash();
+			ensure!(
+				transaction.nonce == Handler::<T>::nonce(source),
+				Error::<T>::InvalidNonce
+			);
 
-		// Deref code, and remove it if ref count is zero.
-		if let Some(AccountInfo {
-			contract_info: Some(contract_info),
-			..
-		}) = Self::accounts(address)
-		{
-			CodeInfos::<T>::mutate_exists(&contract_info.code_hash, |maybe_code_info| {
-				if let Some(code_info) = maybe_code_info.as_mut() {
-					size = code_info.code_size;
-					code_info.ref_count = code_info.ref_count.saturating_sub(1);
-					if code_info.ref_count == 0 {
-						Codes::<T>::remove(&contract_info.code_hash);
-						*maybe_code_info = None;
+			let gas_limit = u64::try_from(transaction.gas_limit).map_err(|_| Error::<T>::NumOutOfBound)?;
+			let value: BalanceOf<T> = u128::try_from(transaction.value)
+				.map_err(|_| Error::<T>::NumOutOfBound)?
+				.unique_saturated_into();
+			let storage_limit = T::EthereumTransactionStorageLimit::get();
+			let gas_price: BalanceOf<T> = u128::try_from(transaction.gas_price)
+				.map_err(|_| Error::<T>::NumOutOfBound)?
+				.unique_saturated_into();
+			let base_fee = Self::base_fee_per_gas();
+			ensure!(gas_price >= base_fee, Error::<T>::GasPriceTooLow);
+
+			// charge gas_limit at the offered gas price, settled after execution,
+			// and the length of the transaction, as the extrinsic itself pays no
+			// fee
+			let who = T::AddressMapping::get_account_id(&source);
+			let len = transaction.encoded_size() as u32;
+			let (_, payed) = T::ChargeTransactionPayment::charge_fee(
+				&who,
+				len,
+				gas_price.saturating_mul(gas_limit.unique_saturated_into()),
+			)
+			.map_err(|_| Error::<T>::ChargeFeeFailed)?;
+			let length_fee = T::ChargeTransactionPayment::length_fee(len);
+
+			let result = frame_support::storage::with_transaction(|| {
+				let result = match transaction.action {
+					TransactionAction::Call(target) => Runner::<T>::call(
+						source,
+						source,
+						target,
+						transaction.input,
+						value,
+						gas_limit,
+						storage_limit,
+						T::config(),
+					)
+					.map(|info| {
+						if info.exit_reason.is_succeed() {
+							Pallet::<T>::deposit_event(Event::<T>::Executed(target));
+						} else {
+							Pallet::<T>::deposit_event(Event::<T>::ExecutedFailed(
+								target,
+								info.exit_reason,
+								info.output,
+							));
+						}
+
+						info.used_gas
+					}),
+					TransactionAction::Create => {
+						Runner::<T>::create(source, transaction.input, value, gas_limit, storage_limit, T::config())
+							.map(|info| {
+								if info.exit_reason.is_succeed() {
+									Pallet::<T>::deposit_event(Event::<T>::Created(info.address));
+								} else {
+									Pallet::<T>::deposit_event(Event::<T>::CreatedFailed(
+										info.address,
+										info.exit_reason,
+										info.output,
+									));
+								}
+
+								info.used_gas
+							})
 					}
+				};
+
+				match result {
+					Ok(used_gas) => TransactionOutcome::Commit(Ok(used_gas)),
+					Err(e) => TransactionOutcome::Rollback(Err(e)),
 				}
 			});
-		}
 
-		Accounts::<T>::remove(address);
-		AccountStorages::<T>::remove_prefix(address);
+			let used_gas = match result {
+				Ok(used_gas) => used_gas,
+				Err(e) => {
+					// the transaction is included, so it must not be replayable for free
+					Handler::<T>::inc_nonce(source);
+					Self::settle_transaction_fee(&who, payed, length_fee, gas_price, base_fee, gas_limit);
+
+					return Err(DispatchErrorWithPostInfo {
+						post_info: PostDispatchInfo {
+							actual_weight: Some(T::GasToWeight::convert(gas_limit)),
+							pays_fee: Pays::No,
+						},
+						error: e,
+					});
+				}
+			};
+
+			// report the Ethereum hash so that tooling can find the receipt
+			let transaction_index = frame_system::Pallet::<T>::extrinsic_index();
+			PendingReceipts::<T>::mutate(|receipts| match receipts.last_mut() {
+				Some(receipt) if Some(receipt.transaction_index) == transaction_index => {
+					receipt.transaction_hash = transaction_hash;
+					receipt.effective_gas_price = transaction.gas_price;
+				}
+				_ => {}
+			});
 
-		Ok(size)
-	}
+			let used_gas: u64 = used_gas.unique_saturated_into();
+			Self::settle_transaction_fee(&who, payed, length_fee, gas_price, base_fee, used_gas);
 
-	/// Get the account basic in EVM format.
-	pub fn account_basic(address: &EvmAddress) -> Account {
-		let account_id = T::AddressMapping::get_account_id(address);
+			Ok(PostDispatchInfo {
+				actual_weight: Some(T::GasToWeight::convert(used_gas)),
+				pays_fee: Pays::No,
+			})
+		}
 
-		let nonce = Self::accounts(address).map_or(Default::default(), |account_info| account_info.nonce);
-		let balance = T::Currency::free_balance(&account_id);
+		/// Switch the EVM to the rules of `hardfork`. Governance schedules the
+		/// switch by dispatching this call at the activation block.
+		#[pallet::weight(<T as Config>::WeightInfo::set_hardfork())]
+		pub fn set_hardfork(origin: OriginFor<T>, hardfork: EvmHardfork) -> DispatchResultWithPostInfo {
+			ensure_root(origin)?;
+			Hardfork::<T>::put(hardfork);
+			Pallet::<T>::deposit_event(Event::<T>::HardforkChanged(hardfork));
+			Ok(().into())
+		}
 
-		Account {
-			nonce: U256::from(UniqueSaturatedInto::<u128>::unique_saturated_into(nonce)),
-			balance: U256::from(UniqueSaturatedInto::<u128>::unique_saturated_into(balance)),
+		/// Move `amount` from the maintainer to the sponsorship funds of
+		/// `contract`.
+		#[pallet::weight(<T as Config>::WeightInfo::fund_sponsorship())]
+		#[transactional]
+		pub fn fund_sponsorship(
+			origin: OriginFor<T>,
+			contract: EvmAddress,
+			amount: BalanceOf<T>,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			Self::ensure_maintainer(&who, &contract)?;
+			T::Currency::transfer(
+				&who,
+				&Self::sponsor_account_id(&contract),
+				amount,
+				ExistenceRequirement::KeepAlive,
+			)?;
+			Pallet::<T>::deposit_event(Event::<T>::SponsorshipFunded(contract, amount));
+			Ok(().into())
 		}
-	}
 
-	/// Get code hash at given address.
-	pub fn code_hash_at_address(address: &EvmAddress) -> H256 {
-		if let Some(AccountInfo {
-			contract_info: Some(contract_info),
-			..
-		}) = Self::accounts(address)
-		{
-			contract_info.code_hash
-		} else {
-			code_hash(&[])
+		/// Move `amount` from the sponsorship funds of `contract` back to the
+		/// maintainer.
+		#[pallet::weight(<T as Config>::WeightInfo::withdraw_sponsorship())]
+		#[transactional]
+		pub fn withdraw_sponsorship(
+			origin: OriginFor<T>,
+			contract: EvmAddress,
+			amount: BalanceOf<T>,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			Self::ensure_maintainer(&who, &contract)?;
+			T::Currency::transfer(
+				&Self::sponsor_account_id(&contract),
+				&who,
+				amount,
+				ExistenceRequirement::AllowDeath,
+			)?;
+			Pallet::<T>::deposit_event(Event::<T>::SponsorshipWithdrawn(contract, amount));
+			Ok(().into())
 		}
-	}
 
-	/// Get code at given address.
-	pub fn code_at_address(address: &EvmAddress) -> Vec<u8> {
-		Self::codes(&Self::code_hash_at_address(address))
+		/// Sponsor the calls to `contract` within `limits`, or stop sponsoring
+		/// them with `None`.
+		#[pallet::weight(<T as Config>::WeightInfo::set_sponsorship())]
+		#[transactional]
+		pub fn set_sponsorship(
+			origin: OriginFor<T>,
+			contract: EvmAddress,
+			limits: Option<SponsorshipLimits<BalanceOf<T>>>,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			Self::ensure_maintainer(&who, &contract)?;
+			if let Some(limits) = limits {
+				Sponsorships::<T>::insert(contract, limits);
+				Pallet::<T>::deposit_event(Event::<T>::SponsorshipSet(contract));
+			} else {
+				Sponsorships::<T>::remove(contract);
+				Pallet::<T>::deposit_event(Event::<T>::SponsorshipRemoved(contract));
+			}
+			Ok(().into())
+		}
+
+		/// Attach the metadata for verifying the source of `contract`, or
+		/// remove it with `None`. It is removed when the code is changed.
+		#[pallet::weight(<T as Config>::WeightInfo::set_verification_metadata())]
+		pub fn set_verification_metadata(
+			origin: OriginFor<T>,
+			contract: EvmAddress,
+			metadata: Option<VerificationMetadata>,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			Self::ensure_maintainer(&who, &contract)?;
+			if let Some(metadata) = metadata {
+				ensure!(
+					metadata.compiler_version.len() <= MAX_VERIFICATION_FIELD_LENGTH
+						&& metadata.source_hash.len() <= MAX_VERIFICATION_FIELD_LENGTH,
+					Error::<T>::VerificationMetadataTooLarge
+				);
+				ContractVerifications::<T>::insert(contract, metadata);
+				Pallet::<T>::deposit_event(Event::<T>::VerificationMetadataSet(contract));
+			} else {
+				ContractVerifications::<T>::remove(contract);
+				Pallet::<T>::deposit_event(Event::<T>::VerificationMetadataRemoved(contract));
+			}
+			Ok(().into())
+		}
+
+		/// Refuse, or allow again, the calls to `contract`.
+		#[pallet::weight(<T as Config>::WeightInfo::set_contract_paused())]
+		pub fn set_contract_paused(
+			origin: OriginFor<T>,
+			contract: EvmAddress,
+			paused: bool,
+		) -> DispatchResultWithPostInfo {
+			T::PauseOrigin::ensure_origin(origin)?;
+			if paused {
+				PausedContracts::<T>::insert(contract, ());
+				Pallet::<T>::deposit_event(Event::<T>::ContractPaused(contract));
+			} else {
+				PausedContracts::<T>::remove(contract);
+				Pallet::<T>::deposit_event(Event::<T>::ContractUnpaused(contract));
+			}
+			Ok(().into())
+		}
+
+		/// Refuse, or allow again, all EVM executions.
+		#[pallet::weight(<T as Config>::WeightInfo::set_evm_paused())]
+		pub fn set_evm_paused(origin: OriginFor<T>, paused: bool) -> DispatchResultWithPostInfo {
+			T::PauseOrigin::ensure_origin(origin)?;
+			EvmPaused::<T>::put(paused);
+			if paused {
+				Pallet::<T>::deposit_event(Event::<T>::EvmPaused);
+			} else {
+				Pallet::<T>::deposit_event(Event::<T>::EvmUnpaused);
+			}
+			Ok(().into())
+		}
+
+		/// Allow, or disallow, contracts to dispatch the runtime call
+		/// `function_name` of `pallet_name`.
+		#[pallet::weight(<T as Config>::WeightInfo::set_dispatch_call_allowed())]
+		pub fn set_dispatch_call_allowed(
+			origin: OriginFor<T>,
+			pallet_name: Vec<u8>,
+			function_name: Vec<u8>,
+			allowed: bool,
+		) -> DispatchResultWithPostInfo {
+			T::DispatchCallOrigin::ensure_origin(origin)?;
+			let key = (pallet_name, function_name);
+			if allowed {
+				DispatchableCalls::<T>::insert(&key, ());
+				Pallet::<T>::deposit_event(Event::<T>::DispatchCallAllowed(key.0, key.1));
+			} else {
+				DispatchableCalls::<T>::remove(&key);
+				Pallet::<T>::deposit_event(Event::<T>::DispatchCallDisallowed(key.0, key.1));
+			}
+			Ok(().into())
+		}
+
+		/// Propose to replace the code of the deployed `contract` with `code`,
+		/// `delay` blocks after the upgrade is approved by
+		/// `CodeUpgradeOrigin`. The storage deposit of `code` is reserved on
+		/// the contract until the upgrade is applied or cancelled.
+		#[pallet::weight(<T as Config>::WeightInfo::propose_code_upgrade())]
+		#[transactional]
+		pub fn propose_code_upgrade(
+			origin: OriginFor<T>,
+			contract: EvmAddress,
+			code: Vec<u8>,
+			delay: T::BlockNumber,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			Self::ensure_maintainer(&who, &contract)?;
+			ensure!(
+				!CodeUpgrades::<T>::contains_key(contract),
+				Error::<T>::CodeUpgradeAlreadyProposed
+			);
+			ensure!(
+				code.len() as u32 <= T::MaxCodeSize::get(),
+				Error::<T>::ContractExceedsMaxCodeSize
+			);
+
+			Self::reserve_storage_deposit(&contract, code.len() as u32)?;
+
+			let code_hash = code_hash(&code);
+			CodeUpgrades::<T>::insert(
+				contract,
+				CodeUpgrade {
+					code,
+					delay,
+					execute_at: None,
+				},
+			);
+			Pallet::<T>::deposit_event(Event::<T>::CodeUpgradeProposed(contract, code_hash));
+			Ok(().into())
+		}
+
+		/// Approve the proposed code upgrade of `contract`, scheduling it
+		/// after its delay.
+		#[pallet::weight(<T as Config>::WeightInfo::approve_code_upgrade())]
+		pub fn approve_code_upgrade(origin: OriginFor<T>, contract: EvmAddress) -> DispatchResultWithPostInfo {
+			T::CodeUpgradeOrigin::ensure_origin(origin)?;
+			let execute_at = CodeUpgrades::<T>::try_mutate(contract, |maybe_upgrade| -> Result<_, Error<T>> {
+				let upgrade = maybe_upgrade.as_mut().ok_or(Error::<T>::CodeUpgradeNotFound)?;
+				ensure!(upgrade.execute_at.is_none(), Error::<T>::CodeUpgradeAlreadyApproved);
+
+				// upgraded in the next block at the earliest
+				let execute_at = frame_system::Module::<T>::block_number()
+					.saturating_add(upgrade.delay)
+					.saturating_add(One::one());
+				upgrade.execute_at = Some(execute_at);
+				Ok(execute_at)
+			})?;
+			ScheduledCodeUpgrades::<T>::append(execute_at, contract);
+			Pallet::<T>::deposit_event(Event::<T>::CodeUpgradeApproved(contract, execute_at));
+			Ok(().into())
+		}
+
+		/// Cancel the code upgrade of `contract`. The maintainer and
+		/// `CodeUpgradeOrigin` can cancel it until it is applied. The
+		/// deposit of the proposed code is returned to the maintainer.
+		#[pallet::weight(<T as Config>::WeightInfo::cancel_code_upgrade())]
+		#[transactional]
+		pub fn cancel_code_upgrade(origin: OriginFor<T>, contract: EvmAddress) -> DispatchResultWithPostInfo {
+			if let Err(origin) = T::CodeUpgradeOrigin::try_origin(origin) {
+				let who = ensure_signed(origin)?;
+				Self::ensure_maintainer(&who, &contract)?;
+			}
+			let upgrade = CodeUpgrades::<T>::take(contract).ok_or(Error::<T>::CodeUpgradeNotFound)?;
+			if let Some(execute_at) = upgrade.execute_at {
+				ScheduledCodeUpgrades::<T>::mutate(execute_at, |contracts| contracts.retain(|c| *c != contract));
+			}
+			Self::release_storage_deposit(&contract, upgrade.code.len() as u32)?;
+			Pallet::<T>::deposit_event(Event::<T>::CodeUpgradeCancelled(contract));
+			Ok(().into())
+		}
+
+		/// Issue an EVM call operation with an EIP-2930 access list. The
+		/// accounts and storage slots of `access_list` are charged upfront
+		/// and warm for the whole call.
+		#[pallet::weight(T::GasToWeight::convert(*gas_limit))]
+		pub fn call_with_access_list(
+			origin: OriginFor<T>,
+			target: EvmAddress,
+			input: Vec<u8>,
+			value: BalanceOf<T>,
+			gas_limit: u64,
+			storage_limit: u32,
+			access_list: Vec<AccessListItem>,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			let source = T::AddressMapping::get_or_create_evm_address(&who);
+
+			let info = Runner::<T>::call_with_access_list(
+				source,
+				source,
+				target,
+				input,
+				value,
+				gas_limit,
+				storage_limit,
+				access_list,
+				T::config(),
+			)?;
+
+			if info.exit_reason.is_succeed() {
+				Pallet::<T>::deposit_event(Event::<T>::Executed(target));
+			} else {
+				Pallet::<T>::deposit_event(Event::<T>::ExecutedFailed(target, info.exit_reason, info.output));
+			}
+
+			let used_gas: u64 = info.used_gas.unique_saturated_into();
+
+			Ok(PostDispatchInfo {
+				actual_weight: Some(T::GasToWeight::convert(used_gas)),
+				pays_fee: Pays::Yes,
+			})
+		}
+
+		/// Issue an EVM create operation with an EIP-2930 access list.
+		#[pallet::weight(T::GasToWeight::convert(*gas_limit))]
+		pub fn create_with_access_list(
+			origin: OriginFor<T>,
+			init: Vec<u8>,
+			value: BalanceOf<T>,
+			gas_limit: u64,
+			storage_limit: u32,
+			access_list: Vec<AccessListItem>,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			let source = T::AddressMapping::get_or_create_evm_address(&who);
+
+			let info = Runner::<T>::create_with_access_list(
+				source,
+				init,
+				value,
+				gas_limit,
+				storage_limit,
+				access_list,
+				T::config(),
+			)?;
+
+			if info.exit_reason.is_succeed() {
+				Pallet::<T>::deposit_event(Event::<T>::Created(info.address));
+			} else {
+				Pallet::<T>::deposit_event(Event::<T>::CreatedFailed(info.address, info.exit_reason, info.output));
+			}
+
+			let used_gas: u64 = info.used_gas.unique_saturated_into();
+
+			Ok(PostDispatchInfo {
+				actual_weight: Some(T::GasToWeight::convert(used_gas)),
+				pays_fee: Pays::Yes,
+			})
+		}
+
+		/// Create a contract with the code already stored under `code_hash`,
+		/// without running init code. Only the new account is charged
+		/// storage, not the code. The contract is then called with `input`,
+		/// unless it is empty, e.g. to initialize a clone.
+		#[pallet::weight(T::GasToWeight::convert(*gas_limit))]
+		pub fn create_from_code_hash(
+			origin: OriginFor<T>,
+			code_hash: H256,
+			input: Vec<u8>,
+			value: BalanceOf<T>,
+			gas_limit: u64,
+			storage_limit: u32,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			let source = T::AddressMapping::get_or_create_evm_address(&who);
+
+			let info = Runner::<T>::create_from_code_hash(
+				source,
+				code_hash,
+				input,
+				value,
+				gas_limit,
+				storage_limit,
+				T::config(),
+			)?;
+
+			if info.exit_reason.is_succeed() {
+				Pallet::<T>::deposit_event(Event::<T>::Created(info.address));
+			} else {
+				Pallet::<T>::deposit_event(Event::<T>::CreatedFailed(info.address, info.exit_reason, info.output));
+			}
+
+			let used_gas: u64 = info.used_gas.unique_saturated_into();
+
+			Ok(PostDispatchInfo {
+				actual_weight: Some(T::GasToWeight::convert(used_gas)),
+				pays_fee: Pays::Yes,
+			})
+		}
+	}
+
+	#[pallet::validate_unsigned]
+	impl<T: Config> ValidateUnsigned for Pallet<T> {
+		type Call = Call<T>;
+
+		fn validate_unsigned(_source: TransactionSource, call: &Self::Call) -> TransactionValidity {
+			if let Call::transact(transaction) = call {
+				let source = Self::recover_transaction_sender(transaction).map_err(|_| InvalidTransaction::BadProof)?;
+
+				let nonce = Handler::<T>::nonce(source);
+				if transaction.nonce < nonce {
+					return InvalidTransaction::Stale.into();
+				}
+
+				let base_fee = U256::from(Self::base_fee_per_gas().saturated_into::<u128>());
+				if transaction.gas_price < base_fee {
+					return InvalidTransaction::Payment.into();
+				}
+
+				if transaction.gas_limit > Self::block_gas_limit() {
+					return InvalidTransaction::ExhaustsResources.into();
+				}
+
+				// the sender must be able to pay for the whole gas_limit, so a
+				// failed execution is still paid for
+				let who = T::AddressMapping::get_account_id(&source);
+				let free_balance = U256::from(T::Currency::free_balance(&who).saturated_into::<u128>());
+				let length_fee = T::ChargeTransactionPayment::length_fee(transaction.encoded_size() as u32);
+				let cost = transaction
+					.gas_limit
+					.saturating_mul(transaction.gas_price)
+					.saturating_add(transaction.value)
+					.saturating_add(U256::from(length_fee.saturated_into::<u128>()));
+				if free_balance < cost {
+					return InvalidTransaction::Payment.into();
+				}
+
+				// prioritize by the tip over the base fee
+				let mut valid = ValidTransaction::with_tag_prefix("EvmTransact")
+					.priority((transaction.gas_price - base_fee).unique_saturated_into())
+					.and_provides((source, transaction.nonce))
+					.longevity(64)
+					.propagate(true);
+				if transaction.nonce > nonce {
+					valid = valid.and_requires((source, transaction.nonce - 1));
+				}
+				valid.build()
+			} else {
+				InvalidTransaction::Call.into()
+			}
+		}
+
+		fn pre_dispatch(call: &Self::Call) -> Result<(), TransactionValidityError> {
+			Self::validate_unsigned(TransactionSource::InBlock, call)?;
+
+			// only the next nonce can be executed, a future one would be
+			// included without paying
+			if let Call::transact(transaction) = call {
+				let source = Self::recover_transaction_sender(transaction).map_err(|_| InvalidTransaction::BadProof)?;
+				if transaction.nonce > Handler::<T>::nonce(source) {
+					return Err(InvalidTransaction::Future.into());
+				}
+			}
+
+			Ok(())
+		}
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// Recover the sender of a raw Ethereum transaction, rejecting
+	/// transactions signed for another chain, or for no chain unless
+	/// `AllowUnprotectedTransactions` is set.
+	pub fn recover_transaction_sender(transaction: &EthereumTransaction) -> Result<EvmAddress, Error<T>> {
+		match transaction.chain_id() {
+			Some(chain_id) => ensure!(chain_id == T::ChainId::get(), Error::<T>::InvalidChainId),
+			None => ensure!(T::AllowUnprotectedTransactions::get(), Error::<T>::InvalidChainId),
+		}
+
+		let signature = transaction.signature().ok_or(Error::<T>::InvalidSignature)?;
+		module_evm_accounts::eth_recover_prehashed(
+			&module_evm_accounts::EcdsaSignature::from_raw(signature),
+			transaction.signing_hash().as_fixed_bytes(),
+		)
+		.ok_or(Error::<T>::InvalidSignature)
+	}
+
+	/// The block author, if known.
+	pub fn block_author() -> Option<T::AccountId> {
+		let digest = frame_system::Pallet::<T>::digest();
+		let pre_runtime_digests = digest.logs.iter().filter_map(|d| d.as_pre_runtime());
+		T::FindAuthor::find_author(pre_runtime_digests)
+	}
+
+	/// The block author mapped to an EVM address, zero if unknown.
+	pub fn block_coinbase() -> EvmAddress {
+		Self::block_author()
+			.map(|author| {
+				T::AddressMapping::get_evm_address(&author)
+					.unwrap_or_else(|| T::AddressMapping::get_default_evm_address(&author))
+			})
+			.unwrap_or_default()
+	}
+
+	/// Gas available to normal extrinsics in a block.
+	pub fn block_gas_limit() -> U256 {
+		let weights = T::BlockWeights::get();
+		let max_weight = weights
+			.get(DispatchClass::Normal)
+			.max_total
+			.unwrap_or(weights.max_block);
+		U256::from(T::WeightToGas::convert(max_weight))
+	}
+
+	/// Block difficulty, taken from the randomness source.
+	pub fn block_difficulty() -> U256 {
+		U256::from_big_endian(T::Randomness::random_seed().as_ref())
+	}
+
+	/// Fee of one gas, adjusted by the transaction payment fee multiplier.
+	/// The fee of one gas can be a fraction, so it is taken from the fee of
+	/// `GAS_PRICE_UNIT` gas and rounded up.
+	pub fn gas_price() -> U256 {
+		let fee = T::ChargeTransactionPayment::weight_to_fee(T::GasToWeight::convert(GAS_PRICE_UNIT));
+		let fee = U256::from(fee.saturated_into::<u128>());
+		fee.saturating_add(U256::from(GAS_PRICE_UNIT - 1)) / GAS_PRICE_UNIT
+	}
+
+	/// Base fee per gas of Ethereum transactions, EIP-1559.
+	pub fn base_fee_per_gas() -> BalanceOf<T> {
+		let weight_per_gas: BalanceOf<T> = T::GasToWeight::convert(1).unique_saturated_into();
+		T::ChargeTransactionPayment::base_fee().saturating_mul(weight_per_gas)
+	}
+
+	/// Refund the unused gas of an Ethereum transaction, burn the length fee
+	/// and the base fee of the used gas and give the priority tip to the
+	/// block author.
+	fn settle_transaction_fee(
+		who: &T::AccountId,
+		payed: NegativeImbalanceOf<T>,
+		length_fee: BalanceOf<T>,
+		gas_price: BalanceOf<T>,
+		base_fee: BalanceOf<T>,
+		used_gas: u64,
+	) {
+		let used_gas: BalanceOf<T> = used_gas.unique_saturated_into();
+		let refund = payed
+			.peek()
+			.saturating_sub(length_fee.saturating_add(gas_price.saturating_mul(used_gas)));
+		let payed = match T::Currency::deposit_into_existing(who, refund) {
+			// the refund never exceeds the payment
+			Ok(refund) => payed
+				.offset(refund)
+				.unwrap_or_else(|_| NegativeImbalanceOf::<T>::zero()),
+			// the account was reaped, the refund is gone
+			Err(_) => payed,
+		};
+
+		let (base_fee, tip) = payed.split(length_fee.saturating_add(base_fee.saturating_mul(used_gas)));
+		Self::on_unbalanceds(Some(base_fee).into_iter().chain(Some(tip)));
+	}
+
+	/// Gas used by normal extrinsics in the current block.
+	pub fn block_gas_used() -> U256 {
+		let used = *frame_system::Pallet::<T>::block_weight().get(DispatchClass::Normal);
+		U256::from(T::WeightToGas::convert(used))
+	}
+
+	/// Record the receipt of an EVM execution of the current extrinsic,
+	/// taking the pending logs. Executions outside of extrinsics, e.g. by
+	/// rpc calls or scheduled calls, are not recorded.
+	pub fn record_receipt(
+		from: EvmAddress,
+		to: Option<EvmAddress>,
+		status: bool,
+		used_gas: U256,
+		contract_address: Option<EvmAddress>,
+	) {
+		let logs = PendingLogs::<T>::take();
+		let transaction_index = match frame_system::Pallet::<T>::extrinsic_index() {
+			Some(index) => index,
+			None => return,
+		};
+
+		let mut logs_bloom = Bloom::default();
+		for log in logs.iter() {
+			logs_bloom.accrue_log(log);
+		}
+
+		PendingReceipts::<T>::mutate(|receipts| {
+			let cumulative_gas_used = receipts
+				.last()
+				.map_or_else(U256::zero, |receipt| receipt.cumulative_gas_used)
+				.saturating_add(used_gas);
+
+			match receipts.last_mut() {
+				// several executions in one extrinsic share a receipt
+				Some(receipt) if receipt.transaction_index == transaction_index => {
+					receipt.status = receipt.status && status;
+					receipt.used_gas = receipt.used_gas.saturating_add(used_gas);
+					receipt.cumulative_gas_used = cumulative_gas_used;
+					receipt.logs.extend(logs);
+					receipt.logs_bloom.accrue_bloom(&logs_bloom);
+					receipt.contract_address = receipt.contract_address.or(contract_address);
+				}
+				_ => {
+					let extrinsic = frame_system::Pallet::<T>::extrinsic_data(transaction_index);
+					receipts.push(Receipt {
+						transaction_hash: H256::from(sp_io::hashing::blake2_256(&extrinsic)),
+						transaction_index,
+						from,
+						to,
+						status,
+						used_gas,
+						cumulative_gas_used,
+						effective_gas_price: Self::gas_price(),
+						logs,
+						logs_bloom,
+						contract_address,
+					});
+				}
+			}
+		});
+	}
+
+	/// The account holding the sponsorship funds of `contract`.
+	pub fn sponsor_account_id(contract: &EvmAddress) -> T::AccountId {
+		T::SponsorModuleId::get().into_sub_account(contract)
+	}
+
+	/// Charge `amount` paid for `caller` to the sponsorship of `contract` and
+	/// return the sponsor account. `None` if the contract is not sponsored,
+	/// or its funds or limits do not cover `amount`.
+	pub fn sponsor(contract: &EvmAddress, caller: &T::AccountId, amount: BalanceOf<T>) -> Option<T::AccountId> {
+		let limits = Self::sponsorships(contract)?;
+		let sponsor = Self::sponsor_account_id(contract);
+		let available = T::Currency::free_balance(&sponsor).saturating_sub(T::Currency::minimum_balance());
+		if amount > available {
+			return None;
+		}
+
+		let caller_amount = Self::sponsored_amounts(contract, caller).saturating_add(amount);
+		let block_amount = Self::block_sponsored_amount(contract).saturating_add(amount);
+		if limits.per_caller.map_or(false, |limit| caller_amount > limit)
+			|| limits.per_block.map_or(false, |limit| block_amount > limit)
+		{
+			return None;
+		}
+
+		SponsoredAmounts::<T>::insert(contract, caller, caller_amount);
+		BlockSponsoredAmounts::<T>::insert(contract, (frame_system::Module::<T>::block_number(), block_amount));
+		Some(sponsor)
+	}
+
+	/// Credit back to the limits of `caller` the part `amount` of a sponsored
+	/// payment that was refunded to the sponsor account.
+	pub fn refund_sponsored(contract: &EvmAddress, caller: &T::AccountId, amount: BalanceOf<T>) {
+		if amount.is_zero() || !Sponsorships::<T>::contains_key(contract) {
+			return;
+		}
+
+		SponsoredAmounts::<T>::mutate(contract, caller, |sponsored| {
+			*sponsored = sponsored.saturating_sub(amount)
+		});
+		let block_amount = Self::block_sponsored_amount(contract).saturating_sub(amount);
+		BlockSponsoredAmounts::<T>::insert(contract, (frame_system::Module::<T>::block_number(), block_amount));
+	}
+
+	/// Amount sponsored by `contract` in the current block.
+	pub fn block_sponsored_amount(contract: &EvmAddress) -> BalanceOf<T> {
+		let (block_number, amount) = BlockSponsoredAmounts::<T>::get(contract);
+		if block_number == frame_system::Module::<T>::block_number() {
+			amount
+		} else {
+			Zero::zero()
+		}
+	}
+
+	/// Funds, limits and usage of the sponsorship of `contract`, if any.
+	pub fn sponsorship(contract: &EvmAddress) -> Option<Sponsorship<BalanceOf<T>>> {
+		let limits = Self::sponsorships(contract)?;
+		Some(Sponsorship {
+			balance: T::Currency::free_balance(&Self::sponsor_account_id(contract)),
+			limits,
+			used_in_block: Self::block_sponsored_amount(contract),
+		})
+	}
+
+	/// Storage bytes, reserved deposit and code size of `contract`, if any.
+	pub fn storage_usage(contract: &EvmAddress) -> Option<StorageUsage<BalanceOf<T>>> {
+		let contract_info = Self::accounts(contract)?.contract_info?;
+		Some(StorageUsage {
+			storage_size: contract_info.storage_size,
+			reserved_deposit: T::Currency::reserved_balance(&T::AddressMapping::get_account_id(contract)),
+			code_size: Self::code_infos(contract_info.code_hash).map_or(0, |code_info| code_info.code_size),
+		})
+	}
+
+	/// Contracts using the code with `code_hash`.
+	pub fn contracts_by_code_hash(code_hash: &H256) -> Vec<EvmAddress> {
+		ContractsByCodeHash::<T>::iter_prefix(code_hash)
+			.map(|(address, _)| address)
+			.collect()
+	}
+
+	/// Fails with a distinct `ExitError` if the EVM, or `address`, is paused.
+	pub fn ensure_not_paused(address: &EvmAddress) -> Result<(), ExitError> {
+		if Self::evm_paused() {
+			return Err(ExitError::Other("EvmPaused".into()));
+		}
+		if PausedContracts::<T>::contains_key(address) {
+			return Err(ExitError::Other("ContractPaused".into()));
+		}
+		Ok(())
+	}
+
+	/// Add `ContractInfo::storage_size` to the existing contracts. It is
+	/// derived from the deposit reserved by the contract account.
+	fn migrate_to_v2() -> Weight {
+		#[derive(Decode)]
+		struct OldContractInfo {
+			code_hash: H256,
+			maintainer: EvmAddress,
+			deployed: bool,
+		}
+
+		#[derive(Decode)]
+		struct OldAccountInfo<T: Config> {
+			nonce: T::Index,
+			contract_info: Option<OldContractInfo>,
+			developer_deposit: Option<BalanceOf<T>>,
+		}
+
+		let deposit_per_byte = T::StorageDepositPerByte::get();
+		let mut count: Weight = 0;
+		Accounts::<T>::translate::<OldAccountInfo<T>, _>(|address, account| {
+			count = count.saturating_add(1);
+			let contract_info = account.contract_info.map(|contract_info| {
+				let deposit = T::Currency::reserved_balance(&T::AddressMapping::get_account_id(&address));
+				let storage_size = if deposit_per_byte.is_zero() {
+					0
+				} else {
+					(deposit / deposit_per_byte).saturated_into()
+				};
+				ContractInfo {
+					code_hash: contract_info.code_hash,
+					maintainer: contract_info.maintainer,
+					deployed: contract_info.deployed,
+					storage_size,
+				}
+			});
+			Some(AccountInfo {
+				nonce: account.nonce,
+				contract_info,
+				developer_deposit: account.developer_deposit,
+			})
+		});
+		StorageVersion::<T>::put(Releases::V2);
+
+		T::DbWeight::get().reads_writes(count.saturating_mul(2), count.saturating_add(1))
+	}
+
+	/// Index the existing contracts in `ContractsByCodeHash`.
+	fn migrate_to_v3() -> Weight {
+		let mut reads: Weight = 0;
+		let mut writes: Weight = 1;
+		for (address, account) in Accounts::<T>::iter() {
+			reads = reads.saturating_add(1);
+			if let Some(contract_info) = account.contract_info {
+				ContractsByCodeHash::<T>::insert(&contract_info.code_hash, address, ());
+				writes = writes.saturating_add(1);
+			}
+		}
+		StorageVersion::<T>::put(Releases::V3);
+
+		T::DbWeight::get().reads_writes(reads, writes)
+	}
+
+	/// Record the result of a run of a recurring call. The status of the
+	/// last run is kept, with no run left, for `RecurringCallRetention`
+	/// blocks.
+	fn finish_recurring_run(task_id: Vec<u8>, mut task: RecurringCall<T>, exit_reason: ExitReason) {
+		let now = frame_system::Module::<T>::block_number();
+		task.remaining_runs = task.remaining_runs.saturating_sub(1);
+		task.last_exit_reason = Some(exit_reason);
+		if task.remaining_runs > 0 {
+			task.next_execution = now.saturating_add(task.period);
+		} else {
+			ExpiringRecurringCalls::<T>::append(now.saturating_add(T::RecurringCallRetention::get()), &task_id);
+		}
+
+		RecurringCalls::<T>::insert(&task_id, task);
+	}
+
+	/// Remove the status of the recurring calls finished
+	/// `RecurringCallRetention` blocks before `now`. Returns the weight used.
+	fn remove_expired_recurring_calls(now: T::BlockNumber) -> Weight {
+		let task_ids = ExpiringRecurringCalls::<T>::take(now);
+		let count = task_ids.len() as Weight;
+
+		for task_id in task_ids {
+			RecurringCalls::<T>::remove(task_id);
+		}
+
+		T::DbWeight::get().reads_writes(1, count.saturating_add(1))
+	}
+
+	/// Stop sponsoring calls to a removed `contract` and return its
+	/// sponsorship funds to `maintainer`.
+	fn close_sponsorship(contract: &EvmAddress, maintainer: &EvmAddress) {
+		Sponsorships::<T>::remove(contract);
+		SponsoredAmounts::<T>::remove_prefix(contract);
+		BlockSponsoredAmounts::<T>::remove(contract);
+
+		let sponsor = Self::sponsor_account_id(contract);
+		let _ = T::Currency::transfer(
+			&sponsor,
+			&T::AddressMapping::get_account_id(maintainer),
+			T::Currency::free_balance(&sponsor),
+			ExistenceRequirement::AllowDeath,
+		);
+	}
+
+	/// Remove an account.
+	pub fn remove_account(address: &EvmAddress) -> Result<u32, ExitError> {
+		let mut size = 0u32;
+
+		// Deref code, and remove it if ref count is zero.
+		if let Some(AccountInfo {
+			contract_info: Some(contract_info),
+			..
+		}) = Self::accounts(address)
+		{
+			Self::close_sponsorship(address, &contract_info.maintainer);
+			Self::queue_storage_cleanup(address, &contract_info.maintainer);
+			ContractVerifications::<T>::remove(address);
+			CodeUpgrades::<T>::remove(address);
+			ContractsByCodeHash::<T>::remove(&contract_info.code_hash, address);
+
+			CodeInfos::<T>::mutate_exists(&contract_info.code_hash, |maybe_code_info| {
+				if let Some(code_info) = maybe_code_info.as_mut() {
+					size = code_info.code_size;
+					code_info.ref_count = code_info.ref_count.saturating_sub(1);
+					if code_info.ref_count == 0 {
+						Codes::<T>::remove(&contract_info.code_hash);
+						*maybe_code_info = None;
+					}
+				}
+			});
+		}
+
+		Accounts::<T>::remove(address);
+
+		Ok(size)
+	}
+
+	/// Queue the storage of the removed `contract` to be cleared. Its
+	/// storage deposit is held on the `maintainer` until then.
+	fn queue_storage_cleanup(contract: &EvmAddress, maintainer: &EvmAddress) {
+		let contract_account_id = T::AddressMapping::get_account_id(contract);
+		let maintainer_account_id = T::AddressMapping::get_account_id(maintainer);
+
+		// the deposit is left to the contract account if the maintainer is a
+		// dead account
+		let reserved = T::Currency::reserved_balance(&contract_account_id);
+		let deposit = T::Currency::repatriate_reserved(
+			&contract_account_id,
+			&maintainer_account_id,
+			reserved,
+			BalanceStatus::Reserved,
+		)
+		.map_or_else(|_| Zero::zero(), |not_moved| reserved.saturating_sub(not_moved));
+
+		PendingCleanups::<T>::insert(
+			contract,
+			PendingCleanup {
+				maintainer: maintainer_account_id,
+				deposit,
+				cleared_slots: 0,
+			},
+		);
+
+		let (head, tail) = CleanupQueueRange::<T>::get();
+		CleanupQueue::<T>::insert(tail, contract);
+		CleanupQueueRange::<T>::put((head, tail.saturating_add(1)));
+	}
+
+	/// Clear the storage of removed contracts, in order, within `limit`, and
+	/// refund the storage deposit of each contract cleared. Returns the
+	/// weight used.
+	fn clear_removed_contracts(limit: Weight) -> Weight {
+		let db_weight = T::DbWeight::get();
+		// reading the next slot key and removing it
+		let slot_weight = db_weight.reads_writes(1, 1).max(1);
+		let mut used_weight: Weight = 0;
+
+		loop {
+			used_weight = used_weight.saturating_add(db_weight.reads(3));
+			let (head, tail) = CleanupQueueRange::<T>::get();
+			if head == tail {
+				break;
+			}
+			let (contract, mut cleanup) = match CleanupQueue::<T>::get(head)
+				.and_then(|contract| Some((contract, PendingCleanups::<T>::get(contract)?)))
+			{
+				Some(pending) => pending,
+				None => {
+					// not expected, skip the entry
+					CleanupQueue::<T>::remove(head);
+					CleanupQueueRange::<T>::put((head.saturating_add(1), tail));
+					used_weight = used_weight.saturating_add(db_weight.writes(2));
+					continue;
+				}
+			};
+
+			let budget = limit.saturating_sub(used_weight) / slot_weight;
+			if budget.is_zero() {
+				break;
+			}
+
+			let slots = AccountStorages::<T>::iter_prefix(contract)
+				.map(|(index, _)| index)
+				.take(budget.saturated_into())
+				.collect::<Vec<_>>();
+			let cleared = slots.len() as u32;
+			for index in slots {
+				AccountStorages::<T>::remove(contract, index);
+			}
+			used_weight = used_weight.saturating_add(slot_weight.saturating_mul(cleared.into()));
+
+			if cleared > 0 {
+				cleanup.cleared_slots = cleanup.cleared_slots.saturating_add(cleared);
+				Pallet::<T>::deposit_event(Event::<T>::ContractStorageCleared(contract, cleared));
+			}
+
+			if Weight::from(cleared) == budget {
+				// slots may be left, continue in the next block
+				PendingCleanups::<T>::insert(contract, cleanup);
+				used_weight = used_weight.saturating_add(db_weight.writes(1));
+				break;
+			}
+
+			PendingCleanups::<T>::remove(contract);
+			CleanupQueue::<T>::remove(head);
+			CleanupQueueRange::<T>::put((head.saturating_add(1), tail));
+			T::Currency::unreserve(&cleanup.maintainer, cleanup.deposit);
+			used_weight = used_weight.saturating_add(db_weight.reads_writes(1, 4));
+			Pallet::<T>::deposit_event(Event::<T>::ContractStorageCleanupFinished(
+				contract,
+				cleanup.maintainer,
+				cleanup.deposit,
+			));
+		}
+
+		used_weight
+	}
+
+	/// Get the account basic in EVM format.
+	pub fn account_basic(address: &EvmAddress) -> Account {
+		let account_id = T::AddressMapping::get_account_id(address);
+
+		let nonce = Self::accounts(address).map_or(Default::default(), |account_info| account_info.nonce);
+		let balance = T::Currency::free_balance(&account_id);
+
+		Account {
+			nonce: U256::from(UniqueSaturatedInto::<u128>::unique_saturated_into(nonce)),
+			balance: U256::from(UniqueSaturatedInto::<u128>::unique_saturated_into(balance)),
+		}
+	}
+
+	/// Get code hash at given address.
+	pub fn code_hash_at_address(address: &EvmAddress) -> H256 {
+		if let Some(AccountInfo {
+			contract_info: Some(contract_info),
+			..
+		}) = Self::accounts(address)
+		{
+			contract_info.code_hash
+		} else {
+			code_hash(&[])
+		}
+	}
+
+	/// Get code at given address.
+	pub fn code_at_address(address: &EvmAddress) -> Vec<u8> {
+		Self::codes(&Self::code_hash_at_address(address))
+	}
+
+	/// Check the code returned by contract creation against the size limit,
+	/// EIP-170, and reject the 0xEF prefix, EIP-3541.
+	pub fn validate_code(code: &[u8]) -> Result<(), ExitError> {
+		if code.len() > T::MaxCodeSize::get() as usize {
+			return Err(ExitError::CreateContractLimit);
+		}
+		if Self::hardfork().disallow_ef_code() && code.first() == Some(&0xEF) {
+			return Err(ExitError::Other("InvalidCodePrefix".into()));
+		}
+		Ok(())
 	}
 
 	/// Handler on new contract initialization.
@@ -735,19 +2332,7 @@ impl<T: Config> Pallet<T> {
 		code: Vec<u8>,
 	) -> Result<(), ExitError> {
 		let code_hash = code_hash(&code.as_slice());
-		let contract_info = ContractInfo {
-			code_hash,
-			maintainer: *maintainer,
-			#[cfg(feature = "with-ethereum-compatibility")]
-			deployed: true,
-			#[cfg(not(feature = "with-ethereum-compatibility"))]
-			deployed: false,
-		};
-
 		let code_size = code.len() as u32;
-		if code_size > T::MaxCodeSize::get() {
-			return Err(ExitError::OutOfGas);
-		}
 		CodeInfos::<T>::mutate_exists(&code_hash, |maybe_code_info| {
 			if let Some(code_info) = maybe_code_info.as_mut() {
 				code_info.ref_count = code_info.ref_count.saturating_add(1);
@@ -762,6 +2347,44 @@ impl<T: Config> Pallet<T> {
 			}
 		});
 
+		Self::insert_contract_info(address, maintainer, code_hash);
+
+		Ok(())
+	}
+
+	/// Handler on new contract initialization with a stored code.
+	///
+	/// - Create new account for the contract.
+	/// - Update codes info.
+	pub fn on_contract_initialization_from_code_hash(
+		address: &EvmAddress,
+		maintainer: &EvmAddress,
+		code_hash: H256,
+	) -> Result<(), ExitError> {
+		CodeInfos::<T>::try_mutate(&code_hash, |maybe_code_info| -> Result<(), ExitError> {
+			let code_info = maybe_code_info
+				.as_mut()
+				.ok_or_else(|| ExitError::Other("CodeNotFound".into()))?;
+			code_info.ref_count = code_info.ref_count.saturating_add(1);
+			Ok(())
+		})?;
+
+		Self::insert_contract_info(address, maintainer, code_hash);
+
+		Ok(())
+	}
+
+	fn insert_contract_info(address: &EvmAddress, maintainer: &EvmAddress, code_hash: H256) {
+		let contract_info = ContractInfo {
+			code_hash,
+			maintainer: *maintainer,
+			#[cfg(feature = "with-ethereum-compatibility")]
+			deployed: true,
+			#[cfg(not(feature = "with-ethereum-compatibility"))]
+			deployed: false,
+			storage_size: 0,
+		};
+
 		Accounts::<T>::mutate(address, |maybe_account_info| {
 			if let Some(account_info) = maybe_account_info.as_mut() {
 				account_info.contract_info = Some(contract_info.clone());
@@ -770,8 +2393,7 @@ impl<T: Config> Pallet<T> {
 				*maybe_account_info = Some(account_info);
 			}
 		});
-
-		Ok(())
+		ContractsByCodeHash::<T>::insert(&code_hash, address, ());
 	}
 
 	fn do_transfer_maintainer(who: T::AccountId, contract: EvmAddress, new_maintainer: EvmAddress) -> DispatchResult {
@@ -798,6 +2420,16 @@ impl<T: Config> Pallet<T> {
 		Ok(())
 	}
 
+	/// Ensure `who` is the maintainer of `contract`.
+	fn ensure_maintainer(who: &T::AccountId, contract: &EvmAddress) -> DispatchResult {
+		let maintainer = T::AddressMapping::get_evm_address(who).ok_or(Error::<T>::AddressNotMapped)?;
+		let contract_info = Self::accounts(contract)
+			.and_then(|account_info| account_info.contract_info)
+			.ok_or(Error::<T>::ContractNotFound)?;
+		ensure!(contract_info.maintainer == maintainer, Error::<T>::NoPermission);
+		Ok(())
+	}
+
 	/// Mark contract as deployed
 	///
 	/// If maintainer is provider then it will check maintainer
@@ -821,47 +2453,170 @@ impl<T: Config> Pallet<T> {
 	}
 
 	fn do_set_code(root_or_signed: Either<(), T::AccountId>, contract: EvmAddress, code: Vec<u8>) -> DispatchResult {
-		Accounts::<T>::mutate(contract, |maybe_account_info| -> DispatchResult {
-			let account_info = maybe_account_info.as_mut().ok_or(Error::<T>::ContractNotFound)?;
-			let contract_info = account_info
-				.contract_info
-				.as_ref()
-				.ok_or(Error::<T>::ContractNotFound)?;
+		let contract_info = Self::accounts(contract)
+			.and_then(|account_info| account_info.contract_info)
+			.ok_or(Error::<T>::ContractNotFound)?;
 
-			if let Either::Right(signer) = root_or_signed {
-				let maintainer = T::AddressMapping::get_evm_address(&signer).ok_or(Error::<T>::AddressNotMapped)?;
-				ensure!(contract_info.maintainer == maintainer, Error::<T>::NoPermission);
-				ensure!(!contract_info.deployed, Error::<T>::ContractAlreadyDeployed);
+		if let Either::Right(signer) = root_or_signed {
+			let maintainer = T::AddressMapping::get_evm_address(&signer).ok_or(Error::<T>::AddressNotMapped)?;
+			ensure!(contract_info.maintainer == maintainer, Error::<T>::NoPermission);
+			ensure!(!contract_info.deployed, Error::<T>::ContractAlreadyDeployed);
+		}
+
+		Self::replace_code(contract, code)?;
+
+		Ok(())
+	}
+
+	/// Replace the code of `contract`, updating the ref counts of the old
+	/// and new code. The storage deposit of the size difference is reserved
+	/// from or released to the maintainer. Returns the old and new code
+	/// hashes.
+	#[transactional]
+	fn replace_code(contract: EvmAddress, code: Vec<u8>) -> Result<(H256, H256), DispatchError> {
+		let code_size = code.len() as u32;
+		let new_hash = code_hash(&code.as_slice());
+
+		let contract_info = Self::accounts(contract)
+			.and_then(|account_info| account_info.contract_info)
+			.ok_or(Error::<T>::ContractNotFound)?;
+		let old_hash = contract_info.code_hash;
+		if old_hash == new_hash {
+			return Ok((old_hash, new_hash));
+		}
+
+		Self::validate_code(&code).map_err(|e| match e {
+			ExitError::CreateContractLimit => Error::<T>::ContractExceedsMaxCodeSize,
+			_ => Error::<T>::InvalidCodePrefix,
+		})?;
+
+		let old_code_size = Self::code_infos(old_hash).map_or(0, |code_info| code_info.code_size);
+		if code_size > old_code_size {
+			Self::reserve_storage_deposit(&contract, code_size - old_code_size)?;
+		} else {
+			Self::release_storage_deposit(&contract, old_code_size - code_size)?;
+		}
+
+		Accounts::<T>::mutate(contract, |maybe_account_info| {
+			if let Some(contract_info) = maybe_account_info
+				.as_mut()
+				.and_then(|account_info| account_info.contract_info.as_mut())
+			{
+				contract_info.code_hash = new_hash;
 			}
+		});
 
-			let code_size = code.len() as u32;
-			let code_hash = code_hash(&code.as_slice());
-			if code_hash == contract_info.code_hash {
-				return Ok(());
+		// the metadata no longer matches the code
+		if ContractVerifications::<T>::take(contract).is_some() {
+			Pallet::<T>::deposit_event(Event::<T>::VerificationMetadataRemoved(contract));
+		}
+
+		ContractsByCodeHash::<T>::remove(&old_hash, contract);
+		ContractsByCodeHash::<T>::insert(&new_hash, contract, ());
+
+		CodeInfos::<T>::mutate_exists(&new_hash, |maybe_code_info| {
+			if let Some(code_info) = maybe_code_info.as_mut() {
+				code_info.ref_count = code_info.ref_count.saturating_add(1);
+			} else {
+				let new = CodeInfo {
+					code_size,
+					ref_count: 1,
+				};
+				*maybe_code_info = Some(new);
+
+				Codes::<T>::insert(&new_hash, code);
 			}
+		});
 
-			ensure!(
-				code_size <= T::MaxCodeSize::get(),
-				Error::<T>::ContractExceedsMaxCodeSize
-			);
+		CodeInfos::<T>::mutate_exists(&old_hash, |maybe_code_info| {
+			if let Some(code_info) = maybe_code_info.as_mut() {
+				code_info.ref_count = code_info.ref_count.saturating_sub(1);
+				if code_info.ref_count == 0 {
+					Codes::<T>::remove(&old_hash);
+					*maybe_code_info = None;
+				}
+			}
+		});
 
-			CodeInfos::<T>::mutate_exists(&code_hash, |maybe_code_info| {
-				if let Some(code_info) = maybe_code_info.as_mut() {
-					code_info.ref_count = code_info.ref_count.saturating_add(1);
-				} else {
-					let new = CodeInfo {
-						code_size,
-						ref_count: 1,
-					};
-					*maybe_code_info = Some(new);
+		Ok((old_hash, new_hash))
+	}
 
-					Codes::<T>::insert(&code_hash, code);
+	/// Apply the code upgrades scheduled at `now`. Returns the weight used.
+	fn apply_code_upgrades(now: T::BlockNumber) -> Weight {
+		let contracts = ScheduledCodeUpgrades::<T>::take(now);
+		let mut used_weight = T::DbWeight::get().reads_writes(1, 1);
+
+		for contract in contracts {
+			// cancelled, or the contract was removed
+			let upgrade = match CodeUpgrades::<T>::take(contract) {
+				Some(upgrade) => upgrade,
+				None => continue,
+			};
+			used_weight = used_weight.saturating_add(<T as Config>::WeightInfo::upgrade_code());
+
+			// the deposit of the proposal pays for the code size difference
+			match Self::release_storage_deposit(&contract, upgrade.code.len() as u32)
+				.and_then(|_| Self::replace_code(contract, upgrade.code))
+			{
+				Ok((old_hash, new_hash)) => {
+					Pallet::<T>::deposit_event(Event::<T>::ContractCodeUpgraded(contract, old_hash, new_hash))
 				}
-			});
+				Err(e) => Pallet::<T>::deposit_event(Event::<T>::ContractCodeUpgradeFailed(contract, e)),
+			}
+		}
+
+		used_weight
+	}
 
+	/// Reserve the deposit of `bytes` more storage of `contract` on the
+	/// contract, paid by its maintainer.
+	#[transactional]
+	fn reserve_storage_deposit(contract: &EvmAddress, bytes: u32) -> DispatchResult {
+		if bytes.is_zero() {
+			return Ok(());
+		}
+
+		Accounts::<T>::try_mutate(contract, |maybe_account_info| -> DispatchResult {
+			let contract_info = maybe_account_info
+				.as_mut()
+				.and_then(|account_info| account_info.contract_info.as_mut())
+				.ok_or(Error::<T>::ContractNotFound)?;
+			let maintainer = T::AddressMapping::get_account_id(&contract_info.maintainer);
+			let contract_account = T::AddressMapping::get_account_id(contract);
+			let amount = T::StorageDepositPerByte::get().saturating_mul(bytes.into());
+
+			T::Currency::transfer(&maintainer, &contract_account, amount, ExistenceRequirement::KeepAlive)?;
+			T::Currency::reserve(&contract_account, amount)?;
+			contract_info.storage_size = contract_info.storage_size.saturating_add(bytes);
 			Ok(())
 		})?;
 
+		Pallet::<T>::deposit_event(Event::<T>::AddStorageQuota(*contract, bytes));
+		Ok(())
+	}
+
+	/// Return the deposit of `bytes` less storage of `contract` to its
+	/// maintainer.
+	fn release_storage_deposit(contract: &EvmAddress, bytes: u32) -> DispatchResult {
+		if bytes.is_zero() {
+			return Ok(());
+		}
+
+		Accounts::<T>::try_mutate(contract, |maybe_account_info| -> DispatchResult {
+			let contract_info = maybe_account_info
+				.as_mut()
+				.and_then(|account_info| account_info.contract_info.as_mut())
+				.ok_or(Error::<T>::ContractNotFound)?;
+			let maintainer = T::AddressMapping::get_account_id(&contract_info.maintainer);
+			let contract_account = T::AddressMapping::get_account_id(contract);
+			let amount = T::StorageDepositPerByte::get().saturating_mul(bytes.into());
+
+			T::Currency::repatriate_reserved(&contract_account, &maintainer, amount, BalanceStatus::Free)?;
+			contract_info.storage_size = contract_info.storage_size.saturating_sub(bytes);
+			Ok(())
+		})?;
+
+		Pallet::<T>::deposit_event(Event::<T>::RemoveStorageQuota(*contract, bytes));
 		Ok(())
 	}
 
@@ -876,7 +2631,11 @@ impl<T: Config> Pallet<T> {
 			ensure!(contract_info.maintainer == *maintainer, Error::<T>::NoPermission);
 			ensure!(!contract_info.deployed, Error::<T>::ContractAlreadyDeployed);
 
-			AccountStorages::<T>::remove_prefix(contract);
+			Self::close_sponsorship(&contract, maintainer);
+			Self::queue_storage_cleanup(&contract, maintainer);
+			ContractVerifications::<T>::remove(contract);
+			CodeUpgrades::<T>::remove(contract);
+			ContractsByCodeHash::<T>::remove(&contract_info.code_hash, contract);
 
 			CodeInfos::<T>::mutate_exists(&contract_info.code_hash, |maybe_code_info| {
 				if let Some(code_info) = maybe_code_info.as_mut() {
@@ -889,10 +2648,6 @@ impl<T: Config> Pallet<T> {
 			});
 
 			let contract_account_id = T::AddressMapping::get_account_id(&contract);
-			T::Currency::unreserve(
-				&contract_account_id,
-				T::Currency::reserved_balance(&contract_account_id),
-			);
 			T::MergeAccount::merge_account(&contract_account_id, &who)?;
 
 			Ok(())
@@ -997,6 +2752,37 @@ impl<T: Config> EVMStateRentTrait<T::AccountId, BalanceOf<T>> for Pallet<T> {
 	}
 }
 
+impl<T: Config, C: IsSubType<Call<T>>> FeeSponsor<T::AccountId, C, BalanceOf<T>> for Pallet<T> {
+	fn fee_sponsor(who: &T::AccountId, call: &C, fee: BalanceOf<T>) -> Option<T::AccountId> {
+		match call.is_sub_type()? {
+			Call::call(target, ..) | Call::call_with_access_list(target, ..) => Self::sponsor(target, who, fee),
+			_ => None,
+		}
+	}
+
+	fn refund_sponsored_fee(sponsor: &T::AccountId, who: &T::AccountId, refund: BalanceOf<T>) {
+		match ModuleId::try_from_sub_account::<EvmAddress>(sponsor) {
+			Some((module_id, contract)) if module_id == T::SponsorModuleId::get() => {
+				Self::refund_sponsored(&contract, who, refund)
+			}
+			_ => {}
+		}
+	}
+}
+
+/// Settles the fees of signed extrinsics like those of Ethereum
+/// transactions: the fee is burned and the tip goes to the block author.
+impl<T: Config> OnUnbalanced<NegativeImbalanceOf<T>> for Pallet<T> {
+	fn on_unbalanceds<B>(mut fees_then_tips: impl Iterator<Item = NegativeImbalanceOf<T>>) {
+		// dropping the fee burns it
+		if let Some(_fee) = fees_then_tips.next() {
+			if let (Some(tip), Some(author)) = (fees_then_tips.next(), Self::block_author()) {
+				T::Currency::resolve_creating(&author, tip);
+			}
+		}
+	}
+}
+
 pub struct CallKillAccount<T>(PhantomData<T>);
 impl<T: Config> OnKilledAccount<T::AccountId> for CallKillAccount<T> {
 	fn on_killed_account(who: &T::AccountId) {
@@ -1008,6 +2794,18 @@ impl<T: Config> OnKilledAccount<T::AccountId> for CallKillAccount<T> {
 	}
 }
 
+/// Allows the runtime calls in `DispatchableCalls`.
+pub struct DispatchCallFilter<T>(PhantomData<T>);
+impl<T: Config, Call: GetCallMetadata> Filter<Call> for DispatchCallFilter<T> {
+	fn filter(call: &Call) -> bool {
+		let metadata = call.get_call_metadata();
+		DispatchableCalls::<T>::contains_key((
+			metadata.pallet_name.as_bytes().to_vec(),
+			metadata.function_name.as_bytes().to_vec(),
+		))
+	}
+}
+
 pub fn code_hash(code: &[u8]) -> H256 {
 	H256::from_slice(Keccak256::digest(code).as_slice())
 }
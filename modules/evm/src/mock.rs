@@ -11,7 +11,7 @@ use sp_core::{H160, H256};
 use sp_runtime::{
 	testing::Header,
 	traits::{BlakeTwo256, IdentityLookup},
-	AccountId32,
+	AccountId32, ConsensusEngineId, ModuleId,
 };
 use std::{collections::BTreeMap, str::FromStr};
 
@@ -108,8 +108,96 @@ impl Convert<u64, u64> for GasToWeight {
 	}
 }
 
+pub struct WeightToGas;
+
+impl Convert<u64, u64> for WeightToGas {
+	fn convert(a: u64) -> u64 {
+		a
+	}
+}
+
+pub const BLOCK_AUTHOR: AccountId32 = AccountId32::new([5u8; 32]);
+
+pub struct AuthorGiven;
+
+impl FindAuthor<AccountId32> for AuthorGiven {
+	fn find_author<'a, I>(_digests: I) -> Option<AccountId32>
+	where
+		I: 'a + IntoIterator<Item = (ConsensusEngineId, &'a [u8])>,
+	{
+		Some(BLOCK_AUTHOR)
+	}
+}
+
+parameter_types! {
+	pub static BaseFee: u64 = 0;
+	pub static FeePerMillionWeight: u64 = 0;
+	pub static TransactionByteFee: u64 = 0;
+}
+
+/// Free transaction payment with settable base and length fees. Only
+/// `charge_fee` takes a fee.
+pub struct MockTransactionPayment;
+
+impl TransactionPayment<AccountId32, u64, NegativeImbalanceOf<Test>> for MockTransactionPayment {
+	fn reserve_fee(_who: &AccountId32, _weight: Weight) -> Result<u64, DispatchError> {
+		Ok(0)
+	}
+
+	fn unreserve_fee(_who: &AccountId32, _fee: u64) {}
+
+	fn unreserve_and_charge_fee(
+		_who: &AccountId32,
+		_weight: Weight,
+	) -> Result<(u64, NegativeImbalanceOf<Test>), TransactionValidityError> {
+		Ok((0, NegativeImbalanceOf::<Test>::zero()))
+	}
+
+	fn refund_fee(
+		_who: &AccountId32,
+		_weight: Weight,
+		_payed: NegativeImbalanceOf<Test>,
+	) -> Result<(), TransactionValidityError> {
+		Ok(())
+	}
+
+	fn weight_to_fee(weight: Weight) -> u64 {
+		weight.saturating_mul(FeePerMillionWeight::get()) / 1_000_000
+	}
+
+	fn base_fee() -> u64 {
+		BaseFee::get()
+	}
+
+	fn length_fee(len: u32) -> u64 {
+		TransactionByteFee::get().saturating_mul(len.into())
+	}
+
+	fn charge_fee(
+		who: &AccountId32,
+		len: u32,
+		fee: u64,
+	) -> Result<(u64, NegativeImbalanceOf<Test>), TransactionValidityError> {
+		let fee = Self::length_fee(len).saturating_add(fee);
+		Balances::withdraw(
+			who,
+			fee,
+			WithdrawReasons::TRANSACTION_PAYMENT,
+			ExistenceRequirement::KeepAlive,
+		)
+		.map(|imbalance| (fee, imbalance))
+		.map_err(|_| InvalidTransaction::Payment.into())
+	}
+}
+
 parameter_types! {
 	pub NetworkContractSource: H160 = trillian();
+	pub static NetGasMetering: bool = false;
+	pub static AllowUnprotectedTransactions: bool = false;
+	pub static StorageCleanupWeight: Weight = 2;
+	pub const SponsorModuleId: ModuleId = ModuleId(*b"snp/evsp");
+	pub const MaxBatchCalls: u32 = 4;
+	pub const RecurringCallRetention: u64 = 10;
 }
 
 ord_parameter_types! {
@@ -121,6 +209,7 @@ ord_parameter_types! {
 	pub const DeploymentFee: u64 = 200;
 	pub const MaxCodeSize: u32 = 1000;
 	pub const ChainId: u64 = 1;
+	pub const EthereumTransactionStorageLimit: u32 = 1000;
 }
 
 impl Config for Test {
@@ -129,19 +218,32 @@ impl Config for Test {
 	type MergeAccount = Currencies;
 	type NewContractExtraBytes = NewContractExtraBytes;
 	type StorageDepositPerByte = StorageDepositPerByte;
+	type StorageCleanupWeight = StorageCleanupWeight;
 	type MaxCodeSize = MaxCodeSize;
 
 	type Event = Event;
 	type Precompiles = ();
 	type ChainId = ChainId;
 	type GasToWeight = GasToWeight;
-	type ChargeTransactionPayment = ();
+	type WeightToGas = WeightToGas;
+	type FindAuthor = AuthorGiven;
+	type Randomness = ();
+	type NetGasMetering = NetGasMetering;
+	type ChargeTransactionPayment = MockTransactionPayment;
 
 	type NetworkContractOrigin = EnsureSignedBy<NetworkContractAccount, AccountId32>;
 	type NetworkContractSource = NetworkContractSource;
 	type DeveloperDeposit = DeveloperDeposit;
 	type DeploymentFee = DeploymentFee;
 	type FreeDeploymentOrigin = EnsureSignedBy<CouncilAccount, AccountId32>;
+	type PauseOrigin = EnsureSignedBy<CouncilAccount, AccountId32>;
+	type CodeUpgradeOrigin = EnsureSignedBy<CouncilAccount, AccountId32>;
+	type DispatchCallOrigin = EnsureSignedBy<CouncilAccount, AccountId32>;
+	type EthereumTransactionStorageLimit = EthereumTransactionStorageLimit;
+	type AllowUnprotectedTransactions = AllowUnprotectedTransactions;
+	type SponsorModuleId = SponsorModuleId;
+	type MaxBatchCalls = MaxBatchCalls;
+	type RecurringCallRetention = RecurringCallRetention;
 
 	type WeightInfo = ();
 }
@@ -156,7 +258,7 @@ construct_runtime!(
 		UncheckedExtrinsic = UncheckedExtrinsic,
 	{
 		System: frame_system::{Module, Call, Storage, Config, Event<T>},
-		EVM: evm_mod::{Module, Config<T>, Call, Storage, Event<T>},
+		EVM: evm_mod::{Module, Config<T>, Call, Storage, Event<T>, ValidateUnsigned},
 		Tokens: orml_tokens::{Module, Storage, Event<T>},
 		Balances: pallet_balances::{Module, Call, Storage, Config<T>, Event<T>},
 		Currencies: orml_currencies::{Module, Call, Event<T>},
@@ -238,6 +340,7 @@ pub fn new_test_ext() -> sp_io::TestExternalities {
 		.unwrap();
 	evm_mod::GenesisConfig::<Test> {
 		accounts,
+		dispatchable_calls: Default::default(),
 	}
 	.assimilate_storage(&mut t)
 	.unwrap();
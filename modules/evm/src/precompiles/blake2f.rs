@@ -0,0 +1,212 @@
+//! The blake2 compression function precompile, EIP-152.
+
+use super::{ensure_cost, Precompile};
+use evm::{Context, ExitError, ExitSucceed};
+use sp_std::{convert::TryInto, vec::Vec};
+
+// Gas cost per round, `GFROUND` in EIP-152.
+const GFROUND: u64 = 1;
+
+// Input is rounds (4) ++ h (64) ++ m (128) ++ t (16) ++ f (1).
+const INPUT_LENGTH: usize = 213;
+
+const IV: [u64; 8] = [
+	0x6a09e667f3bcc908,
+	0xbb67ae8584caa73b,
+	0x3c6ef372fe94f82b,
+	0xa54ff53a5f1d36f1,
+	0x510e527fade682d1,
+	0x9b05688c2b3e6c1f,
+	0x1f83d9abfb41bd6b,
+	0x5be0cd19137e2179,
+];
+
+const SIGMA: [[usize; 16]; 10] = [
+	[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+	[14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+	[11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+	[7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+	[9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+	[2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+	[12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+	[13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+	[6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+	[10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
+
+#[allow(clippy::many_single_char_names)]
+fn g(v: &mut [u64; 16], a: usize, b: usize, c: usize, d: usize, x: u64, y: u64) {
+	v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+	v[d] = (v[d] ^ v[a]).rotate_right(32);
+	v[c] = v[c].wrapping_add(v[d]);
+	v[b] = (v[b] ^ v[c]).rotate_right(24);
+	v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+	v[d] = (v[d] ^ v[a]).rotate_right(16);
+	v[c] = v[c].wrapping_add(v[d]);
+	v[b] = (v[b] ^ v[c]).rotate_right(63);
+}
+
+/// The blake2b compression function `F`, as specified in RFC 7693 with a
+/// configurable number of rounds.
+fn compress(h: &mut [u64; 8], m: &[u64; 16], t: [u64; 2], f: bool, rounds: u32) {
+	let mut v = [0u64; 16];
+	v[..8].copy_from_slice(&h[..]);
+	v[8..].copy_from_slice(&IV);
+	v[12] ^= t[0];
+	v[13] ^= t[1];
+	if f {
+		v[14] = !v[14];
+	}
+
+	for i in 0..rounds as usize {
+		let s = &SIGMA[i % 10];
+		g(&mut v, 0, 4, 8, 12, m[s[0]], m[s[1]]);
+		g(&mut v, 1, 5, 9, 13, m[s[2]], m[s[3]]);
+		g(&mut v, 2, 6, 10, 14, m[s[4]], m[s[5]]);
+		g(&mut v, 3, 7, 11, 15, m[s[6]], m[s[7]]);
+		g(&mut v, 0, 5, 10, 15, m[s[8]], m[s[9]]);
+		g(&mut v, 1, 6, 11, 12, m[s[10]], m[s[11]]);
+		g(&mut v, 2, 7, 8, 13, m[s[12]], m[s[13]]);
+		g(&mut v, 3, 4, 9, 14, m[s[14]], m[s[15]]);
+	}
+
+	for (i, word) in h.iter_mut().enumerate() {
+		*word ^= v[i] ^ v[i + 8];
+	}
+}
+
+fn read_u64_le(input: &[u8], offset: usize) -> u64 {
+	u64::from_le_bytes(input[offset..offset + 8].try_into().expect("slice is 8 bytes; qed"))
+}
+
+/// The blake2f precompile.
+pub struct Blake2F;
+
+impl Precompile for Blake2F {
+	fn execute(
+		input: &[u8],
+		target_gas: Option<u64>,
+		_context: &Context,
+	) -> core::result::Result<(ExitSucceed, Vec<u8>, u64), ExitError> {
+		if input.len() != INPUT_LENGTH {
+			return Err(ExitError::Other("Invalid input length for blake2f".into()));
+		}
+
+		let rounds = u32::from_be_bytes(input[0..4].try_into().expect("slice is 4 bytes; qed"));
+		let cost = ensure_cost(target_gas, GFROUND.saturating_mul(rounds.into()))?;
+
+		let f = match input[212] {
+			0 => false,
+			1 => true,
+			_ => return Err(ExitError::Other("Invalid final block flag for blake2f".into())),
+		};
+
+		let mut h = [0u64; 8];
+		for (i, word) in h.iter_mut().enumerate() {
+			*word = read_u64_le(input, 4 + i * 8);
+		}
+		let mut m = [0u64; 16];
+		for (i, word) in m.iter_mut().enumerate() {
+			*word = read_u64_le(input, 68 + i * 8);
+		}
+		let t = [read_u64_le(input, 196), read_u64_le(input, 204)];
+
+		compress(&mut h, &m, t, f, rounds);
+
+		let mut output = Vec::with_capacity(64);
+		for word in h.iter() {
+			output.extend_from_slice(&word.to_le_bytes());
+		}
+
+		Ok((ExitSucceed::Returned, output, cost))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::precompiles::tests::{check_failure_vectors, check_test_vectors};
+	use sp_core::bytes::from_hex;
+
+	fn context() -> Context {
+		Context {
+			address: Default::default(),
+			caller: Default::default(),
+			apparent_value: Default::default(),
+		}
+	}
+
+	// EIP-152 test vector 5: blake2b("abc") with 12 rounds
+	const VECTOR_5: &str = "\
+		0000000c48c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5d182e6ad7f520e511f6c3e2b\
+		8c68059b6bbd41fbabd9831f79217e1319cde05b61626300000000000000000000000000000000000000000000000000\
+		000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000\
+		000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000\
+		000000000300000000000000000000000000000001";
+
+	#[test]
+	fn blake2f_test_vectors() {
+		check_test_vectors::<Blake2F>(include_str!("testdata/blake2F.json"));
+		check_failure_vectors::<Blake2F>(include_str!("testdata/fail-blake2f.json"));
+	}
+
+	#[test]
+	fn blake2f_eip152_vectors() {
+		let input = from_hex(VECTOR_5).unwrap();
+		assert_eq!(input.len(), INPUT_LENGTH);
+
+		let (_, output, cost) = Blake2F::execute(&input, None, &context()).unwrap();
+		assert_eq!(
+			output,
+			from_hex(
+				"ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d1\
+				7d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923"
+			)
+			.unwrap()
+		);
+		assert_eq!(cost, 12);
+
+		// vector 4: zero rounds
+		let mut zero_rounds = input.clone();
+		zero_rounds[3] = 0;
+		let (_, output, cost) = Blake2F::execute(&zero_rounds, None, &context()).unwrap();
+		assert_eq!(
+			output,
+			from_hex(
+				"08c9bcf367e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5\
+				d282e6ad7f520e511f6c3e2b8c68059b9442be0454267ce079217e1319cde05b"
+			)
+			.unwrap()
+		);
+		assert_eq!(cost, 0);
+
+		// vector 6: final block flag unset
+		let mut not_final = input.clone();
+		not_final[212] = 0;
+		let (_, output, _) = Blake2F::execute(&not_final, None, &context()).unwrap();
+		assert_eq!(
+			output,
+			from_hex(
+				"75ab69d3190a562c51aef8d88f1c2775876944407270c42c9844252c26d28752\
+				98743e7f6d5ea2f2d3e8d226039cd31b4e426ac4f2d3d666a610c2116fde4735"
+			)
+			.unwrap()
+		);
+	}
+
+	#[test]
+	fn blake2f_rejects_invalid_input() {
+		let input = from_hex(VECTOR_5).unwrap();
+
+		// vectors 1 and 2: wrong input length
+		assert!(Blake2F::execute(&[], None, &context()).is_err());
+		assert!(Blake2F::execute(&input[..212], None, &context()).is_err());
+
+		// vector 3: invalid final block flag
+		let mut bad_flag = input.clone();
+		bad_flag[212] = 2;
+		assert!(Blake2F::execute(&bad_flag, None, &context()).is_err());
+
+		assert_eq!(Blake2F::execute(&input, Some(11), &context()), Err(ExitError::OutOfGas));
+	}
+}
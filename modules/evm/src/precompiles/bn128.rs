@@ -0,0 +1,293 @@
+//! The alt_bn128 precompiles, EIP-196 and EIP-197, with the Istanbul gas
+//! prices of EIP-1108.
+
+use super::{ensure_cost, Precompile};
+use bn::{pairing_batch, AffineG1, AffineG2, Fq, Fq2, Fr, Group, Gt, G1, G2};
+use evm::{Context, ExitError, ExitSucceed};
+use sp_std::{cmp::min, vec::Vec};
+
+const ADD_COST: u64 = 150;
+const MUL_COST: u64 = 6_000;
+const PAIRING_BASE_COST: u64 = 45_000;
+const PAIRING_PER_POINT_COST: u64 = 34_000;
+
+// Size of one (G1, G2) pair in the pairing input.
+const PAIR_SIZE: usize = 192;
+
+/// Copy the 32 byte word at `offset`, padding with zeros past the end of
+/// `input`.
+fn read_word(input: &[u8], offset: usize) -> [u8; 32] {
+	let mut word = [0u8; 32];
+	if offset < input.len() {
+		let len = min(32, input.len() - offset);
+		word[..len].copy_from_slice(&input[offset..offset + len]);
+	}
+	word
+}
+
+fn read_fq(input: &[u8], offset: usize) -> Result<Fq, ExitError> {
+	Fq::from_slice(&read_word(input, offset)).map_err(|_| ExitError::Other("Invalid field element".into()))
+}
+
+fn read_fr(input: &[u8], offset: usize) -> Result<Fr, ExitError> {
+	Fr::from_slice(&read_word(input, offset)).map_err(|_| ExitError::Other("Invalid field element".into()))
+}
+
+/// Read a G1 point, `(0, 0)` being the point at infinity.
+fn read_g1(input: &[u8], offset: usize) -> Result<G1, ExitError> {
+	let x = read_fq(input, offset)?;
+	let y = read_fq(input, offset + 32)?;
+
+	if x.is_zero() && y.is_zero() {
+		Ok(G1::zero())
+	} else {
+		AffineG1::new(x, y)
+			.map(Into::into)
+			.map_err(|_| ExitError::Other("Invalid curve point".into()))
+	}
+}
+
+/// Read a G2 point, encoded as `(x_im, x_re, y_im, y_re)`.
+fn read_g2(input: &[u8], offset: usize) -> Result<G2, ExitError> {
+	let x = Fq2::new(read_fq(input, offset + 32)?, read_fq(input, offset)?);
+	let y = Fq2::new(read_fq(input, offset + 96)?, read_fq(input, offset + 64)?);
+
+	if x.is_zero() && y.is_zero() {
+		Ok(G2::zero())
+	} else {
+		AffineG2::new(x, y)
+			.map(Into::into)
+			.map_err(|_| ExitError::Other("Invalid curve point".into()))
+	}
+}
+
+fn encode_g1(point: G1) -> Result<Vec<u8>, ExitError> {
+	let mut output = [0u8; 64];
+	// the point at infinity has no affine form and is encoded as (0, 0)
+	if let Some(point) = AffineG1::from_jacobian(point) {
+		point
+			.x()
+			.to_big_endian(&mut output[0..32])
+			.map_err(|_| ExitError::Other("Cannot encode point".into()))?;
+		point
+			.y()
+			.to_big_endian(&mut output[32..64])
+			.map_err(|_| ExitError::Other("Cannot encode point".into()))?;
+	}
+	Ok(output.to_vec())
+}
+
+/// The alt_bn128 point addition precompile.
+pub struct Bn128Add;
+
+impl Precompile for Bn128Add {
+	fn execute(
+		input: &[u8],
+		target_gas: Option<u64>,
+		_context: &Context,
+	) -> core::result::Result<(ExitSucceed, Vec<u8>, u64), ExitError> {
+		let cost = ensure_cost(target_gas, ADD_COST)?;
+
+		let p1 = read_g1(input, 0)?;
+		let p2 = read_g1(input, 64)?;
+
+		Ok((ExitSucceed::Returned, encode_g1(p1 + p2)?, cost))
+	}
+}
+
+/// The alt_bn128 scalar multiplication precompile.
+pub struct Bn128Mul;
+
+impl Precompile for Bn128Mul {
+	fn execute(
+		input: &[u8],
+		target_gas: Option<u64>,
+		_context: &Context,
+	) -> core::result::Result<(ExitSucceed, Vec<u8>, u64), ExitError> {
+		let cost = ensure_cost(target_gas, MUL_COST)?;
+
+		let p = read_g1(input, 0)?;
+		let fr = read_fr(input, 64)?;
+
+		Ok((ExitSucceed::Returned, encode_g1(p * fr)?, cost))
+	}
+}
+
+/// The alt_bn128 pairing check precompile.
+pub struct Bn128Pairing;
+
+impl Precompile for Bn128Pairing {
+	fn execute(
+		input: &[u8],
+		target_gas: Option<u64>,
+		_context: &Context,
+	) -> core::result::Result<(ExitSucceed, Vec<u8>, u64), ExitError> {
+		if input.len() % PAIR_SIZE != 0 {
+			return Err(ExitError::Other("Invalid pairing input length".into()));
+		}
+
+		let pairs = input.len() / PAIR_SIZE;
+		let cost = ensure_cost(
+			target_gas,
+			PAIRING_PER_POINT_COST
+				.saturating_mul(pairs as u64)
+				.saturating_add(PAIRING_BASE_COST),
+		)?;
+
+		let mut points = Vec::with_capacity(pairs);
+		for i in 0..pairs {
+			let offset = i * PAIR_SIZE;
+			points.push((read_g1(input, offset)?, read_g2(input, offset + 64)?));
+		}
+
+		let mut output = [0u8; 32];
+		if points.is_empty() || pairing_batch(&points) == Gt::one() {
+			output[31] = 1;
+		}
+
+		Ok((ExitSucceed::Returned, output.to_vec(), cost))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::precompiles::tests::{check_failure_vectors, check_test_vectors};
+	use sp_core::bytes::from_hex;
+
+	fn context() -> Context {
+		Context {
+			address: Default::default(),
+			caller: Default::default(),
+			apparent_value: Default::default(),
+		}
+	}
+
+	// G1 generator (1, 2) and its negation (1, p - 2)
+	const G1_GENERATOR: &str = "\
+		0000000000000000000000000000000000000000000000000000000000000001\
+		0000000000000000000000000000000000000000000000000000000000000002";
+	const G1_GENERATOR_NEG: &str = "\
+		0000000000000000000000000000000000000000000000000000000000000001\
+		30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd45";
+	// G2 generator, as defined in EIP-197
+	const G2_GENERATOR: &str = "\
+		198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2\
+		1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed\
+		090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b\
+		12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa";
+
+	#[test]
+	fn bn128_test_vectors() {
+		check_test_vectors::<Bn128Add>(include_str!("testdata/bn256Add.json"));
+		check_failure_vectors::<Bn128Add>(include_str!("testdata/fail-bn256Add.json"));
+		check_test_vectors::<Bn128Mul>(include_str!("testdata/bn256ScalarMul.json"));
+		check_failure_vectors::<Bn128Mul>(include_str!("testdata/fail-bn256ScalarMul.json"));
+		check_test_vectors::<Bn128Pairing>(include_str!("testdata/bn256Pairing.json"));
+		check_failure_vectors::<Bn128Pairing>(include_str!("testdata/fail-bn256Pairing.json"));
+	}
+
+	#[test]
+	fn bn128_add_works() {
+		// chfast1 from go-ethereum bn256Add.json
+		let input = from_hex(
+			"18b18acfb4c2c30276db5411368e7185b311dd124691610c5d3b74034e093dc9\
+			063c909c4720840cb5134cb9f59fa749755796819658d32efc0d288198f37266\
+			07c2b7f58a84bd6145f00c9c2bc0bb1a187f20ff2c92963a88019e7c6a014eed\
+			06614e20c147e940f2d70da3f74c9a17df361706a4485c742bd6788478fa17d7",
+		)
+		.unwrap();
+		let expected = from_hex(
+			"2243525c5efd4b9c3d3c45ac0ca3fe4dd85e830a4ce6b65fa1eeaee202839703\
+			301d1d33be6da8e509df21cc35964723180eed7532537db9ae5e7d48f195c915",
+		)
+		.unwrap();
+
+		let (_, output, cost) = Bn128Add::execute(&input, None, &context()).unwrap();
+		assert_eq!(output, expected);
+		assert_eq!(cost, 150);
+
+		// empty input adds two points at infinity
+		let (_, output, _) = Bn128Add::execute(&[], None, &context()).unwrap();
+		assert_eq!(output, vec![0u8; 64]);
+
+		// P + (-P) is the point at infinity
+		let input = from_hex(&[G1_GENERATOR, G1_GENERATOR_NEG].concat()).unwrap();
+		let (_, output, _) = Bn128Add::execute(&input, None, &context()).unwrap();
+		assert_eq!(output, vec![0u8; 64]);
+
+		// (1, 3) is not on the curve
+		let mut input = from_hex(G1_GENERATOR).unwrap();
+		input[63] = 3;
+		assert!(Bn128Add::execute(&input, None, &context()).is_err());
+
+		assert_eq!(Bn128Add::execute(&[], Some(149), &context()), Err(ExitError::OutOfGas));
+	}
+
+	#[test]
+	fn bn128_mul_works() {
+		let input = from_hex(&[
+			G1_GENERATOR,
+			"0000000000000000000000000000000000000000000000000000000000000002",
+		]
+		.concat())
+		.unwrap();
+		let expected = from_hex(
+			"030644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd3\
+			15ed738c0e0a7c92e7845f96b2ae9c0a68a6a449e3538fc7ff3ebf7a5a18a2c4",
+		)
+		.unwrap();
+
+		let (_, output, cost) = Bn128Mul::execute(&input, None, &context()).unwrap();
+		assert_eq!(output, expected);
+		assert_eq!(cost, 6000);
+
+		let input = from_hex(&[
+			G1_GENERATOR,
+			"0000000000000000000000000000000000000000000000000000000000000009",
+		]
+		.concat())
+		.unwrap();
+		let expected = from_hex(
+			"039730ea8dff1254c0fee9c0ea777d29a9c710b7e616683f194f18c43b43b869\
+			073a5ffcc6fc7a28c30723d6e58ce577356982d65b833a5a5c15bf9024b43d98",
+		)
+		.unwrap();
+
+		let (_, output, _) = Bn128Mul::execute(&input, None, &context()).unwrap();
+		assert_eq!(output, expected);
+
+		// multiplying by zero gives the point at infinity
+		let (_, output, _) = Bn128Mul::execute(&from_hex(G1_GENERATOR).unwrap(), None, &context()).unwrap();
+		assert_eq!(output, vec![0u8; 64]);
+	}
+
+	#[test]
+	fn bn128_pairing_works() {
+		// the empty product is one
+		let (_, output, cost) = Bn128Pairing::execute(&[], None, &context()).unwrap();
+		assert_eq!(output[31], 1);
+		assert_eq!(cost, 45_000);
+
+		// e(P, Q) * e(-P, Q) == 1
+		let input = from_hex(&[G1_GENERATOR, G2_GENERATOR, G1_GENERATOR_NEG, G2_GENERATOR].concat()).unwrap();
+		let (_, output, cost) = Bn128Pairing::execute(&input, None, &context()).unwrap();
+		let mut expected = vec![0u8; 32];
+		expected[31] = 1;
+		assert_eq!(output, expected);
+		assert_eq!(cost, 113_000);
+
+		// e(P, Q) != 1
+		let input = from_hex(&[G1_GENERATOR, G2_GENERATOR].concat()).unwrap();
+		let (_, output, cost) = Bn128Pairing::execute(&input, None, &context()).unwrap();
+		assert_eq!(output, vec![0u8; 32]);
+		assert_eq!(cost, 79_000);
+
+		// input must be a multiple of 192 bytes
+		assert!(Bn128Pairing::execute(&input[..191], None, &context()).is_err());
+		assert_eq!(
+			Bn128Pairing::execute(&input, Some(78_999), &context()),
+			Err(ExitError::OutOfGas)
+		);
+	}
+}
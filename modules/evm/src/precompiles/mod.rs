@@ -3,11 +3,50 @@
 use evm::{Context, ExitError, ExitSucceed};
 use impl_trait_for_tuples::impl_for_tuples;
 use primitive_types::H160;
+use primitives::evm::Log;
 use ripemd160::Digest;
 use sp_runtime::SaturatedConversion;
 use sp_std::{cmp::min, vec::Vec, marker::PhantomData};
 use tiny_keccak::Hasher;
 
+mod blake2f;
+mod bn128;
+mod modexp;
+
+pub use blake2f::Blake2F;
+pub use bn128::{Bn128Add, Bn128Mul, Bn128Pairing};
+pub use modexp::ModExp;
+
+environmental::environmental!(precompile_logs: Vec<Log>);
+
+/// Emit `log` from the precompile being executed. The handler records it
+/// with the transaction logs if the precompile succeeds. Outside of the
+/// handler the log is dropped.
+pub fn log(log: Log) {
+	precompile_logs::with(|logs| logs.push(log));
+}
+
+/// Run `f` and return the logs emitted by the precompiles it executes.
+pub fn collect_logs<R>(f: impl FnOnce() -> R) -> (R, Vec<Log>) {
+	let mut logs = Vec::new();
+	let result = precompile_logs::using(&mut logs, f);
+	(result, logs)
+}
+
+environmental::environmental!(precompile_is_static: bool);
+
+/// Whether the precompile being executed was called in a static frame, by
+/// `STATICCALL` or a call below one. State changing precompiles must fail
+/// if it was. Outside of the handler this is `false`.
+pub fn is_static() -> bool {
+	precompile_is_static::with(|is_static| *is_static).unwrap_or(false)
+}
+
+/// Run `f` with `is_static()` returning `is_static`.
+pub fn with_static<R>(mut is_static: bool, f: impl FnOnce() -> R) -> R {
+	precompile_is_static::using(&mut is_static, f)
+}
+
 /// Custom precompiles to be used by EVM engine.
 pub trait Precompiles {
 	#![allow(clippy::type_complexity)]
@@ -22,6 +61,9 @@ pub trait Precompiles {
 		target_gas: Option<u64>,
 		context: &Context,
 	) -> Option<core::result::Result<(ExitSucceed, Vec<u8>, u64), ExitError>>;
+
+	/// Whether `address` is served by one of these precompiles.
+	fn is_precompile(address: H160) -> bool;
 }
 
 /// One single precompile used by EVM engine.
@@ -58,30 +100,50 @@ impl Precompiles for Tuple {
 
 		None
 	}
+
+	fn is_precompile(address: H160) -> bool {
+		let mut index = 0;
+
+		for_tuples!( #(
+			index += 1;
+			if address == H160::from_low_u64_be(index) {
+				return true
+			}
+		)* );
+
+		false
+	}
 }
 
-pub struct EvmPrecompiles<ECRecover, Sha256, Ripemd160, Identity, ECRecoverPublicKey, Sha3FIPS256, Sha3FIPS512>(
-	PhantomData<(
-		ECRecover,
-		Sha256,
-		Ripemd160,
-		Identity,
-		ECRecoverPublicKey,
-		Sha3FIPS256,
-		Sha3FIPS512,
-	)>,
+/// Ethereum standard precompiles, at addresses `0x01` to `0x09`.
+pub type StandardPrecompiles = (
+	ECRecover,
+	Sha256,
+	Ripemd160,
+	Identity,
+	ModExp,
+	Bn128Add,
+	Bn128Mul,
+	Bn128Pairing,
+	Blake2F,
 );
 
-impl<ECRecover, Sha256, Ripemd160, Identity, ECRecoverPublicKey, Sha3FIPS256, Sha3FIPS512> Precompiles
-	for EvmPrecompiles<ECRecover, Sha256, Ripemd160, Identity, ECRecoverPublicKey, Sha3FIPS256, Sha3FIPS512>
+/// Non-standard precompiles, at addresses starting from `0x80`.
+pub type NonStandardPrecompiles = (ECRecoverPublicKey, Sha3FIPS256, Sha3FIPS512);
+
+// Non-standard precompile starts with 128
+const NON_STANDARD_PRECOMPILE_START: u64 = 128;
+
+/// Standard precompiles at `0x01..` followed by non-standard precompiles at
+/// `0x80..`. Both are tuples of `Precompile`, addressed in order from one.
+pub struct EvmPrecompiles<Standard = StandardPrecompiles, NonStandard = NonStandardPrecompiles>(
+	PhantomData<(Standard, NonStandard)>,
+);
+
+impl<Standard, NonStandard> Precompiles for EvmPrecompiles<Standard, NonStandard>
 where
-	ECRecover: Precompile,
-	Sha256: Precompile,
-	Ripemd160: Precompile,
-	Identity: Precompile,
-	ECRecoverPublicKey: Precompile,
-	Sha3FIPS256: Precompile,
-	Sha3FIPS512: Precompile,
+	Standard: Precompiles,
+	NonStandard: Precompiles,
 {
 	#[allow(clippy::type_complexity)]
 	fn execute(
@@ -91,28 +153,48 @@ where
 		context: &Context,
 	) -> Option<core::result::Result<(ExitSucceed, Vec<u8>, u64), ExitError>> {
 		// https://github.com/ethereum/go-ethereum/blob/9357280fce5c5d57111d690a336cca5f89e34da6/core/vm/contracts.go#L83
-		if address == H160::from_low_u64_be(1) {
-			Some(ECRecover::execute(input, target_gas, context))
-		} else if address == H160::from_low_u64_be(2) {
-			Some(Sha256::execute(input, target_gas, context))
-		} else if address == H160::from_low_u64_be(3) {
-			Some(Ripemd160::execute(input, target_gas, context))
-		} else if address == H160::from_low_u64_be(4) {
-			Some(Identity::execute(input, target_gas, context))
+		let index = address.to_low_u64_be();
+		if address != H160::from_low_u64_be(index) {
+			return None;
+		}
+
+		if index < NON_STANDARD_PRECOMPILE_START {
+			Standard::execute(address, input, target_gas, context)
+		} else {
+			NonStandard::execute(
+				H160::from_low_u64_be(index - NON_STANDARD_PRECOMPILE_START + 1),
+				input,
+				target_gas,
+				context,
+			)
 		}
-		// Non-standard precompile starts with 128
-		else if address == H160::from_low_u64_be(128) {
-			Some(ECRecoverPublicKey::execute(input, target_gas, context))
-		} else if address == H160::from_low_u64_be(129) {
-			Some(Sha3FIPS256::execute(input, target_gas, context))
-		} else if address == H160::from_low_u64_be(130) {
-			Some(Sha3FIPS512::execute(input, target_gas, context))
+	}
+
+	fn is_precompile(address: H160) -> bool {
+		let index = address.to_low_u64_be();
+		if address != H160::from_low_u64_be(index) {
+			return false;
+		}
+
+		if index < NON_STANDARD_PRECOMPILE_START {
+			Standard::is_precompile(address)
 		} else {
-			None
+			NonStandard::is_precompile(H160::from_low_u64_be(index - NON_STANDARD_PRECOMPILE_START + 1))
 		}
 	}
 }
 
+/// Fixed gas cost
+fn ensure_cost(target_gas: Option<u64>, cost: u64) -> Result<u64, ExitError> {
+	if let Some(target_gas) = target_gas {
+		if cost > target_gas {
+			return Err(ExitError::OutOfGas);
+		}
+	}
+
+	Ok(cost)
+}
+
 /// Linear gas cost
 fn ensure_linear_cost(target_gas: Option<u64>, len: usize, base: usize, word: usize) -> Result<u64, ExitError> {
 	let cost: u64 = base
@@ -123,13 +205,7 @@ fn ensure_linear_cost(target_gas: Option<u64>, len: usize, base: usize, word: us
 		.ok_or(ExitError::OutOfGas)?
 		.saturated_into();
 
-	if let Some(target_gas) = target_gas {
-		if cost > target_gas {
-			return Err(ExitError::OutOfGas);
-		}
-	}
-
-	Ok(cost.saturated_into())
+	ensure_cost(target_gas, cost)
 }
 
 /// The identity precompile.
@@ -281,6 +357,79 @@ impl Precompile for Sha3FIPS512 {
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use serde::Deserialize;
+	use sp_core::bytes::from_hex;
+
+	/// A test vector in the format of the go-ethereum
+	/// `core/vm/testdata/precompiles` files.
+	#[derive(Deserialize)]
+	#[serde(rename_all = "PascalCase")]
+	struct TestVector {
+		input: String,
+		expected: String,
+		name: String,
+		gas: u64,
+	}
+
+	/// A failing test vector in the format of the go-ethereum `fail-*.json`
+	/// files, with the error message of this implementation.
+	#[derive(Deserialize)]
+	#[serde(rename_all = "PascalCase")]
+	struct FailureVector {
+		input: String,
+		expected_error: String,
+		name: String,
+	}
+
+	/// Run the test vectors in `json` against `P`, checking the output and
+	/// the gas cost, and that one gas less runs out of gas.
+	pub(crate) fn check_test_vectors<P: Precompile>(json: &str) {
+		let context = Context {
+			address: Default::default(),
+			caller: Default::default(),
+			apparent_value: Default::default(),
+		};
+		let vectors: Vec<TestVector> = serde_json::from_str(json).expect("invalid test vectors");
+
+		for vector in vectors {
+			let input = from_hex(&vector.input).expect("invalid input");
+			let expected = from_hex(&vector.expected).expect("invalid expected");
+
+			let (_, output, cost) = P::execute(&input, Some(vector.gas), &context)
+				.unwrap_or_else(|e| panic!("{} failed: {:?}", vector.name, e));
+			assert_eq!(output, expected, "{} output", vector.name);
+			assert_eq!(cost, vector.gas, "{} gas", vector.name);
+
+			if vector.gas > 0 {
+				assert_eq!(
+					P::execute(&input, Some(vector.gas - 1), &context),
+					Err(ExitError::OutOfGas),
+					"{} out of gas",
+					vector.name
+				);
+			}
+		}
+	}
+
+	/// Run the failing test vectors in `json` against `P`, checking the error.
+	pub(crate) fn check_failure_vectors<P: Precompile>(json: &str) {
+		let context = Context {
+			address: Default::default(),
+			caller: Default::default(),
+			apparent_value: Default::default(),
+		};
+		let vectors: Vec<FailureVector> = serde_json::from_str(json).expect("invalid test vectors");
+
+		for vector in vectors {
+			let input = from_hex(&vector.input).expect("invalid input");
+			assert_eq!(
+				P::execute(&input, None, &context),
+				Err(ExitError::Other(vector.expected_error.into())),
+				"{}",
+				vector.name
+			);
+		}
+	}
 
 	#[test]
 	fn sha3_ipfs_256_should_works() -> std::result::Result<(), ExitError> {
@@ -337,4 +486,51 @@ mod tests {
 			}
 		}
 	}
+
+	#[test]
+	fn evm_precompiles_dispatch_by_address() {
+		let context = Context {
+			address: Default::default(),
+			caller: Default::default(),
+			apparent_value: Default::default(),
+		};
+		let execute = |address: u64, input: &[u8]| {
+			<EvmPrecompiles>::execute(H160::from_low_u64_be(address), input, None, &context)
+		};
+
+		// identity
+		assert_eq!(execute(4, b"hello"), Some(Ok((ExitSucceed::Returned, b"hello".to_vec(), 18))));
+		// bn128 add of two points at infinity
+		assert_eq!(execute(6, &[]), Some(Ok((ExitSucceed::Returned, vec![0u8; 64], 150))));
+		// blake2f rejects empty input
+		assert!(matches!(execute(9, &[]), Some(Err(_))));
+		assert_eq!(execute(10, &[]), None);
+
+		assert_eq!(execute(129, b"hello"), Some(Sha3FIPS256::execute(b"hello", None, &context)));
+		assert_eq!(execute(131, &[]), None);
+		assert_eq!(execute(0, &[]), None);
+		assert_eq!(<EvmPrecompiles>::execute([1u8; 20].into(), &[], None, &context), None);
+	}
+
+	#[test]
+	fn precompiles_know_the_static_context() {
+		assert!(!is_static());
+		assert!(with_static(true, is_static));
+		assert!(!with_static(false, is_static));
+		assert!(with_static(true, || with_static(false, || !is_static()) && is_static()));
+	}
+
+	#[test]
+	fn evm_precompiles_know_their_addresses() {
+		let is_precompile = |address: u64| <EvmPrecompiles>::is_precompile(H160::from_low_u64_be(address));
+
+		assert!((1..=9).all(is_precompile));
+		assert!((128..=130).all(is_precompile));
+		assert!(!is_precompile(0));
+		assert!(!is_precompile(10));
+		assert!(!is_precompile(127));
+		assert!(!is_precompile(131));
+		assert!(!<EvmPrecompiles>::is_precompile([1u8; 20].into()));
+		assert!(!<()>::is_precompile(H160::from_low_u64_be(1)));
+	}
 }
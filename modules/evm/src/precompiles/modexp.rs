@@ -0,0 +1,207 @@
+//! The modexp precompile, EIP-198.
+
+use super::{ensure_cost, Precompile};
+use evm::{Context, ExitError, ExitSucceed};
+use num_bigint::BigUint;
+use primitive_types::U256;
+use sp_std::{cmp::max, vec, vec::Vec};
+
+// Divisor of the gas cost, `GQUADDIVISOR` in EIP-198.
+const GQUADDIVISOR: u64 = 20;
+
+/// The modexp precompile.
+pub struct ModExp;
+
+/// Read `len` bytes at `offset`, padding with zeros past the end of `input`.
+fn read_padded(input: &[u8], offset: usize, len: usize) -> Vec<u8> {
+	let mut out = vec![0u8; len];
+	if offset < input.len() {
+		let available = sp_std::cmp::min(len, input.len() - offset);
+		out[..available].copy_from_slice(&input[offset..offset + available]);
+	}
+	out
+}
+
+fn mult_complexity(x: U256) -> U256 {
+	if x <= U256::from(64) {
+		x * x
+	} else if x <= U256::from(1024) {
+		x * x / 4 + x * 96 - 3072
+	} else {
+		x * x / 16 + x * 480 - 199_680
+	}
+}
+
+fn adjusted_exp_len(exp_len: U256, exp_head: U256) -> U256 {
+	let head_bits = if exp_head.is_zero() {
+		U256::zero()
+	} else {
+		U256::from(exp_head.bits() - 1)
+	};
+
+	if exp_len <= U256::from(32) {
+		head_bits
+	} else {
+		(exp_len - 32).saturating_mul(U256::from(8)).saturating_add(head_bits)
+	}
+}
+
+impl Precompile for ModExp {
+	fn execute(
+		input: &[u8],
+		target_gas: Option<u64>,
+		_context: &Context,
+	) -> core::result::Result<(ExitSucceed, Vec<u8>, u64), ExitError> {
+		let base_len = U256::from_big_endian(&read_padded(input, 0, 32));
+		let exp_len = U256::from_big_endian(&read_padded(input, 32, 32));
+		let mod_len = U256::from_big_endian(&read_padded(input, 64, 32));
+
+		if base_len.is_zero() && mod_len.is_zero() {
+			return Ok((ExitSucceed::Returned, Vec::new(), 0));
+		}
+
+		// lengths past u32 can never be paid for
+		let max_len = U256::from(u32::max_value());
+		if base_len > max_len || mod_len > max_len {
+			return Err(ExitError::OutOfGas);
+		}
+
+		// the exponent head is the first 32 bytes of the exponent, after the base
+		let exp_offset = base_len.saturating_add(U256::from(96));
+		let exp_head = if exp_offset > max_len {
+			U256::zero()
+		} else {
+			let head_len = sp_std::cmp::min(exp_len, U256::from(32)).as_usize();
+			U256::from_big_endian(&read_padded(input, exp_offset.as_usize(), head_len))
+		};
+
+		let gas = mult_complexity(max(base_len, mod_len))
+			.saturating_mul(max(adjusted_exp_len(exp_len, exp_head), U256::one()))
+			/ GQUADDIVISOR;
+		if gas > U256::from(u64::max_value()) {
+			return Err(ExitError::OutOfGas);
+		}
+		let cost = ensure_cost(target_gas, gas.as_u64())?;
+
+		if exp_len > max_len {
+			return Err(ExitError::OutOfGas);
+		}
+		let base_len = base_len.as_usize();
+		let exp_len = exp_len.as_usize();
+		let mod_len = mod_len.as_usize();
+
+		let modulus = BigUint::from_bytes_be(&read_padded(input, 96 + base_len + exp_len, mod_len));
+		let mut output = vec![0u8; mod_len];
+		if modulus != BigUint::from(0u8) {
+			let base = BigUint::from_bytes_be(&read_padded(input, 96, base_len));
+			let exponent = BigUint::from_bytes_be(&read_padded(input, 96 + base_len, exp_len));
+			let result = base.modpow(&exponent, &modulus).to_bytes_be();
+			// result < modulus, so it always fits in `mod_len` bytes
+			output[mod_len.saturating_sub(result.len())..].copy_from_slice(&result[..]);
+		}
+
+		Ok((ExitSucceed::Returned, output, cost))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::precompiles::tests::check_test_vectors;
+	use sp_core::bytes::from_hex;
+
+	fn context() -> Context {
+		Context {
+			address: Default::default(),
+			caller: Default::default(),
+			apparent_value: Default::default(),
+		}
+	}
+
+	#[test]
+	fn modexp_geth_test_vectors() {
+		check_test_vectors::<ModExp>(include_str!("testdata/modexp.json"));
+	}
+
+	#[test]
+	fn modexp_eip198_example_1() {
+		// 3 ** (p - 1) mod p, p = secp256k1 field modulus
+		let input = from_hex(
+			"0000000000000000000000000000000000000000000000000000000000000001\
+			0000000000000000000000000000000000000000000000000000000000000020\
+			0000000000000000000000000000000000000000000000000000000000000020\
+			03\
+			fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2e\
+			fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f",
+		)
+		.unwrap();
+
+		let (_, output, cost) = ModExp::execute(&input, None, &context()).unwrap();
+		assert_eq!(U256::from_big_endian(&output), U256::one());
+		assert_eq!(output.len(), 32);
+		assert_eq!(cost, 13056);
+	}
+
+	#[test]
+	fn modexp_eip198_example_2() {
+		// 0 ** (p - 1) mod p, the modulus is zero padded past the end of input
+		let input = from_hex(
+			"0000000000000000000000000000000000000000000000000000000000000000\
+			0000000000000000000000000000000000000000000000000000000000000020\
+			0000000000000000000000000000000000000000000000000000000000000020\
+			fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2e\
+			fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f",
+		)
+		.unwrap();
+
+		let (_, output, cost) = ModExp::execute(&input, None, &context()).unwrap();
+		assert_eq!(output, vec![0u8; 32]);
+		assert_eq!(cost, 13056);
+	}
+
+	#[test]
+	fn modexp_zero_modulus_and_empty_input() {
+		let (_, output, cost) = ModExp::execute(&[], None, &context()).unwrap();
+		assert!(output.is_empty());
+		assert_eq!(cost, 0);
+
+		let input = from_hex(
+			"0000000000000000000000000000000000000000000000000000000000000001\
+			0000000000000000000000000000000000000000000000000000000000000001\
+			0000000000000000000000000000000000000000000000000000000000000002\
+			02030000",
+		)
+		.unwrap();
+		let (_, output, cost) = ModExp::execute(&input, None, &context()).unwrap();
+		assert_eq!(output, vec![0u8; 2]);
+		assert_eq!(cost, 0);
+	}
+
+	#[test]
+	fn modexp_rejects_unaffordable_input() {
+		// EIP-198 example 3: huge base and modulus lengths must not allocate
+		let input = from_hex(
+			"0000000000000000000000000000000000000000000000000000000000000000\
+			0000000000000000000000000000000000000000000000000000000000000020\
+			ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff\
+			fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffffe\
+			fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffd",
+		)
+		.unwrap();
+		assert_eq!(
+			ModExp::execute(&input, Some(100_000_000), &context()),
+			Err(ExitError::OutOfGas)
+		);
+
+		let input = from_hex(
+			"0000000000000000000000000000000000000000000000000000000000000001\
+			0000000000000000000000000000000000000000000000000000000000000020\
+			0000000000000000000000000000000000000000000000000000000000000020\
+			03\
+			fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2e\
+			fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f",
+		)
+		.unwrap();
+		assert_eq!(ModExp::execute(&input, Some(13055), &context()), Err(ExitError::OutOfGas));
+	}
+}
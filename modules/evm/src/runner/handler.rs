@@ -1,12 +1,16 @@
 #![allow(clippy::type_complexity)]
 
 use crate::{
-	precompiles::Precompiles,
-	runner::storage_meter::{StorageMeter, StorageMeterHandler},
-	AccountInfo, AccountStorages, Accounts, AddressMapping, Codes, Config, ContractInfo, Error, Event, Log,
-	MergeAccount, Pallet, Vicinity,
+	precompiles::{self, Precompiles},
+	runner::{
+		storage_meter::{StorageMeter, StorageMeterHandler},
+		tracing,
+	},
+	AccessListItem, AccountInfo, AccountStorages, Accounts, AddressMapping, Codes, Config, ContractInfo, Error, Event,
+	EvmHardfork, Log, MergeAccount, Pallet, PendingCleanups, PendingLogs, Vicinity, ACCESS_LIST_ADDRESS_COST,
+	ACCESS_LIST_STORAGE_KEY_COST, COLD_ACCOUNT_ACCESS_COST, COLD_SLOAD_COST, WARM_STORAGE_READ_COST,
 };
-use evm::{Capture, Context, CreateScheme, ExitError, ExitReason, Opcode, Runtime, Stack, Transfer};
+use evm::{Capture, Context, CreateScheme, ExitError, ExitReason, Machine, Opcode, Runtime, Stack, Transfer};
 use evm_gasometer::{self as gasometer, Gasometer};
 use evm_runtime::{Config as EvmRuntimeConfig, Handler as HandlerT};
 use frame_support::{
@@ -19,19 +23,94 @@ use sp_runtime::{
 	traits::{One, Saturating, UniqueSaturatedInto, Zero},
 	DispatchError, DispatchResult, SaturatedConversion, TransactionOutcome,
 };
-use sp_std::{cmp::min, convert::Infallible, marker::PhantomData, prelude::*, rc::Rc};
+use sp_std::{
+	cmp::min,
+	collections::{btree_map::BTreeMap, btree_set::BTreeSet},
+	convert::Infallible,
+	marker::PhantomData,
+	prelude::*,
+	rc::Rc,
+};
 
-use primitives::SYSTEM_CONTRACT_ADDRESS_PREFIX;
+use primitives::{evm::CallType, SYSTEM_CONTRACT_ADDRESS_PREFIX};
 
 /// Storage key size and storage value size.
 pub const STORAGE_SIZE: u32 = 64;
 
+/// The BASEFEE opcode of EIP-3198, unknown to the interpreter and executed
+/// by the handler.
+pub const BASEFEE: Opcode = Opcode(0x48);
+/// Gas cost of BASEFEE.
+const GAS_BASE: u64 = 2;
+
+/// Accounts and storage slots accessed by the current transaction, EIP-2929.
+#[derive(Clone)]
+pub struct AccessedSet {
+	addresses: BTreeSet<H160>,
+	storage_keys: BTreeSet<(H160, H256)>,
+	is_precompile: fn(H160) -> bool,
+}
+
+impl AccessedSet {
+	/// An empty set, with the addresses served by `P` always warm.
+	pub fn new<P: Precompiles>() -> Self {
+		Self {
+			addresses: Default::default(),
+			storage_keys: Default::default(),
+			is_precompile: P::is_precompile,
+		}
+	}
+
+	/// Mark `address` as accessed, return `true` if it was cold. Precompiles
+	/// are always warm.
+	pub fn access_address(&mut self, address: H160) -> bool {
+		!(self.is_precompile)(address) && self.addresses.insert(address)
+	}
+
+	/// Mark the storage slot as accessed, return `true` if it was cold.
+	pub fn access_storage(&mut self, address: H160, index: H256) -> bool {
+		self.storage_keys.insert((address, index))
+	}
+
+	/// The accessed accounts and storage slots as an EIP-2930 access list.
+	/// The `excluded` accounts are listed only if their storage was accessed.
+	/// Precompiles are always warm and never listed.
+	pub fn access_list(&self, excluded: &[H160]) -> Vec<AccessListItem> {
+		let is_precompile = |address: &H160| (self.is_precompile)(*address);
+		let mut list = BTreeMap::<H160, Vec<H256>>::new();
+		for address in self
+			.addresses
+			.iter()
+			.filter(|address| !excluded.contains(*address) && !is_precompile(*address))
+		{
+			list.entry(*address).or_default();
+		}
+		for (address, index) in self.storage_keys.iter().filter(|(address, _)| !is_precompile(address)) {
+			list.entry(*address).or_default().push(*index);
+		}
+		list.into_iter()
+			.map(|(address, storage_keys)| AccessListItem { address, storage_keys })
+			.collect()
+	}
+}
+
+/// Storage values at the start of the current transaction and accounts
+/// deleted by it, for EIP-2200 net gas metering and refunds.
+#[derive(Clone, Default)]
+pub struct OriginalState {
+	storage: BTreeMap<(H160, H256), H256>,
+	deleted: BTreeSet<H160>,
+}
+
 pub struct Handler<'vicinity, 'config, 'meter, T: Config> {
 	pub vicinity: &'vicinity Vicinity,
 	pub config: &'config EvmRuntimeConfig,
 	pub gasometer: Gasometer<'config>,
 	pub storage_meter: StorageMeter<'meter>,
 	pub is_static: bool,
+	pub hardfork: EvmHardfork,
+	pub accessed: AccessedSet,
+	pub original: OriginalState,
 	_marker: PhantomData<T>,
 }
 
@@ -50,29 +129,60 @@ impl<'vicinity, 'config, T: Config> Handler<'vicinity, 'config, '_, T> {
 		f: F,
 	) -> Result<R, DispatchError> {
 		frame_support::storage::with_transaction(|| {
-			let mut storage_meter_handler = StorageMeterHandlerImpl::<T> {
-				origin: vicinity.origin,
-				_marker: PhantomData,
+			// storage deposits of calls to sponsored contracts are paid by the sponsor
+			let caller = T::AddressMapping::get_account_id(&vicinity.origin);
+			let deposit = T::StorageDepositPerByte::get().saturating_mul(storage_limit.into());
+			let sponsor = if deposit.is_zero() {
+				None
+			} else {
+				Pallet::<T>::sponsor(&contract, &caller, deposit)
 			};
+			let payer = sponsor.clone().unwrap_or_else(|| caller.clone());
+			let mut storage_meter_handler = StorageMeterHandlerImpl::<T> { payer };
 			let storage_meter = match StorageMeter::new(&mut storage_meter_handler, contract, storage_limit) {
 				Ok(x) => x,
 				Err(e) => return TransactionOutcome::Rollback(Err(e)),
 			};
 
+			// the origin and the called or created contract start warm
+			let mut accessed = AccessedSet::new::<T::Precompiles>();
+			accessed.access_address(vicinity.origin);
+			accessed.access_address(contract);
+
 			let mut substate = Handler::<'vicinity, 'config, '_, T> {
 				vicinity,
 				config,
 				is_static,
 				gasometer: Gasometer::new(gas_limit, config),
 				storage_meter,
+				hardfork: Pallet::<T>::hardfork(),
+				accessed,
+				original: OriginalState::default(),
 				_marker: PhantomData,
 			};
 
-			match f(&mut substate) {
-				TransactionOutcome::Commit(r) => match substate.storage_meter.finish() {
-					Ok(_) => TransactionOutcome::Commit(Ok(r)),
-					Err(e) => TransactionOutcome::Rollback(Err(e)),
-				},
+			let outcome = f(&mut substate);
+
+			if tracing::is_enabled() {
+				tracing::access_list(substate.accessed.access_list(&[vicinity.origin, contract]));
+			}
+
+			match outcome {
+				TransactionOutcome::Commit(r) => {
+					let used_storage = substate.storage_meter.used_storage().max(0) as u32;
+					match substate.storage_meter.finish() {
+						Ok(_) => {
+							// the unused deposit is unreserved, the sponsor is only charged the rest
+							if sponsor.is_some() {
+								let unused = storage_limit.saturating_sub(used_storage);
+								let refund = T::StorageDepositPerByte::get().saturating_mul(unused.into());
+								Pallet::<T>::refund_sponsored(&contract, &caller, refund);
+							}
+							TransactionOutcome::Commit(Ok(r))
+						}
+						Err(e) => TransactionOutcome::Rollback(Err(e)),
+					}
+				}
 				TransactionOutcome::Rollback(e) => TransactionOutcome::Rollback(Ok(e)),
 			}
 		})
@@ -103,12 +213,20 @@ impl<'vicinity, 'config, T: Config> Handler<'vicinity, 'config, '_, T> {
 				is_static,
 				gasometer: Gasometer::new(gas_limit, config),
 				storage_meter,
+				hardfork: self.hardfork,
+				accessed: self.accessed.clone(),
+				original: self.original.clone(),
 				_marker: PhantomData,
 			};
 
 			match f(&mut substate, &mut self.gasometer) {
 				TransactionOutcome::Commit(r) => match substate.storage_meter.finish() {
-					Ok(_) => TransactionOutcome::Commit(Ok(r)),
+					Ok(_) => {
+						// accesses of reverted sub calls stay cold
+						self.accessed = substate.accessed;
+						self.original = substate.original;
+						TransactionOutcome::Commit(Ok(r))
+					}
 					Err(e) => TransactionOutcome::Rollback(Err(e)),
 				},
 				TransactionOutcome::Rollback(e) => TransactionOutcome::Rollback(Ok(e)),
@@ -120,11 +238,82 @@ impl<'vicinity, 'config, T: Config> Handler<'vicinity, 'config, '_, T> {
 	pub fn used_gas(&self) -> u64 {
 		self.gasometer.total_used_gas()
 			- min(
-				self.gasometer.total_used_gas() / 2,
+				self.gasometer.total_used_gas() / self.hardfork.max_refund_quotient(),
 				self.gasometer.refunded_gas() as u64,
 			)
 	}
 
+	/// Warm the accounts and storage slots of an EIP-2930 access list and
+	/// charge for them. Access lists are ignored before Berlin.
+	pub fn record_access_list(&mut self, access_list: &[AccessListItem]) -> Result<(), ExitError> {
+		if !self.hardfork.has_access_costs() {
+			return Ok(());
+		}
+
+		for item in access_list {
+			let cost = ACCESS_LIST_STORAGE_KEY_COST
+				.saturating_mul(item.storage_keys.len() as u64)
+				.saturating_add(ACCESS_LIST_ADDRESS_COST);
+			self.gasometer.record_cost(cost)?;
+
+			self.accessed.access_address(item.address);
+			for index in item.storage_keys.iter() {
+				self.accessed.access_storage(item.address, *index);
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Extra cost of the first access of an account or a storage slot by
+	/// `opcode`, EIP-2929. The opcode itself is charged at the warm price.
+	fn access_cost(&mut self, context: &Context, opcode: Opcode, stack: &Stack) -> Result<u64, ExitError> {
+		if !self.hardfork.has_access_costs() {
+			return Ok(0);
+		}
+
+		let cost = match opcode {
+			Opcode::SLOAD => {
+				if self.accessed.access_storage(context.address, stack.peek(0)?) {
+					COLD_SLOAD_COST - WARM_STORAGE_READ_COST
+				} else {
+					0
+				}
+			}
+			Opcode::SSTORE => {
+				if self.accessed.access_storage(context.address, stack.peek(0)?) {
+					COLD_SLOAD_COST
+				} else {
+					0
+				}
+			}
+			Opcode::BALANCE | Opcode::EXTCODESIZE | Opcode::EXTCODECOPY | Opcode::EXTCODEHASH => {
+				if self.accessed.access_address(stack.peek(0)?.into()) {
+					COLD_ACCOUNT_ACCESS_COST - WARM_STORAGE_READ_COST
+				} else {
+					0
+				}
+			}
+			Opcode::CALL | Opcode::CALLCODE | Opcode::DELEGATECALL | Opcode::STATICCALL => {
+				if self.accessed.access_address(stack.peek(1)?.into()) {
+					COLD_ACCOUNT_ACCESS_COST - WARM_STORAGE_READ_COST
+				} else {
+					0
+				}
+			}
+			Opcode::SUICIDE => {
+				if self.accessed.access_address(stack.peek(0)?.into()) {
+					COLD_ACCOUNT_ACCESS_COST
+				} else {
+					0
+				}
+			}
+			_ => 0,
+		};
+
+		Ok(cost)
+	}
+
 	pub fn used_storage(&self) -> i32 {
 		self.storage_meter.used_storage()
 	}
@@ -145,9 +334,23 @@ impl<'vicinity, 'config, T: Config> Handler<'vicinity, 'config, '_, T> {
 
 		let mut runtime = Runtime::new(Rc::new(code), Rc::new(input), context, self.config);
 
-		let reason = match runtime.run(self) {
-			Capture::Exit(s) => s,
-			Capture::Trap(_) => unreachable!("Trap is Infallible"),
+		let reason = if tracing::is_enabled() {
+			// step one opcode at a time to report the program counter
+			loop {
+				if let Ok(pc) = runtime.machine().position() {
+					tracing::set_pc(*pc as u64);
+				}
+				match runtime.step(self) {
+					Ok(()) => {}
+					Err(Capture::Exit(s)) => break s,
+					Err(Capture::Trap(_)) => unreachable!("Trap is Infallible"),
+				}
+			}
+		} else {
+			match runtime.run(self) {
+				Capture::Exit(s) => s,
+				Capture::Trap(_) => unreachable!("Trap is Infallible"),
+			}
 		};
 
 		match reason {
@@ -215,7 +418,10 @@ impl<'vicinity, 'config, T: Config> Handler<'vicinity, 'config, '_, T> {
 			CreateScheme::Fixed(naddress) => naddress,
 		};
 
-		if address.as_bytes().starts_with(&SYSTEM_CONTRACT_ADDRESS_PREFIX) {
+		// the storage of a removed contract must be cleared first
+		if address.as_bytes().starts_with(&SYSTEM_CONTRACT_ADDRESS_PREFIX)
+			|| PendingCleanups::<T>::contains_key(address)
+		{
 			Err(ExitError::Other(
 				Into::<&str>::into(Error::<T>::ConflictContractAddress).into(),
 			))
@@ -303,11 +509,13 @@ impl<'vicinity, 'config, 'meter, T: Config> HandlerT for Handler<'vicinity, 'con
 		AccountStorages::<T>::get(address, index)
 	}
 
-	fn original_storage(&self, _address: H160, _index: H256) -> H256 {
-		// We do not have the concept of original storage in the native runner, so we
-		// always return empty value. This only affects gas calculation in the current
-		// EVM specification.
-		H256::default()
+	fn original_storage(&self, address: H160, index: H256) -> H256 {
+		// slots not written by the current transaction still hold the original value
+		self.original
+			.storage
+			.get(&(address, index))
+			.copied()
+			.unwrap_or_else(|| AccountStorages::<T>::get(address, index))
 	}
 
 	fn gas_left(&self) -> U256 {
@@ -337,7 +545,7 @@ impl<'vicinity, 'config, 'meter, T: Config> HandlerT for Handler<'vicinity, 'con
 	}
 
 	fn block_coinbase(&self) -> H160 {
-		H160::default()
+		Pallet::<T>::block_coinbase()
 	}
 
 	fn block_timestamp(&self) -> U256 {
@@ -346,11 +554,11 @@ impl<'vicinity, 'config, 'meter, T: Config> HandlerT for Handler<'vicinity, 'con
 	}
 
 	fn block_difficulty(&self) -> U256 {
-		U256::zero()
+		Pallet::<T>::block_difficulty()
 	}
 
 	fn block_gas_limit(&self) -> U256 {
-		U256::zero()
+		Pallet::<T>::block_gas_limit()
 	}
 
 	fn chain_id(&self) -> U256 {
@@ -361,10 +569,13 @@ impl<'vicinity, 'config, 'meter, T: Config> HandlerT for Handler<'vicinity, 'con
 		true
 	}
 
-	fn deleted(&self, _address: H160) -> bool {
+	fn deleted(&self, address: H160) -> bool {
 		// This only affects gas calculation in the current EVM specification.
 		// return true to disable suicide gas refund
-		true
+		if !self.config.sstore_gas_metering || !self.hardfork.has_suicide_refund() {
+			return true;
+		}
+		self.original.deleted.contains(&address)
 	}
 
 	fn set_storage(&mut self, address: H160, index: H256, value: H256) -> Result<(), ExitError> {
@@ -377,10 +588,15 @@ impl<'vicinity, 'config, 'meter, T: Config> HandlerT for Handler<'vicinity, 'con
 			Removed,
 		}
 
+		tracing::storage(index, value);
+
 		let mut storage_change = StorageChange::None;
 
 		let default_value = H256::default();
-		let is_prev_value_default = Pallet::<T>::account_storages(address, index) == default_value;
+		let prev_value = Pallet::<T>::account_storages(address, index);
+		let is_prev_value_default = prev_value == default_value;
+
+		self.original.storage.entry((address, index)).or_insert(prev_value);
 
 		if value == default_value {
 			if !is_prev_value_default {
@@ -405,7 +621,9 @@ impl<'vicinity, 'config, 'meter, T: Config> HandlerT for Handler<'vicinity, 'con
 	}
 
 	fn log(&mut self, address: H160, topics: Vec<H256>, data: Vec<u8>) -> Result<(), ExitError> {
-		Pallet::<T>::deposit_event(Event::<T>::Log(Log { address, topics, data }));
+		let log = Log { address, topics, data };
+		PendingLogs::<T>::append(&log);
+		Pallet::<T>::deposit_event(Event::<T>::Log(log));
 
 		Ok(())
 	}
@@ -418,11 +636,10 @@ impl<'vicinity, 'config, 'meter, T: Config> HandlerT for Handler<'vicinity, 'con
 		let source = T::AddressMapping::get_account_id(&address);
 		let dest = T::AddressMapping::get_account_id(&target);
 
-		let size = Pallet::<T>::remove_account(&address)?;
-
-		self.storage_meter
-			.refund(size.saturating_add(T::NewContractExtraBytes::get()))
-			.map_err(|_| ExitError::Other("RefundStorageError".into()))?;
+		// the storage deposit is held on the maintainer until the storage is
+		// cleared, so nothing is refunded to the storage meter
+		Pallet::<T>::remove_account(&address)?;
+		self.original.deleted.insert(address);
 
 		T::MergeAccount::merge_account(&source, &dest).map_err(|_| ExitError::Other("MergeAccountError".into()))
 	}
@@ -435,6 +652,86 @@ impl<'vicinity, 'config, 'meter, T: Config> HandlerT for Handler<'vicinity, 'con
 		init_code: Vec<u8>,
 		target_gas: Option<u64>,
 	) -> Capture<(ExitReason, Option<H160>, Vec<u8>), Self::CreateInterrupt> {
+		let call_type = match scheme {
+			CreateScheme::Create2 { .. } => CallType::Create2,
+			_ => CallType::Create,
+		};
+		let gas = self.gasometer.gas();
+		tracing::call_enter(
+			call_type,
+			caller,
+			H160::default(),
+			&init_code,
+			value,
+			target_gas.unwrap_or(gas),
+		);
+
+		let result = self.inner_create(caller, scheme, value, init_code, target_gas);
+
+		if let Capture::Exit((reason, address, out)) = &result {
+			tracing::call_exit(reason, out, gas.saturating_sub(self.gasometer.gas()), *address);
+		}
+		result
+	}
+
+	fn call(
+		&mut self,
+		code_address: H160,
+		transfer: Option<Transfer>,
+		input: Vec<u8>,
+		target_gas: Option<u64>,
+		is_static: bool,
+		context: Context,
+	) -> Capture<(ExitReason, Vec<u8>), Self::CallInterrupt> {
+		let (call_type, from) = if is_static {
+			(CallType::StaticCall, context.caller)
+		} else if context.address == code_address {
+			(CallType::Call, context.caller)
+		} else if transfer.is_some() {
+			(CallType::CallCode, context.address)
+		} else {
+			(CallType::DelegateCall, context.address)
+		};
+		let value = transfer.as_ref().map(|transfer| transfer.value).unwrap_or_default();
+		let gas = self.gasometer.gas();
+		tracing::call_enter(call_type, from, code_address, &input, value, target_gas.unwrap_or(gas));
+
+		let result = self.inner_call(code_address, transfer, input, target_gas, is_static, context);
+
+		if let Capture::Exit((reason, out)) = &result {
+			tracing::call_exit(reason, out, gas.saturating_sub(self.gasometer.gas()), None);
+		}
+		result
+	}
+
+	fn other(&mut self, opcode: Opcode, machine: &mut Machine) -> Result<(), ExitError> {
+		if opcode == BASEFEE && self.hardfork.has_base_fee() {
+			let base_fee = U256::from(Pallet::<T>::base_fee_per_gas().saturated_into::<u128>());
+			let mut value = H256::default();
+			base_fee.to_big_endian(&mut value[..]);
+			machine.stack_mut().push(value)
+		} else {
+			Err(ExitError::OutOfGas)
+		}
+	}
+
+	fn pre_validate(&mut self, context: &Context, opcode: Opcode, stack: &Stack) -> Result<(), ExitError> {
+		let gas = self.gasometer.gas();
+		let result = self.record_opcode_cost(context, opcode, stack);
+		tracing::step(opcode, gas, gas.saturating_sub(self.gasometer.gas()), stack);
+		result
+	}
+}
+
+impl<'vicinity, 'config, T: Config> Handler<'vicinity, 'config, '_, T> {
+	fn inner_create(
+		&mut self,
+		caller: H160,
+		scheme: CreateScheme,
+		value: U256,
+		init_code: Vec<u8>,
+		target_gas: Option<u64>,
+	) -> Capture<(ExitReason, Option<H160>, Vec<u8>), Infallible> {
 		debug::debug!(
 			target: "evm",
 			"handler: create: caller {:?}",
@@ -462,6 +759,7 @@ impl<'vicinity, 'config, 'meter, T: Config> HandlerT for Handler<'vicinity, 'con
 			maybe_address.unwrap()
 		};
 		Self::inc_nonce(caller);
+		self.accessed.access_address(address);
 
 		let origin = &self.vicinity.origin;
 
@@ -479,6 +777,10 @@ impl<'vicinity, 'config, 'meter, T: Config> HandlerT for Handler<'vicinity, 'con
 				}));
 
 				let (reason, out) = substate.execute(caller, address, value, init_code, Vec::new());
+				let reason = match reason {
+					ExitReason::Succeed(_) => Pallet::<T>::validate_code(&out).map_or_else(Into::into, |_| reason),
+					_ => reason,
+				};
 
 				match reason {
 					ExitReason::Succeed(s) => match substate.gasometer.record_deposit(out.len()) {
@@ -518,7 +820,7 @@ impl<'vicinity, 'config, 'meter, T: Config> HandlerT for Handler<'vicinity, 'con
 		})
 	}
 
-	fn call(
+	fn inner_call(
 		&mut self,
 		code_address: H160,
 		transfer: Option<Transfer>,
@@ -526,7 +828,7 @@ impl<'vicinity, 'config, 'meter, T: Config> HandlerT for Handler<'vicinity, 'con
 		target_gas: Option<u64>,
 		is_static: bool,
 		context: Context,
-	) -> Capture<(ExitReason, Vec<u8>), Self::CallInterrupt> {
+	) -> Capture<(ExitReason, Vec<u8>), Infallible> {
 		debug::debug!(
 			target: "evm",
 			"handler: call: source {:?} code_address {:?} input: {:?} target_gas {:?} gas_left {:?}",
@@ -543,6 +845,9 @@ impl<'vicinity, 'config, 'meter, T: Config> HandlerT for Handler<'vicinity, 'con
 			return Capture::Exit((ExitError::OutOfGas.into(), Vec::new()));
 		}
 
+		try_or_fail!(Pallet::<T>::ensure_not_paused(&code_address));
+		try_or_fail!(Pallet::<T>::ensure_not_paused(&context.address));
+
 		let mut after_gas = self.gasometer.gas();
 		if self.config.call_l64_after_gas {
 			after_gas = l64(after_gas);
@@ -571,7 +876,13 @@ impl<'vicinity, 'config, 'meter, T: Config> HandlerT for Handler<'vicinity, 'con
 
 				try_or_rollback!(gasometer.record_cost(target_gas));
 
-				if let Some(ret) = T::Precompiles::execute(code_address, &input, Some(target_gas), &context) {
+				let is_static = substate.is_static;
+				let (ret, logs) = precompiles::collect_logs(|| {
+					precompiles::with_static(is_static, || {
+						T::Precompiles::execute(code_address, &input, Some(target_gas), &context)
+					})
+				});
+				if let Some(ret) = ret {
 					debug::debug!(
 						target: "evm",
 						"handler: call-result: precompile result {:?}",
@@ -584,6 +895,9 @@ impl<'vicinity, 'config, 'meter, T: Config> HandlerT for Handler<'vicinity, 'con
 							try_or_rollback!(substate.gasometer.record_cost(cost));
 							try_or_rollback!(gasometer.record_stipend(substate.gasometer.gas()));
 							try_or_rollback!(gasometer.record_refund(substate.gasometer.refunded_gas()));
+							for log in logs {
+								try_or_rollback!(substate.log(log.address, log.topics, log.data));
+							}
 							// precompile contract cost 0
 							// try_or_rollback!(self.storage_meter.record_cost(0));
 							TransactionOutcome::Commit(Capture::Exit((s.into(), out)))
@@ -629,7 +943,11 @@ impl<'vicinity, 'config, 'meter, T: Config> HandlerT for Handler<'vicinity, 'con
 		})
 	}
 
-	fn pre_validate(&mut self, context: &Context, opcode: Opcode, stack: &Stack) -> Result<(), ExitError> {
+	fn record_opcode_cost(&mut self, context: &Context, opcode: Opcode, stack: &Stack) -> Result<(), ExitError> {
+		if opcode == BASEFEE && self.hardfork.has_base_fee() {
+			return self.gasometer.record_cost(GAS_BASE);
+		}
+
 		if let Some(cost) = gasometer::static_opcode_cost(opcode) {
 			self.gasometer.record_cost(cost)?;
 		} else {
@@ -638,13 +956,17 @@ impl<'vicinity, 'config, 'meter, T: Config> HandlerT for Handler<'vicinity, 'con
 
 			self.gasometer.record_dynamic_cost(gas_cost, memory_cost)?;
 		}
+
+		let access_cost = self.access_cost(context, opcode, stack)?;
+		if access_cost > 0 {
+			self.gasometer.record_cost(access_cost)?;
+		}
 		Ok(())
 	}
 }
 
 struct StorageMeterHandlerImpl<T: Config> {
-	origin: H160,
-	_marker: PhantomData<T>,
+	payer: T::AccountId,
 }
 
 impl<T: Config> StorageMeterHandler for StorageMeterHandlerImpl<T> {
@@ -656,14 +978,12 @@ impl<T: Config> StorageMeterHandler for StorageMeterHandlerImpl<T> {
 		debug::debug!(
 			target: "evm",
 			"reserve_storage: from {:?} limit {:?}",
-			self.origin, limit,
+			self.payer, limit,
 		);
 
-		let user = T::AddressMapping::get_account_id(&self.origin);
-
 		let amount = T::StorageDepositPerByte::get().saturating_mul(limit.into());
 
-		T::Currency::reserve(&user, amount)
+		T::Currency::reserve(&self.payer, amount)
 	}
 
 	fn unreserve_storage(&mut self, limit: u32, used: u32, refunded: u32) -> DispatchResult {
@@ -676,15 +996,14 @@ impl<T: Config> StorageMeterHandler for StorageMeterHandlerImpl<T> {
 		debug::debug!(
 			target: "evm",
 			"unreserve_storage: from {:?} used {:?} refunded {:?} unused {:?}",
-			self.origin, used, refunded, unused
+			self.payer, used, refunded, unused
 		);
 
-		let user = T::AddressMapping::get_account_id(&self.origin);
 		let amount = T::StorageDepositPerByte::get().saturating_mul(unused.into());
 
 		// should always be able to unreserve the amount
 		// but otherwise we will just ignore the issue here
-		let _ = T::Currency::unreserve(&user, amount);
+		let _ = T::Currency::unreserve(&self.payer, amount);
 
 		Ok(())
 	}
@@ -698,10 +1017,10 @@ impl<T: Config> StorageMeterHandler for StorageMeterHandlerImpl<T> {
 		debug::debug!(
 			target: "evm",
 			"charge_storage: from {:?} contract {:?} used {:?} refunded {:?}",
-			&self.origin, contract, used, refunded
+			&self.payer, contract, used, refunded
 		);
 
-		let user = T::AddressMapping::get_account_id(&self.origin);
+		let user = &self.payer;
 		let contract_acc = T::AddressMapping::get_account_id(contract);
 
 		if used > refunded {
@@ -711,17 +1030,31 @@ impl<T: Config> StorageMeterHandler for StorageMeterHandlerImpl<T> {
 			// repatriate_reserved requires beneficiary is an existing account but
 			// contract_acc could be a new account so we need to do
 			// unreserve/transfer/reserve
-			T::Currency::unreserve(&user, amount);
-			T::Currency::transfer(&user, &contract_acc, amount, ExistenceRequirement::AllowDeath)?;
+			T::Currency::unreserve(user, amount);
+			T::Currency::transfer(user, &contract_acc, amount, ExistenceRequirement::AllowDeath)?;
 			T::Currency::reserve(&contract_acc, amount)?;
+
+			Pallet::<T>::deposit_event(Event::<T>::AddStorageQuota(*contract, storage));
 		} else {
 			let storage = refunded - used;
 			let amount = T::StorageDepositPerByte::get().saturating_mul(storage.into());
 
 			// user can't be a dead account
-			T::Currency::repatriate_reserved(&contract_acc, &user, amount, BalanceStatus::Reserved)?;
+			T::Currency::repatriate_reserved(&contract_acc, user, amount, BalanceStatus::Reserved)?;
+
+			Pallet::<T>::deposit_event(Event::<T>::RemoveStorageQuota(*contract, storage));
 		};
 
+		Accounts::<T>::mutate(contract, |maybe_account_info| {
+			if let Some(AccountInfo {
+				contract_info: Some(contract_info),
+				..
+			}) = maybe_account_info.as_mut()
+			{
+				contract_info.storage_size = contract_info.storage_size.saturating_add(used).saturating_sub(refunded);
+			}
+		});
+
 		Ok(())
 	}
 
@@ -1,8 +1,12 @@
 pub mod handler;
 pub mod storage_meter;
+pub mod tracing;
 
-use crate::{AddressMapping, BalanceOf, CallInfo, Config, CreateInfo, Error, Pallet, Vicinity};
-use evm::{CreateScheme, ExitError, ExitReason};
+use crate::{
+	AccessListItem, AddressMapping, BalanceOf, BatchCall, BatchCallInfo, CallInfo, Config, CreateInfo, Error, Pallet,
+	Vicinity,
+};
+use evm::{CreateScheme, ExitError, ExitReason, ExitSucceed};
 use evm_runtime::Handler as HandlerT;
 use evm_gasometer::{self as gasometer};
 use frame_support::{
@@ -11,6 +15,7 @@ use frame_support::{
 };
 use handler::Handler;
 use primitive_types::{H160, H256, U256};
+use primitives::evm::CallType;
 use sha3::{Digest, Keccak256};
 use sp_runtime::{traits::Zero, DispatchError, DispatchResult, SaturatedConversion, TransactionOutcome};
 use sp_std::{marker::PhantomData, vec::Vec};
@@ -29,6 +34,7 @@ impl<T: Config> Runner<T> {
 		storage_limit: u32,
 		assigned_address: Option<H160>,
 		salt: Option<H256>,
+		access_list: &[AccessListItem],
 		tag: &'static str,
 		config: &evm::Config,
 	) -> Result<CreateInfo, DispatchError> {
@@ -42,7 +48,7 @@ impl<T: Config> Runner<T> {
 		);
 
 		let vicinity = Vicinity {
-			gas_price: U256::one(),
+			gas_price: Pallet::<T>::gas_price(),
 			origin: source,
 		};
 
@@ -64,7 +70,7 @@ impl<T: Config> Runner<T> {
 
 		Handler::<T>::inc_nonce(source);
 
-		Handler::<T>::run_transaction(
+		let info = Handler::<T>::run_transaction(
 			&vicinity,
 			gas_limit,
 			storage_limit,
@@ -81,13 +87,31 @@ impl<T: Config> Runner<T> {
 					return TransactionOutcome::Rollback(Err(DispatchError::Other("OutOfGas")));
 				}
 
-				let (reason, out) = substate.execute(
-					source,
-					address,
-					U256::from(value.saturated_into::<u128>()),
-					init,
-					Vec::new(),
-				);
+				if substate.record_access_list(access_list).is_err() {
+					return TransactionOutcome::Rollback(Err(DispatchError::Other("OutOfGas")));
+				}
+
+				if let Err(e) = Pallet::<T>::ensure_not_paused(&address) {
+					return TransactionOutcome::Rollback(Ok(CreateInfo {
+						exit_reason: e.into(),
+						address,
+						output: Vec::new(),
+						used_gas: U256::from(substate.used_gas()),
+						used_storage: 0,
+					}));
+				}
+
+				let value = U256::from(value.saturated_into::<u128>());
+				let call_type = if salt.is_some() {
+					CallType::Create2
+				} else {
+					CallType::Create
+				};
+				tracing::call_enter(call_type, source, address, &init, value, gas_limit);
+
+				let (reason, out) = substate.execute(source, address, value, init, Vec::new());
+
+				tracing::call_exit(&reason, &out, substate.used_gas(), Some(address));
 
 				let mut create_info = CreateInfo {
 					exit_reason: reason.clone(),
@@ -109,6 +133,11 @@ impl<T: Config> Runner<T> {
 					return TransactionOutcome::Rollback(Ok(create_info));
 				}
 
+				if let Err(e) = Pallet::<T>::validate_code(&out) {
+					create_info.exit_reason = e.into();
+					return TransactionOutcome::Rollback(Ok(create_info));
+				}
+
 				if let Err(e) = substate.gasometer.record_deposit(out.len()) {
 					create_info.exit_reason = e.into();
 					return TransactionOutcome::Rollback(Ok(create_info));
@@ -136,7 +165,17 @@ impl<T: Config> Runner<T> {
 
 				TransactionOutcome::Commit(Ok(create_info))
 			},
-		)?
+		)??;
+
+		Pallet::<T>::record_receipt(
+			source,
+			None,
+			info.exit_reason.is_succeed(),
+			info.used_gas,
+			Some(info.address).filter(|_| info.exit_reason.is_succeed()),
+		);
+
+		Ok(info)
 	}
 
 	fn transfer(source: H160, target: H160, value: BalanceOf<T>) -> DispatchResult {
@@ -160,6 +199,32 @@ impl<T: Config> Runner<T> {
 		gas_limit: u64,
 		storage_limit: u32,
 		config: &evm::Config,
+	) -> Result<CallInfo, DispatchError> {
+		Self::call_with_access_list(
+			sender,
+			origin,
+			target,
+			input,
+			value,
+			gas_limit,
+			storage_limit,
+			Vec::new(),
+			config,
+		)
+	}
+
+	/// Execute a call with the accounts and storage slots of `access_list`
+	/// warm from the start, EIP-2930.
+	pub fn call_with_access_list(
+		sender: H160,
+		origin: H160,
+		target: H160,
+		input: Vec<u8>,
+		value: BalanceOf<T>,
+		gas_limit: u64,
+		storage_limit: u32,
+		access_list: Vec<AccessListItem>,
+		config: &evm::Config,
 	) -> Result<CallInfo, DispatchError> {
 		debug::debug!(
 			target: "evm",
@@ -173,7 +238,7 @@ impl<T: Config> Runner<T> {
 		);
 
 		let vicinity = Vicinity {
-			gas_price: U256::one(),
+			gas_price: Pallet::<T>::gas_price(),
 			origin,
 		};
 
@@ -185,7 +250,7 @@ impl<T: Config> Runner<T> {
 
 		Handler::<T>::inc_nonce(sender);
 
-		Handler::<T>::run_transaction(&vicinity, gas_limit, storage_limit, target, false, config, |substate| {
+		let info = Handler::<T>::run_transaction(&vicinity, gas_limit, storage_limit, target, false, config, |substate| {
 			if let Err(e) = Self::transfer(sender, target, value) {
 				return TransactionOutcome::Rollback(Err(e));
 			}
@@ -196,8 +261,25 @@ impl<T: Config> Runner<T> {
 				return TransactionOutcome::Rollback(Err(DispatchError::Other("OutOfGas")));
 			}
 
-			let (reason, out) =
-				substate.execute(sender, target, U256::from(value.saturated_into::<u128>()), code, input);
+			if substate.record_access_list(&access_list).is_err() {
+				return TransactionOutcome::Rollback(Err(DispatchError::Other("OutOfGas")));
+			}
+
+			if let Err(e) = Pallet::<T>::ensure_not_paused(&target) {
+				return TransactionOutcome::Rollback(Ok(CallInfo {
+					exit_reason: e.into(),
+					output: Vec::new(),
+					used_gas: U256::from(substate.used_gas()),
+					used_storage: 0,
+				}));
+			}
+
+			let value = U256::from(value.saturated_into::<u128>());
+			tracing::call_enter(CallType::Call, sender, target, &input, value, gas_limit);
+
+			let (reason, out) = substate.execute(sender, target, value, code, input);
+
+			tracing::call_exit(&reason, &out, substate.used_gas(), None);
 
 			let call_info = CallInfo {
 				exit_reason: reason.clone(),
@@ -217,7 +299,66 @@ impl<T: Config> Runner<T> {
 			}
 
 			TransactionOutcome::Commit(Ok(call_info))
-		})?
+		})??;
+
+		Pallet::<T>::record_receipt(origin, Some(target), info.exit_reason.is_succeed(), info.used_gas, None);
+
+		Ok(info)
+	}
+
+	/// Execute `calls` from `source` in order. If a call fails, or returns
+	/// an error, the state changes of all the calls are reverted.
+	pub fn batch_call(
+		source: H160,
+		calls: Vec<BatchCall<BalanceOf<T>>>,
+		config: &evm::Config,
+	) -> Result<BatchCallInfo, DispatchError> {
+		let mut info = BatchCallInfo {
+			exit_reason: ExitReason::Succeed(ExitSucceed::Stopped),
+			output: Vec::new(),
+			index: 0,
+			used_gas: U256::zero(),
+			used_storage: 0,
+		};
+		let mut failed_target = None;
+
+		frame_support::storage::with_transaction(|| {
+			for (index, call) in calls.into_iter().enumerate() {
+				let call_info = match Self::call(
+					source,
+					source,
+					call.target,
+					call.input,
+					call.value,
+					call.gas_limit,
+					call.storage_limit,
+					config,
+				) {
+					Ok(call_info) => call_info,
+					Err(e) => return TransactionOutcome::Rollback(Err(e)),
+				};
+
+				info.index = index as u32;
+				info.used_gas = info.used_gas.saturating_add(call_info.used_gas);
+				info.used_storage = info.used_storage.saturating_add(call_info.used_storage);
+				info.exit_reason = call_info.exit_reason;
+				info.output = call_info.output;
+
+				if !info.exit_reason.is_succeed() {
+					info.used_storage = 0;
+					failed_target = Some(call.target);
+					return TransactionOutcome::Rollback(Ok(()));
+				}
+			}
+			TransactionOutcome::Commit(Ok(()))
+		})?;
+
+		// the receipts of the calls are reverted with them
+		if let Some(target) = failed_target {
+			Pallet::<T>::record_receipt(source, Some(target), false, info.used_gas, None);
+		}
+
+		Ok(info)
 	}
 
 	pub fn create(
@@ -236,11 +377,157 @@ impl<T: Config> Runner<T> {
 			storage_limit,
 			None,
 			None,
+			&[],
+			"create",
+			config,
+		)
+	}
+
+	/// Execute a contract creation with the accounts and storage slots of
+	/// `access_list` warm from the start, EIP-2930.
+	pub fn create_with_access_list(
+		source: H160,
+		init: Vec<u8>,
+		value: BalanceOf<T>,
+		gas_limit: u64,
+		storage_limit: u32,
+		access_list: Vec<AccessListItem>,
+		config: &evm::Config,
+	) -> Result<CreateInfo, DispatchError> {
+		Self::inner_create(
+			source,
+			init,
+			value,
+			gas_limit,
+			storage_limit,
+			None,
+			None,
+			&access_list,
 			"create",
 			config,
 		)
 	}
 
+	/// Create a contract at the next address of `source` with the code
+	/// stored under `code_hash`, then call it with `input` unless it is
+	/// empty. The code is neither charged gas nor storage again.
+	pub fn create_from_code_hash(
+		source: H160,
+		code_hash: H256,
+		input: Vec<u8>,
+		value: BalanceOf<T>,
+		gas_limit: u64,
+		storage_limit: u32,
+		config: &evm::Config,
+	) -> Result<CreateInfo, DispatchError> {
+		debug::debug!(
+			target: "evm",
+			"create-from-code-hash: source {:?}, code_hash: {:?}, gas_limit: {:?}, storage_limit: {:?}",
+			source,
+			code_hash,
+			gas_limit,
+			storage_limit,
+		);
+
+		if Pallet::<T>::code_infos(code_hash).is_none() {
+			return Err(Error::<T>::CodeNotFound.into());
+		}
+
+		let vicinity = Vicinity {
+			gas_price: Pallet::<T>::gas_price(),
+			origin: source,
+		};
+
+		let address = Handler::<T>::create_address(CreateScheme::Legacy { caller: source })
+			.map_err(|_| Error::<T>::ConflictContractAddress)?;
+
+		Handler::<T>::inc_nonce(source);
+
+		let info = Handler::<T>::run_transaction(
+			&vicinity,
+			gas_limit,
+			storage_limit,
+			address,
+			false,
+			config,
+			|substate| {
+				if let Err(e) = Self::transfer(source, address, value) {
+					return TransactionOutcome::Rollback(Err(e));
+				}
+
+				let transaction_cost = gasometer::call_transaction_cost(&input);
+				if substate.gasometer.record_transaction(transaction_cost).is_err() {
+					return TransactionOutcome::Rollback(Err(DispatchError::Other("OutOfGas")));
+				}
+
+				let mut create_info = CreateInfo {
+					exit_reason: ExitReason::Succeed(ExitSucceed::Returned),
+					address,
+					output: Vec::new(),
+					used_gas: U256::from(substate.used_gas()),
+					used_storage: 0,
+				};
+
+				if let Err(e) = Pallet::<T>::ensure_not_paused(&address) {
+					create_info.exit_reason = e.into();
+					return TransactionOutcome::Rollback(Ok(create_info));
+				}
+
+				let value = U256::from(value.saturated_into::<u128>());
+				tracing::call_enter(CallType::Create, source, address, &input, value, gas_limit);
+
+				if substate.storage_meter.charge(T::NewContractExtraBytes::get()).is_err() {
+					create_info.exit_reason = ExitReason::Error(ExitError::OutOfGas);
+				} else if let Err(e) =
+					Pallet::<T>::on_contract_initialization_from_code_hash(&address, &source, code_hash)
+				{
+					create_info.exit_reason = e.into();
+				} else {
+					Handler::<T>::inc_nonce(address);
+
+					if !input.is_empty() {
+						let code = Pallet::<T>::codes(&code_hash);
+						let (reason, out) = substate.execute(source, address, value, code, input);
+						create_info.exit_reason = reason;
+						create_info.output = out;
+					}
+				}
+
+				tracing::call_exit(
+					&create_info.exit_reason,
+					&create_info.output,
+					substate.used_gas(),
+					Some(address),
+				);
+
+				create_info.used_gas = U256::from(substate.used_gas());
+				create_info.used_storage = substate.used_storage();
+
+				debug::debug!(
+					target: "evm",
+					"create-from-code-hash-result: create_info {:?}",
+					create_info
+				);
+
+				if !create_info.exit_reason.is_succeed() {
+					return TransactionOutcome::Rollback(Ok(create_info));
+				}
+
+				TransactionOutcome::Commit(Ok(create_info))
+			},
+		)??;
+
+		Pallet::<T>::record_receipt(
+			source,
+			None,
+			info.exit_reason.is_succeed(),
+			info.used_gas,
+			Some(info.address).filter(|_| info.exit_reason.is_succeed()),
+		);
+
+		Ok(info)
+	}
+
 	pub fn create2(
 		source: H160,
 		init: Vec<u8>,
@@ -258,6 +545,7 @@ impl<T: Config> Runner<T> {
 			storage_limit,
 			None,
 			Some(salt),
+			&[],
 			"create2",
 			config,
 		)
@@ -280,6 +568,7 @@ impl<T: Config> Runner<T> {
 			storage_limit,
 			Some(assigned_address),
 			None,
+			&[],
 			"create-system-contract",
 			config,
 		)
@@ -0,0 +1,126 @@
+//! Opt-in execution tracing. The handler reports calls, opcodes and storage
+//! writes to the tracer set with `using`, and does nothing otherwise.
+
+use evm::{ExitReason, ExitSucceed, Opcode, Stack};
+use primitive_types::{H160, H256, U256};
+use primitives::evm::{AccessListItem, CallTrace, CallType, Step, Trace, TracerConfig};
+use sp_std::vec::Vec;
+
+environmental::environmental!(tracer: Tracer);
+
+pub struct Tracer {
+	config: TracerConfig,
+	frames: Vec<CallTrace>,
+	pc: u64,
+	trace: Trace,
+}
+
+impl Tracer {
+	pub fn new(config: TracerConfig) -> Self {
+		Self {
+			config,
+			frames: Vec::new(),
+			pc: 0,
+			trace: Trace::default(),
+		}
+	}
+
+	pub fn finish(self) -> Trace {
+		self.trace
+	}
+
+	fn enter(&mut self, call_type: CallType, from: H160, to: H160, input: Vec<u8>, value: U256, gas: u64) {
+		self.frames.push(CallTrace {
+			call_type,
+			from,
+			to,
+			input,
+			value,
+			gas,
+			gas_used: 0,
+			output: Vec::new(),
+			// overwritten when the frame exits
+			exit_reason: ExitReason::Succeed(ExitSucceed::Stopped),
+			calls: Vec::new(),
+		});
+	}
+
+	fn exit(&mut self, exit_reason: &ExitReason, output: &[u8], gas_used: u64, created: Option<H160>) {
+		if let Some(mut frame) = self.frames.pop() {
+			frame.exit_reason = exit_reason.clone();
+			frame.output = output.to_vec();
+			frame.gas_used = gas_used;
+			if let Some(address) = created {
+				frame.to = address;
+			}
+
+			match self.frames.last_mut() {
+				Some(parent) => parent.calls.push(frame),
+				None => self.trace.calls.push(frame),
+			}
+		}
+	}
+}
+
+/// Trace all EVM executions in `f` with `tracer`.
+pub fn using<R, F: FnOnce() -> R>(tracer: &mut Tracer, f: F) -> R {
+	tracer::using(tracer, f)
+}
+
+pub(crate) fn is_enabled() -> bool {
+	tracer::with(|_| ()).is_some()
+}
+
+pub(crate) fn call_enter(call_type: CallType, from: H160, to: H160, input: &[u8], value: U256, gas: u64) {
+	tracer::with(|tracer| tracer.enter(call_type, from, to, input.to_vec(), value, gas));
+}
+
+pub(crate) fn call_exit(exit_reason: &ExitReason, output: &[u8], gas_used: u64, created: Option<H160>) {
+	tracer::with(|tracer| tracer.exit(exit_reason, output, gas_used, created));
+}
+
+pub(crate) fn set_pc(pc: u64) {
+	tracer::with(|tracer| tracer.pc = pc);
+}
+
+pub(crate) fn step(opcode: Opcode, gas: u64, gas_cost: u64, stack: &Stack) {
+	tracer::with(|tracer| {
+		if tracer.config == TracerConfig::OpcodeLogger {
+			let step = Step {
+				pc: tracer.pc,
+				op: opcode.0,
+				gas,
+				gas_cost,
+				depth: tracer.frames.len() as u32,
+				stack: stack.data().clone(),
+				storage: Vec::new(),
+			};
+			tracer.trace.steps.push(step);
+		}
+	});
+}
+
+pub(crate) fn storage(index: H256, value: H256) {
+	tracer::with(|tracer| {
+		if let Some(step) = tracer.trace.steps.last_mut() {
+			step.storage.push((index, value));
+		}
+	});
+}
+
+pub(crate) fn access_list(access_list: Vec<AccessListItem>) {
+	tracer::with(|tracer| {
+		for item in access_list {
+			match tracer.trace.access_list.iter_mut().find(|i| i.address == item.address) {
+				Some(traced) => {
+					for index in item.storage_keys {
+						if !traced.storage_keys.contains(&index) {
+							traced.storage_keys.push(index);
+						}
+					}
+				}
+				None => tracer.trace.access_list.push(item),
+			}
+		}
+	});
+}
@@ -3,8 +3,11 @@
 use super::*;
 use mock::{Event, *};
 
-use crate::runner::handler::Handler;
-use frame_support::{assert_noop, assert_ok};
+use crate::runner::handler::{Handler, STORAGE_SIZE};
+use frame_support::{
+	assert_noop, assert_ok,
+	traits::{OnFinalize, OnInitialize, OnRuntimeUpgrade},
+};
 use sp_core::{
 	bytes::{from_hex, to_hex},
 	H160,
@@ -921,6 +924,199 @@ fn should_selfdestruct() {
 	});
 }
 
+#[test]
+fn selfdestruct_opcode_does_not_refund_the_caller() {
+	// init code returning the runtime code `CALLER SELFDESTRUCT`
+	let contract = from_hex("0x6002600c60003960026000f333ff").unwrap();
+
+	new_test_ext().execute_with(|| {
+		let alice_account_id = <Test as Config>::AddressMapping::get_account_id(&trillian());
+
+		let result =
+			Runner::<Test>::create(trillian(), contract, 0, 1_000_000, 1000, <Test as Config>::config()).unwrap();
+		assert_eq!(result.exit_reason, ExitReason::Succeed(ExitSucceed::Returned));
+		let contract_address = result.address;
+		let deposit = reserved_balance(contract_address);
+		assert!(deposit > 0);
+
+		#[cfg(not(feature = "with-ethereum-compatibility"))]
+		deploy_free(contract_address);
+
+		let ford_balance = balance(ford());
+		let ford_reserved = reserved_balance(ford());
+		let result = Runner::<Test>::call(
+			ford(),
+			ford(),
+			contract_address,
+			Vec::new(),
+			0,
+			100_000,
+			1000,
+			<Test as Config>::config(),
+		)
+		.unwrap();
+		assert_eq!(result.exit_reason, ExitReason::Succeed(ExitSucceed::Suicided));
+		assert_eq!(result.used_storage, 0);
+
+		// the deposit is held on the maintainer, the caller gets nothing back
+		assert_eq!(balance(ford()), ford_balance);
+		assert_eq!(reserved_balance(ford()), ford_reserved);
+		assert_eq!(reserved_balance(contract_address), 0);
+		assert_eq!(
+			EVM::pending_cleanups(contract_address),
+			Some(PendingCleanup {
+				maintainer: alice_account_id,
+				deposit,
+				cleared_slots: 0,
+			})
+		);
+	});
+}
+
+#[test]
+fn storage_cleanup_works() {
+	// init code returning the runtime code `PUSH1 1 PUSH1 0 CALLDATALOAD SSTORE`
+	let contract = from_hex("0x6006600c60003960066000f3600160003555").unwrap();
+
+	new_test_ext().execute_with(|| {
+		let alice_account_id = <Test as Config>::AddressMapping::get_account_id(&trillian());
+
+		let result =
+			Runner::<Test>::create(trillian(), contract, 0, 1_000_000, 1000, <Test as Config>::config()).unwrap();
+		assert_eq!(result.exit_reason, ExitReason::Succeed(ExitSucceed::Returned));
+		let contract_address = result.address;
+
+		// stores three slots
+		assert_ok!(EVM::enable_contract_development(Origin::signed(
+			alice_account_id.clone()
+		)));
+		for slot in 1..=3 {
+			let result = Runner::<Test>::call(
+				trillian(),
+				trillian(),
+				contract_address,
+				H256::from_low_u64_be(slot).as_bytes().to_vec(),
+				0,
+				100_000,
+				1000,
+				<Test as Config>::config(),
+			)
+			.unwrap();
+			assert_eq!(result.used_storage, STORAGE_SIZE as i32);
+		}
+		let deposit = reserved_balance(contract_address);
+
+		assert_ok!(EVM::selfdestruct(
+			Origin::signed(alice_account_id.clone()),
+			contract_address
+		));
+		assert_eq!(
+			EVM::pending_cleanups(contract_address),
+			Some(PendingCleanup {
+				maintainer: alice_account_id.clone(),
+				deposit,
+				cleared_slots: 0,
+			})
+		);
+		assert_eq!(AccountStorages::<Test>::iter_prefix(contract_address).count(), 3);
+		assert_eq!(reserved_balance(contract_address), 0);
+		assert_eq!(reserved_balance(trillian()), DeveloperDeposit::get() + deposit);
+
+		// the address cannot be reused until the storage is cleared
+		assert_eq!(
+			Handler::<Test>::create_address(evm::CreateScheme::Fixed(contract_address)),
+			Err(ExitError::Other("ConflictContractAddress".into()))
+		);
+
+		// clears two slots per block
+		EVM::on_initialize(2);
+		let event = Event::evm_mod(crate::Event::ContractStorageCleared(contract_address, 2));
+		assert!(System::events().iter().any(|record| record.event == event));
+		assert_eq!(AccountStorages::<Test>::iter_prefix(contract_address).count(), 1);
+		assert_eq!(EVM::pending_cleanups(contract_address).unwrap().cleared_slots, 2);
+		assert_eq!(reserved_balance(trillian()), DeveloperDeposit::get() + deposit);
+
+		EVM::on_initialize(3);
+		let event = Event::evm_mod(crate::Event::ContractStorageCleared(contract_address, 1));
+		assert!(System::events().iter().any(|record| record.event == event));
+		let event = Event::evm_mod(crate::Event::ContractStorageCleanupFinished(
+			contract_address,
+			alice_account_id,
+			deposit,
+		));
+		assert!(System::events().iter().any(|record| record.event == event));
+		assert_eq!(AccountStorages::<Test>::iter_prefix(contract_address).count(), 0);
+		assert_eq!(EVM::pending_cleanups(contract_address), None);
+		assert_eq!(reserved_balance(trillian()), DeveloperDeposit::get());
+	});
+}
+
+#[test]
+fn removed_contracts_are_cleared_in_order() {
+	new_test_ext().execute_with(|| {
+		// the storage map order of these addresses is 4, 5, 3, 1, 2
+		let contracts = (1..=5).map(H160::from_low_u64_be).collect::<Vec<_>>();
+		for contract in contracts.iter() {
+			AccountStorages::<Test>::insert(contract, H256::default(), H256::from_low_u64_be(1));
+			EVM::queue_storage_cleanup(contract, &trillian());
+		}
+
+		EVM::on_initialize(2);
+		let cleared = System::events()
+			.into_iter()
+			.filter_map(|record| match record.event {
+				Event::evm_mod(crate::Event::ContractStorageCleanupFinished(contract, ..)) => Some(contract),
+				_ => None,
+			})
+			.collect::<Vec<_>>();
+		assert_eq!(cleared, contracts);
+		assert_eq!(CleanupQueueRange::<Test>::get(), (5, 5));
+	});
+}
+
+#[test]
+fn finished_recurring_calls_are_removed_after_retention() {
+	new_test_ext().execute_with(|| {
+		let task_id = b"task".to_vec();
+		RecurringCalls::<Test>::insert(
+			&task_id,
+			RecurringCall {
+				period: 5,
+				remaining_runs: 1,
+				next_execution: 1,
+				reserved_fee: 0,
+				last_exit_reason: None,
+			},
+		);
+
+		assert_ok!(EVM::scheduled_recurring_call(
+			Origin::root(),
+			task_id.clone(),
+			trillian(),
+			ford(),
+			Vec::new(),
+			0,
+			100_000,
+			0,
+		));
+
+		// the status of the last run is kept
+		let task = EVM::recurring_calls(&task_id).unwrap();
+		assert_eq!(task.remaining_runs, 0);
+		assert_eq!(task.last_exit_reason, Some(ExitReason::Succeed(ExitSucceed::Stopped)));
+
+		let expiry = System::block_number() + RecurringCallRetention::get();
+		assert_eq!(ExpiringRecurringCalls::<Test>::get(expiry), vec![task_id.clone()]);
+
+		EVM::on_initialize(expiry - 1);
+		assert!(EVM::recurring_calls(&task_id).is_some());
+
+		EVM::on_initialize(expiry);
+		assert_eq!(EVM::recurring_calls(&task_id), None);
+		assert_eq!(ExpiringRecurringCalls::<Test>::get(expiry), Vec::<Vec<u8>>::new());
+	});
+}
+
 #[test]
 fn storage_limit_should_work() {
 	// pragma solidity ^0.5.0;
@@ -1195,3 +1391,1865 @@ fn evm_execute_mode_should_work() {
 		assert_eq!(balance(trillian()), alice_balance);
 	});
 }
+
+// EIP-155 example transaction, signed by 0x4646..46 for chain id 1
+const EIP155_TRANSACTION: &str = "0xf86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83";
+
+fn eip155_sender() -> H160 {
+	H160::from_str("9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f").unwrap()
+}
+
+#[test]
+fn should_decode_and_recover_ethereum_transaction() {
+	new_test_ext().execute_with(|| {
+		let raw = from_hex(EIP155_TRANSACTION).unwrap();
+		let transaction = EthereumTransaction::decode_rlp(&raw).unwrap();
+
+		assert_eq!(transaction.nonce, U256::from(9));
+		assert_eq!(transaction.gas_limit, U256::from(21_000));
+		assert_eq!(
+			transaction.action,
+			TransactionAction::Call(H160::from_str("3535353535353535353535353535353535353535").unwrap())
+		);
+		assert_eq!(transaction.chain_id(), Some(1));
+		assert_eq!(
+			transaction.signing_hash(),
+			H256::from_str("daf5a779ae972f972197303d7b574746c7ef83eadac0f2791ad23db92e4c8e53").unwrap()
+		);
+		assert_eq!(
+			transaction.hash(),
+			H256::from_str("33469b22e9f636356c4160a87eb19df52b7412e8eac32a4a55ffe88ea8350788").unwrap()
+		);
+		assert_eq!(transaction.encode_rlp(), raw);
+		assert_eq!(EVM::recover_transaction_sender(&transaction), Ok(eip155_sender()));
+
+		let mut other_chain = transaction.clone();
+		other_chain.v = 39;
+		assert_eq!(
+			EVM::recover_transaction_sender(&other_chain),
+			Err(Error::<Test>::InvalidChainId)
+		);
+
+		// transactions without a chain id can be replayed from other chains
+		let mut unprotected = transaction.clone();
+		unprotected.v = 27;
+		assert_eq!(unprotected.chain_id(), None);
+		assert_eq!(
+			EVM::recover_transaction_sender(&unprotected),
+			Err(Error::<Test>::InvalidChainId)
+		);
+		AllowUnprotectedTransactions::set(true);
+		assert!(EVM::recover_transaction_sender(&unprotected).is_ok());
+
+		let mut high_s = transaction;
+		high_s.s = H256::repeat_byte(0xff);
+		assert_eq!(
+			EVM::recover_transaction_sender(&high_s),
+			Err(Error::<Test>::InvalidSignature)
+		);
+	});
+}
+
+#[test]
+fn transact_works() {
+	new_test_ext().execute_with(|| {
+		let transaction = EthereumTransaction::decode_rlp(&from_hex(EIP155_TRANSACTION).unwrap()).unwrap();
+		let target = H160::from_str("3535353535353535353535353535353535353535").unwrap();
+		let sender = <Test as Config>::AddressMapping::get_account_id(&eip155_sender());
+		let _ = Balances::deposit_creating(&sender, 2_000_000_000_000_000_000);
+
+		assert_noop!(
+			EVM::transact(Origin::signed(sender), transaction.clone()),
+			BadOrigin
+		);
+		assert_noop!(
+			EVM::transact(Origin::none(), transaction.clone()),
+			Error::<Test>::InvalidNonce
+		);
+
+		Accounts::<Test>::insert(eip155_sender(), AccountInfo::<Test>::new(9, None));
+		assert_ok!(EVM::transact(Origin::none(), transaction.clone()));
+
+		let event = Event::evm_mod(crate::Event::Executed(target));
+		assert!(System::events().iter().any(|record| record.event == event));
+		assert_eq!(balance(target), 1_000_000_000_000_000_000);
+		assert_eq!(Handler::<Test>::nonce(eip155_sender()), U256::from(10));
+
+		// the used gas is paid at the 20 gwei gas price, all of it tipped to the author
+		let fee = 21_000 * 20_000_000_000;
+		assert_eq!(Balances::free_balance(&sender), 1_000_000_000_000_000_000 - fee);
+		assert_eq!(Balances::free_balance(&BLOCK_AUTHOR), fee);
+
+		// replay is rejected
+		assert_noop!(
+			EVM::transact(Origin::none(), transaction),
+			Error::<Test>::InvalidNonce
+		);
+	});
+}
+
+#[test]
+fn validate_transact_works() {
+	new_test_ext().execute_with(|| {
+		let transaction = EthereumTransaction::decode_rlp(&from_hex(EIP155_TRANSACTION).unwrap()).unwrap();
+		let call = crate::Call::<Test>::transact(transaction.clone());
+
+		// the sender cannot pay for gas_limit and value
+		let sender = <Test as Config>::AddressMapping::get_account_id(&eip155_sender());
+		assert_eq!(
+			EVM::validate_unsigned(TransactionSource::External, &call),
+			InvalidTransaction::Payment.into()
+		);
+		let _ = Balances::deposit_creating(&sender, 1_000_000_000_000_000_000 + 21_000 * 20_000_000_000 - 1);
+		assert_eq!(
+			EVM::validate_unsigned(TransactionSource::External, &call),
+			InvalidTransaction::Payment.into()
+		);
+		let _ = Balances::deposit_creating(&sender, 1);
+
+		// future nonce requires the previous one
+		let valid = EVM::validate_unsigned(TransactionSource::External, &call).unwrap();
+		assert_eq!(valid.provides, vec![("EvmTransact", eip155_sender(), U256::from(9)).encode()]);
+		assert_eq!(valid.requires, vec![("EvmTransact", eip155_sender(), U256::from(8)).encode()]);
+
+		Accounts::<Test>::insert(eip155_sender(), AccountInfo::<Test>::new(10, None));
+		assert_eq!(
+			EVM::validate_unsigned(TransactionSource::External, &call),
+			InvalidTransaction::Stale.into()
+		);
+
+		// only the next nonce is included in a block
+		Accounts::<Test>::insert(eip155_sender(), AccountInfo::<Test>::new(8, None));
+		assert!(EVM::validate_unsigned(TransactionSource::External, &call).is_ok());
+		assert_eq!(EVM::pre_dispatch(&call), Err(InvalidTransaction::Future.into()));
+		Accounts::<Test>::insert(eip155_sender(), AccountInfo::<Test>::new(9, None));
+		assert_ok!(EVM::pre_dispatch(&call));
+
+		let mut bad_signature = transaction;
+		bad_signature.r = H256::zero();
+		assert_eq!(
+			EVM::validate_unsigned(
+				TransactionSource::External,
+				&crate::Call::<Test>::transact(bad_signature)
+			),
+			InvalidTransaction::BadProof.into()
+		);
+	});
+}
+
+#[test]
+fn transact_charges_failed_execution() {
+	new_test_ext().execute_with(|| {
+		let transaction = EthereumTransaction::decode_rlp(&from_hex(EIP155_TRANSACTION).unwrap()).unwrap();
+		let target = H160::from_str("3535353535353535353535353535353535353535").unwrap();
+		let sender = <Test as Config>::AddressMapping::get_account_id(&eip155_sender());
+		let _ = Balances::deposit_creating(&sender, 2_000_000_000_000_000_000);
+		Accounts::<Test>::insert(eip155_sender(), AccountInfo::<Test>::new(9, None));
+
+		// calling an undeployed contract fails
+		Accounts::<Test>::insert(
+			target,
+			AccountInfo::<Test>::new(
+				0,
+				Some(ContractInfo {
+					code_hash: H256::zero(),
+					maintainer: trillian(),
+					deployed: false,
+					storage_size: 0,
+				}),
+			),
+		);
+		assert_eq!(
+			EVM::transact(Origin::none(), transaction.clone()),
+			Err(DispatchErrorWithPostInfo {
+				post_info: PostDispatchInfo {
+					actual_weight: Some(<Test as Config>::GasToWeight::convert(21_000)),
+					pays_fee: Pays::No,
+				},
+				error: Error::<Test>::NoPermission.into(),
+			})
+		);
+
+		// the value is not transferred, but the nonce is bumped and gas_limit paid
+		assert_eq!(balance(target), 0);
+		assert_eq!(Handler::<Test>::nonce(eip155_sender()), U256::from(10));
+		let fee = 21_000 * 20_000_000_000;
+		assert_eq!(Balances::free_balance(&sender), 2_000_000_000_000_000_000 - fee);
+		assert_eq!(Balances::free_balance(&BLOCK_AUTHOR), fee);
+
+		assert_noop!(
+			EVM::transact(Origin::none(), transaction),
+			Error::<Test>::InvalidNonce
+		);
+	});
+}
+
+#[test]
+fn validate_transact_checks_gas_limit() {
+	new_test_ext().execute_with(|| {
+		let transaction = EthereumTransaction::decode_rlp(&from_hex(EIP155_TRANSACTION).unwrap()).unwrap();
+
+		// the gas limit is checked before the balance of the recovered sender
+		assert!(transaction.gas_limit <= EVM::block_gas_limit());
+		let mut over_block_limit = transaction;
+		over_block_limit.gas_limit = EVM::block_gas_limit() + 1;
+		assert_eq!(
+			EVM::validate_unsigned(
+				TransactionSource::External,
+				&crate::Call::<Test>::transact(over_block_limit)
+			),
+			InvalidTransaction::ExhaustsResources.into()
+		);
+	});
+}
+
+#[test]
+fn set_hardfork_works() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(EVM::hardfork(), EvmHardfork::Istanbul);
+		assert_eq!(<Test as Config>::config().gas_sload, 800);
+
+		assert_noop!(
+			EVM::set_hardfork(Origin::signed(CouncilAccount::get()), EvmHardfork::Berlin),
+			BadOrigin
+		);
+
+		assert_ok!(EVM::set_hardfork(Origin::root(), EvmHardfork::Berlin));
+		let event = Event::evm_mod(crate::Event::HardforkChanged(EvmHardfork::Berlin));
+		assert!(System::events().iter().any(|record| record.event == event));
+		assert_eq!(EVM::hardfork(), EvmHardfork::Berlin);
+		assert_eq!(<Test as Config>::config().gas_sload, WARM_STORAGE_READ_COST);
+	});
+}
+
+#[test]
+fn berlin_charges_cold_storage_access() {
+	// init code returning the runtime code `PUSH1 0 SLOAD PUSH1 0 SLOAD STOP`
+	let contract = from_hex("0x6007600c60003960076000f360005460005400").unwrap();
+
+	new_test_ext().execute_with(|| {
+		let result =
+			Runner::<Test>::create(trillian(), contract, 0, 1_000_000, 1000, <Test as Config>::config()).unwrap();
+		assert_eq!(result.exit_reason, ExitReason::Succeed(ExitSucceed::Returned));
+		let contract_address = result.address;
+
+		#[cfg(not(feature = "with-ethereum-compatibility"))]
+		deploy_free(contract_address);
+
+		let call = || {
+			Runner::<Test>::call(
+				trillian(),
+				trillian(),
+				contract_address,
+				Vec::new(),
+				0,
+				1_000_000,
+				1000,
+				<Test as Config>::config(),
+			)
+			.unwrap()
+		};
+
+		let istanbul = call();
+		assert_eq!(istanbul.exit_reason, ExitReason::Succeed(ExitSucceed::Stopped));
+
+		assert_ok!(EVM::set_hardfork(Origin::root(), EvmHardfork::Berlin));
+		let berlin = call();
+		assert_eq!(berlin.exit_reason, ExitReason::Succeed(ExitSucceed::Stopped));
+
+		// one cold and one warm read instead of two reads at 800
+		assert_eq!(
+			berlin.used_gas - istanbul.used_gas,
+			U256::from(COLD_SLOAD_COST + WARM_STORAGE_READ_COST - 2 * 800)
+		);
+	});
+}
+
+#[test]
+fn berlin_charges_cold_access_to_unassigned_low_addresses() {
+	// init code returning the runtime code `PUSH1 5 BALANCE POP STOP`, the
+	// mock runtime has no precompile at 0x05
+	let contract = from_hex("0x6005600c60003960056000f36005315000").unwrap();
+
+	new_test_ext().execute_with(|| {
+		let result =
+			Runner::<Test>::create(trillian(), contract, 0, 1_000_000, 1000, <Test as Config>::config()).unwrap();
+		assert_eq!(result.exit_reason, ExitReason::Succeed(ExitSucceed::Returned));
+		let contract_address = result.address;
+
+		#[cfg(not(feature = "with-ethereum-compatibility"))]
+		deploy_free(contract_address);
+
+		let call = || {
+			Runner::<Test>::call(
+				trillian(),
+				trillian(),
+				contract_address,
+				Vec::new(),
+				0,
+				1_000_000,
+				1000,
+				<Test as Config>::config(),
+			)
+			.unwrap()
+		};
+
+		let istanbul = call();
+		assert_eq!(istanbul.exit_reason, ExitReason::Succeed(ExitSucceed::Stopped));
+
+		assert_ok!(EVM::set_hardfork(Origin::root(), EvmHardfork::Berlin));
+		let berlin = call();
+		assert_eq!(berlin.exit_reason, ExitReason::Succeed(ExitSucceed::Stopped));
+
+		// a cold account access instead of a balance read at 700
+		assert_eq!(
+			berlin.used_gas - istanbul.used_gas,
+			U256::from(COLD_ACCOUNT_ACCESS_COST - 700)
+		);
+
+		// and the address is listed
+		let mut tracer = Tracer::new(TracerConfig::CallTracer);
+		tracing::using(&mut tracer, call);
+		assert_eq!(
+			tracer.finish().access_list,
+			vec![AccessListItem {
+				address: H160::from_low_u64_be(5),
+				storage_keys: vec![],
+			}]
+		);
+	});
+}
+
+#[test]
+fn access_list_warms_storage_slots() {
+	// init code returning the runtime code `PUSH1 0 SLOAD PUSH1 0 SLOAD STOP`
+	let contract = from_hex("0x6007600c60003960076000f360005460005400").unwrap();
+
+	new_test_ext().execute_with(|| {
+		let result =
+			Runner::<Test>::create(trillian(), contract, 0, 1_000_000, 1000, <Test as Config>::config()).unwrap();
+		assert_eq!(result.exit_reason, ExitReason::Succeed(ExitSucceed::Returned));
+		let contract_address = result.address;
+
+		#[cfg(not(feature = "with-ethereum-compatibility"))]
+		deploy_free(contract_address);
+
+		let access_list = vec![AccessListItem {
+			address: contract_address,
+			storage_keys: vec![H256::default()],
+		}];
+		let call = |access_list: Vec<AccessListItem>| {
+			Runner::<Test>::call_with_access_list(
+				trillian(),
+				trillian(),
+				contract_address,
+				Vec::new(),
+				0,
+				1_000_000,
+				1000,
+				access_list,
+				<Test as Config>::config(),
+			)
+			.unwrap()
+		};
+
+		// access lists are ignored before Berlin
+		assert_eq!(call(access_list.clone()).used_gas, call(Vec::new()).used_gas);
+
+		assert_ok!(EVM::set_hardfork(Origin::root(), EvmHardfork::Berlin));
+		let cold = call(Vec::new());
+		let warm = call(access_list.clone());
+		assert_eq!(warm.exit_reason, ExitReason::Succeed(ExitSucceed::Stopped));
+
+		// the listed slot is paid upfront and read warm
+		assert_eq!(
+			warm.used_gas - cold.used_gas,
+			U256::from(
+				ACCESS_LIST_ADDRESS_COST + ACCESS_LIST_STORAGE_KEY_COST + WARM_STORAGE_READ_COST - COLD_SLOAD_COST
+			)
+		);
+
+		// the gas limit covers the access list
+		assert_eq!(
+			Runner::<Test>::call_with_access_list(
+				trillian(),
+				trillian(),
+				contract_address,
+				Vec::new(),
+				0,
+				21_000 + ACCESS_LIST_ADDRESS_COST,
+				1000,
+				access_list.clone(),
+				<Test as Config>::config(),
+			),
+			Err(DispatchError::Other("OutOfGas"))
+		);
+
+		// the accessed slots are traced as an access list, without the caller
+		// and the called contract
+		let mut tracer = Tracer::new(TracerConfig::CallTracer);
+		tracing::using(&mut tracer, || call(Vec::new()));
+		assert_eq!(tracer.finish().access_list, access_list);
+
+		assert_ok!(EVM::call_with_access_list(
+			Origin::signed(<Test as Config>::AddressMapping::get_account_id(&trillian())),
+			contract_address,
+			Vec::new(),
+			0,
+			1_000_000,
+			1000,
+			access_list,
+		));
+		let event = Event::evm_mod(crate::Event::Executed(contract_address));
+		assert!(System::events().iter().any(|record| record.event == event));
+	});
+}
+
+#[test]
+fn net_gas_metering_refunds_cleared_storage() {
+	// init code returning the runtime code
+	// `PUSH1 1 PUSH1 0 SSTORE PUSH1 0 PUSH1 0 SSTORE STOP`
+	let contract = from_hex("0x600b600c600039600b6000f36001600055600060005500").unwrap();
+
+	new_test_ext().execute_with(|| {
+		let result =
+			Runner::<Test>::create(trillian(), contract, 0, 1_000_000, 1000, <Test as Config>::config()).unwrap();
+		assert_eq!(result.exit_reason, ExitReason::Succeed(ExitSucceed::Returned));
+		let contract_address = result.address;
+
+		#[cfg(not(feature = "with-ethereum-compatibility"))]
+		deploy_free(contract_address);
+
+		let call = || {
+			Runner::<Test>::call(
+				trillian(),
+				trillian(),
+				contract_address,
+				Vec::new(),
+				0,
+				1_000_000,
+				1000,
+				<Test as Config>::config(),
+			)
+			.unwrap()
+		};
+
+		// set at 20000 and reset at 5000 without refund
+		let result = call();
+		assert_eq!(result.exit_reason, ExitReason::Succeed(ExitSucceed::Stopped));
+		assert_eq!(result.used_gas, U256::from(21_000 + 12 + 20_000 + 5_000));
+
+		// resetting the slot to its original value is charged as a read and
+		// refunds the set cost
+		NetGasMetering::set(true);
+		let result = call();
+		assert_eq!(result.exit_reason, ExitReason::Succeed(ExitSucceed::Stopped));
+		assert_eq!(result.used_gas, U256::from(21_000 + 12 + 20_000 + 800 - 19_200));
+		assert_eq!(EVM::account_storages(contract_address, H256::default()), H256::default());
+
+		// the refund is capped at a fifth of the used gas from London
+		assert_ok!(EVM::set_hardfork(Origin::root(), EvmHardfork::London));
+		let result = call();
+		assert_eq!(result.exit_reason, ExitReason::Succeed(ExitSucceed::Stopped));
+		let used = 21_000 + 12 + COLD_SLOAD_COST + 20_000 + WARM_STORAGE_READ_COST;
+		assert_eq!(result.used_gas, U256::from(used - used / 5));
+	});
+}
+
+#[test]
+fn london_rejects_ef_code() {
+	// init code returning the single byte 0xEF
+	let contract = from_hex("0x60ef60005360016000f3").unwrap();
+
+	new_test_ext().execute_with(|| {
+		let result = Runner::<Test>::create(trillian(), contract.clone(), 0, 1_000_000, 1000, <Test as Config>::config())
+			.unwrap();
+		assert_eq!(result.exit_reason, ExitReason::Succeed(ExitSucceed::Returned));
+
+		assert_ok!(EVM::set_hardfork(Origin::root(), EvmHardfork::London));
+		let result =
+			Runner::<Test>::create(trillian(), contract, 0, 1_000_000, 1000, <Test as Config>::config()).unwrap();
+		assert_eq!(
+			result.exit_reason,
+			ExitReason::Error(ExitError::Other("InvalidCodePrefix".into()))
+		);
+	});
+}
+
+#[test]
+fn create_enforces_contract_size_limit() {
+	// init code returning `MaxCodeSize` zero bytes
+	let max_size = from_hex("0x6103e86000f3").unwrap();
+	// init code returning `MaxCodeSize + 1` zero bytes
+	let over_size = from_hex("0x6103e96000f3").unwrap();
+
+	new_test_ext().execute_with(|| {
+		assert_eq!(MaxCodeSize::get(), 1000);
+
+		// create
+		let result = Runner::<Test>::create(
+			trillian(),
+			max_size.clone(),
+			0,
+			1_000_000,
+			2000,
+			<Test as Config>::config(),
+		)
+		.unwrap();
+		assert_eq!(result.exit_reason, ExitReason::Succeed(ExitSucceed::Returned));
+		assert_eq!(EVM::code_at_address(&result.address).len(), 1000);
+
+		let result = Runner::<Test>::create(
+			trillian(),
+			over_size.clone(),
+			0,
+			1_000_000,
+			2000,
+			<Test as Config>::config(),
+		)
+		.unwrap();
+		assert_eq!(result.exit_reason, ExitReason::Error(ExitError::CreateContractLimit));
+		assert_eq!(result.used_storage, 0);
+		assert!(EVM::code_at_address(&result.address).is_empty());
+
+		// create2
+		let result = Runner::<Test>::create2(
+			trillian(),
+			max_size.clone(),
+			H256::repeat_byte(1),
+			0,
+			1_000_000,
+			2000,
+			<Test as Config>::config(),
+		)
+		.unwrap();
+		assert_eq!(result.exit_reason, ExitReason::Succeed(ExitSucceed::Returned));
+		assert_eq!(EVM::code_at_address(&result.address).len(), 1000);
+
+		let result = Runner::<Test>::create2(
+			trillian(),
+			over_size.clone(),
+			H256::repeat_byte(2),
+			0,
+			1_000_000,
+			2000,
+			<Test as Config>::config(),
+		)
+		.unwrap();
+		assert_eq!(result.exit_reason, ExitReason::Error(ExitError::CreateContractLimit));
+		assert!(EVM::code_at_address(&result.address).is_empty());
+
+		// create_network_contract
+		assert_ok!(EVM::create_network_contract(
+			Origin::signed(NetworkContractAccount::get()),
+			max_size,
+			0,
+			1_000_000,
+			2000,
+		));
+		let address = H160::from_low_u64_be(primitives::NETWORK_CONTRACT_START);
+		let event = Event::evm_mod(crate::Event::Created(address));
+		assert!(System::events().iter().any(|record| record.event == event));
+		assert_eq!(EVM::code_at_address(&address).len(), 1000);
+
+		assert_ok!(EVM::create_network_contract(
+			Origin::signed(NetworkContractAccount::get()),
+			over_size,
+			0,
+			1_000_000,
+			2000,
+		));
+		let address = H160::from_low_u64_be(primitives::NETWORK_CONTRACT_START + 1);
+		let event = Event::evm_mod(crate::Event::CreatedFailed(
+			address,
+			ExitReason::Error(ExitError::CreateContractLimit),
+			vec![],
+		));
+		assert!(System::events().iter().any(|record| record.event == event));
+		assert!(EVM::code_at_address(&address).is_empty());
+	});
+}
+
+#[test]
+fn contract_create_enforces_code_validation() {
+	// contract creating a contract with the `child` init code, storing its
+	// address, or zero on failure
+	let factory = |child: &str| {
+		let len = child.len() / 2;
+		let code = format!(
+			"{:02x}{}60005260{:02x}60{:02x}6000f060005500",
+			0x5f + len,
+			child,
+			len,
+			32 - len
+		);
+		let init = format!("0x60{0:02x}600c60003960{0:02x}6000f3{1}", code.len() / 2, code);
+		from_hex(&init).unwrap()
+	};
+
+	new_test_ext().execute_with(|| {
+		let create_child = |child: &str| {
+			let result = Runner::<Test>::create(
+				trillian(),
+				factory(child),
+				0,
+				1_000_000,
+				1000,
+				<Test as Config>::config(),
+			)
+			.unwrap();
+			assert_eq!(result.exit_reason, ExitReason::Succeed(ExitSucceed::Returned));
+			let factory_address = result.address;
+
+			#[cfg(not(feature = "with-ethereum-compatibility"))]
+			deploy_free(factory_address);
+
+			let result = Runner::<Test>::call(
+				trillian(),
+				trillian(),
+				factory_address,
+				vec![],
+				0,
+				1_000_000,
+				2000,
+				<Test as Config>::config(),
+			)
+			.unwrap();
+			assert_eq!(result.exit_reason, ExitReason::Succeed(ExitSucceed::Stopped));
+			H160::from(EVM::account_storages(factory_address, H256::default()))
+		};
+
+		// returns `MaxCodeSize` zero bytes
+		let child = create_child("6103e86000f3");
+		assert_eq!(EVM::code_at_address(&child).len(), 1000);
+
+		// returns `MaxCodeSize + 1` zero bytes
+		assert_eq!(create_child("6103e96000f3"), H160::default());
+
+		// returns the single byte 0xEF
+		let child = create_child("60ef60005360016000f3");
+		assert_eq!(EVM::code_at_address(&child), vec![0xEF]);
+		assert_ok!(EVM::set_hardfork(Origin::root(), EvmHardfork::London));
+		assert_eq!(create_child("60ef60005360016000f3"), H160::default());
+	});
+}
+
+#[test]
+fn tracing_records_calls_and_opcodes() {
+	// init code returning the runtime code `PUSH1 0 SLOAD PUSH1 0 SLOAD STOP`
+	let contract = from_hex("0x6007600c60003960076000f360005460005400").unwrap();
+
+	new_test_ext().execute_with(|| {
+		let mut tracer = Tracer::new(TracerConfig::OpcodeLogger);
+		let result = tracing::using(&mut tracer, || {
+			Runner::<Test>::create(trillian(), contract, 0, 1_000_000, 1000, <Test as Config>::config()).unwrap()
+		});
+		let trace = tracer.finish();
+
+		assert_eq!(trace.calls.len(), 1);
+		assert_eq!(trace.calls[0].call_type, CallType::Create);
+		assert_eq!(trace.calls[0].from, trillian());
+		assert_eq!(trace.calls[0].to, result.address);
+		assert_eq!(trace.calls[0].exit_reason, ExitReason::Succeed(ExitSucceed::Returned));
+		assert!(trace.calls[0].calls.is_empty());
+
+		// PUSH1 PUSH1 PUSH1 CODECOPY PUSH1 PUSH1 RETURN
+		assert_eq!(
+			trace.steps.iter().map(|step| (step.pc, step.op)).collect::<Vec<_>>(),
+			vec![(0, 0x60), (2, 0x60), (4, 0x60), (6, 0x39), (7, 0x60), (9, 0x60), (11, 0xf3)]
+		);
+		assert!(trace.steps.iter().all(|step| step.depth == 1));
+		assert_eq!(trace.steps[3].stack.len(), 3);
+		assert_eq!(trace.steps[0].gas_cost, 3);
+
+		// the call tracer skips opcodes
+		#[cfg(not(feature = "with-ethereum-compatibility"))]
+		deploy_free(result.address);
+
+		let mut tracer = Tracer::new(TracerConfig::CallTracer);
+		tracing::using(&mut tracer, || {
+			Runner::<Test>::call(
+				trillian(),
+				trillian(),
+				result.address,
+				Vec::new(),
+				0,
+				1_000_000,
+				1000,
+				<Test as Config>::config(),
+			)
+			.unwrap()
+		});
+		let trace = tracer.finish();
+		assert_eq!(trace.calls.len(), 1);
+		assert_eq!(trace.calls[0].call_type, CallType::Call);
+		assert_eq!(trace.calls[0].exit_reason, ExitReason::Succeed(ExitSucceed::Stopped));
+		assert!(trace.steps.is_empty());
+	});
+}
+
+#[test]
+fn receipts_are_recorded_and_committed_on_finalize() {
+	// init code returning the runtime code
+	// `PUSH1 42 PUSH1 0 MSTORE PUSH1 1 PUSH1 32 PUSH1 0 LOG1 STOP`
+	let contract = from_hex("0x600d600c600039600d6000f3602a600052600160206000a100").unwrap();
+	let topic = H256::from_low_u64_be(1);
+
+	new_test_ext().execute_with(|| {
+		frame_system::Pallet::<Test>::set_extrinsic_index(0);
+		let created =
+			Runner::<Test>::create(trillian(), contract, 0, 1_000_000, 1000, <Test as Config>::config()).unwrap();
+		assert_eq!(created.exit_reason, ExitReason::Succeed(ExitSucceed::Returned));
+
+		#[cfg(not(feature = "with-ethereum-compatibility"))]
+		deploy_free(created.address);
+
+		frame_system::Pallet::<Test>::set_extrinsic_index(1);
+		let called = Runner::<Test>::call(
+			trillian(),
+			trillian(),
+			created.address,
+			Vec::new(),
+			0,
+			1_000_000,
+			1000,
+			<Test as Config>::config(),
+		)
+		.unwrap();
+		assert_eq!(called.exit_reason, ExitReason::Succeed(ExitSucceed::Stopped));
+
+		// not visible until the block is finalized
+		assert!(EVM::current_receipts().is_empty());
+		EVM::on_finalize(1);
+		assert!(PendingReceipts::<Test>::get().is_empty());
+
+		let receipts = EVM::current_receipts();
+		assert_eq!(receipts.len(), 2);
+
+		assert_eq!(receipts[0].transaction_index, 0);
+		assert_eq!(receipts[0].to, None);
+		assert_eq!(receipts[0].contract_address, Some(created.address));
+		assert_eq!(receipts[0].used_gas, created.used_gas);
+		assert_eq!(receipts[0].cumulative_gas_used, created.used_gas);
+		assert!(receipts[0].logs.is_empty());
+		assert_eq!(receipts[0].logs_bloom, Bloom::default());
+
+		let mut value = [0u8; 32];
+		value[31] = 42;
+		assert_eq!(receipts[1].transaction_index, 1);
+		assert_eq!(receipts[1].from, trillian());
+		assert_eq!(receipts[1].to, Some(created.address));
+		assert!(receipts[1].status);
+		assert_eq!(receipts[1].contract_address, None);
+		assert_eq!(receipts[1].cumulative_gas_used, created.used_gas + called.used_gas);
+		assert_eq!(
+			receipts[1].logs,
+			vec![Log {
+				address: created.address,
+				topics: vec![topic],
+				data: value.to_vec(),
+			}]
+		);
+		assert!(receipts[1].logs_bloom.contains_input(created.address.as_bytes()));
+		assert!(receipts[1].logs_bloom.contains_input(topic.as_bytes()));
+	});
+}
+
+#[test]
+fn transact_reports_ethereum_hash_in_receipt() {
+	new_test_ext().execute_with(|| {
+		let transaction = EthereumTransaction::decode_rlp(&from_hex(EIP155_TRANSACTION).unwrap()).unwrap();
+		let sender = <Test as Config>::AddressMapping::get_account_id(&eip155_sender());
+		let _ = Balances::deposit_creating(&sender, 2_000_000_000_000_000_000);
+		Accounts::<Test>::insert(eip155_sender(), AccountInfo::<Test>::new(9, None));
+
+		frame_system::Pallet::<Test>::set_extrinsic_index(0);
+		Runner::<Test>::call(
+			trillian(),
+			trillian(),
+			ford(),
+			Vec::new(),
+			0,
+			1_000_000,
+			1000,
+			<Test as Config>::config(),
+		)
+		.unwrap();
+		let previous = PendingReceipts::<Test>::get()[0].clone();
+
+		frame_system::Pallet::<Test>::set_extrinsic_index(1);
+		assert_ok!(EVM::transact(Origin::none(), transaction.clone()));
+
+		let receipts = PendingReceipts::<Test>::get();
+		assert_eq!(receipts.len(), 2);
+		assert_eq!(receipts[0], previous);
+		assert_eq!(receipts[1].transaction_index, 1);
+		assert_eq!(receipts[1].transaction_hash, transaction.hash());
+		assert_eq!(receipts[1].effective_gas_price, transaction.gas_price);
+	});
+}
+
+#[test]
+fn block_context_works() {
+	// init code returning the runtime code
+	// `COINBASE PUSH1 0 MSTORE GASLIMIT PUSH1 32 MSTORE GASPRICE PUSH1 64 MSTORE
+	// DIFFICULTY PUSH1 96 MSTORE PUSH1 128 PUSH1 0 RETURN`
+	let contract = from_hex("0x6015600c60003960156000f341600052456020523a6040524460605260806000f3").unwrap();
+
+	new_test_ext().execute_with(|| {
+		let created =
+			Runner::<Test>::create(trillian(), contract, 0, 1_000_000, 1000, <Test as Config>::config()).unwrap();
+		assert_eq!(created.exit_reason, ExitReason::Succeed(ExitSucceed::Returned));
+
+		#[cfg(not(feature = "with-ethereum-compatibility"))]
+		deploy_free(created.address);
+
+		let result = Runner::<Test>::call(
+			trillian(),
+			trillian(),
+			created.address,
+			Vec::new(),
+			0,
+			1_000_000,
+			1000,
+			<Test as Config>::config(),
+		)
+		.unwrap();
+		assert_eq!(result.exit_reason, ExitReason::Succeed(ExitSucceed::Returned));
+
+		// the author has no linked address, so the default one is used
+		assert_eq!(H160::from_slice(&result.output[12..32]), H160::from([5u8; 20]));
+		assert_eq!(U256::from_big_endian(&result.output[32..64]), EVM::block_gas_limit());
+		assert!(!EVM::block_gas_limit().is_zero());
+		assert_eq!(U256::from_big_endian(&result.output[64..96]), EVM::gas_price());
+		assert_eq!(U256::from_big_endian(&result.output[96..128]), EVM::block_difficulty());
+	});
+}
+
+#[test]
+fn gas_price_is_not_rounded_to_zero() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(EVM::gas_price(), U256::zero());
+
+		// half a fee unit per gas
+		FeePerMillionWeight::set(500_000);
+		assert_eq!(EVM::gas_price(), U256::one());
+
+		FeePerMillionWeight::set(2_000_000);
+		assert_eq!(EVM::gas_price(), U256::from(2));
+
+		FeePerMillionWeight::set(2_500_000);
+		assert_eq!(EVM::gas_price(), U256::from(3));
+	});
+}
+
+#[test]
+fn transact_burns_base_fee() {
+	new_test_ext().execute_with(|| {
+		let transaction = EthereumTransaction::decode_rlp(&from_hex(EIP155_TRANSACTION).unwrap()).unwrap();
+		let sender = <Test as Config>::AddressMapping::get_account_id(&eip155_sender());
+		let _ = Balances::deposit_creating(&sender, 2_000_000_000_000_000_000);
+		Accounts::<Test>::insert(eip155_sender(), AccountInfo::<Test>::new(9, None));
+
+		// the 20 gwei gas price must cover the base fee
+		BaseFee::set(30_000_000_000);
+		assert_noop!(
+			EVM::transact(Origin::none(), transaction.clone()),
+			Error::<Test>::GasPriceTooLow
+		);
+		assert_eq!(
+			EVM::validate_unsigned(
+				TransactionSource::External,
+				&crate::Call::<Test>::transact(transaction.clone())
+			),
+			InvalidTransaction::Payment.into()
+		);
+
+		BaseFee::set(15_000_000_000);
+		let valid = EVM::validate_unsigned(
+			TransactionSource::External,
+			&crate::Call::<Test>::transact(transaction.clone()),
+		)
+		.unwrap();
+		assert_eq!(valid.priority, 5_000_000_000);
+
+		let issuance = Balances::total_issuance();
+		assert_ok!(EVM::transact(Origin::none(), transaction));
+
+		// the base fee is burned and the rest tipped to the author
+		assert_eq!(Balances::free_balance(&BLOCK_AUTHOR), 21_000 * 5_000_000_000);
+		assert_eq!(Balances::total_issuance(), issuance - 21_000 * 15_000_000_000);
+		assert_eq!(
+			Balances::free_balance(&sender),
+			1_000_000_000_000_000_000 - 21_000 * 20_000_000_000
+		);
+	});
+}
+
+#[test]
+fn transact_charges_the_length_fee() {
+	new_test_ext().execute_with(|| {
+		let transaction = EthereumTransaction::decode_rlp(&from_hex(EIP155_TRANSACTION).unwrap()).unwrap();
+		let sender = <Test as Config>::AddressMapping::get_account_id(&eip155_sender());
+		let _ = Balances::deposit_creating(&sender, 2_000_000_000_000_000_000);
+		Accounts::<Test>::insert(eip155_sender(), AccountInfo::<Test>::new(9, None));
+
+		BaseFee::set(15_000_000_000);
+		TransactionByteFee::set(1_000);
+		let length_fee = 1_000 * transaction.encoded_size() as u64;
+
+		let issuance = Balances::total_issuance();
+		assert_ok!(EVM::transact(Origin::none(), transaction));
+
+		// the length fee is burned with the base fee
+		assert_eq!(Balances::free_balance(&BLOCK_AUTHOR), 21_000 * 5_000_000_000);
+		assert_eq!(
+			Balances::total_issuance(),
+			issuance - 21_000 * 15_000_000_000 - length_fee
+		);
+		assert_eq!(
+			Balances::free_balance(&sender),
+			1_000_000_000_000_000_000 - 21_000 * 20_000_000_000 - length_fee
+		);
+	});
+}
+
+#[test]
+fn signed_extrinsic_fees_are_burned_and_tips_go_to_the_author() {
+	new_test_ext().execute_with(|| {
+		let who = <Test as Config>::AddressMapping::get_account_id(&trillian());
+		let fee = Balances::withdraw(
+			&who,
+			1000,
+			WithdrawReasons::TRANSACTION_PAYMENT,
+			ExistenceRequirement::KeepAlive,
+		)
+		.unwrap();
+		let tip = Balances::withdraw(&who, 100, WithdrawReasons::TIP, ExistenceRequirement::KeepAlive).unwrap();
+
+		let issuance = Balances::total_issuance();
+		EVM::on_unbalanceds(Some(fee).into_iter().chain(Some(tip)));
+
+		assert_eq!(Balances::free_balance(&BLOCK_AUTHOR), 100);
+		assert_eq!(Balances::total_issuance(), issuance - 1000);
+		assert_eq!(balance(trillian()), INITIAL_BALANCE - 1100);
+	});
+}
+
+#[test]
+fn base_fee_opcode_works() {
+	// init code returning the runtime code
+	// `BASEFEE PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN`
+	let contract = from_hex("0x6009600c60003960096000f34860005260206000f3").unwrap();
+
+	new_test_ext().execute_with(|| {
+		BaseFee::set(1_000);
+		let created =
+			Runner::<Test>::create(trillian(), contract, 0, 1_000_000, 1000, <Test as Config>::config()).unwrap();
+		assert_eq!(created.exit_reason, ExitReason::Succeed(ExitSucceed::Returned));
+
+		#[cfg(not(feature = "with-ethereum-compatibility"))]
+		deploy_free(created.address);
+
+		let call = || {
+			Runner::<Test>::call(
+				trillian(),
+				trillian(),
+				created.address,
+				Vec::new(),
+				0,
+				1_000_000,
+				1000,
+				<Test as Config>::config(),
+			)
+			.unwrap()
+		};
+
+		// unknown before London
+		assert!(!call().exit_reason.is_succeed());
+
+		assert_ok!(EVM::set_hardfork(Origin::root(), EvmHardfork::London));
+		let result = call();
+		assert_eq!(result.exit_reason, ExitReason::Succeed(ExitSucceed::Returned));
+		assert_eq!(U256::from_big_endian(&result.output), U256::from(1_000));
+	});
+}
+
+#[test]
+fn sponsorship_works() {
+	// init code returning the runtime code `PUSH1 1 PUSH1 0 SSTORE STOP`
+	let contract = from_hex("0x6006600c60003960066000f3600160005500").unwrap();
+
+	new_test_ext().execute_with(|| {
+		let alice_account_id = <Test as Config>::AddressMapping::get_account_id(&trillian());
+		let bob_account_id = <Test as Config>::AddressMapping::get_account_id(&ford());
+		let charlie_account_id = <Test as Config>::AddressMapping::get_account_id(&charlie());
+
+		let result =
+			Runner::<Test>::create(trillian(), contract, 0, 1_000_000, 1000, <Test as Config>::config()).unwrap();
+		assert_eq!(result.exit_reason, ExitReason::Succeed(ExitSucceed::Returned));
+		let contract_address = result.address;
+
+		#[cfg(not(feature = "with-ethereum-compatibility"))]
+		deploy_free(contract_address);
+
+		// only the maintainer manages the sponsorship
+		assert_noop!(
+			EVM::fund_sponsorship(Origin::signed(bob_account_id.clone()), contract_address, 1_000),
+			Error::<Test>::NoPermission
+		);
+		assert_noop!(
+			EVM::set_sponsorship(Origin::signed(bob_account_id.clone()), contract_address, None),
+			Error::<Test>::NoPermission
+		);
+
+		assert_ok!(EVM::fund_sponsorship(
+			Origin::signed(alice_account_id.clone()),
+			contract_address,
+			10_000
+		));
+		let event = Event::evm_mod(crate::Event::SponsorshipFunded(contract_address, 10_000));
+		assert!(System::events().iter().any(|record| record.event == event));
+		let sponsor = EVM::sponsor_account_id(&contract_address);
+		assert_eq!(Balances::free_balance(&sponsor), 10_000);
+
+		// funds alone do not sponsor calls
+		let call = mock::Call::EVM(crate::Call::<Test>::call(contract_address, Vec::new(), 0, 1_000_000, 0));
+		let fee_sponsor = |who: &AccountId32, fee: u64| <EVM as FeeSponsor<_, _, _>>::fee_sponsor(who, &call, fee);
+		assert_eq!(fee_sponsor(&bob_account_id, 100), None);
+
+		let limits = SponsorshipLimits {
+			per_caller: Some(300),
+			per_block: Some(500),
+		};
+		assert_ok!(EVM::set_sponsorship(
+			Origin::signed(alice_account_id.clone()),
+			contract_address,
+			Some(limits)
+		));
+		let event = Event::evm_mod(crate::Event::SponsorshipSet(contract_address));
+		assert!(System::events().iter().any(|record| record.event == event));
+
+		assert_eq!(fee_sponsor(&bob_account_id, 200), Some(sponsor.clone()));
+		// over the per caller limit
+		assert_eq!(fee_sponsor(&bob_account_id, 200), None);
+		assert_eq!(EVM::sponsored_amounts(contract_address, &bob_account_id), 200);
+		// over the per block limit
+		assert_eq!(fee_sponsor(&alice_account_id, 300), Some(sponsor.clone()));
+		assert_eq!(fee_sponsor(&charlie_account_id, 1), None);
+		assert_eq!(
+			EVM::sponsorship(&contract_address),
+			Some(Sponsorship {
+				balance: 10_000,
+				limits,
+				used_in_block: 500,
+			})
+		);
+
+		System::set_block_number(2);
+		assert_eq!(fee_sponsor(&charlie_account_id, 1), Some(sponsor.clone()));
+
+		// refunded fees do not count against the limits
+		<EVM as FeeSponsor<_, mock::Call, _>>::refund_sponsored_fee(&sponsor, &charlie_account_id, 1);
+		assert_eq!(EVM::sponsored_amounts(contract_address, &charlie_account_id), 0);
+		assert_eq!(EVM::block_sponsored_amount(&contract_address), 0);
+
+		// only calls to the sponsored contract
+		let other_call = mock::Call::EVM(crate::Call::<Test>::call(contract_a(), Vec::new(), 0, 1_000_000, 0));
+		assert_eq!(
+			<EVM as FeeSponsor<_, _, _>>::fee_sponsor(&charlie_account_id, &other_call, 1),
+			None
+		);
+
+		// storage deposits are paid by the sponsor
+		assert_ok!(EVM::set_sponsorship(
+			Origin::signed(alice_account_id.clone()),
+			contract_address,
+			Some(Default::default())
+		));
+		let bob_balance = Balances::free_balance(&bob_account_id);
+		assert_ok!(EVM::call(
+			Origin::signed(bob_account_id.clone()),
+			contract_address,
+			Vec::new(),
+			0,
+			1_000_000,
+			500
+		));
+		// only the storage used is charged to the sponsorship
+		let storage_deposit = STORAGE_SIZE as u64 * <Test as Config>::StorageDepositPerByte::get();
+		assert_eq!(Balances::free_balance(&sponsor), 10_000 - storage_deposit);
+		assert_eq!(Balances::free_balance(&bob_account_id), bob_balance);
+		assert_eq!(
+			EVM::sponsored_amounts(contract_address, &bob_account_id),
+			200 + storage_deposit
+		);
+
+		assert_noop!(
+			EVM::withdraw_sponsorship(Origin::signed(bob_account_id.clone()), contract_address, 1_000),
+			Error::<Test>::NoPermission
+		);
+		assert_ok!(EVM::withdraw_sponsorship(
+			Origin::signed(alice_account_id.clone()),
+			contract_address,
+			1_000
+		));
+		let event = Event::evm_mod(crate::Event::SponsorshipWithdrawn(contract_address, 1_000));
+		assert!(System::events().iter().any(|record| record.event == event));
+		assert_eq!(Balances::free_balance(&sponsor), 9_000 - storage_deposit);
+
+		assert_ok!(EVM::set_sponsorship(
+			Origin::signed(alice_account_id),
+			contract_address,
+			None
+		));
+		let event = Event::evm_mod(crate::Event::SponsorshipRemoved(contract_address));
+		assert!(System::events().iter().any(|record| record.event == event));
+		assert_eq!(EVM::sponsorship(&contract_address), None);
+	});
+}
+
+#[test]
+fn batch_call_works() {
+	// init code returning the runtime code of a counter
+	// `PUSH1 0 SLOAD PUSH1 1 ADD PUSH1 0 SSTORE STOP`
+	let contract = from_hex("0x600a600c600039600a6000f360005460010160005500").unwrap();
+
+	new_test_ext().execute_with(|| {
+		let alice_account_id = <Test as Config>::AddressMapping::get_account_id(&trillian());
+
+		let result =
+			Runner::<Test>::create(trillian(), contract, 0, 1_000_000, 1000, <Test as Config>::config()).unwrap();
+		assert_eq!(result.exit_reason, ExitReason::Succeed(ExitSucceed::Returned));
+		let counter = result.address;
+
+		#[cfg(not(feature = "with-ethereum-compatibility"))]
+		deploy_free(counter);
+
+		let batch_call = |target| BatchCall {
+			target,
+			input: Vec::new(),
+			value: 0,
+			gas_limit: 100_000,
+			storage_limit: 1000,
+		};
+
+		assert_ok!(EVM::batch_call(
+			Origin::signed(alice_account_id.clone()),
+			vec![batch_call(counter), batch_call(counter)]
+		));
+		assert!(System::events()
+			.iter()
+			.any(|record| matches!(record.event, Event::evm_mod(crate::Event::BatchExecuted(2, _)))));
+		assert_eq!(
+			EVM::account_storages(counter, H256::default()),
+			H256::from_low_u64_be(2)
+		);
+
+		assert_noop!(
+			EVM::batch_call(
+				Origin::signed(alice_account_id.clone()),
+				vec![batch_call(counter); MaxBatchCalls::get() as usize + 1]
+			),
+			Error::<Test>::TooManyCalls
+		);
+
+		// the failing call reverts the first one
+		assert_ok!(EVM::batch_call(
+			Origin::signed(alice_account_id),
+			vec![batch_call(counter), batch_call(contract_b())]
+		));
+		assert!(System::events().iter().any(|record| matches!(
+			record.event,
+			Event::evm_mod(crate::Event::BatchExecutedFailed(1, ExitReason::Error(_), _))
+		)));
+		assert_eq!(
+			EVM::account_storages(counter, H256::default()),
+			H256::from_low_u64_be(2)
+		);
+
+		let info = Runner::<Test>::batch_call(
+			trillian(),
+			vec![batch_call(counter), batch_call(counter)],
+			<Test as Config>::config(),
+		)
+		.unwrap();
+		assert_eq!(info.exit_reason, ExitReason::Succeed(ExitSucceed::Stopped));
+		assert_eq!(info.index, 1);
+		assert_eq!(info.used_storage, 0);
+		assert_eq!(
+			EVM::account_storages(counter, H256::default()),
+			H256::from_low_u64_be(4)
+		);
+	});
+}
+
+#[test]
+fn pause_works() {
+	// init code returning the runtime code of a counter
+	// `PUSH1 0 SLOAD PUSH1 1 ADD PUSH1 0 SSTORE STOP`
+	let contract = from_hex("0x600a600c600039600a6000f360005460010160005500").unwrap();
+
+	new_test_ext().execute_with(|| {
+		let result =
+			Runner::<Test>::create(trillian(), contract, 0, 1_000_000, 1000, <Test as Config>::config()).unwrap();
+		let counter = result.address;
+
+		// calls the counter and stores whether the call succeeded
+		// `PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 0 PUSH20 counter GAS CALL PUSH1 0
+		// SSTORE STOP`
+		let proxy = from_hex(&format!(
+			"0x6025600c60003960256000f36000600060006000600073{:x}5af160005500",
+			counter
+		))
+		.unwrap();
+		let result = Runner::<Test>::create(trillian(), proxy, 0, 1_000_000, 1000, <Test as Config>::config()).unwrap();
+		let proxy = result.address;
+
+		#[cfg(not(feature = "with-ethereum-compatibility"))]
+		{
+			deploy_free(counter);
+			deploy_free(proxy);
+		}
+
+		let ford_account_id = <Test as Config>::AddressMapping::get_account_id(&ford());
+		assert_noop!(
+			EVM::set_contract_paused(Origin::signed(ford_account_id), counter, true),
+			BadOrigin
+		);
+		assert_ok!(EVM::set_contract_paused(
+			Origin::signed(CouncilAccount::get()),
+			counter,
+			true
+		));
+		let event = Event::evm_mod(crate::Event::ContractPaused(counter));
+		assert_eq!(System::events().iter().last().unwrap().event, event);
+
+		let result = Runner::<Test>::call(
+			trillian(),
+			trillian(),
+			counter,
+			vec![],
+			0,
+			100_000,
+			1000,
+			<Test as Config>::config(),
+		)
+		.unwrap();
+		assert_eq!(
+			result.exit_reason,
+			ExitReason::Error(ExitError::Other("ContractPaused".into()))
+		);
+
+		// the proxy runs, but cannot enter the counter
+		let result = Runner::<Test>::call(
+			trillian(),
+			trillian(),
+			proxy,
+			vec![],
+			0,
+			200_000,
+			1000,
+			<Test as Config>::config(),
+		)
+		.unwrap();
+		assert_eq!(result.exit_reason, ExitReason::Succeed(ExitSucceed::Stopped));
+		assert_eq!(EVM::account_storages(counter, H256::default()), H256::default());
+		assert_eq!(EVM::account_storages(proxy, H256::default()), H256::default());
+
+		assert_ok!(EVM::set_contract_paused(
+			Origin::signed(CouncilAccount::get()),
+			counter,
+			false
+		));
+		let event = Event::evm_mod(crate::Event::ContractUnpaused(counter));
+		assert_eq!(System::events().iter().last().unwrap().event, event);
+
+		let result = Runner::<Test>::call(
+			trillian(),
+			trillian(),
+			proxy,
+			vec![],
+			0,
+			200_000,
+			1000,
+			<Test as Config>::config(),
+		)
+		.unwrap();
+		assert_eq!(result.exit_reason, ExitReason::Succeed(ExitSucceed::Stopped));
+		assert_eq!(
+			EVM::account_storages(counter, H256::default()),
+			H256::from_low_u64_be(1)
+		);
+		assert_eq!(EVM::account_storages(proxy, H256::default()), H256::from_low_u64_be(1));
+
+		assert_ok!(EVM::set_evm_paused(Origin::signed(CouncilAccount::get()), true));
+		let event = Event::evm_mod(crate::Event::EvmPaused);
+		assert_eq!(System::events().iter().last().unwrap().event, event);
+
+		let result = Runner::<Test>::call(
+			trillian(),
+			trillian(),
+			counter,
+			vec![],
+			0,
+			100_000,
+			1000,
+			<Test as Config>::config(),
+		)
+		.unwrap();
+		assert_eq!(
+			result.exit_reason,
+			ExitReason::Error(ExitError::Other("EvmPaused".into()))
+		);
+		let result = Runner::<Test>::create(
+			trillian(),
+			from_hex("0x600a600c600039600a6000f360005460010160005500").unwrap(),
+			0,
+			1_000_000,
+			1000,
+			<Test as Config>::config(),
+		)
+		.unwrap();
+		assert_eq!(
+			result.exit_reason,
+			ExitReason::Error(ExitError::Other("EvmPaused".into()))
+		);
+
+		assert_ok!(EVM::set_evm_paused(Origin::signed(CouncilAccount::get()), false));
+		let event = Event::evm_mod(crate::Event::EvmUnpaused);
+		assert_eq!(System::events().iter().last().unwrap().event, event);
+		assert!(!EVM::evm_paused());
+	});
+}
+
+#[test]
+fn set_dispatch_call_allowed_works() {
+	new_test_ext().execute_with(|| {
+		let ford_account_id = <Test as Config>::AddressMapping::get_account_id(&ford());
+		let transfer = Call::Balances(pallet_balances::Call::transfer(ford_account_id.clone(), 100));
+		let remark = Call::System(frame_system::Call::remark(vec![]));
+		assert!(!DispatchCallFilter::<Test>::filter(&transfer));
+
+		assert_noop!(
+			EVM::set_dispatch_call_allowed(
+				Origin::signed(ford_account_id),
+				b"Balances".to_vec(),
+				b"transfer".to_vec(),
+				true
+			),
+			BadOrigin
+		);
+
+		assert_ok!(EVM::set_dispatch_call_allowed(
+			Origin::signed(CouncilAccount::get()),
+			b"Balances".to_vec(),
+			b"transfer".to_vec(),
+			true
+		));
+		let event = Event::evm_mod(crate::Event::DispatchCallAllowed(
+			b"Balances".to_vec(),
+			b"transfer".to_vec(),
+		));
+		assert_eq!(System::events().iter().last().unwrap().event, event);
+		assert!(DispatchCallFilter::<Test>::filter(&transfer));
+		assert!(!DispatchCallFilter::<Test>::filter(&remark));
+
+		assert_ok!(EVM::set_dispatch_call_allowed(
+			Origin::signed(CouncilAccount::get()),
+			b"Balances".to_vec(),
+			b"transfer".to_vec(),
+			false
+		));
+		let event = Event::evm_mod(crate::Event::DispatchCallDisallowed(
+			b"Balances".to_vec(),
+			b"transfer".to_vec(),
+		));
+		assert_eq!(System::events().iter().last().unwrap().event, event);
+		assert!(!DispatchCallFilter::<Test>::filter(&transfer));
+	});
+}
+
+#[test]
+fn storage_usage_works() {
+	// init code returning the runtime code `CALLDATASIZE PUSH1 0 SSTORE STOP`
+	let contract = from_hex("0x6005600c60003960056000f33660005500").unwrap();
+
+	new_test_ext().execute_with(|| {
+		let deposit_per_byte = <Test as Config>::StorageDepositPerByte::get();
+
+		let result =
+			Runner::<Test>::create(trillian(), contract, 0, 1_000_000, 1000, <Test as Config>::config()).unwrap();
+		assert_eq!(result.exit_reason, ExitReason::Succeed(ExitSucceed::Returned));
+		let contract_address = result.address;
+		let code_storage = result.used_storage as u32;
+		let created_usage = StorageUsage {
+			storage_size: code_storage,
+			reserved_deposit: code_storage as u64 * deposit_per_byte,
+			code_size: 5,
+		};
+		assert_eq!(EVM::storage_usage(&contract_address), Some(created_usage.clone()));
+
+		#[cfg(not(feature = "with-ethereum-compatibility"))]
+		deploy_free(contract_address);
+
+		// stores the call data size
+		let result = Runner::<Test>::call(
+			trillian(),
+			trillian(),
+			contract_address,
+			vec![1],
+			0,
+			100_000,
+			1000,
+			<Test as Config>::config(),
+		)
+		.unwrap();
+		assert_eq!(result.used_storage, STORAGE_SIZE as i32);
+		let event = Event::evm_mod(crate::Event::AddStorageQuota(contract_address, STORAGE_SIZE));
+		assert!(System::events().iter().any(|record| record.event == event));
+		assert_eq!(
+			EVM::storage_usage(&contract_address),
+			Some(StorageUsage {
+				storage_size: code_storage + STORAGE_SIZE,
+				reserved_deposit: (code_storage + STORAGE_SIZE) as u64 * deposit_per_byte,
+				code_size: 5,
+			})
+		);
+
+		// clears the slot
+		let result = Runner::<Test>::call(
+			trillian(),
+			trillian(),
+			contract_address,
+			vec![],
+			0,
+			100_000,
+			1000,
+			<Test as Config>::config(),
+		)
+		.unwrap();
+		assert_eq!(result.used_storage, -(STORAGE_SIZE as i32));
+		let event = Event::evm_mod(crate::Event::RemoveStorageQuota(contract_address, STORAGE_SIZE));
+		assert!(System::events().iter().any(|record| record.event == event));
+		assert_eq!(EVM::storage_usage(&contract_address), Some(created_usage));
+
+		assert_eq!(EVM::storage_usage(&trillian()), None);
+	});
+}
+
+#[test]
+fn migrate_to_v2_adds_contract_storage_size() {
+	new_test_ext().execute_with(|| {
+		let contract_account = <Test as Config>::AddressMapping::get_account_id(&contract_a());
+		let _ = Balances::deposit_creating(&contract_account, 1_000);
+		assert_ok!(Balances::reserve(
+			&contract_account,
+			50 * <Test as Config>::StorageDepositPerByte::get()
+		));
+
+		// nonce, contract info without storage size and developer deposit
+		let old_contract = (1u64, Some((H256::repeat_byte(1), trillian(), true)), None::<u64>);
+		frame_support::storage::unhashed::put(&Accounts::<Test>::hashed_key_for(contract_a()), &old_contract);
+		let old_account = (3u64, None::<(H256, H160, bool)>, Some(10u64));
+		frame_support::storage::unhashed::put(&Accounts::<Test>::hashed_key_for(charlie()), &old_account);
+		StorageVersion::<Test>::put(Releases::V1);
+
+		<EVM as OnRuntimeUpgrade>::on_runtime_upgrade();
+
+		assert_eq!(StorageVersion::<Test>::get(), Releases::V3);
+		assert_eq!(
+			EVM::accounts(contract_a()),
+			Some(AccountInfo {
+				nonce: 1,
+				contract_info: Some(ContractInfo {
+					code_hash: H256::repeat_byte(1),
+					maintainer: trillian(),
+					deployed: true,
+					storage_size: 50,
+				}),
+				developer_deposit: None,
+			})
+		);
+		assert_eq!(
+			EVM::accounts(charlie()),
+			Some(AccountInfo {
+				nonce: 3,
+				contract_info: None,
+				developer_deposit: Some(10),
+			})
+		);
+	});
+}
+
+#[test]
+fn migrate_to_v3_indexes_contracts_by_code_hash() {
+	// init code returning the runtime code `CALLDATASIZE PUSH1 0 SSTORE STOP`
+	let contract = from_hex("0x6005600c60003960056000f33660005500").unwrap();
+
+	new_test_ext().execute_with(|| {
+		let result =
+			Runner::<Test>::create(trillian(), contract, 0, 1_000_000, 1000, <Test as Config>::config()).unwrap();
+		assert_eq!(result.exit_reason, ExitReason::Succeed(ExitSucceed::Returned));
+		let code_hash = EVM::code_hash_at_address(&result.address);
+
+		ContractsByCodeHash::<Test>::remove_prefix(&code_hash);
+		StorageVersion::<Test>::put(Releases::V2);
+
+		<EVM as OnRuntimeUpgrade>::on_runtime_upgrade();
+
+		assert_eq!(StorageVersion::<Test>::get(), Releases::V3);
+		assert_eq!(EVM::contracts_by_code_hash(&code_hash), vec![result.address]);
+	});
+}
+
+#[test]
+fn create_from_code_hash_works() {
+	// init code returning the runtime code `CALLDATASIZE PUSH1 0 SSTORE STOP`
+	let contract = from_hex("0x6005600c60003960056000f33660005500").unwrap();
+
+	new_test_ext().execute_with(|| {
+		let ford_account_id = <Test as Config>::AddressMapping::get_account_id(&ford());
+
+		let result =
+			Runner::<Test>::create(trillian(), contract, 0, 1_000_000, 1000, <Test as Config>::config()).unwrap();
+		assert_eq!(result.exit_reason, ExitReason::Succeed(ExitSucceed::Returned));
+		let original = result.address;
+		let code_hash = EVM::code_hash_at_address(&original);
+
+		assert_noop!(
+			EVM::create_from_code_hash(
+				Origin::signed(ford_account_id.clone()),
+				H256::repeat_byte(1),
+				Vec::new(),
+				0,
+				1_000_000,
+				1000
+			),
+			Error::<Test>::CodeNotFound
+		);
+
+		// the code is not stored again, only the new account is charged
+		let result = Runner::<Test>::create_from_code_hash(
+			ford(),
+			code_hash,
+			Vec::new(),
+			0,
+			1_000_000,
+			1000,
+			<Test as Config>::config(),
+		)
+		.unwrap();
+		assert_eq!(result.exit_reason, ExitReason::Succeed(ExitSucceed::Returned));
+		let clone = result.address;
+		assert_eq!(result.used_storage, NewContractExtraBytes::get() as i32);
+		assert_eq!(EVM::code_at_address(&clone), EVM::code_at_address(&original));
+		assert_eq!(EVM::accounts(&clone).unwrap().nonce, 1);
+		assert_eq!(EVM::accounts(&clone).unwrap().contract_info.unwrap().maintainer, ford());
+		assert_eq!(EVM::code_infos(code_hash).unwrap().ref_count, 2);
+
+		// the contract is called with a non empty input, storing its size
+		let initialized = Handler::<Test>::create_address(evm::CreateScheme::Legacy { caller: ford() }).unwrap();
+		assert_ok!(EVM::create_from_code_hash(
+			Origin::signed(ford_account_id),
+			code_hash,
+			vec![1, 2],
+			0,
+			1_000_000,
+			1000
+		));
+		let event = Event::evm_mod(crate::Event::Created(initialized));
+		assert!(System::events().iter().any(|record| record.event == event));
+		assert_eq!(EVM::account_storages(initialized, H256::default()), H256::from_low_u64_be(2));
+		assert_eq!(EVM::code_infos(code_hash).unwrap().ref_count, 3);
+
+		let mut contracts = EVM::contracts_by_code_hash(&code_hash);
+		contracts.sort();
+		let mut expected = vec![original, clone, initialized];
+		expected.sort();
+		assert_eq!(contracts, expected);
+
+		// the code is kept until its last contract is removed
+		assert_ok!(EVM::remove_account(&original));
+		assert_ok!(EVM::remove_account(&clone));
+		assert_eq!(EVM::contracts_by_code_hash(&code_hash), vec![initialized]);
+		assert_eq!(EVM::code_infos(code_hash).unwrap().ref_count, 1);
+		assert_ok!(EVM::remove_account(&initialized));
+		assert!(EVM::contracts_by_code_hash(&code_hash).is_empty());
+		assert_eq!(EVM::code_infos(code_hash), None);
+		assert!(EVM::codes(code_hash).is_empty());
+	});
+}
+
+#[test]
+fn verification_metadata_works() {
+	// init code returning the runtime code `CALLDATASIZE PUSH1 0 SSTORE STOP`
+	let contract = from_hex("0x6005600c60003960056000f33660005500").unwrap();
+
+	new_test_ext().execute_with(|| {
+		let alice_account_id = <Test as Config>::AddressMapping::get_account_id(&trillian());
+		let bob_account_id = <Test as Config>::AddressMapping::get_account_id(&ford());
+
+		let result =
+			Runner::<Test>::create(trillian(), contract, 0, 1_000_000, 1000, <Test as Config>::config()).unwrap();
+		let contract_address = result.address;
+
+		let metadata = VerificationMetadata {
+			compiler_version: b"0.8.4+commit.c7e474f2".to_vec(),
+			optimizer_enabled: true,
+			optimizer_runs: 200,
+			source_hash: b"QmWATWQ7fVPP2EFGu71UkfnqhYXDYH566qy47CnJDgvs8u".to_vec(),
+			abi_hash: H256::repeat_byte(1),
+		};
+
+		// not maintainer
+		assert_noop!(
+			EVM::set_verification_metadata(Origin::signed(bob_account_id), contract_address, Some(metadata.clone())),
+			Error::<Test>::NoPermission
+		);
+		assert_noop!(
+			EVM::set_verification_metadata(
+				Origin::signed(alice_account_id.clone()),
+				contract_address,
+				Some(VerificationMetadata {
+					source_hash: vec![0; MAX_VERIFICATION_FIELD_LENGTH + 1],
+					..metadata.clone()
+				})
+			),
+			Error::<Test>::VerificationMetadataTooLarge
+		);
+
+		assert_ok!(EVM::set_verification_metadata(
+			Origin::signed(alice_account_id.clone()),
+			contract_address,
+			Some(metadata.clone())
+		));
+		let event = Event::evm_mod(crate::Event::VerificationMetadataSet(contract_address));
+		assert!(System::events().iter().any(|record| record.event == event));
+		assert_eq!(EVM::verification_metadata(contract_address), Some(metadata.clone()));
+
+		// removed when the code changes
+		assert_ok!(EVM::set_code(
+			Origin::root(),
+			contract_address,
+			from_hex("0x3660015500").unwrap()
+		));
+		let event = Event::evm_mod(crate::Event::VerificationMetadataRemoved(contract_address));
+		assert!(System::events().iter().any(|record| record.event == event));
+		assert_eq!(EVM::verification_metadata(contract_address), None);
+
+		assert_ok!(EVM::set_verification_metadata(
+			Origin::signed(alice_account_id.clone()),
+			contract_address,
+			Some(metadata)
+		));
+		assert_ok!(EVM::set_verification_metadata(
+			Origin::signed(alice_account_id),
+			contract_address,
+			None
+		));
+		assert_eq!(EVM::verification_metadata(contract_address), None);
+	});
+}
+
+#[test]
+fn code_upgrade_works() {
+	// init code returning the runtime code `CALLDATASIZE PUSH1 0 SSTORE STOP`
+	let contract = from_hex("0x6005600c60003960056000f33660005500").unwrap();
+	// `CALLDATASIZE PUSH1 1 SSTORE STOP`
+	let new_code = from_hex("0x3660015500").unwrap();
+
+	new_test_ext().execute_with(|| {
+		let alice_account_id = <Test as Config>::AddressMapping::get_account_id(&trillian());
+		let bob_account_id = <Test as Config>::AddressMapping::get_account_id(&ford());
+
+		let result =
+			Runner::<Test>::create(trillian(), contract, 0, 1_000_000, 1000, <Test as Config>::config()).unwrap();
+		let contract_address = result.address;
+
+		#[cfg(not(feature = "with-ethereum-compatibility"))]
+		deploy_free(contract_address);
+
+		let old_hash = EVM::code_hash_at_address(&contract_address);
+		let new_hash = code_hash(&new_code);
+		let deposit_per_byte = <Test as Config>::StorageDepositPerByte::get();
+		let alice_balance = balance(trillian());
+		let contract_deposit = reserved_balance(contract_address);
+
+		// not maintainer
+		assert_noop!(
+			EVM::propose_code_upgrade(Origin::signed(bob_account_id), contract_address, new_code.clone(), 2),
+			Error::<Test>::NoPermission
+		);
+		assert_ok!(EVM::propose_code_upgrade(
+			Origin::signed(alice_account_id.clone()),
+			contract_address,
+			new_code.clone(),
+			2
+		));
+		let event = Event::evm_mod(crate::Event::CodeUpgradeProposed(contract_address, new_hash));
+		assert!(System::events().iter().any(|record| record.event == event));
+
+		// the deposit of the proposed code is held on the contract
+		assert_eq!(balance(trillian()), alice_balance - 5 * deposit_per_byte);
+		assert_eq!(
+			reserved_balance(contract_address),
+			contract_deposit + 5 * deposit_per_byte
+		);
+		assert_eq!(
+			EVM::code_upgrades(contract_address),
+			Some(CodeUpgrade {
+				code: new_code.clone(),
+				delay: 2,
+				execute_at: None,
+			})
+		);
+		assert_noop!(
+			EVM::propose_code_upgrade(
+				Origin::signed(alice_account_id.clone()),
+				contract_address,
+				new_code.clone(),
+				2
+			),
+			Error::<Test>::CodeUpgradeAlreadyProposed
+		);
+
+		assert_noop!(
+			EVM::approve_code_upgrade(Origin::signed(alice_account_id.clone()), contract_address),
+			BadOrigin
+		);
+		assert_ok!(EVM::approve_code_upgrade(
+			Origin::signed(CouncilAccount::get()),
+			contract_address
+		));
+		let execute_at = System::block_number() + 3;
+		let event = Event::evm_mod(crate::Event::CodeUpgradeApproved(contract_address, execute_at));
+		assert!(System::events().iter().any(|record| record.event == event));
+		assert_noop!(
+			EVM::approve_code_upgrade(Origin::signed(CouncilAccount::get()), contract_address),
+			Error::<Test>::CodeUpgradeAlreadyApproved
+		);
+
+		EVM::on_initialize(execute_at - 1);
+		assert_eq!(EVM::code_hash_at_address(&contract_address), old_hash);
+
+		EVM::on_initialize(execute_at);
+		let event = Event::evm_mod(crate::Event::ContractCodeUpgraded(contract_address, old_hash, new_hash));
+		assert!(System::events().iter().any(|record| record.event == event));
+		assert_eq!(EVM::code_hash_at_address(&contract_address), new_hash);
+		assert_eq!(EVM::code_at_address(&contract_address), new_code);
+		assert_eq!(EVM::code_upgrades(contract_address), None);
+		assert_eq!(CodeInfos::<Test>::get(old_hash), None);
+		assert!(!Codes::<Test>::contains_key(old_hash));
+		assert_eq!(CodeInfos::<Test>::get(new_hash).unwrap().ref_count, 1);
+
+		// the code size is unchanged, the deposit of the proposal is returned
+		assert_eq!(balance(trillian()), alice_balance);
+		assert_eq!(reserved_balance(contract_address), contract_deposit);
+
+		// cancelled upgrades are not applied
+		assert_ok!(EVM::propose_code_upgrade(
+			Origin::signed(alice_account_id.clone()),
+			contract_address,
+			vec![0x00],
+			2
+		));
+		assert_ok!(EVM::approve_code_upgrade(
+			Origin::signed(CouncilAccount::get()),
+			contract_address
+		));
+		assert_ok!(EVM::cancel_code_upgrade(
+			Origin::signed(alice_account_id),
+			contract_address
+		));
+		let event = Event::evm_mod(crate::Event::CodeUpgradeCancelled(contract_address));
+		assert!(System::events().iter().any(|record| record.event == event));
+		assert_eq!(ScheduledCodeUpgrades::<Test>::get(execute_at), vec![]);
+		assert_eq!(EVM::code_upgrades(contract_address), None);
+		assert_eq!(balance(trillian()), alice_balance);
+		assert_eq!(reserved_balance(contract_address), contract_deposit);
+		assert_noop!(
+			EVM::cancel_code_upgrade(Origin::signed(CouncilAccount::get()), contract_address),
+			Error::<Test>::CodeUpgradeNotFound
+		);
+	});
+}
+
+#[test]
+fn code_upgrade_charges_the_code_size_difference() {
+	// init code returning the runtime code `CALLDATASIZE PUSH1 0 SSTORE STOP`
+	let contract = from_hex("0x6005600c60003960056000f33660005500").unwrap();
+	// `CALLDATASIZE PUSH1 1 SSTORE PUSH1 0 POP`
+	let new_code = from_hex("0x36600155600050").unwrap();
+
+	new_test_ext().execute_with(|| {
+		let alice_account_id = <Test as Config>::AddressMapping::get_account_id(&trillian());
+
+		let result =
+			Runner::<Test>::create(trillian(), contract, 0, 1_000_000, 1000, <Test as Config>::config()).unwrap();
+		let contract_address = result.address;
+
+		#[cfg(not(feature = "with-ethereum-compatibility"))]
+		deploy_free(contract_address);
+
+		let deposit_per_byte = <Test as Config>::StorageDepositPerByte::get();
+		let alice_balance = balance(trillian());
+		let contract_deposit = reserved_balance(contract_address);
+		let storage_size = EVM::storage_usage(&contract_address).unwrap().storage_size;
+
+		let upgrade = |code: Vec<u8>| {
+			assert_ok!(EVM::propose_code_upgrade(
+				Origin::signed(alice_account_id.clone()),
+				contract_address,
+				code,
+				0
+			));
+			assert_ok!(EVM::approve_code_upgrade(
+				Origin::signed(CouncilAccount::get()),
+				contract_address
+			));
+			EVM::on_initialize(System::block_number() + 1);
+		};
+
+		// two more bytes of code
+		upgrade(new_code.clone());
+		assert_eq!(EVM::code_at_address(&contract_address), new_code);
+		assert_eq!(balance(trillian()), alice_balance - 2 * deposit_per_byte);
+		assert_eq!(
+			reserved_balance(contract_address),
+			contract_deposit + 2 * deposit_per_byte
+		);
+		assert_eq!(
+			EVM::storage_usage(&contract_address).unwrap().storage_size,
+			storage_size + 2
+		);
+
+		// the code is checked when it is applied
+		assert_ok!(EVM::set_hardfork(Origin::root(), EvmHardfork::London));
+		upgrade(vec![0xef]);
+		let event = Event::evm_mod(crate::Event::ContractCodeUpgradeFailed(
+			contract_address,
+			Error::<Test>::InvalidCodePrefix.into(),
+		));
+		assert!(System::events().iter().any(|record| record.event == event));
+		assert_eq!(EVM::code_at_address(&contract_address), new_code);
+		assert_eq!(balance(trillian()), alice_balance - 2 * deposit_per_byte);
+		assert_eq!(
+			reserved_balance(contract_address),
+			contract_deposit + 2 * deposit_per_byte
+		);
+
+		// smaller code releases the difference
+		upgrade(vec![0x00]);
+		assert_eq!(EVM::code_at_address(&contract_address), vec![0x00]);
+		assert_eq!(balance(trillian()), alice_balance + 4 * deposit_per_byte);
+		assert_eq!(
+			reserved_balance(contract_address),
+			contract_deposit - 4 * deposit_per_byte
+		);
+		assert_eq!(
+			EVM::storage_usage(&contract_address).unwrap().storage_size,
+			storage_size - 4
+		);
+	});
+}
@@ -0,0 +1,152 @@
+//! RLP encoded legacy and EIP-155 Ethereum transactions.
+
+use codec::{Decode, Encode};
+use primitive_types::{H160, H256, U256};
+use rlp::{DecoderError, Rlp, RlpStream};
+use sha3::{Digest, Keccak256};
+use sp_runtime::RuntimeDebug;
+use sp_std::vec::Vec;
+
+/// Half of the secp256k1 curve order, the upper bound of `s` since EIP-2.
+const SECP256K1N_HALF: [u8; 32] = [
+	0x7f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x5d, 0x57, 0x6e,
+	0x73, 0x57, 0xa4, 0x50, 0x1d, 0xdf, 0xe9, 0x2f, 0x46, 0x68, 0x1b, 0x20, 0xa0,
+];
+
+#[derive(Clone, Eq, PartialEq, RuntimeDebug, Encode, Decode)]
+pub enum TransactionAction {
+	Call(H160),
+	Create,
+}
+
+#[derive(Clone, Eq, PartialEq, RuntimeDebug, Encode, Decode)]
+pub struct EthereumTransaction {
+	pub nonce: U256,
+	pub gas_price: U256,
+	pub gas_limit: U256,
+	pub action: TransactionAction,
+	pub value: U256,
+	pub input: Vec<u8>,
+	pub v: u64,
+	pub r: H256,
+	pub s: H256,
+}
+
+impl EthereumTransaction {
+	/// Decode a signed transaction from its RLP encoding.
+	pub fn decode_rlp(bytes: &[u8]) -> Result<Self, DecoderError> {
+		rlp::decode(bytes)
+	}
+
+	/// RLP encoding of the signed transaction.
+	pub fn encode_rlp(&self) -> Vec<u8> {
+		rlp::encode(self).to_vec()
+	}
+
+	/// Chain id the transaction was signed for, `None` for pre EIP-155
+	/// transactions.
+	pub fn chain_id(&self) -> Option<u64> {
+		if self.v >= 35 {
+			Some((self.v - 35) / 2)
+		} else {
+			None
+		}
+	}
+
+	/// Hash of the signed transaction, as reported by Ethereum tooling.
+	pub fn hash(&self) -> H256 {
+		H256::from_slice(Keccak256::digest(&rlp::encode(self)).as_slice())
+	}
+
+	/// Hash of the payload covered by the signature.
+	pub fn signing_hash(&self) -> H256 {
+		let mut stream = RlpStream::new();
+		match self.chain_id() {
+			Some(chain_id) => {
+				stream.begin_list(9);
+				self.append_unsigned(&mut stream);
+				stream.append(&chain_id);
+				stream.append(&0u8);
+				stream.append(&0u8);
+			}
+			None => {
+				stream.begin_list(6);
+				self.append_unsigned(&mut stream);
+			}
+		}
+		H256::from_slice(Keccak256::digest(&stream.out()).as_slice())
+	}
+
+	/// The 65 bytes `r ++ s ++ recovery_id` signature, `None` if `v` or `s`
+	/// are malformed.
+	pub fn signature(&self) -> Option<[u8; 65]> {
+		let recovery_id = match self.v {
+			27 | 28 => self.v - 27,
+			v if v >= 35 => (v - 35) % 2,
+			_ => return None,
+		};
+		if self.r.is_zero() || self.s.is_zero() || self.s.as_bytes() > &SECP256K1N_HALF[..] {
+			return None;
+		}
+
+		let mut sig = [0u8; 65];
+		sig[0..32].copy_from_slice(self.r.as_bytes());
+		sig[32..64].copy_from_slice(self.s.as_bytes());
+		sig[64] = recovery_id as u8;
+		Some(sig)
+	}
+
+	fn append_unsigned(&self, stream: &mut RlpStream) {
+		stream.append(&self.nonce);
+		stream.append(&self.gas_price);
+		stream.append(&self.gas_limit);
+		match self.action {
+			TransactionAction::Call(ref to) => stream.append(to),
+			TransactionAction::Create => stream.append_empty_data(),
+		};
+		stream.append(&self.value);
+		stream.append(&self.input);
+	}
+}
+
+impl rlp::Encodable for EthereumTransaction {
+	fn rlp_append(&self, stream: &mut RlpStream) {
+		stream.begin_list(9);
+		self.append_unsigned(stream);
+		stream.append(&self.v);
+		stream.append(&U256::from_big_endian(self.r.as_bytes()));
+		stream.append(&U256::from_big_endian(self.s.as_bytes()));
+	}
+}
+
+impl rlp::Decodable for EthereumTransaction {
+	fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+		if rlp.item_count()? != 9 {
+			return Err(DecoderError::RlpIncorrectListLen);
+		}
+
+		let to = rlp.at(3)?;
+		let action = if to.is_empty() {
+			TransactionAction::Create
+		} else {
+			TransactionAction::Call(to.as_val()?)
+		};
+
+		let mut r = H256::default();
+		rlp.val_at::<U256>(7)?.to_big_endian(r.as_bytes_mut());
+		let mut s = H256::default();
+		rlp.val_at::<U256>(8)?.to_big_endian(s.as_bytes_mut());
+
+		Ok(Self {
+			nonce: rlp.val_at(0)?,
+			gas_price: rlp.val_at(1)?,
+			gas_limit: rlp.val_at(2)?,
+			action,
+			value: rlp.val_at(4)?,
+			input: rlp.val_at(5)?,
+			v: rlp.val_at(6)?,
+			r,
+			s,
+		})
+	}
+}
@@ -108,6 +108,19 @@ pub trait TransactionPayment<AccountId, Balance, NegativeImbalance> {
 		weight: Weight,
 	) -> Result<(Balance, NegativeImbalance), TransactionValidityError>;
 	fn refund_fee(who: &AccountId, weight: Weight, payed: NegativeImbalance) -> Result<(), TransactionValidityError>;
+	/// Fee of `weight`, adjusted by the fee multiplier.
+	fn weight_to_fee(weight: Weight) -> Balance;
+	/// Base fee per weight of EVM transactions, following block fullness.
+	fn base_fee() -> Balance;
+	/// Fee of the length of an extrinsic of `len` bytes.
+	fn length_fee(len: u32) -> Balance;
+	/// Withdraw `fee` and the length fee of `len` bytes from `who`. Returns
+	/// the whole fee withdrawn.
+	fn charge_fee(
+		who: &AccountId,
+		len: u32,
+		fee: Balance,
+	) -> Result<(Balance, NegativeImbalance), TransactionValidityError>;
 }
 
 #[cfg(feature = "std")]
@@ -136,4 +149,42 @@ impl<AccountId, Balance: Default + Copy, NegativeImbalance: Imbalance<Balance>>
 	) -> Result<(), TransactionValidityError> {
 		Ok(())
 	}
+
+	fn weight_to_fee(_weight: Weight) -> Balance {
+		Default::default()
+	}
+
+	fn base_fee() -> Balance {
+		Default::default()
+	}
+
+	fn length_fee(_len: u32) -> Balance {
+		Default::default()
+	}
+
+	fn charge_fee(
+		_who: &AccountId,
+		_len: u32,
+		_fee: Balance,
+	) -> Result<(Balance, NegativeImbalance), TransactionValidityError> {
+		Ok((Default::default(), Imbalance::zero()))
+	}
+}
+
+/// An account paying the transaction fee of a call instead of its sender.
+pub trait FeeSponsor<AccountId, Call, Balance> {
+	/// Return the account paying `fee` for `who` dispatching `call`, if any.
+	fn fee_sponsor(who: &AccountId, call: &Call, fee: Balance) -> Option<AccountId>;
+
+	/// Called when `refund`, of the fee `sponsor` paid for `who`, is
+	/// refunded to `sponsor`.
+	fn refund_sponsored_fee(sponsor: &AccountId, who: &AccountId, refund: Balance);
+}
+
+impl<AccountId, Call, Balance> FeeSponsor<AccountId, Call, Balance> for () {
+	fn fee_sponsor(_who: &AccountId, _call: &Call, _fee: Balance) -> Option<AccountId> {
+		None
+	}
+
+	fn refund_sponsored_fee(_sponsor: &AccountId, _who: &AccountId, _refund: Balance) {}
 }
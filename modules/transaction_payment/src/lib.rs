@@ -22,7 +22,7 @@ use pallet_transaction_payment_rpc_runtime_api::{FeeDetails, InclusionFee};
 use primitives::{Balance, CurrencyId};
 use sp_runtime::{
 	traits::{
-		CheckedSub, Convert, DispatchInfoOf, PostDispatchInfoOf, SaturatedConversion, Saturating, SignedExtension,
+		CheckedSub, Convert, DispatchInfoOf, One, PostDispatchInfoOf, SaturatedConversion, Saturating, SignedExtension,
 		Zero,
 	},
 	transaction_validity::{
@@ -31,7 +31,7 @@ use sp_runtime::{
 	FixedPointNumber, FixedPointOperand, FixedU128, Perquintill,
 };
 use sp_std::{prelude::*, vec};
-use support::{TransactionPayment};
+use support::{FeeSponsor, TransactionPayment};
 
 mod default_weight;
 mod mock;
@@ -51,6 +51,12 @@ type PalletBalanceOf<T> = <<T as Config>::Currency as Currency<<T as frame_syste
 type NegativeImbalanceOf<T> =
 	<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::NegativeImbalance;
 
+/// The block weight targeted by the base fee is the maximum normal weight
+/// divided by this.
+pub const BASE_FEE_ELASTICITY_MULTIPLIER: Weight = 2;
+/// Bound of the base fee change between blocks, 1/8 as in EIP-1559.
+pub const BASE_FEE_MAX_CHANGE_DENOMINATOR: u32 = 8;
+
 /// A struct to update the weight multiplier per block. It implements
 /// `Convert<Multiplier, Multiplier>`, meaning that it can convert the
 /// previous multiplier to the next one. This should be called on
@@ -238,6 +244,14 @@ pub mod module {
 		/// block's weight.
 		type FeeMultiplierUpdate: MultiplierUpdate;
 
+		/// Initial value and lower bound of the base fee per weight of EVM
+		/// transactions.
+		#[pallet::constant]
+		type MinimumBaseFee: Get<PalletBalanceOf<Self>>;
+
+		/// Find an account paying the fee of a call instead of its sender.
+		type FeeSponsor: FeeSponsor<Self::AccountId, <Self as frame_system::Config>::Call, PalletBalanceOf<Self>>;
+
 		/// Weight information for the extrinsics in this module.
 		type WeightInfo: WeightInfo;
 	}
@@ -251,6 +265,17 @@ pub mod module {
 	#[pallet::getter(fn next_fee_multiplier)]
 	pub type NextFeeMultiplier<T: Config> = StorageValue<_, Multiplier, ValueQuery, DefaultFeeMultiplier>;
 
+	#[pallet::type_value]
+	pub fn DefaultBaseFee<T: Config>() -> PalletBalanceOf<T> {
+		T::MinimumBaseFee::get()
+	}
+
+	/// Base fee per weight of EVM transactions in the next block, following
+	/// EIP-1559.
+	#[pallet::storage]
+	#[pallet::getter(fn base_fee)]
+	pub type BaseFee<T: Config> = StorageValue<_, PalletBalanceOf<T>, ValueQuery, DefaultBaseFee<T>>;
+
 	#[pallet::storage]
 	#[pallet::getter(fn default_fee_currency_id)]
 	pub type DefaultFeeCurrencyId<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, CurrencyId, OptionQuery>;
@@ -269,6 +294,17 @@ pub mod module {
 			NextFeeMultiplier::<T>::mutate(|fm| {
 				*fm = T::FeeMultiplierUpdate::convert(*fm);
 			});
+
+			let weights = T::BlockWeights::get();
+			let target = weights
+				.get(DispatchClass::Normal)
+				.max_total
+				.unwrap_or(weights.max_block)
+				/ BASE_FEE_ELASTICITY_MULTIPLIER;
+			let used = *frame_system::Module::<T>::block_weight().get(DispatchClass::Normal);
+			BaseFee::<T>::mutate(|base_fee| {
+				*base_fee = Self::next_base_fee(*base_fee, used, target).max(T::MinimumBaseFee::get());
+			});
 		}
 
 		#[cfg(feature = "std")]
@@ -334,6 +370,26 @@ pub mod module {
 	}
 }
 
+impl<T: Config> Pallet<T> {
+	/// The base fee following a block with `used` weight, moving by at most
+	/// 1/8 towards keeping blocks at `target` weight.
+	pub fn next_base_fee(base_fee: PalletBalanceOf<T>, used: Weight, target: Weight) -> PalletBalanceOf<T> {
+		if target.is_zero() || used == target {
+			return base_fee;
+		}
+
+		let delta = Perquintill::from_rational_approximation(used.max(target) - used.min(target), target)
+			.mul_floor(base_fee)
+			/ BASE_FEE_MAX_CHANGE_DENOMINATOR.into();
+		if used > target {
+			// always increase a bit, or a zero fee could never grow
+			base_fee.saturating_add(delta.max(One::one()))
+		} else {
+			base_fee.saturating_sub(delta)
+		}
+	}
+}
+
 impl<T: Config> Pallet<T>
 where
 	PalletBalanceOf<T>: FixedPointOperand,
@@ -610,13 +666,16 @@ where
 	fn withdraw_fee(
 		&self,
 		who: &T::AccountId,
-		_call: &<T as frame_system::Config>::Call,
+		call: &<T as frame_system::Config>::Call,
 		info: &DispatchInfoOf<<T as frame_system::Config>::Call>,
 		len: usize,
-	) -> Result<(PalletBalanceOf<T>, Option<NegativeImbalanceOf<T>>), TransactionValidityError> {
+	) -> Result<(PalletBalanceOf<T>, T::AccountId, Option<NegativeImbalanceOf<T>>), TransactionValidityError> {
 		let tip = self.0;
 		let fee = Module::<T>::compute_fee(len as u32, info, tip);
 
+		// sponsored calls are paid, and refunded, by the sponsor
+		let who = &T::FeeSponsor::fee_sponsor(who, call, fee).unwrap_or_else(|| who.clone());
+
 		let reason = if tip.is_zero() {
 			WithdrawReasons::TRANSACTION_PAYMENT
 		} else {
@@ -628,7 +687,7 @@ where
 
 		// withdraw native currency as fee
 		match <T as Config>::Currency::withdraw(who, fee, reason, ExistenceRequirement::KeepAlive) {
-			Ok(imbalance) => Ok((fee, Some(imbalance))),
+			Ok(imbalance) => Ok((fee, who.clone(), Some(imbalance))),
 			Err(_) => Err(InvalidTransaction::Payment.into()),
 		}
 	}
@@ -674,6 +733,7 @@ where
 	type Pre = (
 		PalletBalanceOf<T>,
 		Self::AccountId,
+		Self::AccountId,
 		Option<NegativeImbalanceOf<T>>,
 		PalletBalanceOf<T>,
 	);
@@ -689,7 +749,7 @@ where
 		info: &DispatchInfoOf<Self::Call>,
 		len: usize,
 	) -> TransactionValidity {
-		let (fee, _) = self.withdraw_fee(who, call, info, len)?;
+		let (fee, _, _) = self.withdraw_fee(who, call, info, len)?;
 		Ok(ValidTransaction {
 			priority: Self::get_priority(len, info, fee),
 			..Default::default()
@@ -703,8 +763,8 @@ where
 		info: &DispatchInfoOf<Self::Call>,
 		len: usize,
 	) -> Result<Self::Pre, TransactionValidityError> {
-		let (fee, imbalance) = self.withdraw_fee(who, call, info, len)?;
-		Ok((self.0, who.clone(), imbalance, fee))
+		let (fee, payer, imbalance) = self.withdraw_fee(who, call, info, len)?;
+		Ok((self.0, who.clone(), payer, imbalance, fee))
 	}
 
 	fn post_dispatch(
@@ -714,12 +774,17 @@ where
 		len: usize,
 		_result: &DispatchResult,
 	) -> Result<(), TransactionValidityError> {
-		let (tip, who, imbalance, fee) = pre;
+		let (tip, sender, who, imbalance, fee) = pre;
 		if let Some(payed) = imbalance {
 			let actual_fee = Module::<T>::compute_actual_fee(len as u32, info, post_info, tip);
 			let refund = fee.saturating_sub(actual_fee);
 			let actual_payment = match <T as Config>::Currency::deposit_into_existing(&who, refund) {
 				Ok(refund_imbalance) => {
+					// only the actual fee counts against the sponsorship limits
+					if who != sender {
+						T::FeeSponsor::refund_sponsored_fee(&who, &sender, refund);
+					}
+
 					// The refund cannot be larger than the up front payed max weight.
 					// `PostDispatchInfo::calc_unspent` guards against such a case.
 					match payed.offset(refund_imbalance) {
@@ -801,4 +866,35 @@ where
 
 		Ok(())
 	}
+
+	fn weight_to_fee(weight: Weight) -> PalletBalanceOf<T> {
+		<Module<T> as Convert<Weight, PalletBalanceOf<T>>>::convert(weight)
+	}
+
+	fn base_fee() -> PalletBalanceOf<T> {
+		Module::<T>::base_fee()
+	}
+
+	fn length_fee(len: u32) -> PalletBalanceOf<T> {
+		T::TransactionByteFee::get().saturating_mul(len.into())
+	}
+
+	fn charge_fee(
+		who: &T::AccountId,
+		len: u32,
+		fee: PalletBalanceOf<T>,
+	) -> Result<(PalletBalanceOf<T>, NegativeImbalanceOf<T>), TransactionValidityError> {
+		let fee = Self::length_fee(len).saturating_add(fee);
+		Module::<T>::ensure_can_charge_fee(who, fee, WithdrawReasons::TRANSACTION_PAYMENT);
+
+		match <T as Config>::Currency::withdraw(
+			who,
+			fee,
+			WithdrawReasons::TRANSACTION_PAYMENT,
+			ExistenceRequirement::KeepAlive,
+		) {
+			Ok(imbalance) => Ok((fee, imbalance)),
+			Err(_) => Err(InvalidTransaction::Payment.into()),
+		}
+	}
 }
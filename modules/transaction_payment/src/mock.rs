@@ -163,6 +163,7 @@ parameter_types! {
 	pub AllNonNativeCurrencyIds: Vec<CurrencyId> = vec![SEUR];
 	pub const StableCurrencyId: CurrencyId = SEUR;
 	pub static TransactionByteFee: u128 = 1;
+	pub const MinimumBaseFee: u128 = 10;
 }
 
 impl Config for Runtime {
@@ -175,6 +176,8 @@ impl Config for Runtime {
 	type TransactionByteFee = TransactionByteFee;
 	type WeightToFee = WeightToFee;
 	type FeeMultiplierUpdate = ();
+	type MinimumBaseFee = MinimumBaseFee;
+	type FeeSponsor = ();
 	type WeightInfo = ();
 }
 
@@ -295,3 +295,50 @@ fn compute_fee_does_not_overflow() {
 			);
 		});
 }
+
+#[test]
+fn base_fee_follows_block_fullness() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_eq!(TransactionPayment::base_fee(), 10);
+
+		// a full block raises the base fee by 1/8
+		frame_system::Module::<Runtime>::set_block_consumed_resources(1024, 0);
+		TransactionPayment::on_finalize(1);
+		assert_eq!(TransactionPayment::base_fee(), 11);
+
+		// a block at the target keeps it
+		frame_system::Module::<Runtime>::set_block_consumed_resources(512, 0);
+		TransactionPayment::on_finalize(2);
+		assert_eq!(TransactionPayment::base_fee(), 11);
+
+		// empty blocks lower it down to the minimum
+		frame_system::Module::<Runtime>::set_block_consumed_resources(0, 0);
+		TransactionPayment::on_finalize(3);
+		assert_eq!(TransactionPayment::base_fee(), 10);
+		TransactionPayment::on_finalize(4);
+		assert_eq!(TransactionPayment::base_fee(), 10);
+
+		assert_eq!(TransactionPayment::next_base_fee(800, 1024, 512), 900);
+		assert_eq!(TransactionPayment::next_base_fee(800, 256, 512), 750);
+		assert_eq!(TransactionPayment::next_base_fee(0, 1024, 512), 1);
+		assert_eq!(TransactionPayment::next_base_fee(800, 1024, 0), 800);
+	});
+}
+
+#[test]
+fn charge_fee_includes_the_length_fee() {
+	use support::TransactionPayment as _;
+
+	ExtBuilder::default().build().execute_with(|| {
+		let fee = 23 * 2 + 1000; // len * byte + fee
+		let (charged, imbalance) = ChargeTransactionPayment::<Runtime>::charge_fee(&TRILLIAN, 23, 1000).unwrap();
+		assert_eq!(charged, fee);
+		assert_eq!(imbalance.peek(), fee);
+		assert_eq!(Currencies::free_balance(SNAPR, &TRILLIAN), 100000 - fee);
+
+		assert_err!(
+			ChargeTransactionPayment::<Runtime>::charge_fee(&FORD, 23, 1000),
+			TransactionValidityError::Invalid(InvalidTransaction::Payment)
+		);
+	});
+}
@@ -8,7 +8,7 @@ use snapr_runtime::{
 	StakerStatus,
 	ImOnlineId, AuthorityDiscoveryId,
 	MaxNativeTokenExistentialDeposit,
-	get_all_module_accounts,
+	get_all_module_accounts, dispatchable_calls,
 	opaque::SessionKeys,
 };
 use sp_consensus_babe::AuthorityId as BabeId;
@@ -485,6 +485,7 @@ fn testnet_genesis(
 		}),
 		module_evm: Some(EvmConfig {
 			accounts: evm_genesis_accounts,
+			dispatchable_calls: dispatchable_calls(),
 		}),
 		pallet_sudo: Some(SudoConfig { key: root_key }),
 		pallet_collective_Instance1: Some(Default::default()),
@@ -574,6 +575,7 @@ fn mainnet_genesis(
 		}),
 		module_evm: Some(EvmConfig {
 			accounts: evm_genesis_accounts,
+			dispatchable_calls: dispatchable_calls(),
 		}),
 		pallet_sudo: Some(SudoConfig { key: root_key }),
 		pallet_collective_Instance1: Some(Default::default()),
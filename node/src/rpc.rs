@@ -27,7 +27,7 @@ use sc_finality_grandpa::{
 	SharedAuthoritySet,
 	SharedVoterState
 };
-pub use evm_rpc::{EVMApi, EVMApiServer, EVMRuntimeRPCApi};
+pub use evm_rpc::{DebugApi, DebugApiServer, EVMApi, EVMApiServer, EVMRuntimeRPCApi, EthApi, EthApiServer};
 pub use sc_rpc::SubscriptionTaskExecutor;
 
 /// Extra dependencies for BABE.
@@ -76,13 +76,14 @@ pub fn create_full<C, P, SC, B>(
 ) -> jsonrpc_core::IoHandler<sc_rpc::Metadata> where
 	C: ProvideRuntimeApi<Block>,
 	C: HeaderBackend<Block> + HeaderMetadata<Block, Error=BlockChainError> + 'static,
+	C: sc_client_api::BlockBackend<Block> + sc_client_api::AuxStore,
 	C: Send + Sync + 'static,
 	C::Api: substrate_frame_rpc_system::AccountNonceApi<Block, AccountId, Nonce>,
 	C::Api: pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance>,
 	C::Api: EVMRuntimeRPCApi<Block, Balance>,
 	C::Api: sp_consensus_babe::BabeApi<Block>,
 	C::Api: BlockBuilder<Block>,
-	P: TransactionPool + Sync + Send + 'static,
+	P: TransactionPool<Block = Block> + Sync + Send + 'static,
 	SC: sp_consensus::SelectChain<Block> + 'static,
 	B: sc_client_api::Backend<Block> + Send + Sync + 'static,
 	B::State: sc_client_api::StateBackend<sp_runtime::traits::HashFor<Block>>,
@@ -118,7 +119,7 @@ pub fn create_full<C, P, SC, B>(
 
 
 	io.extend_with(
-		SystemApi::to_delegate(FullSystem::new(client.clone(), pool, deny_unsafe))
+		SystemApi::to_delegate(FullSystem::new(client.clone(), pool.clone(), deny_unsafe))
 	);
 
 	io.extend_with(
@@ -145,6 +146,8 @@ pub fn create_full<C, P, SC, B>(
 		subscription_executor,
 		finality_provider,
 	)));
+	io.extend_with(EthApiServer::to_delegate(EthApi::new(client.clone(), pool)));
+	io.extend_with(DebugApiServer::to_delegate(DebugApi::new(client.clone())));
 	io.extend_with(EVMApiServer::to_delegate(EVMApi::new(client)));
 
 	io
@@ -151,6 +151,12 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 		);
 	}
 
+	// index the EVM transactions by hash for the Ethereum RPC
+	task_manager.spawn_handle().spawn_blocking(
+		"evm-transaction-index",
+		evm_rpc::index_transactions::<_, _, snapr_runtime::Balance>(client.clone()),
+	);
+
 	let role = config.role.clone();
 	let force_authoring = config.force_authoring;
 	let backoff_authoring_blocks = Some(sc_consensus_slots::BackoffAuthoringOnFinalizedHeadLagging::default());
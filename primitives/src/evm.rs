@@ -1,7 +1,7 @@
 use crate::Balance;
 use codec::{Decode, Encode};
 use evm::ExitReason;
-use sp_core::{H160, U256};
+use sp_core::{H160, H256, U256};
 use sp_runtime::RuntimeDebug;
 use sp_std::vec::Vec;
 
@@ -43,6 +43,54 @@ pub struct CallInfo {
 	pub used_gas: U256,
 	pub used_storage: i32,
 }
+
+/// A call of an atomic batch of EVM calls.
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct BatchCall<Balance> {
+	pub target: EvmAddress,
+	pub input: Vec<u8>,
+	pub value: Balance,
+	pub gas_limit: u64,
+	pub storage_limit: u32,
+}
+
+/// Outcome of a batch of EVM calls, applied together or reverted together.
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct BatchCallInfo {
+	/// Exit reason of the last executed call, the failed one if any.
+	pub exit_reason: ExitReason,
+	/// Output of the last executed call.
+	pub output: Vec<u8>,
+	/// Index of the last executed call.
+	pub index: u32,
+	/// Gas used by all executed calls.
+	pub used_gas: U256,
+	/// Storage used by all calls, zero if the batch was reverted.
+	pub used_storage: i32,
+}
+
+/// A deduplicated contract code, stored once for all the contracts using
+/// it.
+#[derive(Clone, Copy, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct CodeInfo {
+	pub code_size: u32,
+	/// Number of contracts using the code, removed when it drops to zero.
+	pub ref_count: u32,
+}
+
+/// An entry of an EIP-2930 access list, the address and storage slots are
+/// warm from the start of the transaction.
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug, Default)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct AccessListItem {
+	pub address: EvmAddress,
+	pub storage_keys: Vec<H256>,
+}
+
 /// A mapping between `AccountId` and `EvmAddress`.
 pub trait AddressMapping<AccountId> {
 	fn get_account_id(evm: &EvmAddress) -> AccountId;
@@ -67,4 +115,212 @@ pub struct EstimateResourcesRequest {
 	pub value: Option<Balance>,
 	/// Data
 	pub data: Option<Vec<u8>>,
+	/// Calls of a batch, `to` and `data` are unset for batches
+	pub batch: Option<Vec<BatchCall<Balance>>>,
+	/// Access list, EIP-2930
+	pub access_list: Option<Vec<AccessListItem>>,
+}
+
+/// How the execution of a traced transaction is recorded.
+#[derive(Clone, Copy, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum TracerConfig {
+	/// Record the call tree only.
+	CallTracer,
+	/// Record the call tree and every executed opcode.
+	OpcodeLogger,
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum CallType {
+	Call,
+	CallCode,
+	DelegateCall,
+	StaticCall,
+	Create,
+	Create2,
+}
+
+/// A frame of the call tree.
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct CallTrace {
+	pub call_type: CallType,
+	pub from: H160,
+	/// The called contract, or the created contract once the frame exits.
+	pub to: H160,
+	pub input: Vec<u8>,
+	pub value: U256,
+	pub gas: u64,
+	pub gas_used: u64,
+	pub output: Vec<u8>,
+	pub exit_reason: ExitReason,
+	pub calls: Vec<CallTrace>,
+}
+
+/// An executed opcode.
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct Step {
+	pub pc: u64,
+	pub op: u8,
+	/// Gas left before the opcode.
+	pub gas: u64,
+	pub gas_cost: u64,
+	/// Call depth, starting from 1.
+	pub depth: u32,
+	pub stack: Vec<H256>,
+	/// Storage slots written by the opcode.
+	pub storage: Vec<(H256, H256)>,
+}
+
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug, Default)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct Trace {
+	/// Top level calls, one per EVM execution.
+	pub calls: Vec<CallTrace>,
+	/// Executed opcodes, empty unless traced with `TracerConfig::OpcodeLogger`.
+	pub steps: Vec<Step>,
+	/// Accounts and storage slots accessed by the top level calls, except
+	/// the callers and the called or created contracts without storage
+	/// slots.
+	pub access_list: Vec<AccessListItem>,
+}
+
+/// Size of the logs bloom in bytes.
+pub const BLOOM_SIZE: usize = 256;
+
+/// The 2048 bit Ethereum logs bloom filter.
+#[derive(Clone, Copy, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub struct Bloom(pub [u8; BLOOM_SIZE]);
+
+impl Default for Bloom {
+	fn default() -> Self {
+		Bloom([0u8; BLOOM_SIZE])
+	}
+}
+
+impl Bloom {
+	/// Positions of the three bits set for `input`, as `(byte, mask)`.
+	fn bits(input: &[u8]) -> [(usize, u8); 3] {
+		let hash = sp_io::hashing::keccak_256(input);
+		let mut bits = [(0, 0); 3];
+		for (i, bit) in bits.iter_mut().enumerate() {
+			let index = (((hash[2 * i] as usize) << 8) | hash[2 * i + 1] as usize) & (BLOOM_SIZE * 8 - 1);
+			*bit = (BLOOM_SIZE - 1 - index / 8, 1u8 << (index % 8));
+		}
+		bits
+	}
+
+	pub fn accrue(&mut self, input: &[u8]) {
+		for (byte, mask) in Self::bits(input).iter() {
+			self.0[*byte] |= mask;
+		}
+	}
+
+	/// Add the address and topics of `log`.
+	pub fn accrue_log(&mut self, log: &Log) {
+		self.accrue(log.address.as_bytes());
+		for topic in log.topics.iter() {
+			self.accrue(topic.as_bytes());
+		}
+	}
+
+	pub fn accrue_bloom(&mut self, other: &Bloom) {
+		for (byte, other) in self.0.iter_mut().zip(other.0.iter()) {
+			*byte |= other;
+		}
+	}
+
+	/// Whether `input` may have been added. False positives are possible.
+	pub fn contains_input(&self, input: &[u8]) -> bool {
+		Self::bits(input).iter().all(|(byte, mask)| self.0[*byte] & mask == *mask)
+	}
+}
+
+/// Outcome of the EVM executions of an extrinsic.
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub struct Receipt {
+	/// Ethereum transaction hash, or the extrinsic hash for other extrinsics.
+	pub transaction_hash: H256,
+	/// Index of the extrinsic in the block.
+	pub transaction_index: u32,
+	pub from: EvmAddress,
+	/// Called contract, `None` for contract creations.
+	pub to: Option<EvmAddress>,
+	/// Whether all executions succeeded.
+	pub status: bool,
+	pub used_gas: U256,
+	/// Gas used by the receipts of the block up to and including this one.
+	pub cumulative_gas_used: U256,
+	/// Price paid per gas, including the priority tip.
+	pub effective_gas_price: U256,
+	pub logs: Vec<Log>,
+	pub logs_bloom: Bloom,
+	/// Created contract, if any.
+	pub contract_address: Option<EvmAddress>,
+}
+
+/// Limits of the fees and storage deposits a contract sponsor pays for
+/// callers, `None` for no limit.
+#[derive(Clone, Copy, Default, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct SponsorshipLimits<Balance> {
+	/// Total amount sponsored for a single caller.
+	pub per_caller: Option<Balance>,
+	/// Amount sponsored in a single block.
+	pub per_block: Option<Balance>,
+}
+
+/// Storage held by a contract.
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct StorageUsage<Balance> {
+	/// Bytes of storage paid for by deposits, code included.
+	pub storage_size: u32,
+	/// Deposit reserved by the contract account for its storage.
+	pub reserved_deposit: Balance,
+	/// Size of the contract code.
+	pub code_size: u32,
+}
+
+/// Metadata for checking that the code of a contract is the output of
+/// compiling its source.
+#[derive(Clone, Default, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct VerificationMetadata {
+	/// Version of the compiler, e.g. `0.8.4+commit.c7e474f2`.
+	pub compiler_version: Vec<u8>,
+	/// Whether the optimizer was enabled.
+	pub optimizer_enabled: bool,
+	/// Optimizer runs setting.
+	pub optimizer_runs: u32,
+	/// Hash of the source bundle, e.g. an IPFS CID.
+	pub source_hash: Vec<u8>,
+	/// Hash of the contract ABI.
+	pub abi_hash: H256,
+}
+
+/// Code upgrade of a deployed contract proposed by its maintainer.
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct CodeUpgrade<BlockNumber> {
+	/// New code of the contract.
+	pub code: Vec<u8>,
+	/// Blocks between the approval and the upgrade.
+	pub delay: BlockNumber,
+	/// Block the code is upgraded at, once approved.
+	pub execute_at: Option<BlockNumber>,
+}
+
+/// Sponsorship of the calls to a contract.
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct Sponsorship<Balance> {
+	/// Funds left to pay for calls.
+	pub balance: Balance,
+	pub limits: SponsorshipLimits<Balance>,
+	/// Amount sponsored in the current block.
+	pub used_in_block: Balance,
 }
@@ -1,6 +1,7 @@
 use codec::Encode;
-use sp_core::{crypto::AccountId32, H160};
+use sp_core::{crypto::AccountId32, H160, H256, U256};
 use sp_io::hashing::blake2_256;
+use sp_std::vec::Vec;
 
 pub struct MockAddressMapping;
 
@@ -36,3 +37,49 @@ impl crate::evm::AddressMapping<AccountId32> for MockAddressMapping {
 		Self::get_or_create_evm_address(account_id) == *evm
 	}
 }
+
+/// Precompile input built from 32-byte params, after a function selector or
+/// an action.
+#[derive(Default)]
+pub struct InputBuilder(Vec<u8>);
+
+impl InputBuilder {
+	/// Input of the function with `selector`.
+	pub fn selector(selector: [u8; 4]) -> Self {
+		Self(selector.to_vec())
+	}
+
+	/// Input of `action`.
+	pub fn action(action: u8) -> Self {
+		Self::default().uint(action)
+	}
+
+	pub fn uint(mut self, value: impl Into<U256>) -> Self {
+		let mut param = [0u8; 32];
+		value.into().to_big_endian(&mut param);
+		self.0.extend_from_slice(&param);
+		self
+	}
+
+	pub fn address(mut self, address: H160) -> Self {
+		self.0.extend_from_slice(H256::from(address).as_bytes());
+		self
+	}
+
+	pub fn bytes32(mut self, param: [u8; 32]) -> Self {
+		self.0.extend_from_slice(&param);
+		self
+	}
+
+	/// The length of `data` followed by `data`, zero padded to 32 bytes.
+	pub fn bytes(self, data: &[u8]) -> Self {
+		let mut input = self.uint(data.len()).0;
+		input.extend_from_slice(data);
+		input.resize(input.len() + (32 - data.len() % 32) % 32, 0);
+		Self(input)
+	}
+
+	pub fn build(self) -> Vec<u8> {
+		self.0
+	}
+}
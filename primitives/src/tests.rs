@@ -36,3 +36,28 @@ fn currency_id_encode_decode_bytes_works() {
 	let bytes: [u8; 32] = currency_id.into();
 	assert_ok!(bytes.try_into(), currency_id)
 }
+
+#[test]
+fn bloom_works() {
+	let log = evm::Log {
+		address: EvmAddress::from_low_u64_be(1),
+		topics: vec![],
+		data: vec![],
+	};
+	let mut bloom = evm::Bloom::default();
+	bloom.accrue_log(&log);
+
+	let mut expected = [0u8; evm::BLOOM_SIZE];
+	expected[57] = 2;
+	expected[114] = 1;
+	expected[239] = 1;
+	assert_eq!(bloom, evm::Bloom(expected));
+
+	assert!(bloom.contains_input(log.address.as_bytes()));
+	assert!(!bloom.contains_input(EvmAddress::from_low_u64_be(2).as_bytes()));
+
+	let mut other = evm::Bloom::default();
+	other.accrue(EvmAddress::from_low_u64_be(2).as_bytes());
+	bloom.accrue_bloom(&other);
+	assert!(bloom.contains_input(EvmAddress::from_low_u64_be(2).as_bytes()));
+}
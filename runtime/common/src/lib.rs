@@ -25,7 +25,10 @@ pub use module_support::{PrecompileCallerFilter};
 pub mod precompile;
 pub use precompile::{
 	AllPrecompiles,
+	DispatchCallPrecompile,
+	EvmAccountsPrecompile,
 	MultiCurrencyPrecompile,
+	PocPrecompile,
 	ScheduleCallPrecompile,
 	StateRentPrecompile,
 };
@@ -56,11 +59,27 @@ impl PrecompileCallerFilter for SystemContractsFilter {
 	}
 }
 
+/// Gas executed per second of block weight, about the gas throughput of the
+/// Ethereum clients.
+pub const GAS_PER_SECOND: u64 = 40_000_000;
+/// Weight of one unit of gas. With it the gas of an EVM call costs as much
+/// block weight as the dispatchables doing the same work.
+pub const WEIGHT_PER_GAS: Weight = WEIGHT_PER_SECOND / GAS_PER_SECOND;
+
 /// Convert gas to weight
 pub struct GasToWeight;
 impl Convert<u64, Weight> for GasToWeight {
 	fn convert(a: u64) -> u64 {
-		a as Weight
+		a.saturating_mul(WEIGHT_PER_GAS)
+	}
+}
+
+/// Convert weight to gas, the inverse of `GasToWeight`. Rounds up, so
+/// precompiles are never charged less gas than their weight.
+pub struct WeightToGas;
+impl Convert<Weight, u64> for WeightToGas {
+	fn convert(a: Weight) -> u64 {
+		a.saturating_add(WEIGHT_PER_GAS - 1) / WEIGHT_PER_GAS
 	}
 }
 
@@ -155,4 +174,16 @@ mod tests {
 		assert!(!is_core_precompile(H160::from_low_u64_be(PREDEPLOY_ADDRESS_START)));
 		assert!(!is_core_precompile([1u8; 20].into()));
 	}
+
+	#[test]
+	fn gas_weight_conversion_works() {
+		assert_eq!(GasToWeight::convert(21_000), 21_000 * WEIGHT_PER_GAS);
+		assert_eq!(WeightToGas::convert(21_000 * WEIGHT_PER_GAS), 21_000);
+		assert_eq!(WeightToGas::convert(21_000 * WEIGHT_PER_GAS + 1), 21_001);
+		assert_eq!(GasToWeight::convert(u64::max_value()), Weight::max_value());
+
+		// the normal extrinsics of a block fit 60M gas
+		let max_weight = BlockWeights::get().get(DispatchClass::Normal).max_total.unwrap();
+		assert_eq!(WeightToGas::convert(max_weight), 60_000_000);
+	}
 }
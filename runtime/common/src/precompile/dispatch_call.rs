@@ -0,0 +1,69 @@
+use codec::DecodeLimit;
+use frame_support::{
+	debug,
+	dispatch::{Dispatchable, GetDispatchInfo},
+	traits::Filter,
+	weights::Weight,
+};
+use module_evm::{precompiles, Context, ExitError, ExitSucceed, Precompile};
+use sp_runtime::traits::Convert;
+use sp_std::{borrow::Cow, fmt::Debug, marker::PhantomData, prelude::*, result};
+
+use super::gas_cost;
+use primitives::evm::AddressMapping as AddressMappingT;
+
+/// The maximum nesting depth of a decoded `Call`, so batch and proxy calls
+/// cannot exhaust the stack.
+pub const MAX_CALL_DEPTH: u32 = 8;
+
+/// The runtime call dispatch precompile.
+///
+/// `input` data is a SCALE encoded runtime `Call`, dispatched with a signed
+/// origin of the account mapped from the caller.
+///
+/// Only calls allowed by `CallFilter` are dispatched, and the call weight is
+/// charged as gas. Nothing is dispatched in a static frame.
+pub struct DispatchCallPrecompile<AccountId, AddressMapping, Call, CallFilter, WeightToGas>(
+	PhantomData<(AccountId, AddressMapping, Call, CallFilter, WeightToGas)>,
+);
+
+impl<AccountId, AddressMapping, Call, CallFilter, WeightToGas> Precompile
+	for DispatchCallPrecompile<AccountId, AddressMapping, Call, CallFilter, WeightToGas>
+where
+	AccountId: Debug + Clone,
+	AddressMapping: AddressMappingT<AccountId>,
+	Call: Dispatchable + GetDispatchInfo + DecodeLimit + Debug,
+	<Call as Dispatchable>::Origin: From<frame_system::RawOrigin<AccountId>>,
+	CallFilter: Filter<Call>,
+	WeightToGas: Convert<Weight, u64>,
+{
+	fn execute(
+		input: &[u8],
+		target_gas: Option<u64>,
+		context: &Context,
+	) -> result::Result<(ExitSucceed, Vec<u8>, u64), ExitError> {
+		debug::debug!(target: "evm", "dispatch_call input: {:?}", input);
+
+		if precompiles::is_static() {
+			return Err(ExitError::Other("static call".into()));
+		}
+
+		let call = Call::decode_all_with_depth_limit(MAX_CALL_DEPTH, input)
+			.map_err(|_| ExitError::Other("Decode call failed".into()))?;
+		debug::debug!(target: "evm", "dispatch_call call: {:?}", call);
+
+		if !CallFilter::filter(&call) {
+			return Err(ExitError::Other("Call not allowed".into()));
+		}
+
+		let cost = gas_cost::<WeightToGas>(call.get_dispatch_info().weight, target_gas)?;
+
+		let origin = AddressMapping::get_account_id(&context.caller);
+		debug::debug!(target: "evm", "dispatch_call origin: {:?}", origin);
+
+		call.dispatch(frame_system::RawOrigin::Signed(origin).into())
+			.map_err(|e| ExitError::Other(Cow::Borrowed(e.error.into())))?;
+
+		Ok((ExitSucceed::Returned, vec![], cost))
+	}
+}
@@ -0,0 +1,40 @@
+use frame_support::{debug, traits::IsType, weights::Weight};
+use module_evm::{Context, ExitError, ExitSucceed, Precompile};
+use sp_core::crypto::AccountId32;
+use sp_runtime::traits::Convert;
+use sp_std::{borrow::Cow, marker::PhantomData, prelude::*, result};
+
+use super::{gas_cost, weights::WeightInfo as WeightInfoT};
+
+/// The `EvmAccounts` impl precompile.
+///
+/// `input` data is an ABI encoded call of the Solidity functions
+/// `getAccountId(address)`, `getEvmAddress(bytes32)`,
+/// `isLinked(bytes32,address)` and `getDefaultEvmAddress(bytes32)`, answered
+/// with `module_evm_accounts::EvmAddressMapping`. The result is ABI encoded.
+///
+/// Each call is charged the gas equivalent of the `WeightInfo` query weight.
+pub struct EvmAccountsPrecompile<Runtime, WeightToGas, WeightInfo>(PhantomData<(Runtime, WeightToGas, WeightInfo)>);
+
+impl<Runtime, WeightToGas, WeightInfo> Precompile for EvmAccountsPrecompile<Runtime, WeightToGas, WeightInfo>
+where
+	Runtime: module_evm_accounts::Config,
+	Runtime::AccountId: IsType<AccountId32>,
+	WeightToGas: Convert<Weight, u64>,
+	WeightInfo: WeightInfoT,
+{
+	fn execute(
+		input: &[u8],
+		target_gas: Option<u64>,
+		_context: &Context,
+	) -> result::Result<(ExitSucceed, Vec<u8>, u64), ExitError> {
+		debug::debug!(target: "evm", "evm_accounts input: {:?}", input);
+
+		let cost = gas_cost::<WeightToGas>(WeightInfo::query(), target_gas)?;
+
+		let output = module_evm_accounts::EvmAccountsPrecompile::<Runtime>::execute(input)
+			.map_err(|e| ExitError::Other(Cow::Borrowed(e)))?;
+
+		Ok((ExitSucceed::Returned, output, cost))
+	}
+}
@@ -13,6 +13,7 @@ use sp_core::H160;
 
 pub const PER_PARAM_BYTES: usize = 32;
 pub const ACTION_INDEX: usize = 0;
+pub const SELECTOR_BYTES: usize = 4;
 
 pub const BALANCE_BYTES: usize = mem::size_of::<Balance>();
 pub const AMOUNT_BYTES: usize = mem::size_of::<Amount>();
@@ -38,24 +39,63 @@ pub trait InputT {
 	fn u32_at(&self, index: usize) -> Result<u32, Self::Error>;
 
 	fn bytes_at(&self, start: usize, len: usize) -> Result<Vec<u8>, Self::Error>;
+	/// Read the dynamic `bytes` param at `index`. It is the length followed
+	/// by the bytes, or the offset of the length in ABI encoded input.
+	fn dynamic_bytes_at(&self, index: usize) -> Result<Vec<u8>, Self::Error>;
 }
 
+/// Return true if `input` starts with a 4-byte Solidity function selector
+/// instead of a 32-byte action, which has its first bytes zeroed.
+pub fn has_selector(input: &[u8]) -> bool {
+	input.len() >= SELECTOR_BYTES && input[..SELECTOR_BYTES] != [0u8; SELECTOR_BYTES]
+}
+
+/// The precompile input. Either an `action` followed by 32-byte params, or a
+/// function selector followed by ABI encoded arguments. Params are indexed
+/// from one in both layouts.
 pub struct Input<'a, Action, AccountId, AddressMapping> {
 	content: &'a [u8],
+	selector: Option<[u8; SELECTOR_BYTES]>,
 	_marker: PhantomData<(Action, AccountId, AddressMapping)>,
 }
 impl<'a, Action, AccountId, AddressMapping> Input<'a, Action, AccountId, AddressMapping> {
 	pub fn new(content: &'a [u8]) -> Self {
 		Self {
 			content,
+			selector: None,
 			_marker: PhantomData,
 		}
 	}
+
+	/// Create from `content` starting with a function selector.
+	pub fn new_with_selector(content: &'a [u8]) -> Self {
+		let mut selector = [0u8; SELECTOR_BYTES];
+		selector.copy_from_slice(&content[..SELECTOR_BYTES]);
+		Self {
+			content: &content[SELECTOR_BYTES..],
+			selector: Some(selector),
+			_marker: PhantomData,
+		}
+	}
+
+	/// Create from `content` in either layout.
+	pub fn parse(content: &'a [u8]) -> Self {
+		if has_selector(content) {
+			Self::new_with_selector(content)
+		} else {
+			Self::new(content)
+		}
+	}
+
+	/// Return true if the input is ABI encoded.
+	pub fn has_selector(&self) -> bool {
+		self.selector.is_some()
+	}
 }
 
 impl<Action, AccountId, AddressMapping> InputT for Input<'_, Action, AccountId, AddressMapping>
 where
-	Action: TryFrom<u8>,
+	Action: TryFrom<u8> + TryFrom<[u8; SELECTOR_BYTES]>,
 	AddressMapping: AddressMappingT<AccountId>,
 {
 	type Error = ExitError;
@@ -63,6 +103,13 @@ where
 	type AccountId = AccountId;
 
 	fn nth_param(&self, n: usize) -> Result<&[u8], Self::Error> {
+		// ABI encoded arguments have no action word
+		let n = if self.has_selector() {
+			n.checked_sub(1)
+				.ok_or_else(|| ExitError::Other("invalid input".into()))?
+		} else {
+			n
+		};
 		let start = PER_PARAM_BYTES * n;
 		let end = start + PER_PARAM_BYTES;
 
@@ -72,6 +119,12 @@ where
 	}
 
 	fn action(&self) -> Result<Self::Action, Self::Error> {
+		if let Some(selector) = self.selector {
+			return selector
+				.try_into()
+				.map_err(|_| ExitError::Other("invalid action".into()));
+		}
+
 		let param = self.nth_param(ACTION_INDEX)?;
 		let action_u8: &u8 = param.last().expect("Action bytes is 32 bytes");
 
@@ -162,6 +215,23 @@ where
 
 		Ok(bytes.to_vec())
 	}
+
+	fn dynamic_bytes_at(&self, index: usize) -> Result<Vec<u8>, Self::Error> {
+		if self.has_selector() {
+			let offset = self.u32_at(index)? as usize;
+			ensure!(
+				offset % PER_PARAM_BYTES == 0,
+				ExitError::Other("invalid bytes input".into())
+			);
+			// the offset is from the start of the arguments
+			let len_index = offset / PER_PARAM_BYTES + 1;
+			let len = self.u32_at(len_index)?;
+			self.bytes_at(offset + PER_PARAM_BYTES, len as usize)
+		} else {
+			let len = self.u32_at(index)?;
+			self.bytes_at((index + 1) * PER_PARAM_BYTES, len as usize)
+		}
+	}
 }
 
 #[cfg(test)]
@@ -188,6 +258,17 @@ mod tests {
 			}
 		}
 	}
+	impl TryFrom<[u8; SELECTOR_BYTES]> for Action {
+		type Error = ();
+
+		fn try_from(selector: [u8; SELECTOR_BYTES]) -> Result<Self, Self::Error> {
+			match selector {
+				// balanceOf(address)
+				[0x70, 0xa0, 0x82, 0x31] => Ok(Action::QueryBalance),
+				_ => Err(()),
+			}
+		}
+	}
 
 	pub type TestInput<'a> = Input<'a, Action, AccountId, MockAddressMapping>;
 
@@ -214,6 +295,51 @@ mod tests {
 		assert_ok!(input.action(), Action::Unknown);
 	}
 
+	#[test]
+	fn selector_works() {
+		let mut raw_input = [0u8; 36];
+		raw_input[..4].copy_from_slice(&[0x70, 0xa0, 0x82, 0x31]);
+		raw_input[35] = 1;
+		let input = TestInput::parse(&raw_input[..]);
+		assert!(input.has_selector());
+		assert_ok!(input.action(), Action::QueryBalance);
+		assert_err!(input.nth_param(0), ExitError::Other("invalid input".into()));
+		assert_ok!(input.evm_address_at(1), H160::from_low_u64_be(1));
+
+		let input = TestInput::parse(&[1u8; 36][..]);
+		assert_err!(input.action(), ExitError::Other("invalid action".into()));
+
+		let input = TestInput::parse(&[0u8; 32][..]);
+		assert!(!input.has_selector());
+		assert_ok!(input.action(), Action::QueryBalance);
+	}
+
+	#[test]
+	fn dynamic_bytes_works() {
+		// action, len, bytes
+		let mut raw_input = [0u8; 3 * 32];
+		raw_input[63] = 2;
+		raw_input[64..66].copy_from_slice(&[0x12, 0x34]);
+		let input = TestInput::new(&raw_input[..]);
+		assert_ok!(input.dynamic_bytes_at(1), vec![0x12, 0x34]);
+
+		// selector, offset, len, bytes
+		let mut raw_input = [0u8; 4 + 3 * 32];
+		raw_input[..4].copy_from_slice(&[0x70, 0xa0, 0x82, 0x31]);
+		raw_input[35] = 32;
+		raw_input[67] = 2;
+		raw_input[68..70].copy_from_slice(&[0x12, 0x34]);
+		let input = TestInput::new_with_selector(&raw_input[..]);
+		assert_ok!(input.dynamic_bytes_at(1), vec![0x12, 0x34]);
+
+		raw_input[67] = 65;
+		let input = TestInput::new_with_selector(&raw_input[..]);
+		assert_err!(
+			input.dynamic_bytes_at(1),
+			ExitError::Other("invalid bytes input".into())
+		);
+	}
+
 	#[test]
 	fn account_id_works() {
 		let mut address = [0u8; 20];
@@ -9,15 +9,16 @@ use frame_support::{
 	RuntimeDebug,
 };
 use frame_system::{EnsureRoot, EnsureSignedBy};
+use module_evm::Context;
 use orml_traits::{parameter_type_with_key};
 pub use primitives::{
-	evm::AddressMapping, mocks::MockAddressMapping,
+	evm::AddressMapping, mocks::{InputBuilder, MockAddressMapping},
 	Amount, BlockNumber, CurrencyId, Header, Nonce, TokenSymbol,
 };
 use sp_core::{crypto::AccountId32, bytes::from_hex, Bytes, H160, H256};
 use sp_runtime::{
 	traits::{BlakeTwo256, Convert, IdentityLookup},
-	Perbill,
+	DispatchResult, ModuleId, Perbill,
 };
 use sp_std::{collections::btree_map::BTreeMap, str::FromStr};
 
@@ -112,6 +113,7 @@ impl module_evm_bridge::Config for Test {
 
 parameter_types! {
 	pub const TransactionByteFee: Balance = 10;
+	pub const MinimumBaseFee: Balance = 1;
 	pub const GetStableCurrencyId: CurrencyId = CurrencyId::Token(TokenSymbol::SEUR);
 	pub AllNonNativeCurrencyIds: Vec<CurrencyId> = vec![CurrencyId::Token(TokenSymbol::SEUR)];
 }
@@ -126,6 +128,8 @@ impl module_transaction_payment::Config for Test {
 	type TransactionByteFee = TransactionByteFee;
 	type WeightToFee = IdentityFee<Balance>;
 	type FeeMultiplierUpdate = ();
+	type MinimumBaseFee = MinimumBaseFee;
+	type FeeSponsor = ();
 	type WeightInfo = ();
 }
 pub type ChargeTransactionPayment = module_transaction_payment::ChargeTransactionPayment<Test>;
@@ -200,11 +204,84 @@ impl pallet_scheduler::Config for Test {
 	type WeightInfo = ();
 }
 
+parameter_types! {
+	pub const EraDuration: BlockNumber = 100;
+	pub const NominatorAPY: Perbill = Perbill::from_percent(10);
+	pub const CouncilInflation: Perbill = Perbill::from_percent(1);
+	pub const CandidacyDeposit: Balance = 1_000;
+	pub const MinLockAmount: Balance = 100;
+	pub const TotalLockedCap: Balance = 10_000_000;
+	pub const MaxCandidates: u32 = 10;
+	pub const MaxMembers: u32 = 3;
+}
+
+impl module_poc::Config for Test {
+	type Event = Event;
+	type Currency = Balances;
+	type EraDuration = EraDuration;
+	type NominatorAPY = NominatorAPY;
+	type CouncilInflation = CouncilInflation;
+	type CandidacyDeposit = CandidacyDeposit;
+	type MinLockAmount = MinLockAmount;
+	type TotalLockedCap = TotalLockedCap;
+	type MaxCandidates = MaxCandidates;
+	type MaxMembers = MaxMembers;
+	type MembershipChanged = ();
+	type WeightInfo = ();
+}
+
+pub struct EvmAccountsOnClaimHandler;
+impl module_evm_accounts::Handler<AccountId> for EvmAccountsOnClaimHandler {
+	fn handle(_who: &AccountId) -> DispatchResult {
+		Ok(())
+	}
+}
+
+impl module_evm_accounts::Config for Test {
+	type Event = Event;
+	type Currency = Balances;
+	type AddressMapping = MockAddressMapping;
+	type MergeAccount = Currencies;
+	type OnClaim = EvmAccountsOnClaimHandler;
+	type WeightInfo = ();
+}
+
 pub type AdaptedBasicCurrency = module_currencies::BasicCurrencyAdapter<Test, Balances, Amount, BlockNumber>;
 
-pub type MultiCurrencyPrecompile = crate::MultiCurrencyPrecompile<AccountId, MockAddressMapping, Currencies>;
+pub const QUERY_WEIGHT: Weight = 200;
+pub const QUERY_CANDIDATE_WEIGHT: Weight = 50;
+pub const TRANSFER_WEIGHT: Weight = 1_000;
+pub const TRANSFER_MAINTAINER_WEIGHT: Weight = 1_500;
+pub const SCHEDULE_CALL_WEIGHT: Weight = 2_000;
+pub const CANCEL_CALL_WEIGHT: Weight = 1_200;
+
+pub struct PrecompileWeights;
+impl crate::precompile::WeightInfo for PrecompileWeights {
+	fn query() -> Weight {
+		QUERY_WEIGHT
+	}
+	fn query_candidates(c: u32) -> Weight {
+		QUERY_WEIGHT + QUERY_CANDIDATE_WEIGHT * c as Weight
+	}
+	fn transfer() -> Weight {
+		TRANSFER_WEIGHT
+	}
+	fn transfer_maintainer() -> Weight {
+		TRANSFER_MAINTAINER_WEIGHT
+	}
+	fn schedule_call() -> Weight {
+		SCHEDULE_CALL_WEIGHT
+	}
+	fn cancel_call() -> Weight {
+		CANCEL_CALL_WEIGHT
+	}
+}
+
+pub type MultiCurrencyPrecompile =
+	crate::MultiCurrencyPrecompile<AccountId, MockAddressMapping, Currencies, WeightToGas, PrecompileWeights>;
 
-pub type StateRentPrecompile = crate::StateRentPrecompile<AccountId, MockAddressMapping, ModuleEVM>;
+pub type StateRentPrecompile =
+	crate::StateRentPrecompile<AccountId, MockAddressMapping, ModuleEVM, WeightToGas, PrecompileWeights>;
 pub type ScheduleCallPrecompile = crate::ScheduleCallPrecompile<
 	AccountId,
 	MockAddressMapping,
@@ -214,8 +291,21 @@ pub type ScheduleCallPrecompile = crate::ScheduleCallPrecompile<
 	Origin,
 	OriginCaller,
 	Test,
+	PrecompileWeights,
 >;
 
+pub type DispatchCallPrecompile = crate::DispatchCallPrecompile<
+	AccountId,
+	MockAddressMapping,
+	Call,
+	module_evm::DispatchCallFilter<Test>,
+	WeightToGas,
+>;
+
+pub type PocPrecompile = crate::PocPrecompile<AccountId, MockAddressMapping, WeightToGas, PrecompileWeights, Test>;
+
+pub type EvmAccountsPrecompile = crate::EvmAccountsPrecompile<Test, WeightToGas, PrecompileWeights>;
+
 parameter_types! {
 	pub NetworkContractSource: H160 = trillian();
 }
@@ -225,12 +315,21 @@ ord_parameter_types! {
 	pub const NetworkContractAccount: AccountId32 = AccountId32::from([0u8; 32]);
 	pub const NewContractExtraBytes: u32 = 100;
 	pub const StorageDepositPerByte: u64 = 10;
+	pub const StorageCleanupWeight: Weight = 10;
 	pub const DeveloperDeposit: u64 = 1000;
 	pub const DeploymentFee: u64 = 200;
 	pub const MaxCodeSize: u32 = 60 * 1024;
+	pub const EthereumTransactionStorageLimit: u32 = 60 * 1024;
+	pub const AllowUnprotectedTransactions: bool = false;
 	pub const ChainId: u64 = 1;
 }
 
+parameter_types! {
+	pub const SponsorModuleId: ModuleId = ModuleId(*b"snp/evsp");
+	pub const MaxBatchCalls: u32 = 4;
+	pub const RecurringCallRetention: BlockNumber = 10;
+}
+
 pub struct GasToWeight;
 impl Convert<u64, Weight> for GasToWeight {
 	fn convert(a: u64) -> u64 {
@@ -238,12 +337,20 @@ impl Convert<u64, Weight> for GasToWeight {
 	}
 }
 
+pub struct WeightToGas;
+impl Convert<Weight, u64> for WeightToGas {
+	fn convert(a: Weight) -> u64 {
+		a as u64
+	}
+}
+
 impl module_evm::Config for Test {
 	type AddressMapping = MockAddressMapping;
 	type Currency = Balances;
 	type MergeAccount = Currencies;
 	type NewContractExtraBytes = NewContractExtraBytes;
 	type StorageDepositPerByte = StorageDepositPerByte;
+	type StorageCleanupWeight = StorageCleanupWeight;
 	type MaxCodeSize = MaxCodeSize;
 	type Event = Event;
 	type Precompiles = AllPrecompiles<
@@ -251,15 +358,30 @@ impl module_evm::Config for Test {
 		MultiCurrencyPrecompile,
 		StateRentPrecompile,
 		ScheduleCallPrecompile,
+		DispatchCallPrecompile,
+		PocPrecompile,
+		EvmAccountsPrecompile,
 	>;
 	type ChainId = ChainId;
 	type GasToWeight = GasToWeight;
+	type WeightToGas = WeightToGas;
+	type FindAuthor = ();
+	type Randomness = ();
+	type NetGasMetering = ();
 	type ChargeTransactionPayment = ChargeTransactionPayment;
 	type NetworkContractOrigin = EnsureSignedBy<NetworkContractAccount, AccountId>;
 	type NetworkContractSource = NetworkContractSource;
 	type DeveloperDeposit = DeveloperDeposit;
 	type DeploymentFee = DeploymentFee;
 	type FreeDeploymentOrigin = EnsureSignedBy<CouncilAccount, AccountId>;
+	type PauseOrigin = EnsureSignedBy<CouncilAccount, AccountId>;
+	type CodeUpgradeOrigin = EnsureSignedBy<CouncilAccount, AccountId>;
+	type DispatchCallOrigin = EnsureSignedBy<CouncilAccount, AccountId>;
+	type EthereumTransactionStorageLimit = EthereumTransactionStorageLimit;
+	type AllowUnprotectedTransactions = AllowUnprotectedTransactions;
+	type SponsorModuleId = SponsorModuleId;
+	type MaxBatchCalls = MaxBatchCalls;
+	type RecurringCallRetention = RecurringCallRetention;
 	type WeightInfo = ();
 }
 
@@ -273,6 +395,15 @@ pub fn ford() -> H160 {
 	H160([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2])
 }
 
+/// The context of a call from `trillian`.
+pub fn context() -> Context {
+	Context {
+		address: Default::default(),
+		caller: trillian(),
+		apparent_value: Default::default(),
+	}
+}
+
 pub fn evm_genesis() -> BTreeMap<H160, module_evm::GenesisAccount<Balance, u64>> {
 	let contracts_json = &include_bytes!("../../../../assets/bytecodes.json")[..];
 	let contracts: Vec<(String, String, String)> = serde_json::from_slice(contracts_json).unwrap();
@@ -317,6 +448,8 @@ frame_support::construct_runtime!(
 		Utility: pallet_utility::{Module, Call, Event},
 		Scheduler: pallet_scheduler::{Module, Call, Storage, Event<T>},
 		ModuleEVM: module_evm::{Module, Config<T>, Call, Storage, Event<T>},
+		Poc: module_poc::{Module, Call, Storage, Event<T>},
+		EvmAccounts: module_evm_accounts::{Module, Call, Storage, Event<T>},
 	}
 );
 
@@ -353,6 +486,7 @@ pub fn new_test_ext() -> sp_io::TestExternalities {
 		.unwrap();
 	module_evm::GenesisConfig::<Test> {
 		accounts,
+		dispatchable_calls: vec![(b"Balances".to_vec(), b"transfer".to_vec())],
 	}
 	.assimilate_storage(&mut storage)
 	.unwrap();
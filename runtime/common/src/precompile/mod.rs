@@ -1,5 +1,9 @@
 //! The precompiles for EVM, includes standard Ethereum precompiles, and more:
 //! - MultiCurrency at address `H160::from_low_u64_be(1024)`.
+//! - DispatchCall at address `H160::from_low_u64_be(1030)` and Poc at address
+//!   `H160::from_low_u64_be(1032)`, callable by any contract.
+//! - EvmAccounts at address `H160::from_low_u64_be(1034)`, read only and
+//!   callable by any contract.
 
 #![allow(clippy::upper_case_acronyms)]
 
@@ -7,42 +11,63 @@ mod mock;
 mod tests;
 
 use crate::is_core_precompile;
-use frame_support::debug;
+use frame_support::{debug, weights::Weight};
 use module_evm::{
-	precompiles::{
-		Precompile, Precompiles, EvmPrecompiles,
-		Identity,
-		Ripemd160, Sha256,
-		Sha3FIPS256, Sha3FIPS512,
-		ECRecover, ECRecoverPublicKey,
-	},
+	precompiles::{EvmPrecompiles, NonStandardPrecompiles, Precompile, Precompiles, StandardPrecompiles},
 	Context, ExitError, ExitSucceed,
 };
 use module_support::PrecompileCallerFilter as PrecompileCallerFilterT;
 use primitives::PRECOMPILE_ADDRESS_START;
 use sp_core::H160;
+use sp_runtime::traits::Convert;
 use sp_std::{marker::PhantomData, prelude::*};
 
+pub mod dispatch_call;
+pub mod evm_accounts;
 pub mod input;
 pub mod multicurrency;
+pub mod poc;
 pub mod schedule_call;
 pub mod state_rent;
+pub mod weights;
 
+pub use dispatch_call::DispatchCallPrecompile;
+pub use evm_accounts::EvmAccountsPrecompile;
 pub use multicurrency::MultiCurrencyPrecompile;
+pub use poc::PocPrecompile;
 pub use schedule_call::ScheduleCallPrecompile;
 pub use state_rent::StateRentPrecompile;
+pub use weights::WeightInfo;
+
+/// Convert the `weight` of a precompile action to gas, failing with
+/// `OutOfGas` if it exceeds `target_gas`.
+pub fn gas_cost<WeightToGas: Convert<Weight, u64>>(weight: Weight, target_gas: Option<u64>) -> Result<u64, ExitError> {
+	let cost = WeightToGas::convert(weight);
+	if let Some(target_gas) = target_gas {
+		if cost > target_gas {
+			return Err(ExitError::OutOfGas);
+		}
+	}
+	Ok(cost)
+}
 
 pub struct AllPrecompiles<
 	PrecompileCallerFilter,
 	MultiCurrencyPrecompile,
 	StateRentPrecompile,
 	ScheduleCallPrecompile,
+	DispatchCallPrecompile,
+	PocPrecompile,
+	EvmAccountsPrecompile,
 >(
 	PhantomData<(
 		PrecompileCallerFilter,
 		MultiCurrencyPrecompile,
 		StateRentPrecompile,
 		ScheduleCallPrecompile,
+		DispatchCallPrecompile,
+		PocPrecompile,
+		EvmAccountsPrecompile,
 	)>,
 );
 
@@ -51,16 +76,25 @@ impl<
 		MultiCurrencyPrecompile,
 		StateRentPrecompile,
 		ScheduleCallPrecompile,
+		DispatchCallPrecompile,
+		PocPrecompile,
+		EvmAccountsPrecompile,
 	> Precompiles
 	for AllPrecompiles<
 		PrecompileCallerFilter,
 		MultiCurrencyPrecompile,
 		StateRentPrecompile,
 		ScheduleCallPrecompile,
+		DispatchCallPrecompile,
+		PocPrecompile,
+		EvmAccountsPrecompile,
 	> where
 	MultiCurrencyPrecompile: Precompile,
 	StateRentPrecompile: Precompile,
 	ScheduleCallPrecompile: Precompile,
+	DispatchCallPrecompile: Precompile,
+	PocPrecompile: Precompile,
+	EvmAccountsPrecompile: Precompile,
 	PrecompileCallerFilter: PrecompileCallerFilterT,
 {
 	#[allow(clippy::type_complexity)]
@@ -70,11 +104,22 @@ impl<
 		target_gas: Option<u64>,
 		context: &Context,
 	) -> Option<core::result::Result<(ExitSucceed, Vec<u8>, u64), ExitError>> {
-		EvmPrecompiles::<ECRecover, Sha256, Ripemd160, Identity, ECRecoverPublicKey, Sha3FIPS256, Sha3FIPS512>::execute(
-			address, input, target_gas, context,
-		)
+		EvmPrecompiles::<StandardPrecompiles, NonStandardPrecompiles>::execute(address, input, target_gas, context)
 		.or_else(|| {
-			if is_core_precompile(address) && !PrecompileCallerFilter::is_allowed(context.caller) {
+			if address == H160::from_low_u64_be(PRECOMPILE_ADDRESS_START + 6)
+				|| address == H160::from_low_u64_be(PRECOMPILE_ADDRESS_START + 8)
+			{
+				// These dispatch with the caller's own origin, so any contract may call them,
+				// but not through `DELEGATECALL` or `CALLCODE` on behalf of another.
+				if context.address != address {
+					debug::debug!(target: "evm", "Precompile no permission");
+					return Some(Err(ExitError::Other("no permission".into())));
+				}
+			} else if is_core_precompile(address)
+				// the EvmAccounts precompile only answers queries
+				&& address != H160::from_low_u64_be(PRECOMPILE_ADDRESS_START + 10)
+				&& !PrecompileCallerFilter::is_allowed(context.caller)
+			{
 				debug::debug!(target: "evm", "Precompile no permission");
 				return Some(Err(ExitError::Other("no permission".into())));
 			}
@@ -85,9 +130,22 @@ impl<
 				Some(StateRentPrecompile::execute(input, target_gas, context))
 			} else if address == H160::from_low_u64_be(PRECOMPILE_ADDRESS_START + 4) {
 				Some(ScheduleCallPrecompile::execute(input, target_gas, context))
+			} else if address == H160::from_low_u64_be(PRECOMPILE_ADDRESS_START + 6) {
+				Some(DispatchCallPrecompile::execute(input, target_gas, context))
+			} else if address == H160::from_low_u64_be(PRECOMPILE_ADDRESS_START + 8) {
+				Some(PocPrecompile::execute(input, target_gas, context))
+			} else if address == H160::from_low_u64_be(PRECOMPILE_ADDRESS_START + 10) {
+				Some(EvmAccountsPrecompile::execute(input, target_gas, context))
 			} else {
 				None
 			}
 		})
 	}
+
+	fn is_precompile(address: H160) -> bool {
+		EvmPrecompiles::<StandardPrecompiles, NonStandardPrecompiles>::is_precompile(address)
+			|| [0, 2, 4, 6, 8, 10]
+				.iter()
+				.any(|offset| address == H160::from_low_u64_be(PRECOMPILE_ADDRESS_START + offset))
+	}
 }
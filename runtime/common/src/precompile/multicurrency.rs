@@ -1,13 +1,24 @@
-use frame_support::debug;
-use module_evm::{Context, ExitError, ExitSucceed, Precompile};
+use frame_support::{debug, ensure, weights::Weight};
+use module_evm::{precompiles, Context, ExitError, ExitSucceed, Log, Precompile};
 use primitives::evm::AddressMapping as AddressMappingT;
-use sp_core::U256;
+use sp_core::{H160, H256, U256};
+use sp_runtime::traits::Convert;
 use sp_std::{convert::TryFrom, fmt::Debug, marker::PhantomData, prelude::*, result};
 
 use orml_traits::MultiCurrency as MultiCurrencyT;
 
-use super::input::{Input, InputT};
-use primitives::{Balance, CurrencyId};
+use super::{
+	gas_cost,
+	input::{Input, InputT, SELECTOR_BYTES},
+	weights::WeightInfo as WeightInfoT,
+};
+use primitives::{Balance, CurrencyId, TokenSymbol, MIRRORED_TOKENS_ADDRESS_START};
+
+/// keccak256("Transfer(address,address,uint256)")
+pub const TRANSFER_EVENT_TOPIC: [u8; 32] = [
+	0xdd, 0xf2, 0x52, 0xad, 0x1b, 0xe2, 0xc8, 0x9b, 0x69, 0xc2, 0xb0, 0x68, 0xfc, 0x37, 0x8d, 0xaa, 0x95, 0x2b, 0xa7,
+	0xf1, 0x63, 0xc4, 0xa1, 0x16, 0x28, 0xf5, 0x5a, 0x4d, 0xf5, 0x23, 0xb3, 0xef,
+];
 
 /// The `MultiCurrency` impl precompile.
 ///
@@ -18,8 +29,15 @@ use primitives::{Balance, CurrencyId};
 /// - Query total issuance.
 /// - Query balance. Rest `input` bytes: `account_id`.
 /// - Transfer. Rest `input` bytes: `from`, `to`, `amount`.
-pub struct MultiCurrencyPrecompile<AccountId, AddressMapping, MultiCurrency>(
-	PhantomData<(AccountId, AddressMapping, MultiCurrency)>,
+///
+/// The actions can also be called by a mirrored token contract with the
+/// Solidity functions `totalSupply()`, `balanceOf(address)` and
+/// `transfer(address,address,uint256)`, on the currency of the token. An ABI
+/// encoded transfer emits an ERC20 `Transfer` log from the token.
+///
+/// Each action is charged the gas equivalent of its `WeightInfo` weight.
+pub struct MultiCurrencyPrecompile<AccountId, AddressMapping, MultiCurrency, WeightToGas, WeightInfo>(
+	PhantomData<(AccountId, AddressMapping, MultiCurrency, WeightToGas, WeightInfo)>,
 );
 
 enum Action {
@@ -41,49 +59,76 @@ impl TryFrom<u8> for Action {
 	}
 }
 
-impl<AccountId, AddressMapping, MultiCurrency> Precompile
-	for MultiCurrencyPrecompile<AccountId, AddressMapping, MultiCurrency>
+impl TryFrom<[u8; SELECTOR_BYTES]> for Action {
+	type Error = ();
+
+	fn try_from(selector: [u8; SELECTOR_BYTES]) -> Result<Self, Self::Error> {
+		match selector {
+			// totalSupply()
+			[0x18, 0x16, 0x0d, 0xdd] => Ok(Action::QueryTotalIssuance),
+			// balanceOf(address)
+			[0x70, 0xa0, 0x82, 0x31] => Ok(Action::QueryBalance),
+			// transfer(address,address,uint256)
+			[0xbe, 0xab, 0xac, 0xc8] => Ok(Action::Transfer),
+			_ => Err(()),
+		}
+	}
+}
+
+impl<AccountId, AddressMapping, MultiCurrency, WeightToGas, WeightInfo> Precompile
+	for MultiCurrencyPrecompile<AccountId, AddressMapping, MultiCurrency, WeightToGas, WeightInfo>
 where
 	AccountId: Debug + Clone,
 	AddressMapping: AddressMappingT<AccountId>,
 	MultiCurrency: MultiCurrencyT<AccountId, Balance = Balance, CurrencyId = CurrencyId>,
+	WeightToGas: Convert<Weight, u64>,
+	WeightInfo: WeightInfoT,
 {
 	fn execute(
 		input: &[u8],
-		_target_gas: Option<u64>,
-		_context: &Context,
+		target_gas: Option<u64>,
+		context: &Context,
 	) -> result::Result<(ExitSucceed, Vec<u8>, u64), ExitError> {
-		//TODO: evaluate cost
-
 		debug::debug!(target: "evm", "input: {:?}", input);
 
-		let input = Input::<Action, AccountId, AddressMapping>::new(input);
+		let input = Input::<Action, AccountId, AddressMapping>::parse(input);
 
 		let action = input.action()?;
-		let currency_id = input.currency_id_at(1)?;
+		// ABI encoded input has no `currency_id`, it is the one of the caller
+		let (currency_id, first_param) = if input.has_selector() {
+			(mirrored_currency_id(context.caller)?, 1)
+		} else {
+			(input.currency_id_at(1)?, 2)
+		};
 
 		debug::debug!(target: "evm", "currency id: {:?}", currency_id);
 
+		let weight = match action {
+			Action::Transfer => WeightInfo::transfer(),
+			_ => WeightInfo::query(),
+		};
+		let cost = gas_cost::<WeightToGas>(weight, target_gas)?;
+
 		match action {
 			Action::QueryTotalIssuance => {
 				let total_issuance = vec_u8_from_balance(MultiCurrency::total_issuance(currency_id));
 				debug::debug!(target: "evm", "total issuance: {:?}", total_issuance);
 
-				Ok((ExitSucceed::Returned, total_issuance, 0))
+				Ok((ExitSucceed::Returned, total_issuance, cost))
 			}
 			Action::QueryBalance => {
-				let who = input.account_id_at(2)?;
+				let who = input.account_id_at(first_param)?;
 				debug::debug!(target: "evm", "who: {:?}", who);
 
 				let balance = vec_u8_from_balance(MultiCurrency::total_balance(currency_id, &who));
 				debug::debug!(target: "evm", "balance: {:?}", balance);
 
-				Ok((ExitSucceed::Returned, balance, 0))
+				Ok((ExitSucceed::Returned, balance, cost))
 			}
 			Action::Transfer => {
-				let from = input.account_id_at(2)?;
-				let to = input.account_id_at(3)?;
-				let amount = input.balance_at(4)?;
+				let from = input.account_id_at(first_param)?;
+				let to = input.account_id_at(first_param + 1)?;
+				let amount = input.balance_at(first_param + 2)?;
 
 				debug::debug!(target: "evm", "from: {:?}", from);
 				debug::debug!(target: "evm", "to: {:?}", to);
@@ -96,12 +141,44 @@ where
 
 				debug::debug!(target: "evm", "transfer success!");
 
-				Ok((ExitSucceed::Returned, vec![], 0))
+				if input.has_selector() {
+					let from = input.evm_address_at(first_param)?;
+					let to = input.evm_address_at(first_param + 1)?;
+					precompiles::log(Log {
+						address: context.caller,
+						topics: vec![TRANSFER_EVENT_TOPIC.into(), address_topic(from), address_topic(to)],
+						data: vec_u8_from_balance(amount),
+					});
+				}
+
+				Ok((ExitSucceed::Returned, vec![], cost))
 			}
 		}
 	}
 }
 
+/// The currency of the mirrored token contract at `address`, which is
+/// `MIRRORED_TOKENS_ADDRESS_START` plus the token symbol.
+fn mirrored_currency_id(address: H160) -> result::Result<CurrencyId, ExitError> {
+	let index = address.to_low_u64_be();
+	ensure!(
+		address == H160::from_low_u64_be(index) && index >= MIRRORED_TOKENS_ADDRESS_START,
+		ExitError::Other("invalid currency id".into())
+	);
+
+	u8::try_from(index - MIRRORED_TOKENS_ADDRESS_START)
+		.ok()
+		.and_then(|symbol| TokenSymbol::try_from(symbol).ok())
+		.map(CurrencyId::Token)
+		.ok_or_else(|| ExitError::Other("invalid currency id".into()))
+}
+
+fn address_topic(address: H160) -> H256 {
+	let mut topic = H256::default();
+	topic.as_bytes_mut()[12..].copy_from_slice(address.as_bytes());
+	topic
+}
+
 fn vec_u8_from_balance(balance: Balance) -> Vec<u8> {
 	let mut be_bytes = [0u8; 32];
 	U256::from(balance).to_big_endian(&mut be_bytes[..]);
@@ -0,0 +1,315 @@
+use frame_support::{debug, dispatch::DispatchResultWithPostInfo, weights::Weight};
+use module_evm::{precompiles, Context, ExitError, ExitSucceed, Precompile};
+use module_poc::{BalanceOf, LockDuration, LockState, WeightInfo as PocWeightInfoT};
+use primitives::{evm::AddressMapping as AddressMappingT, Balance};
+use sp_core::{H160, U256};
+use sp_runtime::{
+	traits::{Convert, Zero},
+	SaturatedConversion,
+};
+use sp_std::{borrow::Cow, convert::TryFrom, marker::PhantomData, prelude::*, result};
+
+use super::{
+	gas_cost,
+	input::{Input, InputT, SELECTOR_BYTES},
+	weights::WeightInfo as WeightInfoT,
+};
+
+/// The `Poc` impl precompile.
+///
+/// `input` data starts with `action`. The actions are dispatched with a
+/// signed origin of the account mapped from the caller.
+///
+/// Actions:
+/// - QueryCommitment. Rest `input` bytes: `account`. Returns the state (0
+///   committed, 1 unbonding), the block the unbonding started, the duration (0
+///   one month, 1 one year, 2 ten years), the amount and the candidate. All
+///   zero if `account` has no commitment.
+/// - QueryVotingWeight. Rest `input` bytes: `account`.
+/// - QueryCurrentEra. Returns the era index and its start block.
+/// - QueryCandidates. Returns the candidates count and addresses.
+/// - QueryMembers. Returns the members count and addresses.
+/// - Commit. Rest `input` bytes: `amount`, `duration`, `candidate`.
+/// - AddFunds. Rest `input` bytes: `amount`.
+/// - Unbond.
+/// - Withdraw.
+/// - VoteCandidate. Rest `input` bytes: `candidate`.
+///
+/// The actions can also be called with the Solidity functions
+/// `commitmentOf(address)`, `votingWeight(address)`, `currentEra()`,
+/// `candidates()`, `members()`, `commit(uint256,uint8,address)`,
+/// `addFunds(uint256)`, `unbond()`, `withdraw()` and `voteCandidate(address)`.
+///
+/// Accounts are returned as their linked EVM address, or the default one if
+/// not linked. Only the queries can be called in a static frame.
+///
+/// The queries are charged the gas equivalent of the `WeightInfo` query
+/// weight, QueryCandidates of the weight for the number of candidates, and
+/// the other actions of their `module_poc::WeightInfo` weight.
+pub struct PocPrecompile<AccountId, AddressMapping, WeightToGas, WeightInfo, Runtime>(
+	PhantomData<(AccountId, AddressMapping, WeightToGas, WeightInfo, Runtime)>,
+);
+
+enum Action {
+	QueryCommitment,
+	QueryVotingWeight,
+	QueryCurrentEra,
+	QueryCandidates,
+	QueryMembers,
+	Commit,
+	AddFunds,
+	Unbond,
+	Withdraw,
+	VoteCandidate,
+}
+
+impl TryFrom<u8> for Action {
+	type Error = ();
+
+	fn try_from(value: u8) -> Result<Self, Self::Error> {
+		// reserve 0 - 127 for query, 128 - 255 for action
+		match value {
+			0 => Ok(Action::QueryCommitment),
+			1 => Ok(Action::QueryVotingWeight),
+			2 => Ok(Action::QueryCurrentEra),
+			3 => Ok(Action::QueryCandidates),
+			4 => Ok(Action::QueryMembers),
+			128 => Ok(Action::Commit),
+			129 => Ok(Action::AddFunds),
+			130 => Ok(Action::Unbond),
+			131 => Ok(Action::Withdraw),
+			132 => Ok(Action::VoteCandidate),
+			_ => Err(()),
+		}
+	}
+}
+
+impl TryFrom<[u8; SELECTOR_BYTES]> for Action {
+	type Error = ();
+
+	fn try_from(selector: [u8; SELECTOR_BYTES]) -> Result<Self, Self::Error> {
+		match selector {
+			// commitmentOf(address)
+			[0xf6, 0x62, 0xb8, 0x81] => Ok(Action::QueryCommitment),
+			// votingWeight(address)
+			[0xc4, 0x7b, 0x35, 0x11] => Ok(Action::QueryVotingWeight),
+			// currentEra()
+			[0x97, 0x36, 0x28, 0xf6] => Ok(Action::QueryCurrentEra),
+			// candidates()
+			[0x6f, 0x04, 0x70, 0xaa] => Ok(Action::QueryCandidates),
+			// members()
+			[0xbd, 0xd4, 0xd1, 0x8d] => Ok(Action::QueryMembers),
+			// commit(uint256,uint8,address)
+			[0x01, 0x94, 0x59, 0x55] => Ok(Action::Commit),
+			// addFunds(uint256)
+			[0xbe, 0x99, 0x97, 0x05] => Ok(Action::AddFunds),
+			// unbond()
+			[0x5d, 0xf6, 0xa6, 0xbc] => Ok(Action::Unbond),
+			// withdraw()
+			[0x3c, 0xcf, 0xd6, 0x0b] => Ok(Action::Withdraw),
+			// voteCandidate(address)
+			[0x26, 0xbb, 0x88, 0x6d] => Ok(Action::VoteCandidate),
+			_ => Err(()),
+		}
+	}
+}
+
+impl<AccountId, AddressMapping, WeightToGas, WeightInfo, Runtime> Precompile
+	for PocPrecompile<AccountId, AddressMapping, WeightToGas, WeightInfo, Runtime>
+where
+	AccountId: Clone,
+	AddressMapping: AddressMappingT<AccountId>,
+	WeightToGas: Convert<Weight, u64>,
+	WeightInfo: WeightInfoT,
+	Runtime: module_poc::Config + frame_system::Config<AccountId = AccountId>,
+{
+	fn execute(
+		input: &[u8],
+		target_gas: Option<u64>,
+		context: &Context,
+	) -> result::Result<(ExitSucceed, Vec<u8>, u64), ExitError> {
+		debug::debug!(target: "evm", "poc input: {:?}", input);
+		let input = Input::<Action, AccountId, AddressMapping>::parse(input);
+
+		let action = input.action()?;
+
+		// only the queries can be called in a static frame
+		if precompiles::is_static()
+			&& matches!(
+				action,
+				Action::Commit | Action::AddFunds | Action::Unbond | Action::Withdraw | Action::VoteCandidate
+			) {
+			return Err(ExitError::Other("static call".into()));
+		}
+
+		let weight = match action {
+			Action::Commit => <Runtime as module_poc::Config>::WeightInfo::commit(),
+			Action::AddFunds => <Runtime as module_poc::Config>::WeightInfo::add_funds(),
+			Action::Unbond => <Runtime as module_poc::Config>::WeightInfo::unbond(),
+			Action::Withdraw => <Runtime as module_poc::Config>::WeightInfo::withdraw(),
+			Action::VoteCandidate => <Runtime as module_poc::Config>::WeightInfo::vote_candidate(),
+			Action::QueryCandidates => WeightInfo::query_candidates(module_poc::Module::<Runtime>::n_candidates()),
+			_ => WeightInfo::query(),
+		};
+		let cost = gas_cost::<WeightToGas>(weight, target_gas)?;
+
+		let origin = || -> <Runtime as frame_system::Config>::Origin {
+			frame_system::RawOrigin::Signed(AddressMapping::get_account_id(&context.caller)).into()
+		};
+
+		match action {
+			Action::QueryCommitment => {
+				let account = input.account_id_at(1)?;
+				let commitment = module_poc::Module::<Runtime>::commitments(&account);
+
+				let mut output = [0u8; 5 * 32];
+				if !commitment.amount.is_zero() {
+					if let LockState::Unbonding(start) = commitment.state {
+						U256::from(1).to_big_endian(&mut output[0..32]);
+						U256::from(start.saturated_into::<u128>()).to_big_endian(&mut output[32..64]);
+					}
+					let duration = match commitment.duration {
+						LockDuration::OneMonth => 0,
+						LockDuration::OneYear => 1,
+						LockDuration::TenYears => 2,
+					};
+					U256::from(duration).to_big_endian(&mut output[64..96]);
+					U256::from(commitment.amount.saturated_into::<Balance>()).to_big_endian(&mut output[96..128]);
+					output[140..160]
+						.copy_from_slice(evm_address_of::<AccountId, AddressMapping>(&commitment.candidate).as_bytes());
+				}
+
+				Ok((ExitSucceed::Returned, output.to_vec(), cost))
+			}
+			Action::QueryVotingWeight => {
+				let account = input.account_id_at(1)?;
+				let commitment = module_poc::Module::<Runtime>::commitments(&account);
+				let weight = module_poc::Module::<Runtime>::voting_weight(&commitment);
+
+				Ok((
+					ExitSucceed::Returned,
+					vec_u8_from_balance(weight.saturated_into()),
+					cost,
+				))
+			}
+			Action::QueryCurrentEra => {
+				let era = module_poc::Module::<Runtime>::current_era();
+
+				let mut output = [0u8; 2 * 32];
+				U256::from(era.index).to_big_endian(&mut output[0..32]);
+				U256::from(era.start.saturated_into::<u128>()).to_big_endian(&mut output[32..64]);
+
+				Ok((ExitSucceed::Returned, output.to_vec(), cost))
+			}
+			Action::QueryCandidates => {
+				// no more candidates than charged for
+				let candidates = module_poc::Candidates::<Runtime>::iter()
+					.take(module_poc::Module::<Runtime>::n_candidates() as usize)
+					.map(|(candidate, _)| evm_address_of::<AccountId, AddressMapping>(&candidate))
+					.collect::<Vec<_>>();
+
+				Ok((
+					ExitSucceed::Returned,
+					addresses_output(&candidates, input.has_selector()),
+					cost,
+				))
+			}
+			Action::QueryMembers => {
+				let members = module_poc::Module::<Runtime>::members()
+					.iter()
+					.map(evm_address_of::<AccountId, AddressMapping>)
+					.collect::<Vec<_>>();
+
+				Ok((
+					ExitSucceed::Returned,
+					addresses_output(&members, input.has_selector()),
+					cost,
+				))
+			}
+			Action::Commit => {
+				let amount = balance_of::<Runtime>(input.balance_at(1)?)?;
+				let duration = match input.u32_at(2)? {
+					0 => LockDuration::OneMonth,
+					1 => LockDuration::OneYear,
+					2 => LockDuration::TenYears,
+					_ => return Err(ExitError::Other("invalid duration".into())),
+				};
+				let candidate = input.account_id_at(3)?;
+
+				dispatch_result(module_poc::Module::<Runtime>::commit(
+					origin(),
+					amount,
+					duration,
+					candidate,
+				))?;
+
+				Ok((ExitSucceed::Returned, vec![], cost))
+			}
+			Action::AddFunds => {
+				let amount = balance_of::<Runtime>(input.balance_at(1)?)?;
+
+				dispatch_result(module_poc::Module::<Runtime>::add_funds(origin(), amount))?;
+
+				Ok((ExitSucceed::Returned, vec![], cost))
+			}
+			Action::Unbond => {
+				dispatch_result(module_poc::Module::<Runtime>::unbond(origin()))?;
+
+				Ok((ExitSucceed::Returned, vec![], cost))
+			}
+			Action::Withdraw => {
+				dispatch_result(module_poc::Module::<Runtime>::withdraw(origin()))?;
+
+				Ok((ExitSucceed::Returned, vec![], cost))
+			}
+			Action::VoteCandidate => {
+				let candidate = input.account_id_at(1)?;
+
+				dispatch_result(module_poc::Module::<Runtime>::vote_candidate(origin(), candidate))?;
+
+				Ok((ExitSucceed::Returned, vec![], cost))
+			}
+		}
+	}
+}
+
+fn dispatch_result(result: DispatchResultWithPostInfo) -> result::Result<(), ExitError> {
+	result
+		.map(|_| ())
+		.map_err(|e| ExitError::Other(Cow::Borrowed(e.error.into())))
+}
+
+fn balance_of<Runtime: module_poc::Config>(amount: Balance) -> result::Result<BalanceOf<Runtime>, ExitError> {
+	BalanceOf::<Runtime>::try_from(amount).map_err(|_| ExitError::Other("invalid amount".into()))
+}
+
+fn evm_address_of<AccountId, AddressMapping: AddressMappingT<AccountId>>(account: &AccountId) -> H160 {
+	AddressMapping::get_evm_address(account).unwrap_or_else(|| AddressMapping::get_default_evm_address(account))
+}
+
+fn vec_u8_from_balance(b: Balance) -> Vec<u8> {
+	let mut be_bytes = [0u8; 32];
+	U256::from(b).to_big_endian(&mut be_bytes[..]);
+	be_bytes.to_vec()
+}
+
+fn addresses_output(addresses: &[H160], with_offset: bool) -> Vec<u8> {
+	let mut output = Vec::new();
+	if with_offset {
+		let mut offset = [0u8; 32];
+		U256::from(32).to_big_endian(&mut offset);
+		output.extend_from_slice(&offset);
+	}
+
+	let mut len = [0u8; 32];
+	U256::from(addresses.len()).to_big_endian(&mut len);
+	output.extend_from_slice(&len);
+
+	for address in addresses {
+		let mut word = [0u8; 32];
+		word[12..].copy_from_slice(address.as_bytes());
+		output.extend_from_slice(&word);
+	}
+
+	output
+}
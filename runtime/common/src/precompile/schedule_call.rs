@@ -10,14 +10,18 @@ use frame_support::{
 		Currency, IsType, OriginTrait,
 	},
 };
-use module_evm::{Context, ExitError, ExitSucceed, Precompile};
+use module_evm::{Context, ExitError, ExitReason, ExitSucceed, Precompile, RecurringCall, RecurringCalls};
 use module_support::TransactionPayment;
 use primitives::{evm::AddressMapping as AddressMappingT, Balance, BlockNumber};
 use sp_core::{H160, U256};
 use sp_runtime::RuntimeDebug;
 use sp_std::{convert::TryFrom, fmt::Debug, marker::PhantomData, prelude::*, result};
 
-use super::input::{Input, InputT, PER_PARAM_BYTES};
+use super::{
+	gas_cost,
+	input::{has_selector, Input, InputT, SELECTOR_BYTES},
+	weights::WeightInfo as WeightInfoT,
+};
 use codec::{Decode, Encode};
 use pallet_scheduler::TaskAddress;
 
@@ -42,6 +46,27 @@ pub struct TaskInfo {
 /// Actions:
 /// - ScheduleCall. Rest `input` bytes: `from`, `target`, `value`, `gas_limit`,
 ///   `storage_limit`, `min_delay`, `input_len`, `input_data`.
+/// - Cancel. Rest `input` bytes: `from`, `task_id_len`, `task_id`.
+/// - Reschedule. Rest `input` bytes: `from`, `min_delay`, `task_id_len`,
+///   `task_id`.
+/// - ScheduleRecurring. Rest `input` bytes: `from`, `target`, `value`,
+///   `gas_limit`, `storage_limit`, `min_delay`, `period`, `count`, `prepay`,
+///   `input_len`, `input_data`. The call runs `count` times, every `period`
+///   blocks. If `prepay` is not zero, the fees of all the runs are reserved
+///   up front, otherwise each run is paid when it executes.
+/// - TaskStatus. Rest `input` bytes: `task_id_len`, `task_id`. Returns the
+///   next execution block, the remaining runs and the last exit status of a
+///   recurring task: 0 not run yet, 1 succeeded, 2 reverted, 3 errored and 4
+///   fatal. The status of a finished task is kept for
+///   `RecurringCallRetention` blocks.
+///
+/// The actions can also be called with the Solidity functions
+/// `scheduleCall(address,address,uint256,uint256,uint256,uint256,bytes)`,
+/// `cancelCall(address,bytes)`, `rescheduleCall(address,uint256,bytes)`,
+/// `scheduleRecurringCall(address,address,uint256,uint256,uint256,uint256,uint256,uint256,bool,bytes)`
+/// and `taskStatus(bytes)`, which return the `task_id` as ABI encoded `bytes`.
+///
+/// Each action is charged the gas equivalent of its `WeightInfo` weight.
 pub struct ScheduleCallPrecompile<
 	AccountId,
 	AddressMapping,
@@ -51,6 +76,7 @@ pub struct ScheduleCallPrecompile<
 	Origin,
 	PalletsOrigin,
 	Runtime,
+	WeightInfo,
 >(
 	PhantomData<(
 		AccountId,
@@ -61,6 +87,7 @@ pub struct ScheduleCallPrecompile<
 		Origin,
 		PalletsOrigin,
 		Runtime,
+		WeightInfo,
 	)>,
 );
 
@@ -68,6 +95,8 @@ enum Action {
 	Schedule,
 	Cancel,
 	Reschedule,
+	ScheduleRecurring,
+	TaskStatus,
 }
 
 impl TryFrom<u8> for Action {
@@ -78,6 +107,28 @@ impl TryFrom<u8> for Action {
 			0 => Ok(Action::Schedule),
 			1 => Ok(Action::Cancel),
 			2 => Ok(Action::Reschedule),
+			3 => Ok(Action::ScheduleRecurring),
+			4 => Ok(Action::TaskStatus),
+			_ => Err(()),
+		}
+	}
+}
+
+impl TryFrom<[u8; SELECTOR_BYTES]> for Action {
+	type Error = ();
+
+	fn try_from(selector: [u8; SELECTOR_BYTES]) -> Result<Self, Self::Error> {
+		match selector {
+			// scheduleCall(address,address,uint256,uint256,uint256,uint256,bytes)
+			[0x63, 0xdf, 0x1b, 0xc3] => Ok(Action::Schedule),
+			// cancelCall(address,bytes)
+			[0x93, 0xe3, 0x26, 0x61] => Ok(Action::Cancel),
+			// rescheduleCall(address,uint256,bytes)
+			[0x28, 0x30, 0x2f, 0x34] => Ok(Action::Reschedule),
+			// scheduleRecurringCall(address,address,uint256,uint256,uint256,uint256,uint256,uint256,bool,bytes)
+			[0x10, 0xa5, 0x90, 0x0a] => Ok(Action::ScheduleRecurring),
+			// taskStatus(bytes)
+			[0xb9, 0x2b, 0xa7, 0x2e] => Ok(Action::TaskStatus),
 			_ => Err(()),
 		}
 	}
@@ -88,7 +139,17 @@ type PalletBalanceOf<T> =
 type NegativeImbalanceOf<T> =
 	<<T as module_evm::Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::NegativeImbalance;
 
-impl<AccountId, AddressMapping, Scheduler, ChargeTransactionPayment, Call, Origin, PalletsOrigin, Runtime> Precompile
+impl<
+		AccountId,
+		AddressMapping,
+		Scheduler,
+		ChargeTransactionPayment,
+		Call,
+		Origin,
+		PalletsOrigin,
+		Runtime,
+		WeightInfo,
+	> Precompile
 	for ScheduleCallPrecompile<
 		AccountId,
 		AddressMapping,
@@ -98,7 +159,9 @@ impl<AccountId, AddressMapping, Scheduler, ChargeTransactionPayment, Call, Origi
 		Origin,
 		PalletsOrigin,
 		Runtime,
-	> where
+		WeightInfo,
+	>
+where
 	AccountId: Debug + Clone,
 	AddressMapping: AddressMappingT<AccountId>,
 	Scheduler: ScheduleNamed<BlockNumber, Call, PalletsOrigin, Address = TaskAddress<BlockNumber>>,
@@ -107,22 +170,34 @@ impl<AccountId, AddressMapping, Scheduler, ChargeTransactionPayment, Call, Origi
 	Origin: IsType<<Runtime as frame_system::Config>::Origin>
 		+ OriginTrait<AccountId = AccountId, PalletsOrigin = PalletsOrigin>,
 	PalletsOrigin: Into<<Runtime as frame_system::Config>::Origin> + From<frame_system::RawOrigin<AccountId>> + Clone,
-	Runtime: module_evm::Config + frame_system::Config<AccountId = AccountId>,
+	Runtime: module_evm::Config + frame_system::Config<AccountId = AccountId, BlockNumber = BlockNumber>,
 	PalletBalanceOf<Runtime>: IsType<Balance>,
+	WeightInfo: WeightInfoT,
 {
 	fn execute(
 		input: &[u8],
-		_target_gas: Option<u64>,
+		target_gas: Option<u64>,
 		_context: &Context,
 	) -> result::Result<(ExitSucceed, Vec<u8>, u64), ExitError> {
 		debug::debug!(target: "evm", "schedule call: input: {:?}", input);
 
 		// Solidity dynamic arrays will add the array size to the front of the array,
-		// pre-compile needs to deal with the `size`.
-		let input = Input::<Action, AccountId, AddressMapping>::new(&input[32..]);
+		// pre-compile needs to deal with the `size`. ABI encoded input has none.
+		let input = if has_selector(input) {
+			Input::<Action, AccountId, AddressMapping>::new_with_selector(input)
+		} else {
+			Input::<Action, AccountId, AddressMapping>::new(&input[32..])
+		};
 
 		let action = input.action()?;
 
+		let weight = match action {
+			Action::Schedule | Action::Reschedule | Action::ScheduleRecurring => WeightInfo::schedule_call(),
+			Action::Cancel => WeightInfo::cancel_call(),
+			Action::TaskStatus => WeightInfo::query(),
+		};
+		let cost = gas_cost::<<Runtime as module_evm::Config>::WeightToGas>(weight, target_gas)?;
+
 		match action {
 			Action::Schedule => {
 				let from = input.evm_address_at(1)?;
@@ -132,19 +207,17 @@ impl<AccountId, AddressMapping, Scheduler, ChargeTransactionPayment, Call, Origi
 				let gas_limit = input.u64_at(4)?;
 				let storage_limit = input.u32_at(5)?;
 				let min_delay = input.u32_at(6)?;
-				let input_len = input.u32_at(7)?;
-				let input_data = input.bytes_at(8 * PER_PARAM_BYTES, input_len as usize)?;
+				let input_data = input.dynamic_bytes_at(7)?;
 
 				debug::debug!(
 					target: "evm",
-					"schedule call: from: {:?}, target: {:?}, value: {:?}, gas_limit: {:?}, storage_limit: {:?}, min_delay: {:?}, input_len: {:?}, input_data: {:?}",
+					"schedule call: from: {:?}, target: {:?}, value: {:?}, gas_limit: {:?}, storage_limit: {:?}, min_delay: {:?}, input_data: {:?}",
 					from,
 					target,
 					value,
 					gas_limit,
 					storage_limit,
 					min_delay,
-					input_len,
 					input_data,
 				);
 
@@ -201,17 +274,15 @@ impl<AccountId, AddressMapping, Scheduler, ChargeTransactionPayment, Call, Origi
 				)
 				.map_err(|_| ExitError::Other("Schedule failed".into()))?;
 
-				// add task_id len prefix
-				let mut task_id_with_len = [0u8; 96];
-				U256::from(task_id.len()).to_big_endian(&mut task_id_with_len[0..32]);
-				task_id_with_len[32..32 + task_id.len()].copy_from_slice(&task_id[..]);
-
-				Ok((ExitSucceed::Returned, task_id_with_len.to_vec(), 0))
+				Ok((
+					ExitSucceed::Returned,
+					task_id_output(&task_id, input.has_selector()),
+					cost,
+				))
 			}
 			Action::Cancel => {
 				let from = input.evm_address_at(1)?;
-				let task_id_len = input.u32_at(2)?;
-				let task_id = input.bytes_at(3 * PER_PARAM_BYTES, task_id_len as usize)?;
+				let task_id = input.dynamic_bytes_at(2)?;
 
 				debug::debug!(
 					target: "evm",
@@ -224,22 +295,26 @@ impl<AccountId, AddressMapping, Scheduler, ChargeTransactionPayment, Call, Origi
 					.map_err(|_| ExitError::Other("Decode task_id failed".into()))?;
 				ensure!(task_info.sender == from, ExitError::Other("NoPermission".into()));
 
-				Scheduler::cancel_named(task_id).map_err(|_| ExitError::Other("Cancel schedule failed".into()))?;
+				Scheduler::cancel_named(task_id.clone())
+					.map_err(|_| ExitError::Other("Cancel schedule failed".into()))?;
+
+				// the past runs of a recurring task have used part of its fee
+				let _fee =
+					RecurringCalls::<Runtime>::take(&task_id).map_or(task_info.fee, |task| task.reserved_fee.into());
 
 				#[cfg(not(feature = "with-ethereum-compatibility"))]
 				{
 					// unreserve the transaction fee for gas_limit
 					let from_account = AddressMapping::get_account_id(&from);
-					ChargeTransactionPayment::unreserve_fee(&from_account, task_info.fee.into());
+					ChargeTransactionPayment::unreserve_fee(&from_account, _fee.into());
 				}
 
-				Ok((ExitSucceed::Returned, vec![], 0))
+				Ok((ExitSucceed::Returned, vec![], cost))
 			}
 			Action::Reschedule => {
 				let from = input.evm_address_at(1)?;
 				let min_delay = input.u32_at(2)?;
-				let task_id_len = input.u32_at(3)?;
-				let task_id = input.bytes_at(4 * PER_PARAM_BYTES, task_id_len as usize)?;
+				let task_id = input.dynamic_bytes_at(3)?;
 
 				debug::debug!(
 					target: "evm",
@@ -253,13 +328,171 @@ impl<AccountId, AddressMapping, Scheduler, ChargeTransactionPayment, Call, Origi
 					.map_err(|_| ExitError::Other("Decode task_id failed".into()))?;
 				ensure!(task_info.sender == from, ExitError::Other("NoPermission".into()));
 
-				Scheduler::reschedule_named(task_id, DispatchTime::After(min_delay)).map_err(|e| {
-					let err_msg: &str = e.into();
-					ExitError::Other(err_msg.into())
-				})?;
+				let (when, _) =
+					Scheduler::reschedule_named(task_id.clone(), DispatchTime::After(min_delay)).map_err(|e| {
+						let err_msg: &str = e.into();
+						ExitError::Other(err_msg.into())
+					})?;
+
+				RecurringCalls::<Runtime>::mutate(&task_id, |maybe_task| {
+					if let Some(task) = maybe_task {
+						task.next_execution = when;
+					}
+				});
+
+				Ok((ExitSucceed::Returned, vec![], cost))
+			}
+			Action::ScheduleRecurring => {
+				let from = input.evm_address_at(1)?;
+				let target = input.evm_address_at(2)?;
+
+				let value = input.balance_at(3)?;
+				let gas_limit = input.u64_at(4)?;
+				let storage_limit = input.u32_at(5)?;
+				let min_delay = input.u32_at(6)?;
+				let period = input.u32_at(7)?;
+				let count = input.u32_at(8)?;
+				let prepay = input.u32_at(9)? != 0;
+				let input_data = input.dynamic_bytes_at(10)?;
+
+				debug::debug!(
+					target: "evm",
+					"schedule recurring call: from: {:?}, target: {:?}, value: {:?}, gas_limit: {:?}, storage_limit: {:?}, min_delay: {:?}, period: {:?}, count: {:?}, prepay: {:?}, input_data: {:?}",
+					from,
+					target,
+					value,
+					gas_limit,
+					storage_limit,
+					min_delay,
+					period,
+					count,
+					prepay,
+					input_data,
+				);
+
+				ensure!(period != 0 && count != 0, ExitError::Other("Invalid recurrence".into()));
+
+				let mut _fee: PalletBalanceOf<Runtime> = Default::default();
+				#[cfg(not(feature = "with-ethereum-compatibility"))]
+				{
+					// reserve the transaction fee of all the runs
+					use sp_runtime::traits::Convert;
+					if prepay {
+						let from_account = AddressMapping::get_account_id(&from);
+						let weight = <Runtime as module_evm::Config>::GasToWeight::convert(gas_limit);
+						_fee =
+							ChargeTransactionPayment::reserve_fee(&from_account, weight.saturating_mul(count.into()))
+								.map_err(|e| {
+								let err_msg: &str = e.into();
+								ExitError::Other(err_msg.into())
+							})?;
+					}
+				}
+
+				let current_id = EvmSchedulerNextID::get();
+				let next_id = current_id
+					.checked_add(1)
+					.ok_or_else(|| ExitError::Other("Scheduler next id overflow".into()))?;
+				EvmSchedulerNextID::set(&next_id);
+
+				let task_id = TaskInfo {
+					prefix: b"ScheduleCall".to_vec(),
+					id: current_id,
+					sender: from,
+					fee: _fee.into(),
+				}
+				.encode();
 
-				Ok((ExitSucceed::Returned, vec![], 0))
+				debug::debug!(
+					target: "evm",
+					"schedule recurring call: task_id: {:?}",
+					task_id,
+				);
+
+				let call = module_evm::Call::<Runtime>::scheduled_recurring_call(
+					task_id.clone(),
+					from,
+					target,
+					input_data,
+					value.into(),
+					gas_limit,
+					storage_limit,
+				)
+				.into();
+
+				let (when, _) = Scheduler::schedule_named(
+					task_id.clone(),
+					DispatchTime::After(min_delay),
+					Some((period, count)),
+					0,
+					Origin::root().caller().clone(),
+					call,
+				)
+				.map_err(|_| ExitError::Other("Schedule failed".into()))?;
+
+				RecurringCalls::<Runtime>::insert(
+					&task_id,
+					RecurringCall {
+						period,
+						remaining_runs: count,
+						next_execution: when,
+						reserved_fee: _fee,
+						last_exit_reason: None,
+					},
+				);
+
+				Ok((
+					ExitSucceed::Returned,
+					task_id_output(&task_id, input.has_selector()),
+					cost,
+				))
+			}
+			Action::TaskStatus => {
+				let task_id = input.dynamic_bytes_at(1)?;
+
+				debug::debug!(
+					target: "evm",
+					"task status: task_id: {:?}",
+					task_id,
+				);
+
+				let task = RecurringCalls::<Runtime>::get(&task_id)
+					.ok_or_else(|| ExitError::Other("Task not found".into()))?;
+
+				let last_exit_status: u8 = match task.last_exit_reason {
+					None => 0,
+					Some(ExitReason::Succeed(_)) => 1,
+					Some(ExitReason::Revert(_)) => 2,
+					Some(ExitReason::Error(_)) => 3,
+					Some(ExitReason::Fatal(_)) => 4,
+				};
+
+				let mut output = [0u8; 96];
+				U256::from(task.next_execution).to_big_endian(&mut output[0..32]);
+				U256::from(task.remaining_runs).to_big_endian(&mut output[32..64]);
+				U256::from(last_exit_status).to_big_endian(&mut output[64..96]);
+
+				Ok((ExitSucceed::Returned, output.to_vec(), cost))
 			}
 		}
 	}
 }
+
+/// The `task_id` with its length prefix. ABI encoded output starts with the
+/// offset of the length.
+fn task_id_output(task_id: &[u8], with_offset: bool) -> Vec<u8> {
+	let mut output = Vec::new();
+	if with_offset {
+		let mut offset = [0u8; 32];
+		U256::from(32).to_big_endian(&mut offset);
+		output.extend_from_slice(&offset);
+	}
+
+	// add task_id len prefix
+	let mut task_id_with_len = [0u8; 96];
+	U256::from(task_id.len()).to_big_endian(&mut task_id_with_len[0..32]);
+	task_id_with_len[32..32 + task_id.len()].copy_from_slice(task_id);
+	output.extend_from_slice(&task_id_with_len);
+
+	output
+}
@@ -1,11 +1,16 @@
-use frame_support::debug;
+use frame_support::{debug, weights::Weight};
 use module_evm::{Context, ExitError, ExitSucceed, Precompile};
 use sp_core::U256;
+use sp_runtime::traits::Convert;
 use sp_std::{borrow::Cow, convert::TryFrom, marker::PhantomData, prelude::*, result};
 
 use module_support::EVMStateRentTrait;
 
-use super::input::{Input, InputT};
+use super::{
+	gas_cost,
+	input::{Input, InputT, SELECTOR_BYTES},
+	weights::WeightInfo as WeightInfoT,
+};
 use primitives::{evm::AddressMapping as AddressMappingT, Balance};
 
 /// The `EVM` impl precompile.
@@ -20,7 +25,16 @@ use primitives::{evm::AddressMapping as AddressMappingT, Balance};
 /// - QueryDeploymentFee.
 /// - TransferMaintainer. Rest `input` bytes: `from`, `contract`,
 ///   `new_maintainer`.
-pub struct StateRentPrecompile<AccountId, AddressMapping, EVM>(PhantomData<(AccountId, AddressMapping, EVM)>);
+///
+/// The actions can also be called with the Solidity functions
+/// `newContractExtraBytes()`, `storageDepositPerByte()`,
+/// `maintainerOf(address)`, `developerDeposit()`, `deploymentFee()` and
+/// `transferMaintainer(address,address,address)`.
+///
+/// Each action is charged the gas equivalent of its `WeightInfo` weight.
+pub struct StateRentPrecompile<AccountId, AddressMapping, EVM, WeightToGas, WeightInfo>(
+	PhantomData<(AccountId, AddressMapping, EVM, WeightToGas, WeightInfo)>,
+);
 
 enum Action {
 	QueryNewContractExtraBytes,
@@ -48,30 +62,61 @@ impl TryFrom<u8> for Action {
 	}
 }
 
-impl<AccountId, AddressMapping, EVM> Precompile for StateRentPrecompile<AccountId, AddressMapping, EVM>
+impl TryFrom<[u8; SELECTOR_BYTES]> for Action {
+	type Error = ();
+
+	fn try_from(selector: [u8; SELECTOR_BYTES]) -> Result<Self, Self::Error> {
+		match selector {
+			// newContractExtraBytes()
+			[0xa2, 0x3e, 0x8b, 0x82] => Ok(Action::QueryNewContractExtraBytes),
+			// storageDepositPerByte()
+			[0x6e, 0x04, 0x39, 0x98] => Ok(Action::QueryStorageDepositPerByte),
+			// maintainerOf(address)
+			[0x06, 0xad, 0x13, 0x55] => Ok(Action::QueryMaintainer),
+			// developerDeposit()
+			[0x68, 0xa1, 0x88, 0x55] => Ok(Action::QueryDeveloperDeposit),
+			// deploymentFee()
+			[0xf2, 0xcf, 0xf5, 0x7f] => Ok(Action::QueryDeploymentFee),
+			// transferMaintainer(address,address,address)
+			[0xee, 0x0d, 0x2e, 0x12] => Ok(Action::TransferMaintainer),
+			_ => Err(()),
+		}
+	}
+}
+
+impl<AccountId, AddressMapping, EVM, WeightToGas, WeightInfo> Precompile
+	for StateRentPrecompile<AccountId, AddressMapping, EVM, WeightToGas, WeightInfo>
 where
 	AccountId: Clone,
 	AddressMapping: AddressMappingT<AccountId>,
 	EVM: EVMStateRentTrait<AccountId, Balance>,
+	WeightToGas: Convert<Weight, u64>,
+	WeightInfo: WeightInfoT,
 {
 	fn execute(
 		input: &[u8],
-		_target_gas: Option<u64>,
+		target_gas: Option<u64>,
 		_context: &Context,
 	) -> result::Result<(ExitSucceed, Vec<u8>, u64), ExitError> {
 		debug::debug!(target: "evm", "state_rent input: {:?}", input);
-		let input = Input::<Action, AccountId, AddressMapping>::new(input);
+		let input = Input::<Action, AccountId, AddressMapping>::parse(input);
 
 		let action = input.action()?;
 
+		let weight = match action {
+			Action::TransferMaintainer => WeightInfo::transfer_maintainer(),
+			_ => WeightInfo::query(),
+		};
+		let cost = gas_cost::<WeightToGas>(weight, target_gas)?;
+
 		match action {
 			Action::QueryNewContractExtraBytes => {
 				let bytes = vec_u8_from_u32(EVM::query_new_contract_extra_bytes());
-				Ok((ExitSucceed::Returned, bytes, 0))
+				Ok((ExitSucceed::Returned, bytes, cost))
 			}
 			Action::QueryStorageDepositPerByte => {
 				let deposit = vec_u8_from_balance(EVM::query_storage_deposit_per_byte());
-				Ok((ExitSucceed::Returned, deposit, 0))
+				Ok((ExitSucceed::Returned, deposit, cost))
 			}
 			Action::QueryMaintainer => {
 				let contract = input.evm_address_at(1)?;
@@ -82,15 +127,15 @@ where
 				let mut address = [0u8; 32];
 				address[12..].copy_from_slice(&maintainer.as_bytes().to_vec());
 
-				Ok((ExitSucceed::Returned, address.to_vec(), 0))
+				Ok((ExitSucceed::Returned, address.to_vec(), cost))
 			}
 			Action::QueryDeveloperDeposit => {
 				let deposit = vec_u8_from_balance(EVM::query_developer_deposit());
-				Ok((ExitSucceed::Returned, deposit, 0))
+				Ok((ExitSucceed::Returned, deposit, cost))
 			}
 			Action::QueryDeploymentFee => {
 				let fee = vec_u8_from_balance(EVM::query_deployment_fee());
-				Ok((ExitSucceed::Returned, fee, 0))
+				Ok((ExitSucceed::Returned, fee, cost))
 			}
 			Action::TransferMaintainer => {
 				let from = input.account_id_at(1)?;
@@ -100,7 +145,7 @@ where
 				EVM::transfer_maintainer(from, contract, new_maintainer)
 					.map_err(|e| ExitError::Other(Cow::Borrowed(e.into())))?;
 
-				Ok((ExitSucceed::Returned, vec![], 0))
+				Ok((ExitSucceed::Returned, vec![], cost))
 			}
 		}
 	}
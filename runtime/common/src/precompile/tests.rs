@@ -2,17 +2,23 @@
 use super::*;
 use crate::precompile::{
 	mock::{
-		trillian, ford, get_task_id, new_test_ext, run_to_block, Balances, Event as TestEvent,
-		ScheduleCallPrecompile, System, Test,
-		SNAPR_ERC20_ADDRESS,
+		context, ford, get_task_id, new_test_ext, run_to_block, trillian, Balances, Call, Currencies,
+		DispatchCallPrecompile, Event as TestEvent, EvmAccountsPrecompile, InputBuilder, MockAddressMapping,
+		MultiCurrencyPrecompile, Origin, Poc, PocPrecompile, ScheduleCallPrecompile, StateRentPrecompile, System, Test,
+		CANCEL_CALL_WEIGHT, QUERY_CANDIDATE_WEIGHT, QUERY_WEIGHT, SCHEDULE_CALL_WEIGHT, SEUR, SNAPR_ERC20_ADDRESS,
+		TRANSFER_WEIGHT,
 	},
+	multicurrency::TRANSFER_EVENT_TOPIC,
 	schedule_call::TaskInfo,
 };
 use codec::Encode;
+use frame_support::{assert_ok, weights::GetDispatchInfo};
 use hex_literal::hex;
-use module_evm::ExitError;
-use primitives::{evm::AddressMapping, Balance, PREDEPLOY_ADDRESS_START};
-use sp_core::{H160, U256};
+use module_evm::{ExitError, Log};
+use module_poc::WeightInfo as PocWeightInfo;
+use orml_traits::MultiCurrency;
+use primitives::{evm::AddressMapping, Balance, MIRRORED_TOKENS_ADDRESS_START, PREDEPLOY_ADDRESS_START};
+use sp_core::{H160, H256, U256};
 
 pub struct DummyPrecompile;
 impl Precompile for DummyPrecompile {
@@ -30,6 +36,9 @@ pub type WithSystemContractFilter = AllPrecompiles<
 	DummyPrecompile,
 	DummyPrecompile,
 	DummyPrecompile,
+	DummyPrecompile,
+	DummyPrecompile,
+	DummyPrecompile,
 >;
 
 #[test]
@@ -129,7 +138,7 @@ fn schedule_call_precompile_should_work() {
 
 		let (reason, output, used_gas) = ScheduleCallPrecompile::execute(&input, None, &context).unwrap();
 		assert_eq!(reason, ExitSucceed::Returned);
-		assert_eq!(used_gas, 0);
+		assert_eq!(used_gas, SCHEDULE_CALL_WEIGHT);
 		let event = TestEvent::pallet_scheduler(pallet_scheduler::RawEvent::Scheduled(3, 0));
 		assert!(System::events().iter().any(|record| record.event == event));
 
@@ -149,13 +158,13 @@ fn schedule_call_precompile_should_work() {
 
 		let (reason, _output, used_gas) = ScheduleCallPrecompile::execute(&cancel_input, None, &context).unwrap();
 		assert_eq!(reason, ExitSucceed::Returned);
-		assert_eq!(used_gas, 0);
+		assert_eq!(used_gas, CANCEL_CALL_WEIGHT);
 		let event = TestEvent::pallet_scheduler(pallet_scheduler::RawEvent::Canceled(3, 0));
 		assert!(System::events().iter().any(|record| record.event == event));
 
 		let (reason, output, used_gas) = ScheduleCallPrecompile::execute(&input, None, &context).unwrap();
 		assert_eq!(reason, ExitSucceed::Returned);
-		assert_eq!(used_gas, 0);
+		assert_eq!(used_gas, SCHEDULE_CALL_WEIGHT);
 
 		run_to_block(2);
 
@@ -177,7 +186,7 @@ fn schedule_call_precompile_should_work() {
 
 		let (reason, _output, used_gas) = ScheduleCallPrecompile::execute(&reschedule_input, None, &context).unwrap();
 		assert_eq!(reason, ExitSucceed::Returned);
-		assert_eq!(used_gas, 0);
+		assert_eq!(used_gas, SCHEDULE_CALL_WEIGHT);
 		let event = TestEvent::pallet_scheduler(pallet_scheduler::RawEvent::Scheduled(5, 0));
 		assert!(System::events().iter().any(|record| record.event == event));
 
@@ -199,7 +208,7 @@ fn schedule_call_precompile_should_work() {
 		run_to_block(5);
 		#[cfg(not(feature = "with-ethereum-compatibility"))]
 		{
-			assert_eq!(Balances::free_balance(from_account.clone()), 999999909417);
+			assert_eq!(Balances::free_balance(from_account.clone()), 999999908417);
 			assert_eq!(Balances::reserved_balance(from_account), 0);
 			assert_eq!(Balances::free_balance(to_account), 1000000001000);
 		}
@@ -246,7 +255,7 @@ fn schedule_call_precompile_should_handle_invalid_input() {
 
 		let (reason, output, used_gas) = ScheduleCallPrecompile::execute(&input, None, &context).unwrap();
 		assert_eq!(reason, ExitSucceed::Returned);
-		assert_eq!(used_gas, 0);
+		assert_eq!(used_gas, SCHEDULE_CALL_WEIGHT);
 
 		let from_account = <Test as module_evm::Config>::AddressMapping::get_account_id(&trillian());
 		let to_account = <Test as module_evm::Config>::AddressMapping::get_account_id(&ford());
@@ -290,6 +299,680 @@ fn schedule_call_precompile_should_handle_invalid_input() {
 }
 
 
+#[test]
+fn schedule_recurring_call_precompile_should_work() {
+	new_test_ext().execute_with(|| {
+		let context = Context {
+			address: Default::default(),
+			caller: trillian(),
+			apparent_value: Default::default(),
+		};
+
+		let mut input = [0u8; 14 * 32 + 4];
+		// array size
+		U256::default().to_big_endian(&mut input[0 * 32..1 * 32]);
+		// action
+		U256::from(3).to_big_endian(&mut input[1 * 32..2 * 32]);
+		// from
+		U256::from(trillian().as_bytes()).to_big_endian(&mut input[2 * 32..3 * 32]);
+		// target
+		U256::from(SNAPR_ERC20_ADDRESS).to_big_endian(&mut input[3 * 32..4 * 32]);
+		// value
+		U256::from(0).to_big_endian(&mut input[4 * 32..5 * 32]);
+		// gas_limit
+		U256::from(300000).to_big_endian(&mut input[5 * 32..6 * 32]);
+		// storage_limit
+		U256::from(100).to_big_endian(&mut input[6 * 32..7 * 32]);
+		// min_delay
+		U256::from(1).to_big_endian(&mut input[7 * 32..8 * 32]);
+		// period
+		U256::from(2).to_big_endian(&mut input[8 * 32..9 * 32]);
+		// count
+		U256::from(3).to_big_endian(&mut input[9 * 32..10 * 32]);
+		// prepay
+		U256::from(1).to_big_endian(&mut input[10 * 32..11 * 32]);
+		// input_len
+		U256::from(4 + 32 + 32).to_big_endian(&mut input[11 * 32..12 * 32]);
+
+		// input_data
+		let mut transfer_to_bob = [0u8; 68];
+		// transfer bytes4(keccak256(signature)) 0xa9059cbb
+		transfer_to_bob[0..4].copy_from_slice(&hex!("a9059cbb"));
+		// to address
+		U256::from(ford().as_bytes()).to_big_endian(&mut transfer_to_bob[4..36]);
+		// amount
+		U256::from(1000).to_big_endian(&mut transfer_to_bob[36..68]);
+
+		U256::from(&transfer_to_bob[0..32]).to_big_endian(&mut input[12 * 32..13 * 32]);
+		U256::from(&transfer_to_bob[32..64]).to_big_endian(&mut input[13 * 32..14 * 32]);
+		input[14 * 32..14 * 32 + 4].copy_from_slice(&transfer_to_bob[64..68]);
+
+		let (reason, output, used_gas) = ScheduleCallPrecompile::execute(&input, None, &context).unwrap();
+		assert_eq!(reason, ExitSucceed::Returned);
+		assert_eq!(used_gas, SCHEDULE_CALL_WEIGHT);
+		let event = TestEvent::pallet_scheduler(pallet_scheduler::RawEvent::Scheduled(3, 0));
+		assert!(System::events().iter().any(|record| record.event == event));
+
+		let task_id = get_task_id(output);
+		let mut status_input = [0u8; 5 * 32];
+		// action
+		U256::from(4).to_big_endian(&mut status_input[1 * 32..2 * 32]);
+		// task_id_len
+		U256::from(task_id.len()).to_big_endian(&mut status_input[2 * 32..3 * 32]);
+		// task_id
+		status_input[3 * 32..3 * 32 + task_id.len()].copy_from_slice(&task_id[..]);
+
+		let status = |next_execution: u32, remaining_runs: u32, last_exit_status: u8| {
+			let mut output = [0u8; 3 * 32];
+			U256::from(next_execution).to_big_endian(&mut output[0 * 32..1 * 32]);
+			U256::from(remaining_runs).to_big_endian(&mut output[1 * 32..2 * 32]);
+			U256::from(last_exit_status).to_big_endian(&mut output[2 * 32..3 * 32]);
+			Ok((ExitSucceed::Returned, output.to_vec(), QUERY_WEIGHT))
+		};
+
+		assert_eq!(
+			ScheduleCallPrecompile::execute(&status_input, None, &context),
+			status(3, 3, 0)
+		);
+
+		let from_account = <Test as module_evm::Config>::AddressMapping::get_account_id(&trillian());
+		let to_account = <Test as module_evm::Config>::AddressMapping::get_account_id(&ford());
+		#[cfg(not(feature = "with-ethereum-compatibility"))]
+		assert_eq!(Balances::reserved_balance(from_account.clone()), 900000);
+
+		run_to_block(3);
+		assert_eq!(
+			ScheduleCallPrecompile::execute(&status_input, None, &context),
+			status(5, 2, 1)
+		);
+		assert_eq!(Balances::free_balance(to_account.clone()), 1000000001000);
+		#[cfg(not(feature = "with-ethereum-compatibility"))]
+		assert_eq!(Balances::reserved_balance(from_account.clone()), 600000);
+
+		// the status of the last run is kept
+		run_to_block(7);
+		assert_eq!(
+			ScheduleCallPrecompile::execute(&status_input, None, &context),
+			status(7, 0, 1)
+		);
+		assert_eq!(Balances::free_balance(to_account), 1000000003000);
+		assert_eq!(Balances::reserved_balance(from_account), 0);
+	});
+}
+
+
+#[test]
+fn multicurrency_precompile_should_charge_gas() {
+	new_test_ext().execute_with(|| {
+		let context = context();
+		let currency_id: [u8; 32] = SEUR.into();
+
+		// QueryBalance
+		let query_input = InputBuilder::action(1).bytes32(currency_id).address(trillian()).build();
+
+		let (reason, output, used_gas) = MultiCurrencyPrecompile::execute(&query_input, None, &context).unwrap();
+		assert_eq!(reason, ExitSucceed::Returned);
+		assert_eq!(U256::from(&output[..]), U256::from(1_000));
+		assert_eq!(used_gas, QUERY_WEIGHT);
+
+		// Transfer
+		let transfer_input = InputBuilder::action(2)
+			.bytes32(currency_id)
+			.address(trillian())
+			.address(ford())
+			.uint(100)
+			.build();
+
+		let from_account = MockAddressMapping::get_account_id(&trillian());
+		let to_account = MockAddressMapping::get_account_id(&ford());
+
+		assert_eq!(
+			MultiCurrencyPrecompile::execute(&transfer_input, Some(TRANSFER_WEIGHT - 1), &context),
+			Err(ExitError::OutOfGas)
+		);
+		assert_eq!(Currencies::free_balance(SEUR, &from_account), 1_000);
+		assert_eq!(Currencies::free_balance(SEUR, &to_account), 0);
+
+		assert_eq!(
+			MultiCurrencyPrecompile::execute(&transfer_input, Some(TRANSFER_WEIGHT), &context),
+			Ok((ExitSucceed::Returned, vec![], TRANSFER_WEIGHT))
+		);
+		assert_eq!(Currencies::free_balance(SEUR, &from_account), 900);
+		assert_eq!(Currencies::free_balance(SEUR, &to_account), 100);
+	});
+}
+
+#[test]
+fn state_rent_precompile_should_charge_gas() {
+	new_test_ext().execute_with(|| {
+		let context = context();
+
+		// QueryNewContractExtraBytes
+		let input = InputBuilder::action(0).build();
+
+		let mut extra_bytes = [0u8; 32];
+		U256::from(100).to_big_endian(&mut extra_bytes[..]);
+
+		assert_eq!(
+			StateRentPrecompile::execute(&input, Some(QUERY_WEIGHT - 1), &context),
+			Err(ExitError::OutOfGas)
+		);
+		assert_eq!(
+			StateRentPrecompile::execute(&input, Some(QUERY_WEIGHT), &context),
+			Ok((ExitSucceed::Returned, extra_bytes.to_vec(), QUERY_WEIGHT))
+		);
+	});
+}
+
+#[test]
+fn schedule_call_precompile_should_charge_gas() {
+	new_test_ext().execute_with(|| {
+		let context = context();
+
+		let input = InputBuilder::default()
+			// array size
+			.uint(0)
+			// Schedule
+			.uint(0)
+			.address(trillian())
+			.uint(SNAPR_ERC20_ADDRESS)
+			// value
+			.uint(0)
+			// gas_limit
+			.uint(300000)
+			// storage_limit
+			.uint(100)
+			// min_delay
+			.uint(1)
+			.bytes(&[0])
+			.build();
+
+		assert_eq!(
+			ScheduleCallPrecompile::execute(&input, Some(SCHEDULE_CALL_WEIGHT - 1), &context),
+			Err(ExitError::OutOfGas)
+		);
+		let event = TestEvent::pallet_scheduler(pallet_scheduler::RawEvent::Scheduled(3, 0));
+		assert!(!System::events().iter().any(|record| record.event == event));
+
+		let from_account = <Test as module_evm::Config>::AddressMapping::get_account_id(&trillian());
+		assert_eq!(Balances::reserved_balance(from_account.clone()), 0);
+
+		let (reason, _output, used_gas) =
+			ScheduleCallPrecompile::execute(&input, Some(SCHEDULE_CALL_WEIGHT), &context).unwrap();
+		assert_eq!(reason, ExitSucceed::Returned);
+		assert_eq!(used_gas, SCHEDULE_CALL_WEIGHT);
+		assert!(System::events().iter().any(|record| record.event == event));
+	});
+}
+
+#[test]
+fn multicurrency_precompile_should_accept_abi_input() {
+	new_test_ext().execute_with(|| {
+		let seur_token = H160::from_low_u64_be(MIRRORED_TOKENS_ADDRESS_START + 1);
+		let token_context = Context {
+			caller: seur_token,
+			..context()
+		};
+
+		// balanceOf(address)
+		let balance_input = InputBuilder::selector(hex!("70a08231")).address(trillian()).build();
+
+		let mut balance = [0u8; 32];
+		U256::from(1_000).to_big_endian(&mut balance);
+		assert_eq!(
+			MultiCurrencyPrecompile::execute(&balance_input, None, &token_context),
+			Ok((ExitSucceed::Returned, balance.to_vec(), QUERY_WEIGHT))
+		);
+
+		// transfer(address,address,uint256)
+		let transfer_input = InputBuilder::selector(hex!("beabacc8"))
+			.address(trillian())
+			.address(ford())
+			.uint(100)
+			.build();
+
+		let (result, logs) = module_evm::precompiles::collect_logs(|| {
+			MultiCurrencyPrecompile::execute(&transfer_input, None, &token_context)
+		});
+		assert_eq!(result, Ok((ExitSucceed::Returned, vec![], TRANSFER_WEIGHT)));
+		assert_eq!(
+			logs,
+			vec![Log {
+				address: seur_token,
+				topics: vec![
+					H256::from(TRANSFER_EVENT_TOPIC),
+					H256::from(trillian()),
+					H256::from(ford()),
+				],
+				data: InputBuilder::default().uint(100).build(),
+			}]
+		);
+
+		let from_account = MockAddressMapping::get_account_id(&trillian());
+		let to_account = MockAddressMapping::get_account_id(&ford());
+		assert_eq!(Currencies::free_balance(SEUR, &from_account), 900);
+		assert_eq!(Currencies::free_balance(SEUR, &to_account), 100);
+
+		// only mirrored tokens have a currency
+		assert_eq!(
+			MultiCurrencyPrecompile::execute(&balance_input, None, &context()),
+			Err(ExitError::Other("invalid currency id".into()))
+		);
+	});
+}
+
+#[test]
+fn state_rent_precompile_should_accept_abi_input() {
+	new_test_ext().execute_with(|| {
+		let context = context();
+
+		// newContractExtraBytes()
+		let input = InputBuilder::selector(hex!("a23e8b82")).build();
+
+		let mut extra_bytes = [0u8; 32];
+		U256::from(100).to_big_endian(&mut extra_bytes[..]);
+
+		assert_eq!(
+			StateRentPrecompile::execute(&input, None, &context),
+			Ok((ExitSucceed::Returned, extra_bytes.to_vec(), QUERY_WEIGHT))
+		);
+	});
+}
+
+#[test]
+fn schedule_call_precompile_should_accept_abi_input() {
+	new_test_ext().execute_with(|| {
+		let context = context();
+
+		// scheduleCall(address,address,uint256,uint256,uint256,uint256,bytes)
+		let input = InputBuilder::selector(hex!("63df1bc3"))
+			.address(trillian())
+			.uint(SNAPR_ERC20_ADDRESS)
+			// value
+			.uint(0)
+			// gas_limit
+			.uint(300000)
+			// storage_limit
+			.uint(100)
+			// min_delay
+			.uint(1)
+			// input_data offset
+			.uint(7 * 32)
+			.bytes(&hex!("12"))
+			.build();
+
+		let (reason, output, used_gas) = ScheduleCallPrecompile::execute(&input, None, &context).unwrap();
+		assert_eq!(reason, ExitSucceed::Returned);
+		assert_eq!(used_gas, SCHEDULE_CALL_WEIGHT);
+		assert_eq!(U256::from(&output[0..32]), U256::from(32));
+		let event = TestEvent::pallet_scheduler(pallet_scheduler::RawEvent::Scheduled(3, 0));
+		assert!(System::events().iter().any(|record| record.event == event));
+
+		let task_id = get_task_id(output[32..].to_vec());
+		// cancelCall(address,bytes)
+		let cancel_input = InputBuilder::selector(hex!("93e32661"))
+			.address(trillian())
+			// task_id offset
+			.uint(2 * 32)
+			.bytes(&task_id)
+			.build();
+
+		assert_eq!(
+			ScheduleCallPrecompile::execute(&cancel_input, None, &context),
+			Ok((ExitSucceed::Returned, vec![], CANCEL_CALL_WEIGHT))
+		);
+		let event = TestEvent::pallet_scheduler(pallet_scheduler::RawEvent::Canceled(3, 0));
+		assert!(System::events().iter().any(|record| record.event == event));
+	});
+}
+
+#[test]
+fn dispatch_call_and_poc_precompiles_do_not_need_system_caller() {
+	for offset in &[6, 8] {
+		let precompile = H160::from_low_u64_be(PRECOMPILE_ADDRESS_START + offset);
+
+		let mut non_system = [0u8; 20];
+		non_system[0] = 1;
+
+		let non_system_caller_context = Context {
+			address: precompile,
+			caller: non_system.into(),
+			apparent_value: 0.into(),
+		};
+		assert_eq!(
+			WithSystemContractFilter::execute(precompile, &[0u8; 1], None, &non_system_caller_context),
+			Some(Ok((ExitSucceed::Stopped, vec![], 0))),
+		);
+
+		// delegate call
+		let delegate_call_context = Context {
+			address: non_system.into(),
+			caller: non_system.into(),
+			apparent_value: 0.into(),
+		};
+		assert_eq!(
+			WithSystemContractFilter::execute(precompile, &[0u8; 1], None, &delegate_call_context),
+			Some(Err(ExitError::Other("no permission".into()))),
+		);
+	}
+}
+
+#[test]
+fn evm_accounts_precompile_does_not_need_system_caller() {
+	let precompile = H160::from_low_u64_be(PRECOMPILE_ADDRESS_START + 10);
+
+	let mut non_system = [0u8; 20];
+	non_system[0] = 1;
+
+	let non_system_caller_context = Context {
+		address: non_system.into(),
+		caller: non_system.into(),
+		apparent_value: 0.into(),
+	};
+	assert_eq!(
+		WithSystemContractFilter::execute(precompile, &[0u8; 1], None, &non_system_caller_context),
+		Some(Ok((ExitSucceed::Stopped, vec![], 0))),
+	);
+}
+
+#[test]
+fn all_precompiles_know_their_addresses() {
+	let is_precompile = |address: u64| WithSystemContractFilter::is_precompile(H160::from_low_u64_be(address));
+
+	assert!(is_precompile(1));
+	assert!(is_precompile(128));
+	for offset in &[0, 2, 4, 6, 8, 10] {
+		assert!(is_precompile(PRECOMPILE_ADDRESS_START + offset));
+	}
+	assert!(!is_precompile(10));
+	assert!(!is_precompile(PRECOMPILE_ADDRESS_START + 1));
+	assert!(!is_precompile(PRECOMPILE_ADDRESS_START + 12));
+	assert!(!is_precompile(PREDEPLOY_ADDRESS_START));
+}
+
+#[test]
+fn evm_accounts_precompile_should_charge_gas() {
+	new_test_ext().execute_with(|| {
+		let context = context();
+
+		// getAccountId(address)
+		let input = InputBuilder::selector(hex!("e0b490f7")).address(trillian()).build();
+
+		let account_id: [u8; 32] = MockAddressMapping::get_account_id(&trillian()).into();
+		assert_eq!(
+			EvmAccountsPrecompile::execute(&input, Some(QUERY_WEIGHT - 1), &context),
+			Err(ExitError::OutOfGas)
+		);
+		assert_eq!(
+			EvmAccountsPrecompile::execute(&input, Some(QUERY_WEIGHT), &context),
+			Ok((ExitSucceed::Returned, account_id.to_vec(), QUERY_WEIGHT))
+		);
+
+		assert_eq!(
+			EvmAccountsPrecompile::execute(&input[..4], None, &context),
+			Err(ExitError::Other("invalid input".into()))
+		);
+	});
+}
+
+#[test]
+fn dispatch_call_precompile_should_work() {
+	new_test_ext().execute_with(|| {
+		let context = context();
+
+		let from_account = MockAddressMapping::get_account_id(&trillian());
+		let to_account = MockAddressMapping::get_account_id(&ford());
+		let from_balance = Balances::free_balance(&from_account);
+		let to_balance = Balances::free_balance(&to_account);
+
+		let call = Call::Balances(pallet_balances::Call::transfer(to_account.clone(), 100));
+		let weight = call.get_dispatch_info().weight;
+		let input = call.encode();
+
+		assert_eq!(
+			DispatchCallPrecompile::execute(&input, Some(weight - 1), &context),
+			Err(ExitError::OutOfGas)
+		);
+		assert_eq!(Balances::free_balance(&from_account), from_balance);
+
+		assert_eq!(
+			DispatchCallPrecompile::execute(&input, Some(weight), &context),
+			Ok((ExitSucceed::Returned, vec![], weight))
+		);
+		assert_eq!(Balances::free_balance(&from_account), from_balance - 100);
+		assert_eq!(Balances::free_balance(&to_account), to_balance + 100);
+
+		// dispatch error
+		let call = Call::Balances(pallet_balances::Call::transfer(to_account, from_balance));
+		assert_eq!(
+			DispatchCallPrecompile::execute(&call.encode(), None, &context),
+			Err(ExitError::Other("InsufficientBalance".into()))
+		);
+	});
+}
+
+#[test]
+fn dispatch_call_precompile_should_not_dispatch_in_static_frames() {
+	new_test_ext().execute_with(|| {
+		let context = context();
+
+		let from_account = MockAddressMapping::get_account_id(&trillian());
+		let to_account = MockAddressMapping::get_account_id(&ford());
+		let from_balance = Balances::free_balance(&from_account);
+
+		let call = Call::Balances(pallet_balances::Call::transfer(to_account, 100));
+		assert_eq!(
+			module_evm::precompiles::with_static(true, || DispatchCallPrecompile::execute(
+				&call.encode(),
+				None,
+				&context
+			)),
+			Err(ExitError::Other("static call".into()))
+		);
+		assert_eq!(Balances::free_balance(&from_account), from_balance);
+	});
+}
+
+#[test]
+fn dispatch_call_precompile_should_handle_invalid_input() {
+	new_test_ext().execute_with(|| {
+		let context = context();
+
+		assert_eq!(
+			DispatchCallPrecompile::execute(&[0xffu8; 4], None, &context),
+			Err(ExitError::Other("Decode call failed".into()))
+		);
+
+		let call = Call::System(frame_system::Call::remark(vec![1, 2, 3]));
+		assert_eq!(
+			DispatchCallPrecompile::execute(&call.encode(), None, &context),
+			Err(ExitError::Other("Call not allowed".into()))
+		);
+	});
+}
+
+#[test]
+fn poc_precompile_should_work() {
+	new_test_ext().execute_with(|| {
+		let context = context();
+
+		let trillian_account = MockAddressMapping::get_account_id(&trillian());
+		let ford_account = MockAddressMapping::get_account_id(&ford());
+		assert_ok!(Poc::start_candidacy(Origin::signed(ford_account)));
+
+		// Commit for one year
+		let commit_input = InputBuilder::action(128).uint(1_000).uint(1).address(ford()).build();
+
+		let balance = Balances::free_balance(&trillian_account);
+		assert_eq!(
+			PocPrecompile::execute(&commit_input, None, &context),
+			Ok((ExitSucceed::Returned, vec![], <() as PocWeightInfo>::commit()))
+		);
+		assert_eq!(Balances::free_balance(&trillian_account), balance - 1_000);
+		assert_eq!(
+			PocPrecompile::execute(&commit_input, None, &context),
+			Err(ExitError::Other("AlreadyCommitted".into()))
+		);
+
+		// QueryCommitment
+		let query_input = InputBuilder::action(0).address(trillian()).build();
+
+		// committed for one year
+		let expected = InputBuilder::default()
+			.uint(0)
+			.uint(0)
+			.uint(1)
+			.uint(1_000)
+			.address(ford())
+			.build();
+		assert_eq!(
+			PocPrecompile::execute(&query_input, Some(QUERY_WEIGHT - 1), &context),
+			Err(ExitError::OutOfGas)
+		);
+		assert_eq!(
+			PocPrecompile::execute(&query_input, Some(QUERY_WEIGHT), &context),
+			Ok((ExitSucceed::Returned, expected, QUERY_WEIGHT))
+		);
+
+		// QueryVotingWeight
+		let voting_weight_input = InputBuilder::action(1).address(trillian()).build();
+		let (_, output, _) = PocPrecompile::execute(&voting_weight_input, None, &context).unwrap();
+		assert_eq!(U256::from(&output[..]), U256::from(10_000));
+
+		// QueryCandidates is charged per candidate
+		let expected = InputBuilder::default().uint(1).address(ford()).build();
+		assert_eq!(
+			PocPrecompile::execute(&InputBuilder::action(3).build(), None, &context),
+			Ok((ExitSucceed::Returned, expected, QUERY_WEIGHT + QUERY_CANDIDATE_WEIGHT))
+		);
+
+		// QueryMembers
+		let expected = InputBuilder::default().uint(0).build();
+		assert_eq!(
+			PocPrecompile::execute(&InputBuilder::action(4).build(), None, &context),
+			Ok((ExitSucceed::Returned, expected, QUERY_WEIGHT))
+		);
+
+		// Withdraw
+		assert_eq!(
+			PocPrecompile::execute(&InputBuilder::action(131).build(), None, &context),
+			Err(ExitError::Other("AlreadyCommitted".into()))
+		);
+
+		// Unbond
+		assert_eq!(
+			PocPrecompile::execute(&InputBuilder::action(130).build(), None, &context),
+			Ok((ExitSucceed::Returned, vec![], <() as PocWeightInfo>::unbond()))
+		);
+
+		let (_, output, _) = PocPrecompile::execute(&query_input, None, &context).unwrap();
+		// unbonding since block 1
+		assert_eq!(U256::from(&output[0 * 32..1 * 32]), U256::from(1));
+		assert_eq!(U256::from(&output[1 * 32..2 * 32]), U256::from(1));
+
+		// AddFunds commits again
+		let add_funds_input = InputBuilder::action(129).uint(500).build();
+		assert_eq!(
+			PocPrecompile::execute(&add_funds_input, None, &context),
+			Ok((ExitSucceed::Returned, vec![], <() as PocWeightInfo>::add_funds()))
+		);
+		assert_eq!(Balances::free_balance(&trillian_account), balance - 1_500);
+
+		// VoteCandidate
+		let vote_input = InputBuilder::action(132).address(trillian()).build();
+		assert_eq!(
+			PocPrecompile::execute(&vote_input, None, &context),
+			Ok((ExitSucceed::Returned, vec![], <() as PocWeightInfo>::vote_candidate()))
+		);
+		assert_eq!(Poc::commitments(&trillian_account).candidate, trillian_account);
+	});
+}
+
+#[test]
+fn poc_precompile_should_only_query_in_static_frames() {
+	new_test_ext().execute_with(|| {
+		let context = context();
+		let execute = |input: Vec<u8>| {
+			module_evm::precompiles::with_static(true, || PocPrecompile::execute(&input, None, &context))
+		};
+
+		let trillian_account = MockAddressMapping::get_account_id(&trillian());
+		let ford_account = MockAddressMapping::get_account_id(&ford());
+		assert_ok!(Poc::start_candidacy(Origin::signed(ford_account)));
+
+		let balance = Balances::free_balance(&trillian_account);
+		for input in vec![
+			InputBuilder::action(128).uint(1_000).uint(1).address(ford()).build(),
+			InputBuilder::action(129).uint(1_000).build(),
+			InputBuilder::action(130).build(),
+			InputBuilder::action(131).build(),
+			InputBuilder::action(132).address(ford()).build(),
+		] {
+			assert_eq!(execute(input), Err(ExitError::Other("static call".into())));
+		}
+		assert_eq!(Balances::free_balance(&trillian_account), balance);
+
+		let expected = InputBuilder::default().uint(1).address(ford()).build();
+		assert_eq!(
+			execute(InputBuilder::action(3).build()),
+			Ok((ExitSucceed::Returned, expected, QUERY_WEIGHT + QUERY_CANDIDATE_WEIGHT))
+		);
+		assert!(execute(InputBuilder::action(0).address(trillian()).build()).is_ok());
+	});
+}
+
+#[test]
+fn poc_precompile_should_accept_abi_input() {
+	new_test_ext().execute_with(|| {
+		let context = context();
+
+		let ford_account = MockAddressMapping::get_account_id(&ford());
+		assert_ok!(Poc::start_candidacy(Origin::signed(ford_account)));
+
+		// commit(uint256,uint8,address)
+		let commit_input = InputBuilder::selector(hex!("01945955"))
+			.uint(1_000)
+			.uint(0)
+			.address(ford())
+			.build();
+		assert_eq!(
+			PocPrecompile::execute(&commit_input, None, &context),
+			Ok((ExitSucceed::Returned, vec![], <() as PocWeightInfo>::commit()))
+		);
+
+		// votingWeight(address)
+		let query_input = InputBuilder::selector(hex!("c47b3511")).address(trillian()).build();
+		let (_, output, _) = PocPrecompile::execute(&query_input, None, &context).unwrap();
+		assert_eq!(U256::from(&output[..]), U256::from(1_000));
+
+		// currentEra()
+		let expected = InputBuilder::default().uint(0).uint(0).build();
+		assert_eq!(
+			PocPrecompile::execute(&InputBuilder::selector(hex!("973628f6")).build(), None, &context),
+			Ok((ExitSucceed::Returned, expected, QUERY_WEIGHT))
+		);
+
+		// candidates()
+		let expected = InputBuilder::default().uint(32).uint(1).address(ford()).build();
+		assert_eq!(
+			PocPrecompile::execute(&InputBuilder::selector(hex!("6f0470aa")).build(), None, &context),
+			Ok((ExitSucceed::Returned, expected, QUERY_WEIGHT + QUERY_CANDIDATE_WEIGHT))
+		);
+
+		// unbond()
+		assert_eq!(
+			PocPrecompile::execute(&InputBuilder::selector(hex!("5df6a6bc")).build(), None, &context),
+			Ok((ExitSucceed::Returned, vec![], <() as PocWeightInfo>::unbond()))
+		);
+		// voteCandidate(address)
+		let vote_input = InputBuilder::selector(hex!("26bb886d")).address(ford()).build();
+		assert_eq!(
+			PocPrecompile::execute(&vote_input, None, &context),
+			Err(ExitError::Other("NotCommitted".into()))
+		);
+	});
+}
+
 #[test]
 fn task_id_max_and_min() {
 	let task_id = TaskInfo {
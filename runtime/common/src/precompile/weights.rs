@@ -0,0 +1,49 @@
+//! Weights of the precompile actions, converted to gas with the inverse of
+//! `GasToWeight` when a precompile is executed.
+
+#![allow(unused_parens)]
+#![allow(clippy::unnecessary_cast)]
+
+use frame_support::weights::{constants::RocksDbWeight as DbWeight, Weight};
+
+/// Weight functions needed for the precompiles.
+pub trait WeightInfo {
+	fn query() -> Weight;
+	fn query_candidates(c: u32) -> Weight;
+	fn transfer() -> Weight;
+	fn transfer_maintainer() -> Weight;
+	fn schedule_call() -> Weight;
+	fn cancel_call() -> Weight;
+}
+
+impl WeightInfo for () {
+	fn query() -> Weight {
+		(20_000_000 as Weight).saturating_add(DbWeight::get().reads(2 as Weight))
+	}
+	fn query_candidates(c: u32) -> Weight {
+		(20_000_000 as Weight)
+			.saturating_add((2_000_000 as Weight).saturating_mul(c as Weight))
+			.saturating_add(DbWeight::get().reads(1 as Weight))
+			.saturating_add(DbWeight::get().reads((c as Weight)))
+	}
+	fn transfer() -> Weight {
+		(110_000_000 as Weight)
+			.saturating_add(DbWeight::get().reads(4 as Weight))
+			.saturating_add(DbWeight::get().writes(4 as Weight))
+	}
+	fn transfer_maintainer() -> Weight {
+		(249_253_000 as Weight)
+			.saturating_add(DbWeight::get().reads(4 as Weight))
+			.saturating_add(DbWeight::get().writes(2 as Weight))
+	}
+	fn schedule_call() -> Weight {
+		(180_000_000 as Weight)
+			.saturating_add(DbWeight::get().reads(5 as Weight))
+			.saturating_add(DbWeight::get().writes(5 as Weight))
+	}
+	fn cancel_call() -> Weight {
+		(150_000_000 as Weight)
+			.saturating_add(DbWeight::get().reads(4 as Weight))
+			.saturating_add(DbWeight::get().writes(4 as Weight))
+	}
+}
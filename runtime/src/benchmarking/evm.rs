@@ -1,7 +1,10 @@
-use crate::{AccountId, Balance, Event, EvmAccounts, Origin, Runtime, System, EUROS, Evm};
+use crate::{AccountId, Balance, Event, EvmAccounts, MaxBatchCalls, MaxCodeSize, Origin, Runtime, System, EUROS, Evm};
 
 use super::utils::set_snapr_balance;
-use frame_support::dispatch::DispatchError;
+use frame_support::{
+	dispatch::DispatchError,
+	traits::{Get, OnInitialize},
+};
 use frame_system::RawOrigin;
 use orml_benchmarking::runtime_benchmarks;
 use sp_core::H160;
@@ -21,7 +24,7 @@ fn ford() -> secp256k1::SecretKey {
 	secp256k1::SecretKey::parse(&keccak_256(b"Ford")).unwrap()
 }
 
-fn deploy_contract(caller: AccountId) -> Result<H160, DispatchError> {
+pub fn deploy_contract(caller: AccountId) -> Result<H160, DispatchError> {
 	// pragma solidity ^0.5.0;
 	//
 	// contract Factory {
@@ -108,6 +111,98 @@ runtime_benchmarks! {
 		set_snapr_balance(&alice_account_id(), euro(1000));
 		let contract = deploy_contract(alice_account_id())?;
 	}: _(RawOrigin::Signed(alice_account_id()), contract)
+
+	set_hardfork {
+	}: _(RawOrigin::Root, module_evm::EvmHardfork::London)
+
+	fund_sponsorship {
+		set_snapr_balance(&alice_account_id(), euro(1000));
+		let contract = deploy_contract(alice_account_id())?;
+	}: _(RawOrigin::Signed(alice_account_id()), contract, euro(100))
+
+	withdraw_sponsorship {
+		set_snapr_balance(&alice_account_id(), euro(1000));
+		let contract = deploy_contract(alice_account_id())?;
+		Evm::fund_sponsorship(Origin::signed(alice_account_id()), contract, euro(100))?;
+	}: _(RawOrigin::Signed(alice_account_id()), contract, euro(50))
+
+	set_sponsorship {
+		set_snapr_balance(&alice_account_id(), euro(1000));
+		let contract = deploy_contract(alice_account_id())?;
+		let limits = module_evm::SponsorshipLimits {
+			per_caller: Some(euro(1)),
+			per_block: Some(euro(10)),
+		};
+	}: _(RawOrigin::Signed(alice_account_id()), contract, Some(limits))
+
+	set_contract_paused {
+		set_snapr_balance(&alice_account_id(), euro(1000));
+		let contract = deploy_contract(alice_account_id())?;
+	}: _(RawOrigin::Root, contract, true)
+
+	set_evm_paused {
+	}: _(RawOrigin::Root, true)
+
+	set_dispatch_call_allowed {
+	}: _(RawOrigin::Root, b"Identity".to_vec(), b"set_identity".to_vec(), true)
+
+	set_verification_metadata {
+		set_snapr_balance(&alice_account_id(), euro(1000));
+		let contract = deploy_contract(alice_account_id())?;
+		let metadata = module_evm::VerificationMetadata {
+			compiler_version: vec![b'0'; module_evm::MAX_VERIFICATION_FIELD_LENGTH],
+			optimizer_enabled: true,
+			optimizer_runs: 200,
+			source_hash: vec![b'Q'; module_evm::MAX_VERIFICATION_FIELD_LENGTH],
+			abi_hash: Default::default(),
+		};
+	}: _(RawOrigin::Signed(alice_account_id()), contract, Some(metadata))
+
+	propose_code_upgrade {
+		set_snapr_balance(&alice_account_id(), euro(1000));
+		let contract = deploy_contract(alice_account_id())?;
+		Evm::deploy_free(Origin::root(), contract)?;
+		let code = vec![0x00; MaxCodeSize::get() as usize];
+	}: _(RawOrigin::Signed(alice_account_id()), contract, code, 10)
+
+	approve_code_upgrade {
+		set_snapr_balance(&alice_account_id(), euro(1000));
+		let contract = deploy_contract(alice_account_id())?;
+		Evm::deploy_free(Origin::root(), contract)?;
+		Evm::propose_code_upgrade(Origin::signed(alice_account_id()), contract, vec![0x00], 10)?;
+	}: _(RawOrigin::Root, contract)
+
+	cancel_code_upgrade {
+		set_snapr_balance(&alice_account_id(), euro(1000));
+		let contract = deploy_contract(alice_account_id())?;
+		Evm::deploy_free(Origin::root(), contract)?;
+		Evm::propose_code_upgrade(Origin::signed(alice_account_id()), contract, vec![0x00], 10)?;
+		Evm::approve_code_upgrade(Origin::root(), contract)?;
+	}: _(RawOrigin::Signed(alice_account_id()), contract)
+
+	upgrade_code {
+		set_snapr_balance(&alice_account_id(), euro(1000));
+		let contract = deploy_contract(alice_account_id())?;
+		Evm::deploy_free(Origin::root(), contract)?;
+		let code = vec![0x00; MaxCodeSize::get() as usize];
+		Evm::propose_code_upgrade(Origin::signed(alice_account_id()), contract, code, 0)?;
+		Evm::approve_code_upgrade(Origin::root(), contract)?;
+	}: {
+		Evm::on_initialize(System::block_number() + 1);
+	}
+
+	batch_call {
+		let c in 1 .. MaxBatchCalls::get();
+
+		set_snapr_balance(&alice_account_id(), euro(1000));
+		let call = module_evm::BatchCall {
+			target: EvmAccounts::eth_address(&ford()),
+			input: vec![],
+			value: 0,
+			gas_limit: 21_000,
+			storage_limit: 0,
+		};
+	}: _(RawOrigin::Signed(alice_account_id()), vec![call; c as usize])
 }
 
 #[cfg(test)]
@@ -173,4 +268,95 @@ mod tests {
 			assert_ok!(test_benchmark_selfdestruct());
 		});
 	}
+
+	#[test]
+	fn test_set_hardfork() {
+		new_test_ext().execute_with(|| {
+			assert_ok!(test_benchmark_set_hardfork());
+		});
+	}
+
+	#[test]
+	fn test_fund_sponsorship() {
+		new_test_ext().execute_with(|| {
+			assert_ok!(test_benchmark_fund_sponsorship());
+		});
+	}
+
+	#[test]
+	fn test_withdraw_sponsorship() {
+		new_test_ext().execute_with(|| {
+			assert_ok!(test_benchmark_withdraw_sponsorship());
+		});
+	}
+
+	#[test]
+	fn test_set_sponsorship() {
+		new_test_ext().execute_with(|| {
+			assert_ok!(test_benchmark_set_sponsorship());
+		});
+	}
+
+	#[test]
+	fn test_set_contract_paused() {
+		new_test_ext().execute_with(|| {
+			assert_ok!(test_benchmark_set_contract_paused());
+		});
+	}
+
+	#[test]
+	fn test_set_evm_paused() {
+		new_test_ext().execute_with(|| {
+			assert_ok!(test_benchmark_set_evm_paused());
+		});
+	}
+
+	#[test]
+	fn test_set_dispatch_call_allowed() {
+		new_test_ext().execute_with(|| {
+			assert_ok!(test_benchmark_set_dispatch_call_allowed());
+		});
+	}
+
+	#[test]
+	fn test_set_verification_metadata() {
+		new_test_ext().execute_with(|| {
+			assert_ok!(test_benchmark_set_verification_metadata());
+		});
+	}
+
+	#[test]
+	fn test_propose_code_upgrade() {
+		new_test_ext().execute_with(|| {
+			assert_ok!(test_benchmark_propose_code_upgrade());
+		});
+	}
+
+	#[test]
+	fn test_approve_code_upgrade() {
+		new_test_ext().execute_with(|| {
+			assert_ok!(test_benchmark_approve_code_upgrade());
+		});
+	}
+
+	#[test]
+	fn test_cancel_code_upgrade() {
+		new_test_ext().execute_with(|| {
+			assert_ok!(test_benchmark_cancel_code_upgrade());
+		});
+	}
+
+	#[test]
+	fn test_upgrade_code() {
+		new_test_ext().execute_with(|| {
+			assert_ok!(test_benchmark_upgrade_code());
+		});
+	}
+
+	#[test]
+	fn test_batch_call() {
+		new_test_ext().execute_with(|| {
+			assert_ok!(test_benchmark_batch_call());
+		});
+	}
 }
@@ -0,0 +1,232 @@
+use crate::{
+	AccountId, Balance, CandidacyDeposit, CurrencyId, EvmAccounts, MultiCurrencyPrecompile, Poc, PocPrecompile,
+	Runtime, ScheduleCallPrecompile, StateRentPrecompile, TechCouncilMaxCandidates, TokenSymbol, EUROS,
+};
+
+use super::{
+	evm::{alice_account_id, bob_account_id, deploy_contract},
+	utils::set_snapr_balance,
+};
+use frame_benchmarking::account;
+use frame_system::RawOrigin;
+use module_evm::{Context, Precompile};
+use orml_benchmarking::runtime_benchmarks;
+use primitives::PRECOMPILE_ADDRESS_START;
+use sp_core::{H160, U256};
+use sp_io::hashing::keccak_256;
+use sp_std::prelude::*;
+
+const SEED: u32 = 0;
+
+fn euro(d: u32) -> Balance {
+	let d: Balance = d.into();
+	EUROS.saturating_mul(d)
+}
+
+fn trillian() -> secp256k1::SecretKey {
+	secp256k1::SecretKey::parse(&keccak_256(b"Trillian")).unwrap()
+}
+
+fn ford() -> secp256k1::SecretKey {
+	secp256k1::SecretKey::parse(&keccak_256(b"Ford")).unwrap()
+}
+
+fn context() -> Context {
+	Context {
+		address: H160::from_low_u64_be(PRECOMPILE_ADDRESS_START),
+		caller: EvmAccounts::eth_address(&trillian()),
+		apparent_value: Default::default(),
+	}
+}
+
+fn address_param(address: H160) -> [u8; 32] {
+	let mut param = [0u8; 32];
+	param[12..].copy_from_slice(address.as_bytes());
+	param
+}
+
+fn u256_param(value: u128) -> [u8; 32] {
+	let mut param = [0u8; 32];
+	U256::from(value).to_big_endian(&mut param);
+	param
+}
+
+fn schedule_call_input() -> Vec<u8> {
+	let mut input = Vec::new();
+	// array size
+	input.extend_from_slice(&u256_param(0));
+	// action
+	input.extend_from_slice(&u256_param(0));
+	// from
+	input.extend_from_slice(&address_param(EvmAccounts::eth_address(&trillian())));
+	// target
+	input.extend_from_slice(&address_param(EvmAccounts::eth_address(&ford())));
+	// value
+	input.extend_from_slice(&u256_param(0));
+	// gas_limit
+	input.extend_from_slice(&u256_param(300_000));
+	// storage_limit
+	input.extend_from_slice(&u256_param(100));
+	// min_delay
+	input.extend_from_slice(&u256_param(1));
+	// input_len
+	input.extend_from_slice(&u256_param(0));
+	input
+}
+
+runtime_benchmarks! {
+	{ Runtime, precompile }
+
+	_ {}
+
+	query {
+		set_snapr_balance(&alice_account_id(), euro(1000));
+		let currency_id: [u8; 32] = CurrencyId::Token(TokenSymbol::SNAPR).into();
+		let mut input = Vec::new();
+		// action
+		input.extend_from_slice(&u256_param(1));
+		// currency_id
+		input.extend_from_slice(&currency_id);
+		// account_id
+		input.extend_from_slice(&address_param(EvmAccounts::eth_address(&trillian())));
+	}: {
+		assert!(MultiCurrencyPrecompile::execute(&input, None, &context()).is_ok());
+	}
+
+	transfer {
+		set_snapr_balance(&alice_account_id(), euro(1000));
+		let currency_id: [u8; 32] = CurrencyId::Token(TokenSymbol::SNAPR).into();
+		let mut input = Vec::new();
+		// action
+		input.extend_from_slice(&u256_param(2));
+		// currency_id
+		input.extend_from_slice(&currency_id);
+		// from
+		input.extend_from_slice(&address_param(EvmAccounts::eth_address(&trillian())));
+		// to
+		input.extend_from_slice(&address_param(EvmAccounts::eth_address(&ford())));
+		// amount
+		input.extend_from_slice(&u256_param(euro(100)));
+	}: {
+		assert!(MultiCurrencyPrecompile::execute(&input, None, &context()).is_ok());
+	}
+
+	transfer_maintainer {
+		set_snapr_balance(&alice_account_id(), euro(1000));
+		set_snapr_balance(&bob_account_id(), euro(1000));
+		let contract = deploy_contract(alice_account_id())?;
+		let mut input = Vec::new();
+		// action
+		input.extend_from_slice(&u256_param(128));
+		// from
+		input.extend_from_slice(&address_param(EvmAccounts::eth_address(&trillian())));
+		// contract
+		input.extend_from_slice(&address_param(contract));
+		// new_maintainer
+		input.extend_from_slice(&address_param(EvmAccounts::eth_address(&ford())));
+	}: {
+		assert!(StateRentPrecompile::execute(&input, None, &context()).is_ok());
+	}
+
+	schedule_call {
+		set_snapr_balance(&alice_account_id(), euro(1000));
+		let input = schedule_call_input();
+	}: {
+		assert!(ScheduleCallPrecompile::execute(&input, None, &context()).is_ok());
+	}
+
+	query_candidates {
+		let c in 1 .. TechCouncilMaxCandidates::get();
+
+		for i in 0 .. c {
+			let candidate: AccountId = account("candidate", i, SEED);
+			set_snapr_balance(&candidate, 2 * CandidacyDeposit::get());
+			Poc::start_candidacy(RawOrigin::Signed(candidate).into()).map_err(|e| e.error)?;
+		}
+		// action: QueryCandidates
+		let input = u256_param(3).to_vec();
+	}: {
+		assert!(PocPrecompile::execute(&input, None, &context()).is_ok());
+	}
+
+	cancel_call {
+		set_snapr_balance(&alice_account_id(), euro(1000));
+		let (_, output, _) = ScheduleCallPrecompile::execute(&schedule_call_input(), None, &context())
+			.map_err(|_| "schedule call failed")?;
+		let task_id_len = U256::from(&output[0..32]).as_usize();
+		let task_id = &output[32..32 + task_id_len];
+
+		let mut input = Vec::new();
+		// array size
+		input.extend_from_slice(&u256_param(0));
+		// action
+		input.extend_from_slice(&u256_param(1));
+		// from
+		input.extend_from_slice(&address_param(EvmAccounts::eth_address(&trillian())));
+		// task_id_len
+		input.extend_from_slice(&u256_param(task_id_len as u128));
+		// task_id
+		input.extend_from_slice(task_id);
+	}: {
+		assert!(ScheduleCallPrecompile::execute(&input, None, &context()).is_ok());
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::System;
+	use frame_support::assert_ok;
+
+	fn new_test_ext() -> sp_io::TestExternalities {
+		let t = frame_system::GenesisConfig::default()
+			.build_storage::<Runtime>()
+			.unwrap();
+
+		let mut ext = sp_io::TestExternalities::new(t);
+		ext.execute_with(|| System::set_block_number(1));
+		ext
+	}
+
+	#[test]
+	fn test_query() {
+		new_test_ext().execute_with(|| {
+			assert_ok!(test_benchmark_query());
+		});
+	}
+
+	#[test]
+	fn test_transfer() {
+		new_test_ext().execute_with(|| {
+			assert_ok!(test_benchmark_transfer());
+		});
+	}
+
+	#[test]
+	fn test_transfer_maintainer() {
+		new_test_ext().execute_with(|| {
+			assert_ok!(test_benchmark_transfer_maintainer());
+		});
+	}
+
+	#[test]
+	fn test_schedule_call() {
+		new_test_ext().execute_with(|| {
+			assert_ok!(test_benchmark_schedule_call());
+		});
+	}
+
+	#[test]
+	fn test_query_candidates() {
+		new_test_ext().execute_with(|| {
+			assert_ok!(test_benchmark_query_candidates());
+		});
+	}
+
+	#[test]
+	fn test_cancel_call() {
+		new_test_ext().execute_with(|| {
+			assert_ok!(test_benchmark_cancel_call());
+		});
+	}
+}
@@ -12,13 +12,13 @@ use sp_std::prelude::*;
 use sp_core::{
 	crypto::KeyTypeId,
 	u32_trait::{_2, _3, _4},
-	H160, OpaqueMetadata, Decode,
+	H160, H256, U256, OpaqueMetadata, Decode,
 };
 use sp_runtime::{
 	ApplyExtrinsicResult, generic, create_runtime_str, impl_opaque_keys,
 	transaction_validity::{TransactionValidity, TransactionSource, TransactionPriority},
 	curve::PiecewiseLinear,
-	FixedPointNumber,
+	FixedPointNumber, ModuleId,
 };
 use sp_runtime::traits::{
 	BlakeTwo256,
@@ -83,7 +83,7 @@ pub use primitives::{
 };
 
 pub use runtime_common::{
-	BlockLength, BlockWeights, GasToWeight, OffchainSolutionWeightLimit,
+	BlockLength, BlockWeights, GasToWeight, OffchainSolutionWeightLimit, WeightToGas,
 	Price, Rate, Ratio, SystemContractsFilter,
 };
 
@@ -540,6 +540,8 @@ parameter_types! {
 	pub const TargetBlockFullness: Perquintill = Perquintill::from_percent(25);
 	pub AdjustmentVariable: Multiplier = Multiplier::saturating_from_rational(1, 100_000);
 	pub MinimumMultiplier:  Multiplier = Multiplier::saturating_from_rational(1, 1_000_000_000 as u128);
+	/// The weight fee of `fee::WeightToFee`.
+	pub MinimumBaseFee: Balance = MILLI_SNAPR / Balance::from(ExtrinsicBaseWeight::get());
 }
 
 impl module_transaction_payment::Config for Runtime {
@@ -548,10 +550,12 @@ impl module_transaction_payment::Config for Runtime {
 	type StableCurrencyId = GetStableCurrencyId;
 	type Currency = Balances;
 	type MultiCurrency = Currencies;
-	type OnTransactionPayment = (); // fees get burned
+	type OnTransactionPayment = Evm; // fees get burned, tips go to the block author
 	type TransactionByteFee = TransactionByteFee;
 	type WeightToFee = fee::WeightToFee;
 	type FeeMultiplierUpdate = TargetedFeeAdjustment<Self, TargetBlockFullness, AdjustmentVariable, MinimumMultiplier>;
+	type MinimumBaseFee = MinimumBaseFee;
+	type FeeSponsor = Evm;
 	type WeightInfo = weights::transaction_payment::WeightInfo<Runtime>;
 }
 
@@ -580,9 +584,6 @@ impl module_evm_accounts::Config for Runtime {
 	type WeightInfo = weights::evm_accounts::WeightInfo<Runtime>;
 }
 
-#[cfg(feature = "with-ethereum-compatibility")]
-static ISTANBUL_CONFIG: evm::Config = evm::Config::istanbul();
-
 parameter_types! {
 	//In [3]: random.randint(1000, 100_000)
 	//Out[3]: 13939
@@ -590,15 +591,34 @@ parameter_types! {
 	// 10 SNAPR minimum storage deposit
 	pub const NewContractExtraBytes: u32 = 10_000;
 	pub const StorageDepositPerByte: Balance = 1 * MILLI_SNAPR;
+	pub StorageCleanupWeight: Weight = Perbill::from_percent(5) * BlockWeights::get().max_block;
 	pub const MaxCodeSize: u32 = 60 * 1024;
 	pub NetworkContractSource: H160 = H160::from_low_u64_be(0);
 	pub const DeveloperDeposit: Balance = 1_000 * SNAPR;
 	pub const DeploymentFee: Balance    = 100 * SNAPR;
+	pub const EthereumTransactionStorageLimit: u32 = 60 * 1024;
+	pub const AllowUnprotectedTransactions: bool = false;
+	// Ethereum meters SSTORE and refunds cleared slots, EIP-2200
+	pub const NetGasMetering: bool = cfg!(feature = "with-ethereum-compatibility");
+	pub const EvmSponsorModuleId: ModuleId = ModuleId(*b"snp/evsp");
+	pub const MaxBatchCalls: u32 = 16;
+	pub const RecurringCallRetention: BlockNumber = 7 * DAYS;
 }
 
-pub type MultiCurrencyPrecompile =
-	runtime_common::MultiCurrencyPrecompile<AccountId, EvmAddressMapping<Runtime>, Currencies>;
-pub type StateRentPrecompile = runtime_common::StateRentPrecompile<AccountId, EvmAddressMapping<Runtime>, Evm>;
+pub type MultiCurrencyPrecompile = runtime_common::MultiCurrencyPrecompile<
+	AccountId,
+	EvmAddressMapping<Runtime>,
+	Currencies,
+	WeightToGas,
+	weights::precompile::WeightInfo<Runtime>,
+>;
+pub type StateRentPrecompile = runtime_common::StateRentPrecompile<
+	AccountId,
+	EvmAddressMapping<Runtime>,
+	Evm,
+	WeightToGas,
+	weights::precompile::WeightInfo<Runtime>,
+>;
 pub type ScheduleCallPrecompile = runtime_common::ScheduleCallPrecompile<
 	AccountId,
 	EvmAddressMapping<Runtime>,
@@ -608,7 +628,41 @@ pub type ScheduleCallPrecompile = runtime_common::ScheduleCallPrecompile<
 	Origin,
 	OriginCaller,
 	Runtime,
+	weights::precompile::WeightInfo<Runtime>,
+>;
+
+/// The runtime calls contracts may dispatch through the
+/// `DispatchCallPrecompile` at genesis, changed by `DispatchCallOrigin`.
+pub fn dispatchable_calls() -> Vec<(Vec<u8>, Vec<u8>)> {
+	[
+		("Identity", "set_identity"),
+		("Identity", "clear_identity"),
+		("Identity", "request_judgement"),
+		("Identity", "cancel_request"),
+		("Poc", "start_candidacy"),
+		("Poc", "stop_candidacy"),
+	]
+	.iter()
+	.map(|(pallet_name, function_name)| (pallet_name.as_bytes().to_vec(), function_name.as_bytes().to_vec()))
+	.collect()
+}
+
+pub type DispatchCallPrecompile = runtime_common::DispatchCallPrecompile<
+	AccountId,
+	EvmAddressMapping<Runtime>,
+	Call,
+	module_evm::DispatchCallFilter<Runtime>,
+	WeightToGas,
+>;
+pub type PocPrecompile = runtime_common::PocPrecompile<
+	AccountId,
+	EvmAddressMapping<Runtime>,
+	WeightToGas,
+	weights::precompile::WeightInfo<Runtime>,
+	Runtime,
 >;
+pub type EvmAccountsPrecompile =
+	runtime_common::EvmAccountsPrecompile<Runtime, WeightToGas, weights::precompile::WeightInfo<Runtime>>;
 
 impl module_evm::Config for Runtime {
 	type AddressMapping = EvmAddressMapping<Runtime>;
@@ -616,6 +670,7 @@ impl module_evm::Config for Runtime {
 	type MergeAccount = Currencies;
 	type NewContractExtraBytes = NewContractExtraBytes;
 	type StorageDepositPerByte = StorageDepositPerByte;
+	type StorageCleanupWeight = StorageCleanupWeight;
 	type MaxCodeSize = MaxCodeSize;
 	type Event = Event;
 	type Precompiles = runtime_common::AllPrecompiles<
@@ -623,21 +678,31 @@ impl module_evm::Config for Runtime {
 		MultiCurrencyPrecompile,
 		StateRentPrecompile,
 		ScheduleCallPrecompile,
+		DispatchCallPrecompile,
+		PocPrecompile,
+		EvmAccountsPrecompile,
 	>;
 	type ChainId = ChainId;
 	type GasToWeight = GasToWeight;
+	type WeightToGas = WeightToGas;
+	type FindAuthor = pallet_session::FindAccountFromAuthorIndex<Self, Babe>;
+	type Randomness = pallet_babe::RandomnessFromOneEpochAgo<Self>;
+	type NetGasMetering = NetGasMetering;
 	type ChargeTransactionPayment = module_transaction_payment::ChargeTransactionPayment<Runtime>;
 	type NetworkContractOrigin = EnsureRoot<AccountId>; // todo: EnsureRootOrTwoThridsTechCouncil
 	type NetworkContractSource = NetworkContractSource;
 	type DeveloperDeposit = DeveloperDeposit;
 	type DeploymentFee = DeploymentFee;
 	type FreeDeploymentOrigin = EnsureRoot<AccountId>; // todo: EnsureRootOrTwoThridsTechCouncil
+	type PauseOrigin = EnsureRootOrTwoThridsTechCouncil;
+	type CodeUpgradeOrigin = EnsureRootOrTwoThridsTechCouncil;
+	type DispatchCallOrigin = EnsureRootOrTwoThridsTechCouncil;
+	type EthereumTransactionStorageLimit = EthereumTransactionStorageLimit;
+	type AllowUnprotectedTransactions = AllowUnprotectedTransactions;
+	type SponsorModuleId = EvmSponsorModuleId;
+	type MaxBatchCalls = MaxBatchCalls;
+	type RecurringCallRetention = RecurringCallRetention;
 	type WeightInfo = weights::evm::WeightInfo<Runtime>;
-
-	#[cfg(feature = "with-ethereum-compatibility")]
-	fn config() -> &'static evm::Config {
-		&ISTANBUL_CONFIG
-	}
 }
 
 impl module_evm_bridge::Config for Runtime {
@@ -788,7 +853,7 @@ construct_runtime!(
 
 		// Smart contracts
 		EvmAccounts: module_evm_accounts::{Module, Call, Storage, Event<T>} = 20,
-		Evm: module_evm::{Module, Config<T>, Call, Storage, Event<T>} = 21,
+		Evm: module_evm::{Module, Config<T>, Call, Storage, Event<T>, ValidateUnsigned} = 21,
 		EVMBridge: module_evm_bridge::{Module} = 22,
 
 		// Consensus
@@ -1088,13 +1153,14 @@ impl_runtime_apis! {
 			value: Balance,
 			gas_limit: u64,
 			storage_limit: u32,
+			access_list: Vec<module_evm::AccessListItem>,
 			estimate: bool,
 		) -> Result<CallInfo, sp_runtime::DispatchError> {
 			let mut config = <Runtime as module_evm::Config>::config().clone();
 			if estimate {
 				config.estimate = true;
 			}
-			module_evm::Runner::<Runtime>::call(
+			module_evm::Runner::<Runtime>::call_with_access_list(
 				from,
 				from,
 				to,
@@ -1102,6 +1168,7 @@ impl_runtime_apis! {
 				value,
 				gas_limit,
 				storage_limit,
+				access_list,
 				&config,
 			)
 		}
@@ -1112,22 +1179,36 @@ impl_runtime_apis! {
 			value: Balance,
 			gas_limit: u64,
 			storage_limit: u32,
+			access_list: Vec<module_evm::AccessListItem>,
 			estimate: bool,
 		) -> Result<CreateInfo, sp_runtime::DispatchError> {
 			let mut config = <Runtime as module_evm::Config>::config().clone();
 			if estimate {
 				config.estimate = true;
 			}
-			module_evm::Runner::<Runtime>::create(
+			module_evm::Runner::<Runtime>::create_with_access_list(
 				from,
 				data,
 				value,
 				gas_limit,
 				storage_limit,
+				access_list,
 				&config,
 			)
 		}
 
+		fn batch_call(
+			from: H160,
+			calls: Vec<module_evm::BatchCall<Balance>>,
+			estimate: bool,
+		) -> Result<module_evm::BatchCallInfo, sp_runtime::DispatchError> {
+			let mut config = <Runtime as module_evm::Config>::config().clone();
+			if estimate {
+				config.estimate = true;
+			}
+			module_evm::Runner::<Runtime>::batch_call(from, calls, &config)
+		}
+
 		fn get_estimate_resources_request(
 			extrinsic: Vec<u8>,
 		) -> Result<EstimateResourcesRequest, sp_runtime::DispatchError> {
@@ -1144,6 +1225,8 @@ impl_runtime_apis! {
 						storage_limit: Some(storage_limit),
 						value: Some(value),
 						data: Some(data),
+						batch: None,
+						access_list: None,
 					})
 				}
 				Call::Evm(module_evm::Call::create(data, value, gas_limit, storage_limit)) => {
@@ -1154,6 +1237,57 @@ impl_runtime_apis! {
 						storage_limit: Some(storage_limit),
 						value: Some(value),
 						data: Some(data),
+						batch: None,
+						access_list: None,
+					})
+				}
+				Call::Evm(module_evm::Call::batch_call(calls)) => {
+					Some(EstimateResourcesRequest {
+						from: None,
+						to: None,
+						gas_limit: None,
+						storage_limit: None,
+						value: None,
+						data: None,
+						batch: Some(calls),
+						access_list: None,
+					})
+				}
+				Call::Evm(module_evm::Call::call_with_access_list(
+					to,
+					data,
+					value,
+					gas_limit,
+					storage_limit,
+					access_list,
+				)) => {
+					Some(EstimateResourcesRequest {
+						from: None,
+						to: Some(to),
+						gas_limit: Some(gas_limit),
+						storage_limit: Some(storage_limit),
+						value: Some(value),
+						data: Some(data),
+						batch: None,
+						access_list: Some(access_list),
+					})
+				}
+				Call::Evm(module_evm::Call::create_with_access_list(
+					data,
+					value,
+					gas_limit,
+					storage_limit,
+					access_list,
+				)) => {
+					Some(EstimateResourcesRequest {
+						from: None,
+						to: None,
+						gas_limit: Some(gas_limit),
+						storage_limit: Some(storage_limit),
+						value: Some(value),
+						data: Some(data),
+						batch: None,
+						access_list: Some(access_list),
 					})
 				}
 				_ => None,
@@ -1162,6 +1296,141 @@ impl_runtime_apis! {
 			request.ok_or(sp_runtime::DispatchError::Other("Invalid parameter extrinsic, not evm Call"))
 		}
 
+		fn chain_id() -> u64 {
+			ChainId::get()
+		}
+
+		fn account_basic(address: H160) -> module_evm::Account {
+			Evm::account_basic(&address)
+		}
+
+		fn get_code(address: H160) -> Vec<u8> {
+			Evm::code_at_address(&address)
+		}
+
+		fn get_storage_at(address: H160, index: H256) -> H256 {
+			Evm::account_storages(address, index)
+		}
+
+		fn block_receipts() -> Vec<module_evm::Receipt> {
+			Evm::current_receipts()
+		}
+
+		fn base_fee_per_gas() -> U256 {
+			U256::from(Evm::base_fee_per_gas())
+		}
+
+		fn block_gas() -> (U256, U256) {
+			(Evm::block_gas_used(), Evm::block_gas_limit())
+		}
+
+		fn sponsorship(contract: H160) -> Option<module_evm::Sponsorship<Balance>> {
+			Evm::sponsorship(&contract)
+		}
+
+		fn storage_usage(contract: H160) -> Option<module_evm::StorageUsage<Balance>> {
+			Evm::storage_usage(&contract)
+		}
+
+		fn verification_metadata(contract: H160) -> Option<module_evm::VerificationMetadata> {
+			Evm::verification_metadata(contract)
+		}
+
+		fn code_upgrade(contract: H160) -> Option<module_evm::CodeUpgrade<BlockNumber>> {
+			Evm::code_upgrades(contract)
+		}
+
+		fn code_info(code_hash: H256) -> Option<module_evm::CodeInfo> {
+			Evm::code_infos(code_hash)
+		}
+
+		fn contracts_by_code_hash(code_hash: H256) -> Vec<H160> {
+			Evm::contracts_by_code_hash(&code_hash)
+		}
+
+		fn evm_paused() -> bool {
+			Evm::evm_paused()
+		}
+
+		fn contract_paused(contract: H160) -> bool {
+			Evm::paused_contracts(contract).is_some()
+		}
+
+		fn convert_transaction(transaction: Vec<u8>) -> Result<<Block as BlockT>::Extrinsic, sp_runtime::DispatchError> {
+			let transaction = module_evm::EthereumTransaction::decode_rlp(&transaction)
+				.map_err(|_| sp_runtime::DispatchError::Other("Invalid Ethereum transaction"))?;
+			Ok(UncheckedExtrinsic::new_unsigned(Call::Evm(module_evm::Call::transact(transaction))))
+		}
+
+		fn ethereum_transaction(extrinsic: <Block as BlockT>::Extrinsic) -> Option<Vec<u8>> {
+			match extrinsic.function {
+				Call::Evm(module_evm::Call::transact(transaction)) => Some(transaction.encode_rlp()),
+				_ => None,
+			}
+		}
+
+		fn trace_extrinsic(
+			header: <Block as BlockT>::Header,
+			extrinsics: Vec<<Block as BlockT>::Extrinsic>,
+			transaction_index: u32,
+			tracer: module_evm::TracerConfig,
+		) -> Result<module_evm::Trace, sp_runtime::DispatchError> {
+			let transaction_index = transaction_index as usize;
+			if transaction_index >= extrinsics.len() {
+				return Err(sp_runtime::DispatchError::Other("Invalid transaction index"));
+			}
+
+			Executive::initialize_block(&header);
+			let mut extrinsics = extrinsics;
+			let traced = extrinsics.swap_remove(transaction_index);
+			extrinsics.truncate(transaction_index);
+			for extrinsic in extrinsics {
+				let _ = Executive::apply_extrinsic(extrinsic);
+			}
+
+			let mut tracer = module_evm::Tracer::new(tracer);
+			let _ = module_evm::tracing::using(&mut tracer, || Executive::apply_extrinsic(traced));
+			Ok(tracer.finish())
+		}
+
+		fn trace_call(
+			from: H160,
+			to: Option<H160>,
+			data: Vec<u8>,
+			value: Balance,
+			gas_limit: u64,
+			storage_limit: u32,
+			access_list: Vec<module_evm::AccessListItem>,
+			tracer: module_evm::TracerConfig,
+		) -> Result<module_evm::Trace, sp_runtime::DispatchError> {
+			let config = <Runtime as module_evm::Config>::config();
+			let mut tracer = module_evm::Tracer::new(tracer);
+			module_evm::tracing::using(&mut tracer, || match to {
+				Some(to) => module_evm::Runner::<Runtime>::call_with_access_list(
+					from,
+					from,
+					to,
+					data,
+					value,
+					gas_limit,
+					storage_limit,
+					access_list,
+					config,
+				)
+				.map(|_| ()),
+				None => module_evm::Runner::<Runtime>::create_with_access_list(
+					from,
+					data,
+					value,
+					gas_limit,
+					storage_limit,
+					access_list,
+					config,
+				)
+				.map(|_| ()),
+			})?;
+			Ok(tracer.finish())
+		}
 	}
 
 	#[cfg(feature = "runtime-benchmarks")]
@@ -1198,6 +1467,7 @@ impl_runtime_apis! {
 
 			orml_add_benchmark!(params, batches, evm, benchmarking::evm);
 			orml_add_benchmark!(params, batches, evm_accounts, benchmarking::evm_accounts);
+			orml_add_benchmark!(params, batches, precompile, benchmarking::precompile);
 
 			if batches.is_empty() { return Err("Benchmark not found for this pallet.".into()) }
 			Ok(batches)
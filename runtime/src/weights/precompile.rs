@@ -0,0 +1,45 @@
+//! Weights for precompile
+//!
+//! These are estimates, not benchmark results: they match the `()` impl of
+//! `runtime_common::precompile::WeightInfo` until the `precompile`
+//! benchmarks are run against the runtime.
+
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::weights::{constants::RocksDbWeight as DbWeight, Weight};
+use sp_std::marker::PhantomData;
+
+/// Weight functions for precompile.
+pub struct WeightInfo<T>(PhantomData<T>);
+impl<T: frame_system::Config> runtime_common::precompile::WeightInfo for WeightInfo<T> {
+	fn query() -> Weight {
+		(20_000_000 as Weight).saturating_add(DbWeight::get().reads(2 as Weight))
+	}
+	fn query_candidates(c: u32) -> Weight {
+		(20_000_000 as Weight)
+			.saturating_add((2_000_000 as Weight).saturating_mul(c as Weight))
+			.saturating_add(DbWeight::get().reads(1 as Weight))
+			.saturating_add(DbWeight::get().reads((c as Weight)))
+	}
+	fn transfer() -> Weight {
+		(110_000_000 as Weight)
+			.saturating_add(DbWeight::get().reads(4 as Weight))
+			.saturating_add(DbWeight::get().writes(4 as Weight))
+	}
+	fn transfer_maintainer() -> Weight {
+		(249_253_000 as Weight)
+			.saturating_add(DbWeight::get().reads(4 as Weight))
+			.saturating_add(DbWeight::get().writes(2 as Weight))
+	}
+	fn schedule_call() -> Weight {
+		(180_000_000 as Weight)
+			.saturating_add(DbWeight::get().reads(5 as Weight))
+			.saturating_add(DbWeight::get().writes(5 as Weight))
+	}
+	fn cancel_call() -> Weight {
+		(150_000_000 as Weight)
+			.saturating_add(DbWeight::get().reads(4 as Weight))
+			.saturating_add(DbWeight::get().writes(4 as Weight))
+	}
+}
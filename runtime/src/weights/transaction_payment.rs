@@ -11,8 +11,8 @@ pub struct WeightInfo<T>(PhantomData<T>);
 impl<T: frame_system::Config> module_transaction_payment::WeightInfo for WeightInfo<T> {
 	fn on_finalize() -> Weight {
 		(39_708_000 as Weight)
-			.saturating_add(DbWeight::get().reads(2 as Weight))
-			.saturating_add(DbWeight::get().writes(1 as Weight))
+			.saturating_add(DbWeight::get().reads(3 as Weight))
+			.saturating_add(DbWeight::get().writes(2 as Weight))
 	}
 	fn set_default_fee_token() -> Weight {
 		(1_000_000 as Weight).saturating_add(DbWeight::get().writes(1 as Weight))
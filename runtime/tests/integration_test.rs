@@ -3,20 +3,22 @@
 use codec::Encode;
 use frame_support::{
 	assert_noop, assert_ok,
-	traits::{GenesisBuild, OnFinalize, OnInitialize},
+	traits::{Filter, GenesisBuild, OnFinalize, OnInitialize},
 };
 use snapr_runtime::{
-	get_all_module_accounts,
+	dispatchable_calls, get_all_module_accounts,
 	AccountId, AuthoritysOriginId,
 	Balance, Balances, Call,
 	CurrencyId,
 	Event, EvmAccounts, GetNativeCurrencyId,
-	NativeTokenExistentialDeposit, Origin,
+	NativeTokenExistentialDeposit, Nonce, Origin,
 	Perbill, Runtime, System,
 	TokenSymbol, Evm,
 };
 use module_support::{Price};
+use sp_core::{H160, H256, U256};
 use sp_io::hashing::keccak_256;
+use std::collections::BTreeMap;
 use sp_runtime::{
 	traits::{BadOrigin},
 	DispatchError, FixedPointNumber, MultiAddress,
@@ -97,12 +99,38 @@ impl ExtBuilder {
 		.assimilate_storage(&mut t)
 		.unwrap();
 
+		module_evm::GenesisConfig::<Runtime> {
+			accounts: evm_genesis(),
+			..Default::default()
+		}
+		.assimilate_storage(&mut t)
+		.unwrap();
+
 		let mut ext = sp_io::TestExternalities::new(t);
 		ext.execute_with(|| SystemModule::set_block_number(1));
 		ext
 	}
 }
 
+/// The predeployed contracts of the chain spec.
+fn evm_genesis() -> BTreeMap<H160, module_evm::GenesisAccount<Balance, Nonce>> {
+	let contracts_json = &include_bytes!("../../assets/bytecodes.json")[..];
+	let contracts: Vec<(String, String, String)> = serde_json::from_slice(contracts_json).unwrap();
+	contracts
+		.into_iter()
+		.map(|(_, address, code)| {
+			let account = module_evm::GenesisAccount {
+				nonce: 0,
+				balance: NativeTokenExistentialDeposit::get(),
+				storage: Default::default(),
+				code: hex::decode(code.trim_start_matches("0x")).unwrap(),
+			};
+			let address = H160::from_slice(&hex::decode(address.trim_start_matches("0x")).unwrap());
+			(address, account)
+		})
+		.collect()
+}
+
 pub fn origin_of(account_id: AccountId) -> <Runtime as frame_system::Config>::Origin {
 	<Runtime as frame_system::Config>::Origin::signed(account_id)
 }
@@ -135,8 +163,6 @@ pub fn bob_account_id() -> AccountId {
 	AccountId::from(Into::<[u8; 32]>::into(data))
 }
 
-#[cfg(not(feature = "with-ethereum-compatibility"))]
-use sp_core::H160;
 #[cfg(not(feature = "with-ethereum-compatibility"))]
 fn deploy_contract(account: AccountId) -> Result<H160, DispatchError> {
 	// pragma solidity ^0.5.0;
@@ -532,3 +558,88 @@ fn test_evm_module() {
 			}
 		});
 }
+
+#[test]
+fn predeploy_transfer_fits_a_normal_gas_limit() {
+	ExtBuilder::default()
+		.balances(vec![
+			(alice_account_id(), CurrencyId::Token(TokenSymbol::SNAPR), amount(1 * MILLI_SNAPR)),
+			(bob_account_id(), CurrencyId::Token(TokenSymbol::SNAPR), amount(1 * MILLI_SNAPR)),
+		])
+		.build()
+		.execute_with(|| {
+			// the SNAPR ERC20 predeploy, backed by the MultiCurrency precompile
+			let snapr = H160::from_low_u64_be(0x0100_0000);
+			let bob_address = EvmAccounts::eth_address(&ford());
+
+			// transfer(address,uint256)
+			let mut input = hex_literal::hex!("a9059cbb").to_vec();
+			input.extend_from_slice(H256::from(bob_address).as_bytes());
+			let mut value = [0u8; 32];
+			U256::from(amount(10 * MICRO_SNAPR)).to_big_endian(&mut value);
+			input.extend_from_slice(&value);
+
+			assert_ok!(Evm::call(
+				Origin::signed(alice_account_id()),
+				snapr,
+				input,
+				0,
+				100_000,
+				1_000
+			));
+			assert!(System::events()
+				.iter()
+				.any(|record| record.event == Event::module_evm(module_evm::Event::Executed(snapr))));
+			assert_eq!(
+				Balances::free_balance(bob_account_id()),
+				amount(1 * MILLI_SNAPR) + amount(10 * MICRO_SNAPR)
+			);
+		});
+}
+
+#[test]
+fn dispatch_call_filter_allows_governance_listed_calls() {
+	type DispatchCallFilter = module_evm::DispatchCallFilter<Runtime>;
+
+	ExtBuilder::default().build().execute_with(|| {
+		let clear_identity = Call::Identity(pallet_identity::Call::clear_identity());
+		let bond_extra = Call::Staking(pallet_staking::Call::bond_extra(1));
+		assert!(!DispatchCallFilter::filter(&clear_identity));
+
+		for (pallet_name, function_name) in dispatchable_calls() {
+			assert_ok!(Evm::set_dispatch_call_allowed(
+				Origin::root(),
+				pallet_name,
+				function_name,
+				true
+			));
+		}
+		assert!(DispatchCallFilter::filter(&clear_identity));
+		assert!(!DispatchCallFilter::filter(&bond_extra));
+
+		assert_noop!(
+			Evm::set_dispatch_call_allowed(
+				Origin::signed(AccountId::from(FORD)),
+				b"Staking".to_vec(),
+				b"bond_extra".to_vec(),
+				true
+			),
+			BadOrigin
+		);
+
+		assert_ok!(Evm::set_dispatch_call_allowed(
+			Origin::root(),
+			b"Identity".to_vec(),
+			b"clear_identity".to_vec(),
+			false
+		));
+		assert_eq!(
+			last_event(),
+			Event::module_evm(module_evm::Event::DispatchCallDisallowed(
+				b"Identity".to_vec(),
+				b"clear_identity".to_vec()
+			))
+		);
+		assert!(!DispatchCallFilter::filter(&clear_identity));
+	});
+}